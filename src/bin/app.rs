@@ -2,7 +2,7 @@ use futures::FutureExt;
 use rustfulapi::constant::CONFIG;
 use rustfulapi::error::AppResult;
 use rustfulapi::server::AppServer;
-use rustfulapi::server::worker::MessengerTask;
+use rustfulapi::server::worker::{MessengerTask, PurgeTask};
 use rustfulapi::{configure, util};
 use tracing::info;
 
@@ -18,10 +18,13 @@ async fn main() -> AppResult<()> {
   let server = AppServer::new(config).await?;
   info!("Create a new messenger task.");
   let messenger = MessengerTask::new(server.state.clone());
+  info!("Create a new purge task.");
+  let purge = PurgeTask::new(server.state.clone());
   info!("Run the server.");
   util::task::join_all(vec![
     (true, server.run().boxed()),
     (true, messenger.run().boxed()),
+    (true, purge.run().boxed()),
   ])
   .await?;
   Ok(())
@@ -25,8 +25,91 @@ pub trait RedisClientExt: ClientBuilder {
   -> impl std::future::Future<Output = Result<Option<String>, RedisError>>;
   fn del(&self, key: &str) -> impl std::future::Future<Output = Result<bool, RedisError>>;
   fn ttl(&self, key: &str) -> impl std::future::Future<Output = Result<i64, RedisError>>;
+  fn sadd(
+    &self,
+    key: &str,
+    member: &str,
+    expire: Duration,
+  ) -> impl std::future::Future<Output = Result<(), RedisError>>;
+  fn srem(
+    &self,
+    key: &str,
+    member: &str,
+  ) -> impl std::future::Future<Output = Result<bool, RedisError>>;
+  fn smembers(
+    &self,
+    key: &str,
+  ) -> impl std::future::Future<Output = Result<Vec<String>, RedisError>>;
+  fn set_nx(
+    &self,
+    key: &str,
+    value: &str,
+    expire: Duration,
+  ) -> impl std::future::Future<Output = Result<bool, RedisError>>;
+  fn set_if_eq(
+    &self,
+    key: &str,
+    current: &str,
+    value: &str,
+    expire: Duration,
+  ) -> impl std::future::Future<Output = Result<bool, RedisError>>;
+  fn del_if_eq(
+    &self,
+    key: &str,
+    current: &str,
+  ) -> impl std::future::Future<Output = Result<bool, RedisError>>;
+  fn gcra(
+    &self,
+    key: &str,
+    emission_interval: Duration,
+    burst_tolerance: Duration,
+  ) -> impl std::future::Future<Output = Result<Option<Duration>, RedisError>>;
+  fn incr_expire(
+    &self,
+    key: &str,
+    expire: Duration,
+  ) -> impl std::future::Future<Output = Result<u64, RedisError>>;
 }
 
+const SET_IF_EQ_SCRIPT: &str = r#"-- set_if_eq
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+  redis.call('SET', KEYS[1], ARGV[2], 'EX', ARGV[3])
+  return 1
+end
+return 0"#;
+
+const DEL_IF_EQ_SCRIPT: &str = r#"-- del_if_eq
+if redis.call('GET', KEYS[1]) == ARGV[1] then
+  redis.call('DEL', KEYS[1])
+  return 1
+end
+return 0"#;
+
+// Generic cell rate algorithm, the key holds the theoretical arrival time in milliseconds.
+const GCRA_SCRIPT: &str = r#"-- gcra
+local interval = tonumber(ARGV[1])
+local tolerance = tonumber(ARGV[2])
+local now = tonumber(ARGV[3])
+local tat = tonumber(redis.call('GET', KEYS[1])) or now
+if tat < now then
+  tat = now
+end
+local new_tat = tat + interval
+local allow_at = new_tat - tolerance - interval
+if now < allow_at then
+  return {0, allow_at - now}
+end
+redis.call('SET', KEYS[1], new_tat, 'PX', new_tat - now)
+return {1, 0}"#;
+
+// The expire time is set by the first increment only, so the counter window is fixed.
+const INCR_EXPIRE_SCRIPT: &str = r#"-- incr_expire
+local value = redis.call('INCR', KEYS[1])
+if value == 1 then
+  redis.call('EXPIRE', KEYS[1], ARGV[1])
+end
+return value"#;
+
 impl ClientBuilder for RedisClient {
   fn build_from_config(config: &AppConfig) -> AppResult<Self> {
     Ok(redis::Client::open(config.redis.get_url())?)
@@ -103,6 +186,113 @@ impl RedisClientExt for Client {
     info!("get TTL value: {key}");
     Ok(value)
   }
+
+  async fn sadd(&self, key: &str, member: &str, expire: Duration) -> Result<(), RedisError> {
+    let mut conn = self.get_multiplexed_async_connection().await?;
+    let _: i32 = redis::cmd("SADD")
+      .arg(&[key, member])
+      .query_async(&mut conn)
+      .await?;
+    let _: i32 = redis::cmd("EXPIRE")
+      .arg(&[key, &expire.as_secs().to_string()])
+      .query_async(&mut conn)
+      .await?;
+    info!("add member to set: {key}");
+    Ok(())
+  }
+
+  async fn srem(&self, key: &str, member: &str) -> Result<bool, RedisError> {
+    let mut conn = self.get_multiplexed_async_connection().await?;
+    let value: i32 = redis::cmd("SREM")
+      .arg(&[key, member])
+      .query_async(&mut conn)
+      .await?;
+    info!("remove member from set: {key}");
+    Ok(value == 1)
+  }
+
+  async fn smembers(&self, key: &str) -> Result<Vec<String>, RedisError> {
+    let mut conn = self.get_multiplexed_async_connection().await?;
+    let members: Vec<String> = redis::cmd("SMEMBERS")
+      .arg(key)
+      .query_async(&mut conn)
+      .await?;
+    info!("get members of set: {key}");
+    Ok(members)
+  }
+
+  async fn set_nx(&self, key: &str, value: &str, expire: Duration) -> Result<bool, RedisError> {
+    let mut conn = self.get_multiplexed_async_connection().await?;
+    let msg: Option<String> = redis::cmd("SET")
+      .arg(&[key, value, "NX", "EX", &expire.as_secs().to_string()])
+      .query_async(&mut conn)
+      .await?;
+    info!("set key if not exists: {key}");
+    Ok(msg.is_some())
+  }
+
+  async fn set_if_eq(
+    &self,
+    key: &str,
+    current: &str,
+    value: &str,
+    expire: Duration,
+  ) -> Result<bool, RedisError> {
+    let mut conn = self.get_multiplexed_async_connection().await?;
+    let value: i32 = redis::Script::new(SET_IF_EQ_SCRIPT)
+      .key(key)
+      .arg(current)
+      .arg(value)
+      .arg(expire.as_secs())
+      .invoke_async(&mut conn)
+      .await?;
+    info!("compare and set key: {key}");
+    Ok(value == 1)
+  }
+
+  async fn del_if_eq(&self, key: &str, current: &str) -> Result<bool, RedisError> {
+    let mut conn = self.get_multiplexed_async_connection().await?;
+    let value: i32 = redis::Script::new(DEL_IF_EQ_SCRIPT)
+      .key(key)
+      .arg(current)
+      .invoke_async(&mut conn)
+      .await?;
+    info!("compare and delete key: {key}");
+    Ok(value == 1)
+  }
+
+  async fn gcra(
+    &self,
+    key: &str,
+    emission_interval: Duration,
+    burst_tolerance: Duration,
+  ) -> Result<Option<Duration>, RedisError> {
+    let mut conn = self.get_multiplexed_async_connection().await?;
+    let now = std::time::SystemTime::now()
+      .duration_since(std::time::UNIX_EPOCH)
+      .unwrap_or_default()
+      .as_millis() as u64;
+    let (allowed, retry_after): (i32, u64) = redis::Script::new(GCRA_SCRIPT)
+      .key(key)
+      .arg(emission_interval.as_millis() as u64)
+      .arg(burst_tolerance.as_millis() as u64)
+      .arg(now)
+      .invoke_async(&mut conn)
+      .await?;
+    info!("rate limit key: {key}");
+    Ok((allowed == 0).then(|| Duration::from_millis(retry_after)))
+  }
+
+  async fn incr_expire(&self, key: &str, expire: Duration) -> Result<u64, RedisError> {
+    let mut conn = self.get_multiplexed_async_connection().await?;
+    let value: u64 = redis::Script::new(INCR_EXPIRE_SCRIPT)
+      .key(key)
+      .arg(expire.as_secs())
+      .invoke_async(&mut conn)
+      .await?;
+    info!("increment key: {key}");
+    Ok(value)
+  }
 }
 
 #[cfg(test)]
@@ -186,4 +376,80 @@ mod tests {
     let resp = REDIS.ttl(&key).await.unwrap();
     assert!(resp < 0);
   }
+
+  #[tokio::test]
+  async fn test_set_members_redis() {
+    let key: String = Faker.fake();
+    for member in ["first", "second"] {
+      REDIS
+        .sadd(&key, member, Duration::from_secs(4))
+        .await
+        .unwrap();
+    }
+    let mut members = REDIS.smembers(&key).await.unwrap();
+    members.sort();
+    assert_eq!(members, vec!["first", "second"]);
+    assert!(REDIS.srem(&key, "first").await.unwrap());
+    assert!(!REDIS.srem(&key, "first").await.unwrap());
+    assert_eq!(REDIS.smembers(&key).await.unwrap(), vec!["second"]);
+    assert!(REDIS.ttl(&key).await.unwrap() > 0);
+  }
+
+  #[tokio::test]
+  async fn test_set_nx_redis() {
+    let key: String = Faker.fake();
+    assert!(
+      REDIS
+        .set_nx(&key, "value", Duration::from_secs(4))
+        .await
+        .unwrap()
+    );
+    assert!(
+      !REDIS
+        .set_nx(&key, "other", Duration::from_secs(4))
+        .await
+        .unwrap()
+    );
+    assert_eq!(REDIS.get(&key).await.unwrap().as_deref(), Some("value"));
+  }
+
+  #[tokio::test]
+  async fn test_del_if_eq_redis() {
+    let key: String = Faker.fake();
+    REDIS
+      .set(&key, "value", Duration::from_secs(4))
+      .await
+      .unwrap();
+    assert!(!REDIS.del_if_eq(&key, "other").await.unwrap());
+    assert!(REDIS.exist(&key).await.unwrap());
+    assert!(REDIS.del_if_eq(&key, "value").await.unwrap());
+    assert!(!REDIS.exist(&key).await.unwrap());
+  }
+
+  #[tokio::test]
+  async fn test_gcra_redis() {
+    let key = Uuid::new_v4().to_string();
+    let interval = Duration::from_secs(10);
+    let tolerance = Duration::from_secs(20);
+    for _ in 0..3 {
+      let resp = REDIS.gcra(&key, interval, tolerance).await.unwrap();
+      assert!(resp.is_none());
+    }
+    let resp = REDIS.gcra(&key, interval, tolerance).await.unwrap();
+    assert!(matches!(resp, Some(d) if d > Duration::ZERO && d <= interval));
+  }
+
+  #[tokio::test]
+  async fn test_incr_expire_redis() {
+    let key = Uuid::new_v4().to_string();
+    for i in 1..=3 {
+      let resp = REDIS
+        .incr_expire(&key, Duration::from_secs(4))
+        .await
+        .unwrap();
+      assert_eq!(resp, i);
+    }
+    let resp = REDIS.ttl(&key).await.unwrap();
+    assert!(resp <= 4 && resp > 0);
+  }
 }
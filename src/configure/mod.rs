@@ -6,8 +6,11 @@ use serde::Deserialize;
 use crate::util::dir::get_project_root;
 
 use self::{
-  db::DatabaseConfig, email::EmailConfig, http::HttpClientConfig, redis::RedisConfig,
-  secret::SecretConfig, sentry::SentryConfig, server::ServerConfig, worker::WorkerConfig,
+  db::DatabaseConfig, email::EmailConfig, http::HttpClientConfig, oidc::OidcConfig,
+  privacy::PrivacyConfig, rate_limit::RateLimitConfig, redis::RedisConfig,
+  registration::RegistrationConfig, secret::SecretConfig, sentry::SentryConfig,
+  server::ServerConfig, session_cookie::SessionCookieConfig, webauthn::WebauthnConfig,
+  worker::WorkerConfig,
 };
 
 pub mod db;
@@ -15,12 +18,18 @@ pub mod deserialize;
 pub mod email;
 pub mod env;
 pub mod http;
+pub mod oidc;
+pub mod privacy;
+pub mod rate_limit;
 pub mod redis;
+pub mod registration;
 pub mod secret;
 pub mod sentry;
 pub mod server;
+pub mod session_cookie;
 pub mod template;
 pub mod tracing;
+pub mod webauthn;
 pub mod worker;
 
 #[derive(Debug, Deserialize, Clone)]
@@ -33,6 +42,12 @@ pub struct AppConfig {
   pub secret: SecretConfig,
   pub worker: WorkerConfig,
   pub http: HttpClientConfig,
+  pub registration: RegistrationConfig,
+  pub oidc: OidcConfig,
+  pub privacy: PrivacyConfig,
+  pub webauthn: WebauthnConfig,
+  pub rate_limit: RateLimitConfig,
+  pub session_cookie: SessionCookieConfig,
 }
 
 impl AppConfig {
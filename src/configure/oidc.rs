@@ -0,0 +1,39 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct OidcConfig {
+  #[serde(default)]
+  pub providers: Vec<OidcProviderConfig>,
+}
+
+/// An OpenID Connect issuer users may sign in with, its endpoints are read
+/// from the discovery document of the issuer.
+#[derive(Debug, Deserialize, Clone)]
+pub struct OidcProviderConfig {
+  pub name: String,
+  pub issuer: String,
+  pub client_id: String,
+  pub client_secret: String,
+  pub redirect_uri: String,
+  #[serde(default = "default_scope")]
+  pub scope: Vec<String>,
+}
+
+impl OidcConfig {
+  pub fn provider(&self, name: &str) -> Option<&OidcProviderConfig> {
+    self.providers.iter().find(|p| p.name == name)
+  }
+}
+
+impl OidcProviderConfig {
+  pub fn discovery_url(&self) -> String {
+    format!(
+      "{}/.well-known/openid-configuration",
+      self.issuer.trim_end_matches('/')
+    )
+  }
+}
+
+fn default_scope() -> Vec<String> {
+  ["openid", "email", "profile"].map(String::from).to_vec()
+}
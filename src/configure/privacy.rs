@@ -0,0 +1,8 @@
+use serde::Deserialize;
+
+/// When enabled login, forgot password and register answer the same way
+/// whether or not an account exists for the email.
+#[derive(Debug, Deserialize, Clone)]
+pub struct PrivacyConfig {
+  pub enable: bool,
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::configure::deserialize::deserialize_duration;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct RateLimitConfig {
+  pub enable: bool,
+  #[serde(default)]
+  pub policies: Vec<RateLimitPolicy>,
+}
+
+/// Allows `limit` requests per `period` for every distinct value of `key` on a route.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RateLimitPolicy {
+  pub route: String,
+  #[serde(default)]
+  pub method: Option<String>,
+  pub key: RateLimitKeyKind,
+  pub limit: u32,
+  #[serde(deserialize_with = "deserialize_duration")]
+  pub period: Duration,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, strum::Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum RateLimitKeyKind {
+  // client ip address
+  Ip,
+  // email or user id sent in the query or body
+  Account,
+  // every caller of the route
+  Route,
+}
+
+impl RateLimitPolicy {
+  pub fn matches(&self, route: &str, method: &str) -> bool {
+    self.route == route
+      && self
+        .method
+        .as_ref()
+        .is_none_or(|m| m.eq_ignore_ascii_case(method))
+  }
+
+  pub fn emission_interval(&self) -> Duration {
+    self.period / self.limit.max(1)
+  }
+
+  pub fn burst_tolerance(&self) -> Duration {
+    self.period.saturating_sub(self.emission_interval())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_rate_limit_policy() {
+    let policy = RateLimitPolicy {
+      route: "/api/v1/user/password".to_string(),
+      method: Some("PUT".to_string()),
+      key: RateLimitKeyKind::Ip,
+      limit: 5,
+      period: Duration::from_secs(60),
+    };
+    assert!(policy.matches("/api/v1/user/password", "put"));
+    assert!(!policy.matches("/api/v1/user/password", "GET"));
+    assert!(!policy.matches("/api/v1/user/login", "PUT"));
+    assert_eq!(policy.emission_interval(), Duration::from_secs(12));
+    assert_eq!(policy.burst_tolerance(), Duration::from_secs(48));
+  }
+
+  #[test]
+  fn test_read_rate_limit_config() {
+    let policy = crate::constant::CONFIG
+      .rate_limit
+      .policies
+      .iter()
+      .find(|p| p.matches("/api/v1/user/login2fa", "POST"))
+      .unwrap();
+    assert_eq!(policy.key, RateLimitKeyKind::Account);
+  }
+}
@@ -0,0 +1,27 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct RegistrationConfig {
+  pub mode: RegistrationMode,
+  /// Lets users without the admin role invite others as regular users.
+  pub allow_user_invitations: bool,
+  /// When not empty only emails of these domains, or their subdomains, may register.
+  #[serde(default)]
+  pub allowed_domains: Vec<String>,
+  #[serde(default)]
+  pub denied_domains: Vec<String>,
+  pub block_disposable_email: bool,
+  /// New accounts can not login until an admin approves them, invited accounts are approved.
+  pub require_approval: bool,
+}
+
+/// Who may create an account through the register endpoint.
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, strum::Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum RegistrationMode {
+  Open,
+  // only with a valid invitation code
+  InviteOnly,
+  Closed,
+}
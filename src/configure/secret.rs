@@ -1,37 +1,133 @@
 use std::{fs, path::PathBuf};
 
+use base64::{Engine, engine::general_purpose::STANDARD};
 use serde::Deserialize;
 
 use crate::util;
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct SecretConfig {
-  pub private_access_key: PathBuf,
-  pub public_access_key: PathBuf,
-  pub private_refresh_key: PathBuf,
-  pub public_refresh_key: PathBuf,
+  #[serde(default)]
+  pub algorithm: JwtAlgorithm,
+  pub issuer: String,
+  pub audience: Vec<String>,
+  pub private_access_key: KeySource,
+  pub public_access_key: KeySource,
+  pub private_refresh_key: KeySource,
+  pub public_refresh_key: KeySource,
+  #[serde(default)]
+  pub retired_access_keys: Vec<KeySource>,
+  #[serde(default)]
+  pub retired_refresh_keys: Vec<KeySource>,
+  pub totp_key: String,
+  // encrypts the codes and links of queued messages until they are sent
+  pub message_key: String,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JwtAlgorithm {
+  #[default]
+  RS256,
+  PS256,
+  ES256,
+  EdDSA,
+}
+
+impl From<JwtAlgorithm> for jsonwebtoken::Algorithm {
+  fn from(value: JwtAlgorithm) -> Self {
+    match value {
+      JwtAlgorithm::RS256 => jsonwebtoken::Algorithm::RS256,
+      JwtAlgorithm::PS256 => jsonwebtoken::Algorithm::PS256,
+      JwtAlgorithm::ES256 => jsonwebtoken::Algorithm::ES256,
+      JwtAlgorithm::EdDSA => jsonwebtoken::Algorithm::EdDSA,
+    }
+  }
+}
+
+/// Where a PEM key is loaded from: an inline PEM, `env:NAME` for a base64
+/// encoded PEM in an environment variable, or a path relative to the project root.
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+#[serde(from = "String")]
+pub enum KeySource {
+  Pem(String),
+  Env(String),
+  File(PathBuf),
+}
+
+impl From<String> for KeySource {
+  fn from(value: String) -> Self {
+    if let Some(name) = value.strip_prefix("env:") {
+      Self::Env(name.to_string())
+    } else if value.trim_start().starts_with("-----BEGIN") {
+      Self::Pem(value)
+    } else {
+      Self::File(PathBuf::from(value))
+    }
+  }
+}
+
+impl KeySource {
+  pub fn read(&self) -> Result<String, std::io::Error> {
+    match self {
+      Self::Pem(pem) => Ok(pem.clone()),
+      Self::Env(name) => {
+        let value = std::env::var(name)
+          .map_err(|e| std::io::Error::new(std::io::ErrorKind::NotFound, format!("{name}: {e}")))?;
+        let pem = STANDARD
+          .decode(value.trim())
+          .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        String::from_utf8(pem).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+      }
+      Self::File(path) => fs::read_to_string(util::dir::get_project_root()?.join(path)),
+    }
+  }
 }
 
 impl SecretConfig {
   pub fn read_private_access_key(&self) -> Result<String, std::io::Error> {
-    fs::read_to_string(util::dir::get_project_root()?.join(&self.private_access_key))
+    self.private_access_key.read()
   }
 
   pub fn read_public_access_key(&self) -> Result<String, std::io::Error> {
-    fs::read_to_string(util::dir::get_project_root()?.join(&self.public_access_key))
+    self.public_access_key.read()
   }
 
   pub fn read_private_refresh_key(&self) -> Result<String, std::io::Error> {
-    fs::read_to_string(util::dir::get_project_root()?.join(&self.private_refresh_key))
+    self.private_refresh_key.read()
   }
 
   pub fn read_public_refresh_key(&self) -> Result<String, std::io::Error> {
-    fs::read_to_string(util::dir::get_project_root()?.join(&self.public_refresh_key))
+    self.public_refresh_key.read()
+  }
+
+  pub fn read_access_public_keys(&self) -> Result<Vec<String>, std::io::Error> {
+    let mut keys = vec![self.read_public_access_key()?];
+    for key in &self.retired_access_keys {
+      keys.push(key.read()?);
+    }
+    Ok(keys)
+  }
+
+  pub fn read_refresh_public_keys(&self) -> Result<Vec<String>, std::io::Error> {
+    let mut keys = vec![self.read_public_refresh_key()?];
+    for key in &self.retired_refresh_keys {
+      keys.push(key.read()?);
+    }
+    Ok(keys)
+  }
+
+  pub fn read_totp_key(&self) -> Result<Vec<u8>, base64::DecodeError> {
+    STANDARD.decode(&self.totp_key)
+  }
+
+  pub fn read_message_key(&self) -> Result<Vec<u8>, base64::DecodeError> {
+    STANDARD.decode(&self.message_key)
   }
 }
 
 #[cfg(test)]
 mod tests {
+  use super::*;
   use crate::constant::CONFIG;
 
   #[test]
@@ -57,4 +153,47 @@ mod tests {
     let key = CONFIG.secret.read_public_refresh_key().unwrap();
     assert!(!key.is_empty())
   }
+
+  #[test]
+  fn test_read_access_public_keys() {
+    let keys = CONFIG.secret.read_access_public_keys().unwrap();
+    assert_eq!(keys.len(), 1 + CONFIG.secret.retired_access_keys.len())
+  }
+
+  #[test]
+  fn test_read_totp_key() {
+    let key = CONFIG.secret.read_totp_key().unwrap();
+    assert_eq!(key.len(), 32)
+  }
+
+  #[test]
+  fn test_read_message_key() {
+    let key = CONFIG.secret.read_message_key().unwrap();
+    assert_eq!(key.len(), 32)
+  }
+
+  #[test]
+  fn test_key_source_from_string() {
+    let pem = "-----BEGIN PUBLIC KEY-----\nMCow\n-----END PUBLIC KEY-----".to_string();
+    assert_eq!(KeySource::from(pem.clone()), KeySource::Pem(pem.clone()));
+    assert_eq!(
+      KeySource::from("env:APP_TEST_KEY".to_string()),
+      KeySource::Env("APP_TEST_KEY".to_string())
+    );
+    assert_eq!(
+      KeySource::from("static/key/public_access_rsa_key.pem".to_string()),
+      KeySource::File(PathBuf::from("static/key/public_access_rsa_key.pem"))
+    );
+    assert_eq!(KeySource::Pem(pem.clone()).read().unwrap(), pem);
+  }
+
+  #[test]
+  fn test_key_source_read_env() {
+    let pem = CONFIG.secret.read_public_access_key().unwrap();
+    let name = "APP_TEST_KEY_SOURCE_READ_ENV";
+    unsafe { std::env::set_var(name, STANDARD.encode(&pem)) };
+    assert_eq!(KeySource::Env(name.to_string()).read().unwrap(), pem);
+    unsafe { std::env::remove_var(name) };
+    assert!(KeySource::Env(name.to_string()).read().is_err());
+  }
 }
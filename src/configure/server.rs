@@ -1,11 +1,36 @@
-use std::net::{AddrParseError, SocketAddr};
+use std::net::{AddrParseError, IpAddr, SocketAddr};
 
 use serde::Deserialize;
 
+use super::secret::KeySource;
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct ServerConfig {
   pub addr: String,
   pub port: u16,
+  #[serde(default)]
+  pub tls: TlsConfig,
+  // proxies whose `X-Forwarded-For` header names the client address
+  #[serde(default)]
+  pub trusted_proxies: Vec<IpAddr>,
+  // the URL users and clients reach the server at, e.g. of the proxy in front of it
+  #[serde(default)]
+  pub public_url: Option<String>,
+}
+
+/// TLS terminated by the server itself. Clients may present a certificate signed
+/// by `client_ca`, which signs in the service account it names.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct TlsConfig {
+  pub enabled: bool,
+  // PEM of the server certificate followed by its chain
+  pub certificate: Option<KeySource>,
+  pub private_key: Option<KeySource>,
+  // client certificates are not requested without it
+  pub client_ca: Option<KeySource>,
+  // binds tokens issued to a client with a certificate to it (RFC 8705)
+  #[serde(default)]
+  pub certificate_bound_tokens: bool,
 }
 
 impl ServerConfig {
@@ -14,8 +39,18 @@ impl ServerConfig {
   }
 
   pub fn get_http_addr(&self) -> String {
-    format!("http://{}:{}", self.addr, self.port)
+    let scheme = if self.tls.enabled { "https" } else { "http" };
+    format!("{scheme}://{}:{}", self.addr, self.port)
+  }
+
+  /// Base of the links handed to users and clients, the bind address when no public URL is set.
+  pub fn get_public_url(&self) -> String {
+    match &self.public_url {
+      Some(url) => url.trim_end_matches('/').to_string(),
+      None => self.get_http_addr(),
+    }
   }
+
   pub fn get_socket_addr(&self) -> Result<SocketAddr, AddrParseError> {
     self.get_addr().parse()
   }
@@ -28,10 +63,29 @@ pub mod tests {
 
   #[test]
   pub fn app_config_http_addr_test() {
-    let config = ServerConfig {
+    let mut config = ServerConfig {
       addr: "127.0.0.1".to_string(),
       port: 1024,
+      tls: TlsConfig::default(),
+      trusted_proxies: vec![],
+      public_url: None,
     };
     assert_eq!(config.get_http_addr(), "http://127.0.0.1:1024");
+    config.tls.enabled = true;
+    assert_eq!(config.get_http_addr(), "https://127.0.0.1:1024");
+  }
+
+  #[test]
+  pub fn app_config_public_url_test() {
+    let mut config = ServerConfig {
+      addr: "0.0.0.0".to_string(),
+      port: 8080,
+      tls: TlsConfig::default(),
+      trusted_proxies: vec![],
+      public_url: None,
+    };
+    assert_eq!(config.get_public_url(), "http://0.0.0.0:8080");
+    config.public_url = Some("https://api.example.com/".to_string());
+    assert_eq!(config.get_public_url(), "https://api.example.com");
   }
 }
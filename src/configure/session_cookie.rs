@@ -0,0 +1,34 @@
+use axum_extra::extract::cookie::SameSite;
+use serde::Deserialize;
+
+/// Browser session mode: the tokens of a login are set as `HttpOnly` cookies
+/// instead of being returned in the body, and requests sent with them have to
+/// echo the CSRF cookie in a header.
+#[derive(Debug, Deserialize, Clone)]
+pub struct SessionCookieConfig {
+  pub enable: bool,
+  // also keeps the access token in a cookie, otherwise it stays in the body
+  pub access_token: bool,
+  pub secure: bool,
+  pub same_site: CookieSameSite,
+  #[serde(default)]
+  pub domain: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CookieSameSite {
+  Strict,
+  Lax,
+  None,
+}
+
+impl From<CookieSameSite> for SameSite {
+  fn from(value: CookieSameSite) -> Self {
+    match value {
+      CookieSameSite::Strict => SameSite::Strict,
+      CookieSameSite::Lax => SameSite::Lax,
+      CookieSameSite::None => SameSite::None,
+    }
+  }
+}
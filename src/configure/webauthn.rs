@@ -0,0 +1,11 @@
+use serde::Deserialize;
+
+/// The relying party passkeys are registered to, assertions are only accepted
+/// from the listed origins.
+#[derive(Debug, Deserialize, Clone)]
+pub struct WebauthnConfig {
+  // the domain of the origins, or a registrable suffix of it
+  pub rp_id: String,
+  pub rp_name: String,
+  pub origins: Vec<String>,
+}
@@ -3,4 +3,7 @@ use serde::Deserialize;
 #[derive(Debug, Deserialize, Clone)]
 pub struct WorkerConfig {
   pub failed_task_delay: u64,
+  /// Seconds after which a never activated account is deleted.
+  pub purge_inactive_user_after: u64,
+  pub purge_task_interval: u64,
 }
@@ -1,25 +1,93 @@
-use jsonwebtoken::{DecodingKey, EncodingKey};
-use std::{path::PathBuf, sync::LazyLock, time::Duration};
+use std::{collections::HashSet, path::PathBuf, sync::LazyLock, time::Duration};
 use utoipa::OpenApi;
 
 use crate::{
   client::{ClientBuilder, email::EmailClient, http::HttpClient, redis::RedisClient},
   configure::{env::get_profile, get_static_dir, template::TemplateEngine},
   handler::openapi::ApiDoc,
+  util::{claim::ENCODE_HEADER, jwk::KeyRing},
 };
 
 pub const CODE_LEN: usize = 5;
+pub const RECOVERY_CODE_LEN: usize = 10;
+pub const RECOVERY_CODE_COUNT: usize = 10;
+pub const API_KEY_PREFIX: &str = "rfa";
+pub const API_KEY_ID_LEN: usize = 8;
+pub const API_KEY_SECRET_LEN: usize = 32;
+pub const INVITATION_CODE_LEN: usize = 16;
+pub const API_KEY_SCHEME: &str = "ApiKey";
+pub const MAGIC_LINK_AUDIENCE: &str = "magic-link";
+pub const OIDC_STATE_LEN: usize = 32;
+// tries of a random suffix before giving up on a free username for a new OIDC user
+pub const OIDC_USERNAME_ATTEMPTS: usize = 5;
+pub const PKCE_VERIFIER_LEN: usize = 64;
+pub const AUTHORIZATION_CODE_LEN: usize = 32;
+pub const DEVICE_CODE_LEN: usize = 32;
+pub const USER_CODE_LEN: usize = 8;
+// consonants only, so a user code neither spells words nor mixes up 0 and O
+pub const USER_CODE_CHARSET: &[u8] = b"BCDFGHJKLMNPQRSTVWXZ";
+pub const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+pub const OAUTH_CLIENT_ID_LEN: usize = 24;
+pub const OAUTH_CLIENT_SECRET_LEN: usize = 48;
+pub const PASSKEY_CHALLENGE_LEN: usize = 32;
+pub const DPOP_SCHEME: &str = "DPoP";
+pub const DPOP_HEADER: &str = "dpop";
+pub const DPOP_PROOF_TYPE: &str = "dpop+jwt";
+pub const DPOP_JTI_MAX_LEN: usize = 128;
+pub const TLS_ACCEPT_QUEUE_LEN: usize = 128;
+pub const ACCESS_TOKEN_COOKIE: &str = "access_token";
+pub const REFRESH_TOKEN_COOKIE: &str = "refresh_token";
+pub const CSRF_COOKIE: &str = "csrf_token";
+pub const CSRF_HEADER: &str = "x-csrf-token";
+pub const CSRF_TOKEN_LEN: usize = 32;
+// the API routes only, so pages like the OAuth consent form never receive it
+pub const ACCESS_TOKEN_COOKIE_PATH: &str = "/api";
+pub const REFRESH_TOKEN_COOKIE_PATH: &str = "/api/v1/token/refresh";
+pub const RATE_LIMIT_MAX_BODY_SIZE: usize = 64 * 1024;
+pub const MAX_LOGIN_ATTEMPTS: u64 = 5;
+pub const MAX_CODE_ATTEMPTS: u64 = 5;
+pub const MAX_PASSWORD_CHECK_ATTEMPTS: u64 = 5;
+// ten years, the longest lifetime an API key may be created with
+pub const MAX_API_KEY_EXPIRE_SECS: u64 = 10 * 365 * 24 * 3600;
+// one year, the longest an invitation code may stay usable
+pub const MAX_INVITATION_EXPIRE_SECS: u64 = 365 * 24 * 3600;
 pub const CLIENT_TIMEOUT: Duration = Duration::from_secs(120);
+pub const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+// pause after a failed accept, e.g. out of file descriptors, as axum's serve does
+pub const TLS_ACCEPT_RETRY_DELAY: Duration = Duration::from_millis(100);
 pub const EXPIRE_SESSION_CODE_SECS: Duration = Duration::from_secs(2000);
 pub const EXPIRE_INVITATION_CODE_SECS: Duration = Duration::from_secs(86000);
-pub const EXPIRE_BLOCKED_EMAIL_SECS: Duration = Duration::from_secs(100);
+pub const EXPIRE_BLOCKED_EMAIL_SECS: Duration = Duration::from_secs(86400);
 pub const EXPIRE_FORGET_PASS_CODE_SECS: Duration = Duration::from_secs(100);
 pub const EXPIRE_TWO_FACTOR_CODE_SECS: Duration = Duration::from_secs(200);
+pub const EXPIRE_TOTP_ENROLL_SECS: Duration = Duration::from_secs(600);
+pub const EXPIRE_ACTIVE_CODE_SECS: Duration = Duration::from_secs(86400);
+pub const EXPIRE_ACTIVE_RESEND_SECS: Duration = Duration::from_secs(60);
+pub const EXPIRE_MAGIC_LINK_SECS: Duration = Duration::from_secs(600);
+pub const EXPIRE_AUTHORIZATION_CODE_SECS: Duration = Duration::from_secs(60);
+pub const EXPIRE_DEVICE_CODE_SECS: Duration = Duration::from_secs(600);
+pub const DEVICE_POLL_INTERVAL_SECS: Duration = Duration::from_secs(5);
+pub const EXPIRE_PASSKEY_CHALLENGE_SECS: Duration = Duration::from_secs(300);
+// a proof is accepted this long before or after its `iat`
+pub const DPOP_PROOF_LEEWAY_SECS: Duration = Duration::from_secs(60);
+// covers the whole window a proof is accepted in, so its `jti` is not forgotten early
+pub const EXPIRE_DPOP_PROOF_SECS: Duration = Duration::from_secs(120);
+pub const EXPIRE_OIDC_STATE_SECS: Duration = Duration::from_secs(600);
+pub const EXPIRE_EMAIL_CHANGE_CODE_SECS: Duration = Duration::from_secs(600);
+pub const EXPIRE_LOGIN_ATTEMPT_SECS: Duration = Duration::from_secs(900);
+pub const EXPIRE_CODE_ATTEMPT_SECS: Duration = Duration::from_secs(900);
+pub const EXPIRE_PASSWORD_CHECK_ATTEMPT_SECS: Duration = Duration::from_secs(900);
+pub const EXPIRE_LOCKOUT_SECS: Duration = Duration::from_secs(900);
 pub const EXPIRE_BEARER_TOKEN_SECS: Duration = Duration::from_secs(600);
 pub const EXPIRE_REFRESH_TOKEN_SECS: Duration = Duration::from_secs(3600);
 pub const QUEUE_EMPTY_DELAY_SECS: Duration = Duration::from_secs(60);
 pub const COMPLETE_TASK_DELAY_SECS: Duration = Duration::from_secs(10);
 pub const CHECK_EMAIL_MESSAGE: &str = "Please check you email.";
+pub const CHECK_AUTHENTICATOR_MESSAGE: &str = "Please enter the code from your authenticator app.";
+pub const TOTP_DIGITS: u32 = 6;
+pub const TOTP_STEP_SECS: u64 = 30;
+pub const TOTP_SKEW_STEPS: u64 = 1;
+pub const TOTP_SECRET_LEN: usize = 20;
 pub const AUTHORIZATION: &str = "Authorization";
 pub const BEARER: &str = "Bearer";
 pub const APP_DOMAIN: &str = "rustfulapi.com";
@@ -38,23 +106,32 @@ pub static EMAIL: LazyLock<EmailClient> =
   LazyLock::new(|| EmailClient::build_from_config(&CONFIG).unwrap());
 pub const MAX_RETRY: u32 = 10;
 pub const MINIMUM_DELAY_TIME: std::time::Duration = std::time::Duration::from_millis(100);
-pub static REFRESH_TOKEN_ENCODE_KEY: LazyLock<EncodingKey> = LazyLock::new(|| {
-  let key = CONFIG.secret.read_private_refresh_key().unwrap();
-  EncodingKey::from_rsa_pem(key.as_bytes()).unwrap()
+pub static REFRESH_TOKEN_KEY_RING: LazyLock<KeyRing> = LazyLock::new(|| {
+  let private_key = CONFIG.secret.read_private_refresh_key().unwrap();
+  let public_keys = CONFIG.secret.read_refresh_public_keys().unwrap();
+  KeyRing::new(&ENCODE_HEADER, &private_key, &public_keys).unwrap()
 });
-pub static REFRESH_TOKEN_DECODE_KEY: LazyLock<DecodingKey> = LazyLock::new(|| {
-  let key = CONFIG.secret.read_public_refresh_key().unwrap();
-  DecodingKey::from_rsa_pem(key.as_bytes()).unwrap()
+pub static ACCESS_TOKEN_KEY_RING: LazyLock<KeyRing> = LazyLock::new(|| {
+  let private_key = CONFIG.secret.read_private_access_key().unwrap();
+  let public_keys = CONFIG.secret.read_access_public_keys().unwrap();
+  KeyRing::new(&ENCODE_HEADER, &private_key, &public_keys).unwrap()
 });
-pub static ACCESS_TOKEN_ENCODE_KEY: LazyLock<EncodingKey> = LazyLock::new(|| {
-  let key = CONFIG.secret.read_private_access_key().unwrap();
-  EncodingKey::from_rsa_pem(key.as_bytes()).unwrap()
-});
-pub static ACCESS_TOKEN_DECODE_KEY: LazyLock<DecodingKey> = LazyLock::new(|| {
-  let key = CONFIG.secret.read_public_access_key().unwrap();
-  DecodingKey::from_rsa_pem(key.as_bytes()).unwrap()
+/// Verified against when no account matches, so a missing account takes as long as a wrong password.
+pub static DUMMY_PASSWORD_HASH: LazyLock<String> = LazyLock::new(|| {
+  crate::util::hash::argon_hash(crate::util::random::generate_random_string(16)).unwrap()
 });
+pub static TOTP_ENCRYPT_KEY: LazyLock<Vec<u8>> =
+  LazyLock::new(|| CONFIG.secret.read_totp_key().unwrap());
+pub static MESSAGE_ENCRYPT_KEY: LazyLock<Vec<u8>> =
+  LazyLock::new(|| CONFIG.secret.read_message_key().unwrap());
 pub static API_DOC: LazyLock<utoipa::openapi::OpenApi> = LazyLock::new(ApiDoc::openapi);
+pub static DISPOSABLE_EMAIL_DOMAINS: LazyLock<HashSet<&'static str>> = LazyLock::new(|| {
+  include_str!("../../static/disposable_domains.txt")
+    .lines()
+    .map(str::trim)
+    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    .collect()
+});
 pub static TEMPLATE_ENGIN: LazyLock<TemplateEngine> = LazyLock::new(|| {
   let path = get_static_dir()
     .unwrap()
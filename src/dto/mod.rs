@@ -69,6 +69,72 @@ pub enum Template {
     user_id: Uuid,
     code: String,
   },
+  RecoveryCodeUsed {
+    username: String,
+    user_id: Uuid,
+    remaining: String,
+  },
+  AccountLocked {
+    username: String,
+    user_id: Uuid,
+    locked_until: String,
+  },
+  PasswordChanged {
+    username: String,
+    user_id: Uuid,
+    changed_at: String,
+  },
+  AccountExists {
+    username: String,
+    user_id: Uuid,
+  },
+  EmailChangeCode {
+    username: String,
+    user_id: Uuid,
+    code: String,
+  },
+  EmailChangeNotice {
+    username: String,
+    user_id: Uuid,
+    new_email: String,
+  },
+  MagicLink {
+    username: String,
+    user_id: Uuid,
+    link: String,
+  },
+  MagicLinkConfirm {
+    token: String,
+  },
+  Invitation {
+    username: String,
+    user_id: Uuid,
+    code: String,
+  },
+  RegistrationApproved {
+    username: String,
+    user_id: Uuid,
+  },
+  RegistrationRejected {
+    username: String,
+  },
+  Authorize {
+    client_name: String,
+    scope: Vec<String>,
+    params: Box<AuthorizeParams>,
+    email: String,
+    error: Option<String>,
+    code_required: bool,
+  },
+  DeviceVerification {
+    user_code: String,
+    client_name: Option<String>,
+    scope: Vec<String>,
+    email: String,
+    error: Option<String>,
+    message: Option<String>,
+    code_required: bool,
+  },
 }
 
 impl Template {
@@ -105,6 +171,128 @@ impl Template {
         ctx.insert("user_id", user_id);
         (ctx, "forget_password.html")
       }
+      Self::RecoveryCodeUsed {
+        username,
+        user_id,
+        remaining,
+      } => {
+        ctx.insert("username", username);
+        ctx.insert("user_id", user_id);
+        ctx.insert("remaining", remaining);
+        (ctx, "recovery_code_used.html")
+      }
+      Self::AccountLocked {
+        username,
+        user_id,
+        locked_until,
+      } => {
+        ctx.insert("username", username);
+        ctx.insert("user_id", user_id);
+        ctx.insert("locked_until", locked_until);
+        (ctx, "account_locked.html")
+      }
+      Self::PasswordChanged {
+        username,
+        user_id,
+        changed_at,
+      } => {
+        ctx.insert("username", username);
+        ctx.insert("user_id", user_id);
+        ctx.insert("changed_at", changed_at);
+        (ctx, "password_changed.html")
+      }
+      Self::AccountExists { username, user_id } => {
+        ctx.insert("username", username);
+        ctx.insert("user_id", user_id);
+        (ctx, "account_exists.html")
+      }
+      Self::EmailChangeCode {
+        username,
+        user_id,
+        code,
+      } => {
+        ctx.insert("username", username);
+        ctx.insert("user_id", user_id);
+        ctx.insert("code", code);
+        (ctx, "email_change_code.html")
+      }
+      Self::EmailChangeNotice {
+        username,
+        user_id,
+        new_email,
+      } => {
+        ctx.insert("username", username);
+        ctx.insert("user_id", user_id);
+        ctx.insert("new_email", new_email);
+        (ctx, "email_change_notice.html")
+      }
+      Self::MagicLink {
+        username,
+        user_id,
+        link,
+      } => {
+        ctx.insert("username", username);
+        ctx.insert("user_id", user_id);
+        ctx.insert("link", link);
+        (ctx, "magic_link.html")
+      }
+      Self::MagicLinkConfirm { token } => {
+        ctx.insert("token", token);
+        (ctx, "magic_link_confirm.html")
+      }
+      Self::Invitation {
+        username,
+        user_id,
+        code,
+      } => {
+        ctx.insert("username", username);
+        ctx.insert("user_id", user_id);
+        ctx.insert("code", code);
+        (ctx, "invitation.html")
+      }
+      Self::RegistrationApproved { username, user_id } => {
+        ctx.insert("username", username);
+        ctx.insert("user_id", user_id);
+        (ctx, "registration_approved.html")
+      }
+      Self::RegistrationRejected { username } => {
+        ctx.insert("username", username);
+        (ctx, "registration_rejected.html")
+      }
+      Self::Authorize {
+        client_name,
+        scope,
+        params,
+        email,
+        error,
+        code_required,
+      } => {
+        ctx.insert("client_name", client_name);
+        ctx.insert("scope", scope);
+        ctx.insert("params", params);
+        ctx.insert("email", email);
+        ctx.insert("error", error);
+        ctx.insert("code_required", code_required);
+        (ctx, "authorize.html")
+      }
+      Self::DeviceVerification {
+        user_code,
+        client_name,
+        scope,
+        email,
+        error,
+        message,
+        code_required,
+      } => {
+        ctx.insert("user_code", user_code);
+        ctx.insert("client_name", client_name);
+        ctx.insert("scope", scope);
+        ctx.insert("email", email);
+        ctx.insert("error", error);
+        ctx.insert("message", message);
+        ctx.insert("code_required", code_required);
+        (ctx, "device.html")
+      }
     }
   }
 }
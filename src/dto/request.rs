@@ -6,6 +6,9 @@ use strum::Display;
 use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
+use crate::constant::{MAX_API_KEY_EXPIRE_SECS, MAX_INVITATION_EXPIRE_SECS};
+use crate::entity::{role::RoleUser, scope::Scope, two_factor::TwoFactorMethod};
+
 #[derive(Debug, Deserialize, Serialize, Dummy, Validate, utoipa::ToSchema)]
 pub struct RegisterRequest {
   #[dummy(faker = "Username()")]
@@ -17,6 +20,9 @@ pub struct RegisterRequest {
   #[dummy(faker = "Password(8..100)")]
   #[garde(length(min = 8))]
   pub password: String,
+  #[dummy(expr = "None")]
+  #[garde(skip)]
+  pub invitation_code: Option<String>,
 }
 
 impl RegisterRequest {
@@ -25,6 +31,7 @@ impl RegisterRequest {
       password: password.to_string(),
       username: username.to_string(),
       email: email.to_string(),
+      invitation_code: None,
     }
   }
 
@@ -81,6 +88,26 @@ pub struct ActiveRequest {
   pub user_id: Uuid,
 }
 
+#[derive(Debug, Serialize, Deserialize, Validate, Dummy, ToSchema)]
+pub struct ResendActiveCodeRequest {
+  #[dummy(faker = "SafeEmail()")]
+  #[garde(email)]
+  pub email: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Dummy, ToSchema, Validate)]
+pub struct MagicLinkRequest {
+  #[dummy(faker = "SafeEmail()")]
+  #[garde(email)]
+  pub email: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Validate, IntoParams)]
+pub struct MagicLinkQueryParam {
+  #[garde(length(min = 1))]
+  pub token: String,
+}
+
 #[derive(Debug, Deserialize, Serialize, Dummy, ToSchema, Validate)]
 #[serde(tag = "type")]
 pub struct LoginRequest {
@@ -96,10 +123,98 @@ pub struct LoginRequest {
 pub struct Login2faRequest {
   #[garde(skip)]
   pub user_id: Uuid,
-  #[garde(length(min = 5))]
+  #[garde(inner(length(min = 5)))]
+  pub code: Option<String>,
+  // a passkey assertion answers in place of the code
+  #[serde(default)]
+  #[garde(skip)]
+  pub passkey: Option<PasskeyAssertionCredential>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Validate, Dummy)]
+pub struct Login2faEmailRequest {
+  #[garde(skip)]
+  pub user_id: Uuid,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Validate, Dummy)]
+pub struct ConfirmTotpRequest {
+  #[garde(length(min = 6, max = 6))]
   pub code: String,
 }
 
+#[derive(Debug, Deserialize, Serialize, ToSchema, Validate, Dummy)]
+pub struct DisableTotpRequest {
+  // a code of the authenticator app or a recovery code
+  #[garde(length(min = 6, max = 64))]
+  pub code: String,
+}
+
+/// A new credential as serialized by `PublicKeyCredential.toJSON()`.
+#[derive(Debug, Deserialize, Serialize, ToSchema, Dummy, Clone)]
+pub struct PasskeyRegistrationCredential {
+  pub id: String,
+  pub response: PasskeyAttestationResponse,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Dummy, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyAttestationResponse {
+  #[serde(rename = "clientDataJSON")]
+  pub client_data_json: String,
+  pub attestation_object: String,
+}
+
+/// An assertion as serialized by `PublicKeyCredential.toJSON()`.
+#[derive(Debug, Deserialize, Serialize, ToSchema, Dummy, Clone)]
+pub struct PasskeyAssertionCredential {
+  pub id: String,
+  pub response: PasskeyAssertionResponse,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Dummy, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyAssertionResponse {
+  #[serde(rename = "clientDataJSON")]
+  pub client_data_json: String,
+  pub authenticator_data: String,
+  pub signature: String,
+  #[serde(default)]
+  pub user_handle: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Validate, Dummy)]
+pub struct RegisterPasskeyRequest {
+  #[garde(length(min = 1, max = 64))]
+  pub name: String,
+  #[garde(skip)]
+  pub credential: PasskeyRegistrationCredential,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Validate, Dummy, Default)]
+pub struct PasskeyLoginOptionsRequest {
+  // set to answer the second factor of a pending password login
+  #[serde(default)]
+  #[garde(skip)]
+  pub user_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Validate, Dummy)]
+pub struct PasskeyLoginRequest {
+  #[garde(skip)]
+  pub credential: PasskeyAssertionCredential,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Validate, Dummy)]
+pub struct CreateApiKeyRequest {
+  #[garde(length(min = 1, max = 64))]
+  pub name: String,
+  #[garde(skip)]
+  pub scope: Option<Vec<Scope>>,
+  #[garde(range(min = 60, max = MAX_API_KEY_EXPIRE_SECS))]
+  pub expire_secs: Option<u64>,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema, Validate, Dummy, IntoParams)]
 pub struct RefreshTokenRequest {
   #[garde(length(min = 30))]
@@ -111,6 +226,20 @@ pub struct TokenInfoRequest {
   #[garde(length(min = 30))]
   pub token: String,
 }
+#[derive(Debug, Deserialize, Serialize, ToSchema, Validate, Dummy)]
+pub struct CreateInvitationRequest {
+  // email invited, the code is sent to it and only it may use the code
+  #[dummy(faker = "SafeEmail()")]
+  #[garde(inner(email))]
+  pub email: Option<String>,
+  #[garde(skip)]
+  pub role: Option<RoleUser>,
+  #[garde(range(min = 1, max = 1000))]
+  pub max_uses: Option<i32>,
+  #[garde(range(min = 60, max = MAX_INVITATION_EXPIRE_SECS))]
+  pub expire_secs: Option<u64>,
+}
+
 #[derive(Debug, Deserialize, ToSchema, Validate, Dummy, IntoParams)]
 pub struct ForgetPasswordQueryParam {
   #[dummy(faker = "SafeEmail()")]
@@ -129,17 +258,38 @@ pub struct SetPasswordRequest {
   pub user_id: Uuid,
 }
 
+#[derive(Debug, Deserialize, Serialize, ToSchema, Validate, Dummy)]
+pub struct ChangePasswordRequest {
+  #[garde(length(min = 1))]
+  pub current_password: String,
+  #[dummy(faker = "Password(8..100)")]
+  #[garde(length(min = 8))]
+  pub new_password: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Validate, Dummy)]
+pub struct ChangeEmailRequest {
+  #[dummy(faker = "SafeEmail()")]
+  #[garde(email)]
+  pub email: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Validate, Dummy)]
+pub struct ConfirmEmailRequest {
+  #[garde(length(min = 5))]
+  pub code: String,
+}
+
 #[derive(Debug, Deserialize, Serialize, ToSchema, Validate, Dummy, Default)]
 pub struct UpdateProfileRequest {
   #[dummy(faker = "Username()")]
   #[garde(skip)]
   pub username: Option<String>,
-  #[dummy(faker = "Password(8..100)")]
-  #[garde(length(min = 8))]
-  pub password: Option<String>,
   #[garde(skip)]
   pub is_2fa: Option<bool>,
   #[garde(skip)]
+  pub two_factor_method: Option<TwoFactorMethod>,
+  #[garde(skip)]
   pub is_private: Option<bool>,
 }
 
@@ -171,3 +321,102 @@ mod tests {
     assert!(req.validate().is_ok());
   }
 }
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Validate, Dummy)]
+pub struct OidcCallbackRequest {
+  #[garde(length(min = 1))]
+  pub code: String,
+  #[garde(length(min = 1))]
+  pub state: String,
+}
+
+/// Parameters of an authorization request, the login and consent page posts them back.
+#[derive(Debug, Deserialize, Serialize, ToSchema, IntoParams, Dummy, Clone, Default)]
+#[serde(default)]
+#[into_params(parameter_in = Query)]
+pub struct AuthorizeParams {
+  pub response_type: String,
+  pub client_id: String,
+  pub redirect_uri: String,
+  pub scope: Option<String>,
+  pub state: Option<String>,
+  pub nonce: Option<String>,
+  pub code_challenge: Option<String>,
+  pub code_challenge_method: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Dummy, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthorizeDecision {
+  Allow,
+  Deny,
+}
+
+#[derive(Deserialize, Serialize, ToSchema, Dummy, Clone)]
+pub struct AuthorizeConsentRequest {
+  #[serde(flatten)]
+  pub params: AuthorizeParams,
+  #[serde(default)]
+  pub email: String,
+  #[serde(default)]
+  pub password: String,
+  #[serde(default)]
+  pub code: Option<String>,
+  pub decision: AuthorizeDecision,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Dummy, Clone, Default)]
+#[serde(default)]
+pub struct OauthTokenRequest {
+  pub grant_type: String,
+  pub code: Option<String>,
+  pub redirect_uri: Option<String>,
+  pub code_verifier: Option<String>,
+  pub refresh_token: Option<String>,
+  pub device_code: Option<String>,
+  pub client_id: Option<String>,
+  pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Validate, Dummy, Clone)]
+pub struct CreateOauthClientRequest {
+  #[garde(length(min = 1, max = 100))]
+  pub name: String,
+  #[garde(length(min = 1), inner(url))]
+  pub redirect_uris: Vec<String>,
+  // defaults to the scopes of a user when not given
+  #[garde(skip)]
+  pub scope: Option<Vec<Scope>>,
+  // a confidential client gets a secret, a public one must use PKCE
+  #[garde(skip)]
+  #[serde(default)]
+  pub confidential: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, Dummy, Clone, Default)]
+#[serde(default)]
+pub struct DeviceAuthorizationRequest {
+  pub client_id: Option<String>,
+  pub client_secret: Option<String>,
+  pub scope: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, ToSchema, IntoParams, Dummy, Clone, Default)]
+#[serde(default)]
+#[into_params(parameter_in = Query)]
+pub struct DeviceVerificationParams {
+  pub user_code: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema, Dummy, Clone)]
+pub struct DeviceVerificationRequest {
+  #[serde(default)]
+  pub user_code: String,
+  #[serde(default)]
+  pub email: String,
+  #[serde(default)]
+  pub password: String,
+  #[serde(default)]
+  pub code: Option<String>,
+  pub decision: AuthorizeDecision,
+}
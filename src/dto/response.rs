@@ -6,8 +6,9 @@ use uuid::Uuid;
 
 use crate::{
   constant::BEARER,
-  entity::{self, role::RoleUser},
+  entity::{self, role::RoleUser, scope::Scope, two_factor::TwoFactorMethod},
   error::AppResponseError,
+  service::redis::SessionValue,
 };
 
 #[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
@@ -23,6 +24,7 @@ pub struct GetUserResponse {
   pub role_name: RoleUser,
   pub is_active: bool,
   pub is_2fa: bool,
+  pub is_approved: bool,
   pub create_at: DateTime<Utc>,
 }
 
@@ -99,7 +101,10 @@ impl From<TokenResponse> for LoginResponse {
 #[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
 pub struct TokenResponse {
   pub token_type: String,
+  // left out when the browser session mode keeps the token in a cookie
+  #[serde(default, skip_serializing_if = "String::is_empty")]
   pub access_token: String,
+  #[serde(default, skip_serializing_if = "String::is_empty")]
   pub refresh_token: String,
   pub expire_in: u64,
 }
@@ -121,12 +126,148 @@ pub struct ForgetPasswordResponse {
   pub message: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy)]
+pub struct ChangeEmailResponse {
+  pub expire_in: u64,
+  pub message: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct SessionResponse {
+  pub id: Uuid,
+  pub user_agent: Option<String>,
+  pub ip: Option<String>,
+  pub current: bool,
+  pub create_at: DateTime<Utc>,
+  pub last_seen_at: DateTime<Utc>,
+}
+
+impl SessionResponse {
+  pub fn new(session: SessionValue, current_session_id: Uuid) -> Self {
+    Self {
+      id: session.session_id,
+      user_agent: session.user_agent,
+      ip: session.ip,
+      current: session.session_id == current_session_id,
+      create_at: session.create_at,
+      last_seen_at: session.last_seen_at,
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct SessionListResponse {
+  pub list: Vec<SessionResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct ApiKeyResponse {
+  pub id: Uuid,
+  pub name: String,
+  pub prefix: String,
+  pub scope: Vec<Scope>,
+  pub expire_at: Option<DateTime<Utc>>,
+  pub last_used_at: Option<DateTime<Utc>>,
+  pub create_at: DateTime<Utc>,
+}
+
+impl From<entity::api_key::Model> for ApiKeyResponse {
+  fn from(value: entity::api_key::Model) -> Self {
+    Self {
+      scope: value.scopes(),
+      id: value.id,
+      name: value.name,
+      prefix: value.prefix,
+      expire_at: value.expire_at,
+      last_used_at: value.last_used_at,
+      create_at: value.create_at,
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct CreateApiKeyResponse {
+  // returned only once, it can not be recovered later
+  pub key: String,
+  pub api_key: ApiKeyResponse,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct ApiKeyListResponse {
+  pub list: Vec<ApiKeyResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct InvitationResponse {
+  pub id: Uuid,
+  pub email: Option<String>,
+  pub role: RoleUser,
+  pub max_uses: i32,
+  pub use_count: i32,
+  pub expire_at: DateTime<Utc>,
+  pub create_at: DateTime<Utc>,
+}
+
+impl From<entity::invitation::Model> for InvitationResponse {
+  fn from(value: entity::invitation::Model) -> Self {
+    Self {
+      id: value.id,
+      email: value.email,
+      role: value.role,
+      max_uses: value.max_uses,
+      use_count: value.use_count,
+      expire_at: value.expire_at,
+      create_at: value.create_at,
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct CreateInvitationResponse {
+  // returned only once, it can not be recovered later
+  pub code: String,
+  pub invitation: InvitationResponse,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct InvitationListResponse {
+  pub list: Vec<InvitationResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy)]
+pub struct RecoveryCodesResponse {
+  pub recovery_codes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy)]
+pub struct UpdateProfileResponse {
+  pub message: String,
+  pub recovery_codes: Option<Vec<String>>,
+}
+
+impl UpdateProfileResponse {
+  pub fn new<S: Into<String>>(message: S, recovery_codes: Option<Vec<String>>) -> Self {
+    Self {
+      message: message.into(),
+      recovery_codes,
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy)]
+pub struct TotpEnrollResponse {
+  pub secret: String,
+  pub uri: String,
+  pub expire_in: u64,
+}
+
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct ProfileResponse {
   pub username: String,
   pub email: String,
   pub is_active: bool,
   pub is_2fa: bool,
+  pub two_factor_method: TwoFactorMethod,
   pub create_at: DateTime<Utc>,
 }
 
@@ -137,6 +278,7 @@ impl From<entity::user::Model> for ProfileResponse {
       email: user.email,
       is_active: user.is_active,
       is_2fa: user.is_2fa,
+      two_factor_method: user.two_factor_method,
       create_at: user.create_at,
     }
   }
@@ -151,6 +293,7 @@ impl From<entity::user::Model> for GetUserResponse {
       role_name: user.role,
       is_active: user.is_active,
       is_2fa: user.is_2fa,
+      is_approved: user.is_approved,
       create_at: user.create_at,
     }
   }
@@ -173,3 +316,223 @@ impl<R> AppResultResponse<R> {
     !self.is_ok()
   }
 }
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct OidcAuthorizeResponse {
+  pub authorization_url: String,
+  pub state: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct IdentityResponse {
+  pub id: Uuid,
+  pub provider: String,
+  pub email: Option<String>,
+  pub create_at: DateTime<Utc>,
+}
+
+impl From<entity::identity::Model> for IdentityResponse {
+  fn from(value: entity::identity::Model) -> Self {
+    Self {
+      id: value.id,
+      provider: value.provider,
+      email: value.email,
+      create_at: value.create_at,
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct IdentityListResponse {
+  pub list: Vec<IdentityResponse>,
+}
+
+/// Options for `navigator.credentials.create()`, binary members are base64url
+/// encoded as `PublicKeyCredential.parseCreationOptionsFromJSON()` expects.
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyCreationOptionsResponse {
+  pub challenge: String,
+  pub rp: PasskeyRelyingParty,
+  pub user: PasskeyUser,
+  pub pub_key_cred_params: Vec<PasskeyCredentialParameter>,
+  // milliseconds
+  pub timeout: u64,
+  pub exclude_credentials: Vec<PasskeyCredentialDescriptor>,
+  pub authenticator_selection: PasskeyAuthenticatorSelection,
+  pub attestation: String,
+}
+
+/// Options for `navigator.credentials.get()`, binary members are base64url
+/// encoded as `PublicKeyCredential.parseRequestOptionsFromJSON()` expects.
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyRequestOptionsResponse {
+  pub challenge: String,
+  pub rp_id: String,
+  // milliseconds
+  pub timeout: u64,
+  pub allow_credentials: Vec<PasskeyCredentialDescriptor>,
+  pub user_verification: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct PasskeyRelyingParty {
+  pub id: String,
+  pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyUser {
+  pub id: String,
+  pub name: String,
+  pub display_name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct PasskeyCredentialParameter {
+  #[serde(rename = "type")]
+  pub kind: String,
+  pub alg: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct PasskeyCredentialDescriptor {
+  #[serde(rename = "type")]
+  pub kind: String,
+  pub id: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PasskeyAuthenticatorSelection {
+  pub resident_key: String,
+  pub user_verification: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct PasskeyResponse {
+  pub id: Uuid,
+  pub name: String,
+  pub last_used_at: Option<DateTime<Utc>>,
+  pub create_at: DateTime<Utc>,
+}
+
+impl From<entity::passkey::Model> for PasskeyResponse {
+  fn from(value: entity::passkey::Model) -> Self {
+    Self {
+      id: value.id,
+      name: value.name,
+      last_used_at: value.last_used_at,
+      create_at: value.create_at,
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct PasskeyListResponse {
+  pub list: Vec<PasskeyResponse>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct OauthTokenResponse {
+  pub access_token: String,
+  pub token_type: String,
+  pub expires_in: u64,
+  pub refresh_token: String,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub id_token: Option<String>,
+  // left out when unchanged from the original grant
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub scope: Option<String>,
+}
+
+impl From<TokenResponse> for OauthTokenResponse {
+  fn from(value: TokenResponse) -> Self {
+    Self {
+      access_token: value.access_token,
+      token_type: value.token_type,
+      expires_in: value.expire_in,
+      refresh_token: value.refresh_token,
+      id_token: None,
+      scope: None,
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct DeviceAuthorizationResponse {
+  pub device_code: String,
+  pub user_code: String,
+  pub verification_uri: String,
+  pub verification_uri_complete: String,
+  pub expires_in: u64,
+  pub interval: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct UserInfoResponse {
+  pub sub: String,
+  pub email: String,
+  pub email_verified: bool,
+  pub preferred_username: String,
+}
+
+/// OpenID Provider metadata published for discovery.
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct OpenIdConfigurationResponse {
+  pub issuer: String,
+  pub authorization_endpoint: String,
+  pub token_endpoint: String,
+  pub userinfo_endpoint: String,
+  pub device_authorization_endpoint: String,
+  pub jwks_uri: String,
+  pub response_types_supported: Vec<String>,
+  pub grant_types_supported: Vec<String>,
+  pub subject_types_supported: Vec<String>,
+  pub id_token_signing_alg_values_supported: Vec<String>,
+  pub scopes_supported: Vec<String>,
+  pub token_endpoint_auth_methods_supported: Vec<String>,
+  pub code_challenge_methods_supported: Vec<String>,
+  pub dpop_signing_alg_values_supported: Vec<String>,
+  pub tls_client_certificate_bound_access_tokens: bool,
+  pub claims_supported: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct OauthClientResponse {
+  pub id: Uuid,
+  pub client_id: String,
+  pub name: String,
+  pub redirect_uris: Vec<String>,
+  pub scope: Vec<Scope>,
+  pub confidential: bool,
+  pub create_at: DateTime<Utc>,
+}
+
+impl From<entity::oauth_client::Model> for OauthClientResponse {
+  fn from(value: entity::oauth_client::Model) -> Self {
+    Self {
+      id: value.id,
+      client_id: value.client_id.clone(),
+      name: value.name.clone(),
+      redirect_uris: value.redirect_uris(),
+      scope: value.scopes(),
+      confidential: !value.is_public(),
+      create_at: value.create_at,
+    }
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct CreateOauthClientResponse {
+  pub client: OauthClientResponse,
+  // shown only once, the server keeps a hash of it
+  pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema, Dummy, Clone)]
+pub struct OauthClientListResponse {
+  pub list: Vec<OauthClientResponse>,
+}
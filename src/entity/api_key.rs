@@ -0,0 +1,66 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use fake::Dummy;
+use sea_orm::entity::prelude::*;
+
+use crate::entity::scope::Scope;
+use crate::error::ResourceType;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Dummy, DeriveEntityModel)]
+#[sea_orm(table_name = "api_key")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: Uuid,
+  pub user_id: Uuid,
+  #[sea_orm(column_type = "Text")]
+  pub name: String,
+  #[sea_orm(column_type = "Text", unique)]
+  pub prefix: String,
+  #[sea_orm(column_type = "Text")]
+  pub key_hash: String,
+  // space separated scopes
+  #[sea_orm(column_type = "Text")]
+  pub scope: String,
+  pub expire_at: Option<DateTime<Utc>>,
+  pub last_used_at: Option<DateTime<Utc>>,
+  pub create_at: DateTime<Utc>,
+}
+
+impl Model {
+  pub fn scopes(&self) -> Vec<Scope> {
+    self
+      .scope
+      .split_whitespace()
+      .filter_map(|s| Scope::from_str(s).ok())
+      .collect()
+  }
+
+  pub fn is_expired(&self) -> bool {
+    self
+      .expire_at
+      .is_some_and(|expire_at| expire_at <= Utc::now())
+  }
+}
+
+impl super::AppEntity for Model {
+  const RESOURCE: crate::error::ResourceType = ResourceType::ApiKey;
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(
+    belongs_to = "super::user::Entity",
+    from = "Column::UserId",
+    to = "super::user::Column::Id"
+  )]
+  User,
+}
+
+impl Related<super::user::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::User.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
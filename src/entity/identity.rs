@@ -0,0 +1,43 @@
+use chrono::{DateTime, Utc};
+use fake::Dummy;
+use sea_orm::entity::prelude::*;
+
+use crate::error::ResourceType;
+
+/// Links the subject of an external identity provider to a user.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Dummy, DeriveEntityModel)]
+#[sea_orm(table_name = "identity")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: Uuid,
+  pub user_id: Uuid,
+  #[sea_orm(column_type = "Text")]
+  pub provider: String,
+  #[sea_orm(column_type = "Text")]
+  pub subject: String,
+  #[sea_orm(column_type = "Text", nullable)]
+  pub email: Option<String>,
+  pub create_at: DateTime<Utc>,
+}
+
+impl super::AppEntity for Model {
+  const RESOURCE: crate::error::ResourceType = ResourceType::Identity;
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(
+    belongs_to = "super::user::Entity",
+    from = "Column::UserId",
+    to = "super::user::Column::Id"
+  )]
+  User,
+}
+
+impl Related<super::user::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::User.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
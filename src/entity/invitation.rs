@@ -0,0 +1,57 @@
+use chrono::{DateTime, Utc};
+use fake::Dummy;
+use sea_orm::entity::prelude::*;
+
+use crate::entity::role::RoleUser;
+use crate::error::ResourceType;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Dummy, DeriveEntityModel)]
+#[sea_orm(table_name = "invitation")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: Uuid,
+  // user who created the invitation
+  pub user_id: Uuid,
+  #[sea_orm(column_type = "Text", unique)]
+  pub code_hash: String,
+  // only this email may register with the code when set
+  #[sea_orm(column_type = "Text", nullable)]
+  pub email: Option<String>,
+  pub role: RoleUser,
+  pub max_uses: i32,
+  pub use_count: i32,
+  pub expire_at: DateTime<Utc>,
+  pub create_at: DateTime<Utc>,
+}
+
+impl Model {
+  pub fn is_expired(&self) -> bool {
+    self.expire_at <= Utc::now()
+  }
+
+  pub fn is_used_up(&self) -> bool {
+    self.use_count >= self.max_uses
+  }
+}
+
+impl super::AppEntity for Model {
+  const RESOURCE: crate::error::ResourceType = ResourceType::Invitation;
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(
+    belongs_to = "super::user::Entity",
+    from = "Column::UserId",
+    to = "super::user::Column::Id"
+  )]
+  User,
+}
+
+impl Related<super::user::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::User.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -16,6 +16,9 @@ pub struct Model {
   #[sea_orm(column_type = "Text")]
   pub content: String,
   pub user_id: Uuid,
+  // the address the message is sent to instead of the email of the user
+  #[sea_orm(column_type = "Text", nullable)]
+  pub receiver: Option<String>,
   pub create_at: DateTime<Utc>,
   pub update_at: DateTime<Utc>,
 }
@@ -67,6 +70,40 @@ pub enum MessageKind {
   LoginCode,
   #[sea_orm(string_value = "ForgetPasswordCode")]
   ForgetPasswordCode,
+  #[sea_orm(string_value = "RecoveryCodeUsed")]
+  RecoveryCodeUsed,
+  #[sea_orm(string_value = "AccountLocked")]
+  AccountLocked,
+  #[sea_orm(string_value = "PasswordChanged")]
+  PasswordChanged,
+  #[sea_orm(string_value = "AccountExists")]
+  AccountExists,
+  #[sea_orm(string_value = "EmailChangeCode")]
+  EmailChangeCode,
+  #[sea_orm(string_value = "EmailChangeNotice")]
+  EmailChangeNotice,
+  #[sea_orm(string_value = "MagicLink")]
+  MagicLink,
+  #[sea_orm(string_value = "Invitation")]
+  Invitation,
+  #[sea_orm(string_value = "RegistrationApproved")]
+  RegistrationApproved,
+  #[sea_orm(string_value = "RegistrationRejected")]
+  RegistrationRejected,
+}
+
+impl MessageKind {
+  /// Whether the content is a live credential, kept encrypted until it is sent
+  /// and removed afterwards.
+  pub fn is_secret(&self) -> bool {
+    matches!(
+      self,
+      MessageKind::ForgetPasswordCode
+        | MessageKind::EmailChangeCode
+        | MessageKind::MagicLink
+        | MessageKind::Invitation
+    )
+  }
 }
 
 #[derive(
@@ -8,8 +8,17 @@ use crate::{
   error::ResourceType,
 };
 
+pub mod api_key;
+pub mod identity;
+pub mod invitation;
 pub mod message;
+pub mod oauth_client;
+pub mod passkey;
+pub mod recovery_code;
 pub mod role;
+pub mod scope;
+pub mod security_event;
+pub mod two_factor;
 pub mod user;
 
 pub trait AppEntity {
@@ -0,0 +1,65 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use fake::Dummy;
+use sea_orm::entity::prelude::*;
+
+use crate::entity::scope::Scope;
+use crate::error::ResourceType;
+
+/// An application allowed to sign users in through the authorization server.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Dummy, DeriveEntityModel)]
+#[sea_orm(table_name = "oauth_client")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: Uuid,
+  #[sea_orm(column_type = "Text", unique)]
+  pub client_id: String,
+  // none for public clients, which must use PKCE instead
+  #[sea_orm(column_type = "Text", nullable)]
+  pub secret_hash: Option<String>,
+  #[sea_orm(column_type = "Text")]
+  pub name: String,
+  // space separated redirect uris
+  #[sea_orm(column_type = "Text")]
+  pub redirect_uris: String,
+  // space separated scopes
+  #[sea_orm(column_type = "Text")]
+  pub scope: String,
+  pub create_at: DateTime<Utc>,
+}
+
+impl Model {
+  pub fn scopes(&self) -> Vec<Scope> {
+    self
+      .scope
+      .split_whitespace()
+      .filter_map(|s| Scope::from_str(s).ok())
+      .collect()
+  }
+
+  pub fn redirect_uris(&self) -> Vec<String> {
+    self
+      .redirect_uris
+      .split_whitespace()
+      .map(String::from)
+      .collect()
+  }
+
+  pub fn has_redirect_uri(&self, uri: &str) -> bool {
+    self.redirect_uris.split_whitespace().any(|u| u == uri)
+  }
+
+  pub fn is_public(&self) -> bool {
+    self.secret_hash.is_none()
+  }
+}
+
+impl super::AppEntity for Model {
+  const RESOURCE: crate::error::ResourceType = ResourceType::OauthClient;
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use fake::Dummy;
+use sea_orm::entity::prelude::*;
+
+use crate::error::ResourceType;
+
+/// A public key credential of a user, it signs in without a password.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Dummy, DeriveEntityModel)]
+#[sea_orm(table_name = "passkey")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: Uuid,
+  pub user_id: Uuid,
+  #[sea_orm(column_type = "Text")]
+  pub name: String,
+  // base64url, as the browser reports it
+  #[sea_orm(column_type = "Text", unique)]
+  pub credential_id: String,
+  // DER encoded subject public key info
+  pub public_key: Vec<u8>,
+  // COSE algorithm identifier
+  pub algorithm: i32,
+  pub sign_count: i64,
+  pub last_used_at: Option<DateTime<Utc>>,
+  pub create_at: DateTime<Utc>,
+}
+
+impl super::AppEntity for Model {
+  const RESOURCE: crate::error::ResourceType = ResourceType::Passkey;
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(
+    belongs_to = "super::user::Entity",
+    from = "Column::UserId",
+    to = "super::user::Column::Id"
+  )]
+  User,
+}
+
+impl Related<super::user::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::User.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,102 @@
+use serde::{Deserialize, Serialize};
+
+use super::role::RoleUser;
+
+#[derive(
+  Debug,
+  PartialEq,
+  Eq,
+  strum::EnumString,
+  PartialOrd,
+  Ord,
+  Deserialize,
+  Serialize,
+  utoipa::ToSchema,
+  fake::Dummy,
+  Clone,
+  Copy,
+  strum::EnumIter,
+  strum::Display,
+  Hash,
+)]
+pub enum Scope {
+  #[serde(rename = "user:read")]
+  #[strum(serialize = "user:read")]
+  UserRead,
+  #[serde(rename = "user:write")]
+  #[strum(serialize = "user:write")]
+  UserWrite,
+  #[serde(rename = "admin:read")]
+  #[strum(serialize = "admin:read")]
+  AdminRead,
+  #[serde(rename = "admin:write")]
+  #[strum(serialize = "admin:write")]
+  AdminWrite,
+  #[serde(rename = "token:info")]
+  #[strum(serialize = "token:info")]
+  TokenInfo,
+}
+
+impl Scope {
+  /// Scopes granted to a token issued by login.
+  pub fn for_role(role: RoleUser) -> Vec<Scope> {
+    match role {
+      RoleUser::User => vec![Scope::UserRead, Scope::UserWrite],
+      RoleUser::Admin => vec![
+        Scope::UserRead,
+        Scope::UserWrite,
+        Scope::AdminRead,
+        Scope::AdminWrite,
+      ],
+      RoleUser::System => vec![Scope::UserRead, Scope::UserWrite, Scope::TokenInfo],
+    }
+  }
+}
+
+/// Marker types naming the scope an endpoint requires.
+pub trait RequiredScope {
+  const SCOPE: Scope;
+}
+
+pub struct UserRead;
+pub struct UserWrite;
+pub struct AdminRead;
+pub struct AdminWrite;
+pub struct TokenInfo;
+
+impl RequiredScope for UserRead {
+  const SCOPE: Scope = Scope::UserRead;
+}
+
+impl RequiredScope for UserWrite {
+  const SCOPE: Scope = Scope::UserWrite;
+}
+
+impl RequiredScope for AdminRead {
+  const SCOPE: Scope = Scope::AdminRead;
+}
+
+impl RequiredScope for AdminWrite {
+  const SCOPE: Scope = Scope::AdminWrite;
+}
+
+impl RequiredScope for TokenInfo {
+  const SCOPE: Scope = Scope::TokenInfo;
+}
+
+#[cfg(test)]
+mod tests {
+  use std::str::FromStr;
+
+  use super::*;
+
+  #[test]
+  fn test_scope_string_format() {
+    assert_eq!(Scope::UserRead.to_string(), "user:read");
+    assert_eq!(Scope::from_str("token:info").unwrap(), Scope::TokenInfo);
+    assert_eq!(
+      serde_json::to_string(&Scope::AdminRead).unwrap(),
+      "\"admin:read\""
+    );
+  }
+}
@@ -0,0 +1,71 @@
+use chrono::{DateTime, Utc};
+use fake::Dummy;
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+use strum::Display;
+
+use crate::error::ResourceType;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Dummy, DeriveEntityModel)]
+#[sea_orm(table_name = "security_event")]
+pub struct Model {
+  #[sea_orm(primary_key)]
+  pub id: Uuid,
+  pub kind: SecurityEventKind,
+  pub user_id: Uuid,
+  pub session_id: Option<Uuid>,
+  #[sea_orm(column_type = "Text", nullable)]
+  pub ip: Option<String>,
+  #[sea_orm(column_type = "Text", nullable)]
+  pub user_agent: Option<String>,
+  pub create_at: DateTime<Utc>,
+}
+
+impl super::AppEntity for Model {
+  const RESOURCE: crate::error::ResourceType = ResourceType::SecurityEvent;
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+  #[sea_orm(
+    belongs_to = "super::user::Entity",
+    from = "Column::UserId",
+    to = "super::user::Column::Id"
+  )]
+  User,
+}
+
+impl Related<super::user::Entity> for Entity {
+  fn to() -> RelationDef {
+    Relation::User.def()
+  }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+#[derive(
+  Debug,
+  PartialEq,
+  Eq,
+  strum::EnumString,
+  PartialOrd,
+  Ord,
+  Deserialize,
+  Serialize,
+  Dummy,
+  Clone,
+  Copy,
+  EnumIter,
+  Display,
+  Hash,
+  DeriveActiveEnum,
+)]
+#[sea_orm(
+  rs_type = "String",
+  db_type = "Enum",
+  enum_name = "SECURITY_EVENT_KIND"
+)]
+pub enum SecurityEventKind {
+  #[sea_orm(string_value = "RefreshTokenReuse")]
+  RefreshTokenReuse,
+}
@@ -0,0 +1,28 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(
+  Debug,
+  PartialEq,
+  Eq,
+  strum::EnumString,
+  PartialOrd,
+  Ord,
+  Deserialize,
+  Serialize,
+  utoipa::ToSchema,
+  fake::Dummy,
+  Clone,
+  Copy,
+  EnumIter,
+  strum::Display,
+  Hash,
+  DeriveActiveEnum,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "TWO_FACTOR_METHOD")]
+pub enum TwoFactorMethod {
+  #[sea_orm(string_value = "Email")]
+  Email,
+  #[sea_orm(string_value = "Totp")]
+  Totp,
+}
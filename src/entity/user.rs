@@ -7,6 +7,7 @@ use crate::error::ResourceType;
 
 use super::AppEntity;
 use super::role::RoleUser;
+use super::two_factor::TwoFactorMethod;
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Dummy, DeriveEntityModel)]
 #[sea_orm(table_name = "users")]
@@ -25,6 +26,11 @@ pub struct Model {
   pub role: RoleUser,
   pub is_active: bool,
   pub is_2fa: bool,
+  // false while the registration waits for an admin decision
+  pub is_approved: bool,
+  pub two_factor_method: TwoFactorMethod,
+  #[sea_orm(column_type = "Text", nullable)]
+  pub totp_secret: Option<String>,
   pub create_at: DateTime<Utc>,
   pub update_at: DateTime<Utc>,
 }
@@ -73,6 +79,9 @@ pub mod tests {
       role: Set(fake::Faker.fake()),
       is_active: Set(fake::Faker.fake()),
       is_2fa: Set(fake::Faker.fake()),
+      is_approved: Set(fake::Faker.fake()),
+      two_factor_method: Set(fake::Faker.fake()),
+      totp_secret: Set(None),
       create_at: Set(fake::Faker.fake()),
       update_at: Set(fake::Faker.fake()),
     }
@@ -1,6 +1,6 @@
 use axum::{
   Json,
-  http::StatusCode,
+  http::{HeaderValue, StatusCode, header::RETRY_AFTER},
   response::{IntoResponse, Response},
 };
 use serde::Deserialize;
@@ -9,6 +9,8 @@ use strum::EnumString;
 
 use crate::entity;
 
+pub mod oauth;
+
 pub type AppResult<T = ()> = std::result::Result<T, AppError>;
 
 #[derive(Debug, thiserror::Error)]
@@ -29,6 +31,10 @@ pub enum AppError {
   ConflictError(String),
   #[error("{0}")]
   UnauthorizedError(String),
+  #[error("Too many requests, retry after {0} seconds.")]
+  TooManyRequestsError(u64),
+  #[error("User is temporarily locked, retry after {0} seconds.")]
+  UserLockedError(u64),
   #[error("bad request {0}")]
   BadRequestError(String),
   #[error("{0}")]
@@ -40,7 +46,7 @@ pub enum AppError {
   #[error(transparent)]
   DatabaseError(#[from] sea_orm::error::DbErr),
   #[error(transparent)]
-  WebSocketError(#[from] tokio_tungstenite::tungstenite::Error),
+  WebSocketError(Box<tokio_tungstenite::tungstenite::Error>),
   #[error(transparent)]
   IoError(#[from] std::io::Error),
   #[error(transparent)]
@@ -70,6 +76,8 @@ pub enum AppError {
   #[error(transparent)]
   Base64Error(#[from] base64::DecodeError),
   #[error(transparent)]
+  CryptoError(#[from] openssl::error::ErrorStack),
+  #[error(transparent)]
   StrumParseError(#[from] strum::ParseError),
   #[error(transparent)]
   SystemTimeError(#[from] std::time::SystemTimeError),
@@ -83,6 +91,12 @@ pub enum AppError {
   TypeHeaderError(#[from] axum_extra::typed_header::TypedHeaderRejection),
 }
 
+impl From<tokio_tungstenite::tungstenite::Error> for AppError {
+  fn from(value: tokio_tungstenite::tungstenite::Error) -> Self {
+    AppError::WebSocketError(Box::new(value))
+  }
+}
+
 impl From<argon2::password_hash::Error> for AppError {
   fn from(value: argon2::password_hash::Error) -> Self {
     AppError::HashError(value.to_string())
@@ -100,6 +114,18 @@ impl AppError {
         vec![],
         StatusCode::BAD_REQUEST,
       ),
+      TooManyRequestsError(retry_after) => (
+        "TOO_MANY_REQUESTS_ERROR".to_string(),
+        None,
+        vec![("retry_after".to_string(), retry_after.to_string())],
+        StatusCode::TOO_MANY_REQUESTS,
+      ),
+      UserLockedError(retry_after) => (
+        "USER_LOCKED_ERROR".to_string(),
+        None,
+        vec![("retry_after".to_string(), retry_after.to_string())],
+        StatusCode::LOCKED,
+      ),
       BadRequestError(_err) => (
         "BAD_REQUEST_ERROR".to_string(),
         None,
@@ -286,6 +312,12 @@ impl AppError {
         vec![],
         StatusCode::INTERNAL_SERVER_ERROR,
       ),
+      CryptoError(_err) => (
+        "CRYPTO_ERROR".to_string(),
+        None,
+        vec![],
+        StatusCode::INTERNAL_SERVER_ERROR,
+      ),
       InvalidInputError(err) => (
         "INVALID_INPUT_ERROR".to_string(),
         None,
@@ -324,8 +356,20 @@ impl AppError {
 
 impl IntoResponse for AppError {
   fn into_response(self) -> Response {
+    let retry_after = match &self {
+      AppError::TooManyRequestsError(retry_after) | AppError::UserLockedError(retry_after) => {
+        Some(*retry_after)
+      }
+      _ => None,
+    };
     let (status_code, body) = self.response();
-    (status_code, Json(body)).into_response()
+    let mut response = (status_code, Json(body)).into_response();
+    if let Some(retry_after) = retry_after {
+      response
+        .headers_mut()
+        .insert(RETRY_AFTER, HeaderValue::from(retry_after));
+    }
+    response
   }
 }
 
@@ -376,6 +420,22 @@ pub enum ResourceType {
   Session,
   #[strum(serialize = "MESSAGE")]
   Message,
+  #[strum(serialize = "RECOVERY_CODE")]
+  RecoveryCode,
+  #[strum(serialize = "SECURITY_EVENT")]
+  SecurityEvent,
+  #[strum(serialize = "API_KEY")]
+  ApiKey,
+  #[strum(serialize = "INVITATION")]
+  Invitation,
+  #[strum(serialize = "IDENTITY")]
+  Identity,
+  #[strum(serialize = "OIDC_PROVIDER")]
+  OidcProvider,
+  #[strum(serialize = "OAUTH_CLIENT")]
+  OauthClient,
+  #[strum(serialize = "PASSKEY")]
+  Passkey,
 }
 
 pub fn invalid_input_error(field: &'static str, message: &'static str) -> AppError {
@@ -0,0 +1,112 @@
+use axum::{
+  Json,
+  http::{HeaderValue, StatusCode, header},
+  response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+
+use super::AppError;
+
+pub type OauthResult<T = ()> = std::result::Result<T, OauthError>;
+
+/// Errors answered to OAuth clients in the form defined by RFC 6749.
+#[derive(Debug, thiserror::Error)]
+pub enum OauthError {
+  #[error("{0}")]
+  InvalidRequest(String),
+  #[error("{0}")]
+  InvalidClient(String),
+  #[error("{0}")]
+  InvalidGrant(String),
+  #[error("{0}")]
+  UnauthorizedClient(String),
+  #[error("{0}")]
+  UnsupportedGrantType(String),
+  #[error("{0}")]
+  UnsupportedResponseType(String),
+  #[error("{0}")]
+  InvalidScope(String),
+  #[error("The resource owner denied the request.")]
+  AccessDenied,
+  #[error("The user has not answered the authorization request yet.")]
+  AuthorizationPending,
+  #[error("Polling too often, wait longer between requests.")]
+  SlowDown,
+  #[error("The device code has expired.")]
+  ExpiredToken,
+  #[error(transparent)]
+  ServerError(#[from] AppError),
+}
+
+impl OauthError {
+  pub fn code(&self) -> &'static str {
+    match self {
+      Self::InvalidRequest(_) => "invalid_request",
+      Self::InvalidClient(_) => "invalid_client",
+      Self::InvalidGrant(_) => "invalid_grant",
+      Self::UnauthorizedClient(_) => "unauthorized_client",
+      Self::UnsupportedGrantType(_) => "unsupported_grant_type",
+      Self::UnsupportedResponseType(_) => "unsupported_response_type",
+      Self::InvalidScope(_) => "invalid_scope",
+      Self::AccessDenied => "access_denied",
+      Self::AuthorizationPending => "authorization_pending",
+      Self::SlowDown => "slow_down",
+      Self::ExpiredToken => "expired_token",
+      Self::ServerError(_) => "server_error",
+    }
+  }
+
+  pub fn response(self) -> (StatusCode, OauthResponseError) {
+    let status_code = match &self {
+      Self::InvalidClient(_) => StatusCode::UNAUTHORIZED,
+      Self::ServerError(_) => StatusCode::INTERNAL_SERVER_ERROR,
+      _ => StatusCode::BAD_REQUEST,
+    };
+    let error_description = match &self {
+      // internal details are not shared with clients
+      Self::ServerError(_) => "The server encountered an unexpected error.".to_string(),
+      _ => self.to_string(),
+    };
+    let body = OauthResponseError {
+      error: self.code().to_string(),
+      error_description,
+    };
+    (status_code, body)
+  }
+}
+
+impl IntoResponse for OauthError {
+  fn into_response(self) -> Response {
+    let is_invalid_client = matches!(self, Self::InvalidClient(_));
+    let (status_code, body) = self.response();
+    let mut response = (status_code, Json(body)).into_response();
+    let headers = response.headers_mut();
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    if is_invalid_client {
+      headers.insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Basic"));
+    }
+    response
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, utoipa::ToSchema)]
+pub struct OauthResponseError {
+  pub error: String,
+  pub error_description: String,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_oauth_error_response() {
+    let (status, body) = OauthError::InvalidClient("Unknown client.".to_string()).response();
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body.error, "invalid_client");
+    let (status, body) =
+      OauthError::ServerError(AppError::BadRequestError("secret".to_string())).response();
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(!body.error_description.contains("secret"));
+  }
+}
@@ -0,0 +1,108 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use garde::Validate;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::entity::scope::{AdminRead, AdminWrite};
+use crate::error::{AppResponseError, AppResult};
+use crate::server::state::AppState;
+use crate::util::claim::ScopedClaims;
+use crate::{dto::*, service};
+
+/// Register an application that signs users in through the authorization server.
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/clients",
+    request_body = CreateOauthClientRequest,
+    responses(
+        (status = 200, description = "Success register client", body = [CreateOauthClientResponse]),
+        (status = 400, description = "Invalid data input", body = [AppResponseError]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 403, description = "Permission denied", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn create(
+  State(state): State<AppState>,
+  user: ScopedClaims<AdminWrite>,
+  Json(req): Json<CreateOauthClientRequest>,
+) -> AppResult<Json<CreateOauthClientResponse>> {
+  info!("Create oauth client by: {} request: {req:?}.", user.uid);
+  req.validate()?;
+  match service::admin::client::create(&state, &user, req).await {
+    Ok(resp) => {
+      info!(
+        "Success create oauth client: {} by: {}.",
+        resp.client.client_id, user.uid
+      );
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessful create oauth client: {e:?}");
+      Err(e)
+    }
+  }
+}
+
+/// Get list of registered clients.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/clients",
+    responses(
+        (status = 200, description = "Success get list of clients", body = [OauthClientListResponse]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 403, description = "Permission denied", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn list(
+  State(state): State<AppState>,
+  user: ScopedClaims<AdminRead>,
+) -> AppResult<Json<OauthClientListResponse>> {
+  info!("Get list of oauth clients by: {}.", user.uid);
+  match service::admin::client::list(&state, &user).await {
+    Ok(resp) => {
+      info!("Success get list of oauth clients by: {}.", user.uid);
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessful get list of oauth clients: {e:?}");
+      Err(e)
+    }
+  }
+}
+
+/// Delete a registered client.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/clients/{id}",
+    params(("id" = Uuid, Path, description = "Client id")),
+    responses(
+        (status = 200, description = "Success delete client", body = [MessageResponse]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 403, description = "Permission denied", body = [AppResponseError]),
+        (status = 404, description = "Client not found", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn delete(
+  State(state): State<AppState>,
+  user: ScopedClaims<AdminWrite>,
+  Path(id): Path<Uuid>,
+) -> AppResult<Json<MessageResponse>> {
+  info!("Delete oauth client: {id} by: {}.", user.uid);
+  match service::admin::client::delete(&state, &user, id).await {
+    Ok(_) => {
+      info!("Success delete oauth client: {id} by: {}.", user.uid);
+      Ok(Json(MessageResponse::new("The client has been deleted.")))
+    }
+    Err(e) => {
+      warn!("Unsuccessful delete oauth client: {e:?}");
+      Err(e)
+    }
+  }
+}
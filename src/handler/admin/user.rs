@@ -1,10 +1,12 @@
 use axum::Json;
-use axum::extract::{Query, State};
-use tracing::info;
+use axum::extract::{Path, Query, State};
+use tracing::{info, warn};
+use uuid::Uuid;
 
+use crate::entity::scope::{AdminRead, AdminWrite};
 use crate::error::{AppResponseError, AppResult};
 use crate::server::state::AppState;
-use crate::util::claim::UserClaims;
+use crate::util::claim::ScopedClaims;
 use crate::{dto::*, service};
 
 /// Get list of user.
@@ -22,7 +24,7 @@ use crate::{dto::*, service};
 )]
 pub async fn list(
   State(state): State<AppState>,
-  user: UserClaims,
+  user: ScopedClaims<AdminRead>,
   Query(param): Query<PageQueryParam>,
 ) -> AppResult<Json<GetUserListResponse>> {
   info!("Get list of user by: {} parameter: {:?}.", user.uid, param);
@@ -40,3 +42,131 @@ pub async fn list(
     }
   }
 }
+
+/// Unlock a user locked after too many failed login attempts.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/admin/user/{id}/lock",
+    params(("id" = Uuid, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Success unlock user", body = [MessageResponse]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 403, description = "Permission denied", body = [AppResponseError]),
+        (status = 404, description = "User not found", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn unlock(
+  State(state): State<AppState>,
+  user: ScopedClaims<AdminWrite>,
+  Path(id): Path<Uuid>,
+) -> AppResult<Json<MessageResponse>> {
+  info!("Unlock user: {id} by: {}.", user.uid);
+  match service::admin::user::unlock(&state, &user, id).await {
+    Ok(true) => {
+      info!("Success unlock user: {id} by: {}.", user.uid);
+      Ok(Json(MessageResponse::new("The user has been unlocked.")))
+    }
+    Ok(false) => Ok(Json(MessageResponse::new("The user is not locked."))),
+    Err(e) => {
+      warn!("Unsuccessful unlock user: {e:?}");
+      Err(e)
+    }
+  }
+}
+
+/// Get list of users waiting for registration approval.
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/user/pending",
+    responses(
+        (status = 200, description = "Success get list of pending users", body = [GetUserListResponse]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 403, description = "Permission denied", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn list_pending(
+  State(state): State<AppState>,
+  user: ScopedClaims<AdminRead>,
+) -> AppResult<Json<GetUserListResponse>> {
+  info!("Get list of pending users by: {}.", user.uid);
+  match service::admin::user::list_pending(&state, &user).await {
+    Ok(resp) => {
+      info!("Success get list of pending users by: {}.", user.uid);
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessful get list of pending users: {e:?}");
+      Err(e)
+    }
+  }
+}
+
+/// Approve a registration waiting for approval.
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/user/{id}/approve",
+    params(("id" = Uuid, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Success approve user", body = [MessageResponse]),
+        (status = 400, description = "User is already approved", body = [AppResponseError]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 403, description = "Permission denied", body = [AppResponseError]),
+        (status = 404, description = "User not found", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn approve(
+  State(state): State<AppState>,
+  user: ScopedClaims<AdminWrite>,
+  Path(id): Path<Uuid>,
+) -> AppResult<Json<MessageResponse>> {
+  info!("Approve user: {id} by: {}.", user.uid);
+  match service::admin::user::approve(&state, &user, id).await {
+    Ok(_) => {
+      info!("Success approve user: {id} by: {}.", user.uid);
+      Ok(Json(MessageResponse::new("The user has been approved.")))
+    }
+    Err(e) => {
+      warn!("Unsuccessful approve user: {e:?}");
+      Err(e)
+    }
+  }
+}
+
+/// Reject a registration waiting for approval, the account is deleted.
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/user/{id}/reject",
+    params(("id" = Uuid, Path, description = "User id")),
+    responses(
+        (status = 200, description = "Success reject user", body = [MessageResponse]),
+        (status = 400, description = "User is already approved", body = [AppResponseError]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 403, description = "Permission denied", body = [AppResponseError]),
+        (status = 404, description = "User not found", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn reject(
+  State(state): State<AppState>,
+  user: ScopedClaims<AdminWrite>,
+  Path(id): Path<Uuid>,
+) -> AppResult<Json<MessageResponse>> {
+  info!("Reject user: {id} by: {}.", user.uid);
+  match service::admin::user::reject(&state, &user, id).await {
+    Ok(_) => {
+      info!("Success reject user: {id} by: {}.", user.uid);
+      Ok(Json(MessageResponse::new("The user has been rejected.")))
+    }
+    Err(e) => {
+      warn!("Unsuccessful reject user: {e:?}");
+      Err(e)
+    }
+  }
+}
@@ -0,0 +1,114 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use garde::Validate;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::entity::scope::{UserRead, UserWrite};
+use crate::error::{AppResponseError, AppResult};
+use crate::server::state::AppState;
+use crate::util::claim::ScopedClaims;
+use crate::{dto::*, service};
+
+/// Create invitation code.
+#[utoipa::path(
+    post,
+    path = "/api/v1/invitations",
+    request_body = CreateInvitationRequest,
+    responses(
+        (status = 200, description = "Success create invitation", body = [CreateInvitationResponse]),
+        (status = 400, description = "Invalid data input", body = [AppResponseError]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 403, description = "Permission denied", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn create(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserWrite>,
+  Json(req): Json<CreateInvitationRequest>,
+) -> AppResult<Json<CreateInvitationResponse>> {
+  info!("Create invitation user_id: {}.", user.uid);
+  req.validate()?;
+  match service::invitation::create(&state, &user, req).await {
+    Ok(resp) => {
+      info!(
+        "Success create invitation user_id: {} invitation_id: {}.",
+        user.uid, resp.invitation.id
+      );
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully create invitation: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// List invitations created by user.
+#[utoipa::path(
+    get,
+    path = "/api/v1/invitations",
+    responses(
+        (status = 200, description = "Success list invitations", body = [InvitationListResponse]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn list(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserRead>,
+) -> AppResult<Json<InvitationListResponse>> {
+  info!("List invitations user_id: {}", user.uid);
+  match service::invitation::list(&state, user.uid).await {
+    Ok(resp) => {
+      info!("Success list invitations user_id: {}", user.uid);
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully list invitations: {e:?}");
+      Err(e)
+    }
+  }
+}
+
+/// Revoke an invitation created by user.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/invitations/{id}",
+    params(("id" = Uuid, Path, description = "Invitation id")),
+    responses(
+        (status = 200, description = "Success revoke invitation", body = [MessageResponse]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 404, description = "Invitation not found", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn revoke(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserWrite>,
+  Path(id): Path<Uuid>,
+) -> AppResult<Json<MessageResponse>> {
+  info!(
+    "Revoke invitation user_id: {} invitation_id: {id}",
+    user.uid
+  );
+  match service::invitation::revoke(&state, user.uid, id).await {
+    Ok(_) => {
+      info!(
+        "Success revoke invitation user_id: {} invitation_id: {id}",
+        user.uid
+      );
+      Ok(Json(MessageResponse::new(
+        "The invitation has been revoked.",
+      )))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully revoke invitation: {e:?}");
+      Err(e)
+    }
+  }
+}
@@ -1,5 +1,21 @@
 pub mod admin;
+pub mod invitation;
+pub mod oauth;
+pub mod oidc;
 pub mod openapi;
 pub mod server;
 pub mod token;
 pub mod user;
+pub mod well_known;
+
+use axum::http::{HeaderValue, header};
+use axum::response::{Html, IntoResponse, Response};
+
+/// An HTML page holding a credential or taking one, it is neither cached nor framed by other sites.
+pub(crate) fn page_response(page: String) -> Response {
+  let headers = [
+    (header::CACHE_CONTROL, HeaderValue::from_static("no-store")),
+    (header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY")),
+  ];
+  (headers, Html(page)).into_response()
+}
@@ -0,0 +1,240 @@
+use axum::Form;
+use axum::Json;
+use axum::extract::{Query, State};
+use axum::http::{HeaderValue, header};
+use axum::response::{IntoResponse, Redirect, Response};
+use axum_extra::TypedHeader;
+use axum_extra::headers::Authorization;
+use axum_extra::headers::authorization::Basic;
+use tracing::{info, warn};
+
+use crate::entity::scope::UserRead;
+use crate::error::oauth::{OauthResponseError, OauthResult};
+use crate::error::{AppResponseError, AppResult};
+use crate::handler::page_response;
+use crate::server::state::AppState;
+use crate::service::oauth::AuthorizeOutcome;
+use crate::util::claim::ScopedClaims;
+use crate::util::client_info::ClientInfo;
+use crate::{dto::*, service};
+
+/// Show the login and consent page of an authorization request.
+#[utoipa::path(
+    get,
+    path = "/oauth2/authorize",
+    params(AuthorizeParams),
+    responses(
+        (status = 200, description = "Login and consent page", body = String, content_type = "text/html"),
+        (status = 303, description = "Redirect to the client with an error"),
+        (status = 400, description = "Unknown client or redirect uri", body = [OauthResponseError]),
+        (status = 500, description = "Internal server error", body = [OauthResponseError])
+    ),
+)]
+pub async fn authorize(
+  State(state): State<AppState>,
+  Query(params): Query<AuthorizeParams>,
+) -> OauthResult<Response> {
+  let client_id = params.client_id.clone();
+  info!("Authorize of client: {client_id}.");
+  match service::oauth::authorize(&state, params).await {
+    Ok(outcome) => {
+      info!("Success answer authorize request of client: {client_id} with: {outcome:?}.");
+      Ok(outcome_response(outcome))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully authorize request error: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Sign in and answer the consent page of an authorization request.
+#[utoipa::path(
+    post,
+    path = "/oauth2/authorize",
+    request_body(content = AuthorizeConsentRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Login and consent page with an error or a code prompt", body = String, content_type = "text/html"),
+        (status = 303, description = "Redirect to the client with a code or an error"),
+        (status = 400, description = "Unknown client or redirect uri", body = [OauthResponseError]),
+        (status = 500, description = "Internal server error", body = [OauthResponseError])
+    ),
+)]
+pub async fn consent(
+  State(state): State<AppState>,
+  Form(req): Form<AuthorizeConsentRequest>,
+) -> OauthResult<Response> {
+  let client_id = req.params.client_id.clone();
+  let decision = req.decision;
+  info!("Consent of client: {client_id} with decision: {decision:?}.");
+  match service::oauth::consent(&state, req).await {
+    Ok(outcome) => {
+      info!(
+        "Success answer consent of client: {client_id} decision: {decision:?} with: {outcome:?}."
+      );
+      Ok(outcome_response(outcome))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully answer authorize request error: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Exchange an authorization code or a refresh token for tokens.
+#[utoipa::path(
+    post,
+    path = "/oauth2/token",
+    request_body(content = OauthTokenRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Success issue tokens", body = [OauthTokenResponse]),
+        (status = 400, description = "Invalid grant or request", body = [OauthResponseError]),
+        (status = 401, description = "Client authentication failed", body = [OauthResponseError]),
+        (status = 500, description = "Internal server error", body = [OauthResponseError])
+    ),
+    security(("basic" = []))
+)]
+pub async fn token(
+  State(state): State<AppState>,
+  client: ClientInfo,
+  basic: Option<TypedHeader<Authorization<Basic>>>,
+  Form(req): Form<OauthTokenRequest>,
+) -> OauthResult<Response> {
+  info!("Token with grant: {}.", req.grant_type);
+  let credentials = basic.map(|TypedHeader(Authorization(basic))| {
+    (basic.username().to_string(), basic.password().to_string())
+  });
+  match service::oauth::token(&state, client, credentials, req).await {
+    Ok(resp) => {
+      info!("Success issue tokens with scope: {:?}.", resp.scope);
+      let cache = [(header::CACHE_CONTROL, HeaderValue::from_static("no-store"))];
+      Ok((cache, Json(resp)).into_response())
+    }
+    Err(e) => {
+      warn!("Unsuccessfully issue tokens error: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Start a device authorization for a client that can not open a browser.
+#[utoipa::path(
+    post,
+    path = "/oauth2/device_authorization",
+    request_body(content = DeviceAuthorizationRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Success start device authorization", body = [DeviceAuthorizationResponse]),
+        (status = 400, description = "Invalid scope or request", body = [OauthResponseError]),
+        (status = 401, description = "Client authentication failed", body = [OauthResponseError]),
+        (status = 500, description = "Internal server error", body = [OauthResponseError])
+    ),
+    security(("basic" = []))
+)]
+pub async fn device_authorization(
+  State(state): State<AppState>,
+  basic: Option<TypedHeader<Authorization<Basic>>>,
+  Form(req): Form<DeviceAuthorizationRequest>,
+) -> OauthResult<Response> {
+  info!("Device authorization with request: {req:?}.");
+  let credentials = basic.map(|TypedHeader(Authorization(basic))| {
+    (basic.username().to_string(), basic.password().to_string())
+  });
+  match service::device::authorize(&state, credentials, req).await {
+    Ok(resp) => {
+      info!(
+        "Success start device authorization user code: {}.",
+        resp.user_code
+      );
+      let cache = [(header::CACHE_CONTROL, HeaderValue::from_static("no-store"))];
+      Ok((cache, Json(resp)).into_response())
+    }
+    Err(e) => {
+      warn!("Unsuccessfully start device authorization error: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Show the page where a user approves the code shown on a device.
+#[utoipa::path(
+    get,
+    path = "/oauth2/device",
+    params(DeviceVerificationParams),
+    responses(
+        (status = 200, description = "Device verification page", body = String, content_type = "text/html"),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+)]
+pub async fn device_page(
+  State(state): State<AppState>,
+  Query(params): Query<DeviceVerificationParams>,
+) -> AppResult<Response> {
+  info!("Show device verification page with request: {params:?}.");
+  match service::device::page(&state, params).await {
+    Ok(page) => Ok(page_response(page)),
+    Err(e) => {
+      warn!("Unsuccessfully show device verification page error: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Sign in and approve or deny the code shown on a device.
+#[utoipa::path(
+    post,
+    path = "/oauth2/device",
+    request_body(content = DeviceVerificationRequest, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Device verification page with the result", body = String, content_type = "text/html"),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+)]
+pub async fn device_verify(
+  State(state): State<AppState>,
+  Form(req): Form<DeviceVerificationRequest>,
+) -> AppResult<Response> {
+  info!("Verify device with decision: {:?}.", req.decision);
+  match service::device::verify(&state, req).await {
+    Ok(page) => Ok(page_response(page)),
+    Err(e) => {
+      warn!("Unsuccessfully verify device error: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Get claims about the signed in user.
+#[utoipa::path(
+    get,
+    path = "/oauth2/userinfo",
+    responses(
+        (status = 200, description = "Success get user info", body = [UserInfoResponse]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 404, description = "User not found", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn userinfo(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserRead>,
+) -> AppResult<Json<UserInfoResponse>> {
+  info!("Get user info by user_id: {}.", user.uid);
+  match service::oauth::userinfo(&state, user.uid).await {
+    Ok(resp) => {
+      info!("Success get user info response: {resp:?}.");
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully get user info error: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+fn outcome_response(outcome: AuthorizeOutcome) -> Response {
+  match outcome {
+    AuthorizeOutcome::Page(page) => page_response(page),
+    AuthorizeOutcome::Redirect(url) => Redirect::to(&url).into_response(),
+  }
+}
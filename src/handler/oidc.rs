@@ -0,0 +1,206 @@
+use axum::Json;
+use axum::extract::{Path, State};
+use axum_extra::extract::CookieJar;
+use garde::Validate;
+use tracing::{info, warn};
+
+use crate::entity::scope::{UserRead, UserWrite};
+use crate::error::{AppResponseError, AppResult};
+use crate::server::state::AppState;
+use crate::util::claim::ScopedClaims;
+use crate::util::client_info::ClientInfo;
+use crate::util::session_cookie;
+use crate::{dto::*, service};
+
+/// Start signing in with an identity provider.
+#[utoipa::path(
+    get,
+    path = "/api/v1/user/oidc/{provider}/authorize",
+    params(("provider" = String, Path, description = "Identity provider name")),
+    responses(
+        (status = 200, description = "Success create authorization url", body = [OidcAuthorizeResponse]),
+        (status = 404, description = "Identity provider not found", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    )
+)]
+pub async fn authorize(
+  State(state): State<AppState>,
+  Path(provider): Path<String>,
+) -> AppResult<Json<OidcAuthorizeResponse>> {
+  info!("Authorize with provider: {provider}.");
+  match service::oidc::authorize(&state, &provider, None).await {
+    Ok(resp) => {
+      info!("Success authorize with provider: {provider}.");
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully authorize with provider: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Login user with the authorization code returned by an identity provider.
+#[utoipa::path(
+    post,
+    path = "/api/v1/user/oidc/{provider}/login",
+    request_body = OidcCallbackRequest,
+    params(("provider" = String, Path, description = "Identity provider name")),
+    responses(
+        (status = 200, description = "Success login user", body = [LoginResponse]),
+        (status = 400, description = "Invalid data input", body = [AppResponseError]),
+        (status = 401, description = "Invalid authorization code or state", body = [AppResponseError]),
+        (status = 404, description = "Identity provider not found", body = [AppResponseError]),
+        (status = 409, description = "Email belongs to an existing user", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    )
+)]
+pub async fn login(
+  State(state): State<AppState>,
+  jar: CookieJar,
+  client: ClientInfo,
+  Path(provider): Path<String>,
+  Json(req): Json<OidcCallbackRequest>,
+) -> AppResult<(CookieJar, Json<LoginResponse>)> {
+  info!("Login user with provider: {provider}.");
+  req.validate()?;
+  match service::oidc::login(&state, client, &provider, req).await {
+    Ok(mut resp) => {
+      info!("Success login user with provider: {resp:?}.");
+      let jar = session_cookie::issue(&state.config.session_cookie, jar, &mut resp);
+      Ok((jar, Json(resp)))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully login user with provider: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// List identity providers linked to user.
+#[utoipa::path(
+    get,
+    path = "/api/v1/user/profile/identities",
+    responses(
+        (status = 200, description = "Success list identities", body = [IdentityListResponse]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn list_identities(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserRead>,
+) -> AppResult<Json<IdentityListResponse>> {
+  info!("List identities user_id: {}.", user.uid);
+  match service::oidc::list(&state, user.uid).await {
+    Ok(resp) => {
+      info!("Success list identities user_id: {}.", user.uid);
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully list identities: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Start linking an identity provider to user.
+#[utoipa::path(
+    post,
+    path = "/api/v1/user/profile/identities/{provider}/authorize",
+    params(("provider" = String, Path, description = "Identity provider name")),
+    responses(
+        (status = 200, description = "Success create authorization url", body = [OidcAuthorizeResponse]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 404, description = "Identity provider not found", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn authorize_link(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserWrite>,
+  Path(provider): Path<String>,
+) -> AppResult<Json<OidcAuthorizeResponse>> {
+  info!("Authorize link provider: {provider} user_id: {}.", user.uid);
+  match service::oidc::authorize(&state, &provider, Some(user.uid)).await {
+    Ok(resp) => {
+      info!("Success authorize link provider: {provider}.");
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully authorize link provider: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Link an identity provider to user with the authorization code it returned.
+#[utoipa::path(
+    post,
+    path = "/api/v1/user/profile/identities/{provider}",
+    request_body = OidcCallbackRequest,
+    params(("provider" = String, Path, description = "Identity provider name")),
+    responses(
+        (status = 200, description = "Success link identity", body = [IdentityResponse]),
+        (status = 400, description = "Invalid data input", body = [AppResponseError]),
+        (status = 401, description = "Invalid authorization code or state", body = [AppResponseError]),
+        (status = 404, description = "Identity provider not found", body = [AppResponseError]),
+        (status = 409, description = "Identity already linked", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn link_identity(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserWrite>,
+  Path(provider): Path<String>,
+  Json(req): Json<OidcCallbackRequest>,
+) -> AppResult<Json<IdentityResponse>> {
+  info!("Link provider: {provider} user_id: {}.", user.uid);
+  req.validate()?;
+  match service::oidc::link(&state, user.uid, &provider, req).await {
+    Ok(resp) => {
+      info!("Success link provider: {provider} user_id: {}.", user.uid);
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully link provider: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Unlink an identity provider from user.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/user/profile/identities/{provider}",
+    params(("provider" = String, Path, description = "Identity provider name")),
+    responses(
+        (status = 200, description = "Success unlink identity", body = [MessageResponse]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 404, description = "Identity not found", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn unlink_identity(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserWrite>,
+  Path(provider): Path<String>,
+) -> AppResult<Json<MessageResponse>> {
+  info!("Unlink provider: {provider} user_id: {}.", user.uid);
+  match service::oidc::unlink(&state, user.uid, &provider).await {
+    Ok(_) => {
+      info!("Success unlink provider: {provider} user_id: {}.", user.uid);
+      Ok(Json(MessageResponse::new(
+        "The identity has been unlinked.",
+      )))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully unlink provider: {e:?}.");
+      Err(e)
+    }
+  }
+}
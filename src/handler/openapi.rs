@@ -5,8 +5,11 @@ use utoipa::{
 
 use crate::dto::*;
 use crate::entity::role::RoleUser;
+use crate::entity::scope::Scope;
+use crate::entity::two_factor::TwoFactorMethod;
 use crate::error::AppResponseError;
-use crate::util::claim::UserClaims;
+use crate::error::oauth::OauthResponseError;
+use crate::util::claim::{Confirmation, UserClaims};
 
 #[derive(utoipa::OpenApi)]
 #[openapi(
@@ -18,21 +21,74 @@ use crate::util::claim::UserClaims;
         // server api 
         crate::handler::server::health_check,
         crate::handler::server::server_state,
+        crate::handler::well_known::jwks,
+        crate::handler::well_known::openid_configuration,
         // user api
         crate::handler::user::register,
         crate::handler::user::active,
+        crate::handler::user::resend_active_code,
+        crate::handler::user::request_magic_link,
+        crate::handler::user::magic_link_page,
+        crate::handler::user::login_magic_link,
         crate::handler::user::login,
         crate::handler::user::login2fa,
+        crate::handler::user::login2fa_email,
+        crate::handler::user::login_passkey_options,
+        crate::handler::user::login_passkey,
+        crate::handler::user::enroll_totp,
+        crate::handler::user::confirm_totp,
+        crate::handler::user::disable_totp,
+        crate::handler::user::regenerate_recovery_codes,
         crate::handler::user::forget_password,
         crate::handler::user::reset_password,
         crate::handler::user::get_profile,
         crate::handler::user::update_profile,
+        crate::handler::user::change_password,
+        crate::handler::user::request_email_change,
+        crate::handler::user::confirm_email_change,
         crate::handler::user::logout,
+        crate::handler::user::list_sessions,
+        crate::handler::user::revoke_session,
+        crate::handler::user::revoke_other_sessions,
+        crate::handler::user::create_api_key,
+        crate::handler::user::list_api_keys,
+        crate::handler::user::revoke_api_key,
+        crate::handler::user::start_passkey_registration,
+        crate::handler::user::register_passkey,
+        crate::handler::user::list_passkeys,
+        crate::handler::user::delete_passkey,
+        // oidc api
+        crate::handler::oidc::authorize,
+        crate::handler::oidc::login,
+        crate::handler::oidc::list_identities,
+        crate::handler::oidc::authorize_link,
+        crate::handler::oidc::link_identity,
+        crate::handler::oidc::unlink_identity,
+        // oauth api
+        crate::handler::oauth::authorize,
+        crate::handler::oauth::consent,
+        crate::handler::oauth::token,
+        crate::handler::oauth::device_authorization,
+        crate::handler::oauth::device_page,
+        crate::handler::oauth::device_verify,
+        crate::handler::oauth::userinfo,
+        // invitation api
+        crate::handler::invitation::create,
+        crate::handler::invitation::list,
+        crate::handler::invitation::revoke,
         // token api
         crate::handler::token::info,
         crate::handler::token::refresh,
         //admin user api 
         crate::handler::admin::user::list,
+        crate::handler::admin::user::unlock,
+        crate::handler::admin::user::list_pending,
+        crate::handler::admin::user::approve,
+        crate::handler::admin::user::reject,
+        //admin client api
+        crate::handler::admin::client::create,
+        crate::handler::admin::client::list,
+        crate::handler::admin::client::delete,
 
     ),
     components(
@@ -41,6 +97,9 @@ use crate::util::claim::UserClaims;
             RegisterResponse,
             ActiveRequest,
             ActiveRequest,
+            ResendActiveCodeRequest,
+            MagicLinkRequest,
+            MagicLinkQueryParam,
             LoginRequest,
             LoginResponse,
             LoginRequest,
@@ -49,12 +108,17 @@ use crate::util::claim::UserClaims;
             MessageResponse,
             TokenInfoRequest,
             UserClaims,
+            Confirmation,
             ForgetPasswordResponse,
             SetPasswordRequest,
             RegisterResponse,
             TokenResponse,
             ProfileResponse,
             UpdateProfileRequest,
+            ChangePasswordRequest,
+            ChangeEmailRequest,
+            ConfirmEmailRequest,
+            ChangeEmailResponse,
             Direction,
             ServiceStatusResponse,
             GetUserResponse,
@@ -62,12 +126,69 @@ use crate::util::claim::UserClaims;
             RefreshTokenRequest,
             MessageResponse,
             RoleUser,
+            Scope,
+            TwoFactorMethod,
+            Login2faEmailRequest,
+            ConfirmTotpRequest,
+            DisableTotpRequest,
+            TotpEnrollResponse,
+            RecoveryCodesResponse,
+            UpdateProfileResponse,
+            SessionResponse,
+            SessionListResponse,
+            CreateApiKeyRequest,
+            CreateApiKeyResponse,
+            ApiKeyResponse,
+            ApiKeyListResponse,
+            PasskeyRegistrationCredential,
+            PasskeyAttestationResponse,
+            PasskeyAssertionCredential,
+            PasskeyAssertionResponse,
+            RegisterPasskeyRequest,
+            PasskeyLoginOptionsRequest,
+            PasskeyLoginRequest,
+            PasskeyCreationOptionsResponse,
+            PasskeyRequestOptionsResponse,
+            PasskeyRelyingParty,
+            PasskeyUser,
+            PasskeyCredentialParameter,
+            PasskeyCredentialDescriptor,
+            PasskeyAuthenticatorSelection,
+            PasskeyResponse,
+            PasskeyListResponse,
+            CreateInvitationRequest,
+            CreateInvitationResponse,
+            InvitationResponse,
+            InvitationListResponse,
+            OidcCallbackRequest,
+            OidcAuthorizeResponse,
+            IdentityResponse,
+            IdentityListResponse,
+            AuthorizeParams,
+            AuthorizeDecision,
+            AuthorizeConsentRequest,
+            OauthTokenRequest,
+            OauthTokenResponse,
+            OauthResponseError,
+            UserInfoResponse,
+            OpenIdConfigurationResponse,
+            DeviceAuthorizationRequest,
+            DeviceAuthorizationResponse,
+            DeviceVerificationParams,
+            DeviceVerificationRequest,
+            CreateOauthClientRequest,
+            CreateOauthClientResponse,
+            OauthClientResponse,
+            OauthClientListResponse,
         )
     ),
     tags(
         (name = "crate::handler::server", description = "server endpoints."),
         (name = "crate::handler::user", description = "user endpoints."),
         (name = "crate::handler::token", description = "token endpoints."),
+        (name = "crate::handler::oidc", description = "identity provider endpoints."),
+        (name = "crate::handler::oauth", description = "authorization server endpoints."),
+        (name = "crate::handler::invitation", description = "invitation endpoints."),
         (name = "crate::handler::admin", description = "admin endpoints."),
     ),
     modifiers(&SecurityAddon)
@@ -82,6 +203,10 @@ impl Modify for SecurityAddon {
     components.add_security_scheme(
       "jwt",
       SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+    );
+    components.add_security_scheme(
+      "basic",
+      SecurityScheme::Http(Http::new(HttpAuthScheme::Basic)),
     )
   }
 }
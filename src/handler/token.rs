@@ -1,11 +1,15 @@
 use axum::Json;
 use axum::extract::State;
+use axum_extra::extract::CookieJar;
 use garde::Validate;
 use tracing::{info, warn};
 
-use crate::error::{AppResponseError, AppResult};
+use crate::entity::scope::TokenInfo;
+use crate::error::{AppResponseError, AppResult, invalid_input_error};
 use crate::server::state::AppState;
-use crate::util::claim::UserClaims;
+use crate::util::claim::{ScopedClaims, UserClaims};
+use crate::util::client_info::ClientInfo;
+use crate::util::session_cookie;
 use crate::{dto::*, service};
 
 /// Refresh token.
@@ -21,13 +25,24 @@ use crate::{dto::*, service};
 )]
 pub async fn refresh(
   State(state): State<AppState>,
-  Json(req): Json<RefreshTokenRequest>,
-) -> AppResult<Json<TokenResponse>> {
+  jar: CookieJar,
+  client: ClientInfo,
+  req: Option<Json<RefreshTokenRequest>>,
+) -> AppResult<(CookieJar, Json<TokenResponse>)> {
+  // the body is left out when the browser session mode keeps the token in a cookie
+  let req = match req {
+    Some(Json(req)) => req,
+    None => RefreshTokenRequest {
+      token: session_cookie::refresh_token(&state.config.session_cookie, &jar)
+        .ok_or_else(|| invalid_input_error("token", "The refresh token is missing."))?,
+    },
+  };
   info!("Refresh token with request: {req:?}.");
-  match service::token::refresh(&state, req).await {
-    Ok(resp) => {
+  match service::token::refresh(&state, client, None, req).await {
+    Ok(mut resp) => {
       info!("Success refresh token user response: {resp:?}.");
-      Ok(Json(resp))
+      let jar = session_cookie::issue(&state.config.session_cookie, jar, &mut resp);
+      Ok((jar, Json(resp)))
     }
     Err(e) => {
       warn!("Unsuccessfully refresh token error: {e:?}.");
@@ -51,12 +66,12 @@ pub async fn refresh(
 )]
 pub async fn info(
   State(state): State<AppState>,
-  user: UserClaims,
+  user: ScopedClaims<TokenInfo>,
   Json(req): Json<TokenInfoRequest>,
 ) -> AppResult<Json<UserClaims>> {
   req.validate()?;
   info!("Get token information by user_id: {}.", user.uid);
-  match service::token::info(&state, user, req).await {
+  match service::token::info(&state, user.into_inner(), req).await {
     Ok(resp) => {
       info!("Success get token information response: {resp:?}.");
       Ok(Json(resp))
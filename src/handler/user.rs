@@ -1,11 +1,19 @@
-use axum::Json;
-use axum::extract::{Query, State};
+use axum::extract::{Path, Query, State};
+use axum::response::Response;
+use axum::{Form, Json};
+use axum_extra::extract::CookieJar;
 use garde::Validate;
 use tracing::{info, warn};
+use uuid::Uuid;
 
+use crate::constant::CHECK_EMAIL_MESSAGE;
+use crate::entity::scope::{UserRead, UserWrite};
 use crate::error::{AppResponseError, AppResult};
+use crate::handler::page_response;
 use crate::server::state::AppState;
-use crate::util::claim::UserClaims;
+use crate::util::claim::{ScopedClaims, UserClaims};
+use crate::util::client_info::ClientInfo;
+use crate::util::session_cookie;
 use crate::{dto::*, service};
 
 /// Register new user.
@@ -38,6 +46,37 @@ pub async fn register(
   }
 }
 
+/// Resend the activation code of registered user.
+#[utoipa::path(
+    post,
+    request_body = ResendActiveCodeRequest,
+    path = "/api/v1/user/active/resend",
+    responses(
+        (status = 200, description = "Success resend active code", body = [MessageResponse]),
+        (status = 400, description = "Invalid data input", body = [AppResponseError]),
+        (status = 404, description = "User not found", body = [AppResponseError]),
+        (status = 429, description = "Too many requests", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    )
+)]
+pub async fn resend_active_code(
+  State(state): State<AppState>,
+  Json(req): Json<ResendActiveCodeRequest>,
+) -> AppResult<Json<MessageResponse>> {
+  info!("Resend active code with request: {req:?}");
+  req.validate()?;
+  match service::user::resend_active_code(&state, req).await {
+    Ok(_) => {
+      info!("Success resend active code.");
+      Ok(Json(MessageResponse::new(CHECK_EMAIL_MESSAGE)))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully resend active code: {e:?}");
+      Err(e)
+    }
+  }
+}
+
 /// Active registered user.
 #[utoipa::path(
     put,
@@ -80,13 +119,16 @@ pub async fn active(
 )]
 pub async fn login(
   State(state): State<AppState>,
+  jar: CookieJar,
+  client: ClientInfo,
   Json(req): Json<LoginRequest>,
-) -> AppResult<Json<LoginResponse>> {
+) -> AppResult<(CookieJar, Json<LoginResponse>)> {
   info!("Login user with request: {req:?}.");
-  match service::user::login(&state, req).await {
-    Ok(resp) => {
+  match service::user::login(&state, client, req).await {
+    Ok(mut resp) => {
       info!("Success login user_id: {resp:?}.");
-      Ok(Json(resp))
+      let jar = session_cookie::issue(&state.config.session_cookie, jar, &mut resp);
+      Ok((jar, Json(resp)))
     }
     Err(e) => {
       warn!("Unsuccessfully login user error: {e:?}.");
@@ -95,6 +137,92 @@ pub async fn login(
   }
 }
 
+/// Email a sign-in link to user.
+#[utoipa::path(
+    post,
+    request_body = MagicLinkRequest,
+    path = "/api/v1/user/login/magic-link",
+    responses(
+        (status = 200, description = "Success send sign-in link", body = [MessageResponse]),
+        (status = 400, description = "Invalid data input", body = [AppResponseError]),
+        (status = 404, description = "User not found", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    )
+)]
+pub async fn request_magic_link(
+  State(state): State<AppState>,
+  Json(req): Json<MagicLinkRequest>,
+) -> AppResult<Json<MessageResponse>> {
+  info!("Request magic link with request: {req:?}");
+  req.validate()?;
+  match service::magic_link::request(&state, req).await {
+    Ok(_) => {
+      info!("Success request magic link.");
+      Ok(Json(MessageResponse::new(CHECK_EMAIL_MESSAGE)))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully request magic link: {e:?}");
+      Err(e)
+    }
+  }
+}
+
+/// Show the page a sign-in link opens, it posts the token to sign in.
+#[utoipa::path(
+    get,
+    path = "/api/v1/user/login/magic-link",
+    params(MagicLinkQueryParam),
+    responses(
+        (status = 200, description = "Sign-in link confirmation page", body = String, content_type = "text/html"),
+        (status = 400, description = "Invalid data input", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    )
+)]
+pub async fn magic_link_page(Query(param): Query<MagicLinkQueryParam>) -> AppResult<Response> {
+  info!("Show magic link page.");
+  param.validate()?;
+  match service::magic_link::page(param) {
+    Ok(page) => Ok(page_response(page)),
+    Err(e) => {
+      warn!("Unsuccessfully show magic link page: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Login user with a sign-in link.
+#[utoipa::path(
+    post,
+    path = "/api/v1/user/login/magic-link/verify",
+    request_body(content = MagicLinkQueryParam, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Success login user", body = [LoginResponse]),
+        (status = 400, description = "Invalid data input", body = [AppResponseError]),
+        (status = 401, description = "Invalid sign-in link", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    )
+)]
+pub async fn login_magic_link(
+  State(state): State<AppState>,
+  jar: CookieJar,
+  client: ClientInfo,
+  Form(param): Form<MagicLinkQueryParam>,
+) -> AppResult<(CookieJar, Json<LoginResponse>)> {
+  info!("Login user with magic link.");
+  param.validate()?;
+  match service::magic_link::verify(&state, client, param).await {
+    Ok(mut resp) => {
+      info!("Success login user with magic link: {resp:?}.");
+      let jar = session_cookie::issue(&state.config.session_cookie, jar, &mut resp);
+      Ok((jar, Json(resp)))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully login user with magic link: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
 /// Login2fa user.
 #[utoipa::path(
     post,
@@ -109,13 +237,17 @@ pub async fn login(
 )]
 pub async fn login2fa(
   State(state): State<AppState>,
+  jar: CookieJar,
+  client: ClientInfo,
   Json(req): Json<Login2faRequest>,
-) -> AppResult<Json<LoginResponse>> {
+) -> AppResult<(CookieJar, Json<LoginResponse>)> {
   info!("Two factor login user with request: {req:?}.");
-  match service::user::login2fa(&state, req).await {
+  match service::user::login2fa(&state, client, req).await {
     Ok(resp) => {
       info!("Success login user_id: {resp:?}.");
-      Ok(Json(LoginResponse::Token(resp)))
+      let mut resp = LoginResponse::Token(resp);
+      let jar = session_cookie::issue(&state.config.session_cookie, jar, &mut resp);
+      Ok((jar, Json(resp)))
     }
     Err(e) => {
       warn!("Unsuccessfully login user error: {e:?}.");
@@ -124,6 +256,224 @@ pub async fn login2fa(
   }
 }
 
+/// Send the pending two factor login code by email.
+#[utoipa::path(
+    post,
+    request_body = Login2faEmailRequest,
+    path = "/api/v1/user/login2fa/email",
+    responses(
+        (status = 200, description = "Success send login code", body = [LoginResponse]),
+        (status = 400, description = "No pending two factor login", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    )
+)]
+pub async fn login2fa_email(
+  State(state): State<AppState>,
+  Json(req): Json<Login2faEmailRequest>,
+) -> AppResult<Json<LoginResponse>> {
+  info!("Two factor login email fallback with request: {req:?}.");
+  match service::user::login2fa_email(&state, req).await {
+    Ok(resp) => {
+      info!("Success send two factor login code.");
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully send two factor login code: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Get the options of a passkey login.
+#[utoipa::path(
+    post,
+    request_body = PasskeyLoginOptionsRequest,
+    path = "/api/v1/user/login/passkey/options",
+    responses(
+        (status = 200, description = "Success create passkey challenge", body = [PasskeyRequestOptionsResponse]),
+        (status = 400, description = "No pending two factor login", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    )
+)]
+pub async fn login_passkey_options(
+  State(state): State<AppState>,
+  Json(req): Json<PasskeyLoginOptionsRequest>,
+) -> AppResult<Json<PasskeyRequestOptionsResponse>> {
+  info!("Passkey login options with request: {req:?}.");
+  match service::passkey::login_options(&state, req).await {
+    Ok(resp) => {
+      info!("Success create passkey login challenge.");
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully create passkey login challenge: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Login user with a passkey.
+#[utoipa::path(
+    post,
+    request_body = PasskeyLoginRequest,
+    path = "/api/v1/user/login/passkey",
+    responses(
+        (status = 200, description = "Success login user", body = [LoginResponse]),
+        (status = 400, description = "Invalid data input", body = [AppResponseError]),
+        (status = 401, description = "Invalid passkey", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    )
+)]
+pub async fn login_passkey(
+  State(state): State<AppState>,
+  jar: CookieJar,
+  client: ClientInfo,
+  Json(req): Json<PasskeyLoginRequest>,
+) -> AppResult<(CookieJar, Json<LoginResponse>)> {
+  info!(
+    "Passkey login user with credential id: {}.",
+    req.credential.id
+  );
+  match service::passkey::login(&state, client, req).await {
+    Ok(mut resp) => {
+      info!("Success login user with passkey.");
+      let jar = session_cookie::issue(&state.config.session_cookie, jar, &mut resp);
+      Ok((jar, Json(resp)))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully login user with passkey: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Start authenticator app enrollment.
+#[utoipa::path(
+    post,
+    path = "/api/v1/user/2fa/totp",
+    responses(
+        (status = 200, description = "Success create totp secret", body = [TotpEnrollResponse]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn enroll_totp(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserWrite>,
+) -> AppResult<Json<TotpEnrollResponse>> {
+  info!("Enroll totp user_id: {}.", user.uid);
+  match service::user::enroll_totp(&state, user.uid).await {
+    Ok(resp) => {
+      info!("Success enroll totp user_id: {}.", user.uid);
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully enroll totp user: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Confirm authenticator app enrollment.
+#[utoipa::path(
+    put,
+    path = "/api/v1/user/2fa/totp",
+    request_body = ConfirmTotpRequest,
+    responses(
+        (status = 200, description = "Success confirm totp", body = [UpdateProfileResponse]),
+        (status = 400, description = "Invalid data input", body = [AppResponseError]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn confirm_totp(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserWrite>,
+  Json(req): Json<ConfirmTotpRequest>,
+) -> AppResult<Json<UpdateProfileResponse>> {
+  info!("Confirm totp user_id: {}.", user.uid);
+  req.validate()?;
+  match service::user::confirm_totp(&state, user.uid, req).await {
+    Ok(recovery_codes) => {
+      info!("Success confirm totp user_id: {}.", user.uid);
+      Ok(Json(UpdateProfileResponse::new(
+        "Authenticator app has been enabled.",
+        recovery_codes,
+      )))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully confirm totp user: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Remove authenticator app.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/user/2fa/totp",
+    request_body = DisableTotpRequest,
+    responses(
+        (status = 200, description = "Success remove totp", body = [MessageResponse]),
+        (status = 400, description = "Invalid data input", body = [AppResponseError]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 429, description = "Too many invalid codes", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn disable_totp(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserWrite>,
+  Json(req): Json<DisableTotpRequest>,
+) -> AppResult<Json<MessageResponse>> {
+  info!("Disable totp user_id: {}.", user.uid);
+  req.validate()?;
+  match service::user::disable_totp(&state, user.uid, req).await {
+    Ok(_) => {
+      info!("Success disable totp user_id: {}.", user.uid);
+      Ok(Json(MessageResponse::new(
+        "Authenticator app has been removed.",
+      )))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully disable totp user: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Regenerate two factor recovery codes.
+#[utoipa::path(
+    post,
+    path = "/api/v1/user/2fa/recovery_codes",
+    responses(
+        (status = 200, description = "Success regenerate recovery codes", body = [RecoveryCodesResponse]),
+        (status = 400, description = "Two factor is not enabled", body = [AppResponseError]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn regenerate_recovery_codes(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserWrite>,
+) -> AppResult<Json<RecoveryCodesResponse>> {
+  info!("Regenerate recovery codes user_id: {}.", user.uid);
+  match service::user::regenerate_recovery_codes(&state, user.uid).await {
+    Ok(resp) => {
+      info!("Success regenerate recovery codes user_id: {}.", user.uid);
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully regenerate recovery codes user: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
 /// Logout user.
 #[utoipa::path(
     get,
@@ -137,15 +487,20 @@ pub async fn login2fa(
 )]
 pub async fn logout(
   State(state): State<AppState>,
+  jar: CookieJar,
   user: UserClaims,
-) -> AppResult<Json<MessageResponse>> {
+) -> AppResult<(CookieJar, Json<MessageResponse>)> {
   info!("Logout user_id: {}", user.uid);
-  match service::user::logout(&state, user.uid).await {
+  match service::user::logout(&state, &user).await {
     Ok(_) => {
       info!("Success logout user user_id: {}", user.uid);
-      Ok(Json(MessageResponse::new(
-        "This user has successfully logged out.",
-      )))
+      let jar = session_cookie::clear(&state.config.session_cookie, jar);
+      Ok((
+        jar,
+        Json(MessageResponse::new(
+          "This user has successfully logged out.",
+        )),
+      ))
     }
     Err(e) => {
       warn!("unsuccessfully logout user: {e:?}");
@@ -154,6 +509,321 @@ pub async fn logout(
   }
 }
 
+/// List active sessions of user.
+#[utoipa::path(
+    get,
+    path = "/api/v1/user/sessions",
+    responses(
+        (status = 200, description = "Success list user sessions", body = [SessionListResponse]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn list_sessions(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserRead>,
+) -> AppResult<Json<SessionListResponse>> {
+  info!("List sessions user_id: {}", user.uid);
+  match service::user::list_sessions(&state, &user).await {
+    Ok(resp) => {
+      info!("Success list sessions user_id: {}", user.uid);
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully list sessions user: {e:?}");
+      Err(e)
+    }
+  }
+}
+
+/// Revoke a session of user.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/user/sessions/{sid}",
+    params(("sid" = Uuid, Path, description = "Session id")),
+    responses(
+        (status = 200, description = "Success revoke session", body = [MessageResponse]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 404, description = "Session not found", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn revoke_session(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserWrite>,
+  Path(sid): Path<Uuid>,
+) -> AppResult<Json<MessageResponse>> {
+  info!("Revoke session user_id: {} session_id: {sid}", user.uid);
+  match service::user::revoke_session(&state, user.uid, sid).await {
+    Ok(_) => {
+      info!(
+        "Success revoke session user_id: {} session_id: {sid}",
+        user.uid
+      );
+      Ok(Json(MessageResponse::new("The session has been revoked.")))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully revoke session user: {e:?}");
+      Err(e)
+    }
+  }
+}
+
+/// Revoke all sessions of user except the current one.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/user/sessions",
+    responses(
+        (status = 200, description = "Success revoke other sessions", body = [MessageResponse]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn revoke_other_sessions(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserWrite>,
+) -> AppResult<Json<MessageResponse>> {
+  info!("Revoke other sessions user_id: {}", user.uid);
+  match service::user::revoke_other_sessions(&state, &user).await {
+    Ok(count) => {
+      info!("Success revoke {count} sessions user_id: {}", user.uid);
+      Ok(Json(MessageResponse::new(format!(
+        "{count} other sessions have been revoked."
+      ))))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully revoke other sessions user: {e:?}");
+      Err(e)
+    }
+  }
+}
+
+/// Create api key.
+#[utoipa::path(
+    post,
+    path = "/api/v1/user/api-keys",
+    request_body = CreateApiKeyRequest,
+    responses(
+        (status = 200, description = "Success create api key", body = [CreateApiKeyResponse]),
+        (status = 400, description = "Invalid data input", body = [AppResponseError]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn create_api_key(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserWrite>,
+  Json(req): Json<CreateApiKeyRequest>,
+) -> AppResult<Json<CreateApiKeyResponse>> {
+  info!("Create api key user_id: {}.", user.uid);
+  req.validate()?;
+  match service::api_key::create(&state, &user, req).await {
+    Ok(resp) => {
+      info!(
+        "Success create api key user_id: {} api_key_id: {}.",
+        user.uid, resp.api_key.id
+      );
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully create api key user: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// List api keys of user.
+#[utoipa::path(
+    get,
+    path = "/api/v1/user/api-keys",
+    responses(
+        (status = 200, description = "Success list api keys", body = [ApiKeyListResponse]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn list_api_keys(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserRead>,
+) -> AppResult<Json<ApiKeyListResponse>> {
+  info!("List api keys user_id: {}", user.uid);
+  match service::api_key::list(&state, user.uid).await {
+    Ok(resp) => {
+      info!("Success list api keys user_id: {}", user.uid);
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully list api keys user: {e:?}");
+      Err(e)
+    }
+  }
+}
+
+/// Revoke an api key of user.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/user/api-keys/{id}",
+    params(("id" = Uuid, Path, description = "Api key id")),
+    responses(
+        (status = 200, description = "Success revoke api key", body = [MessageResponse]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 404, description = "Api key not found", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn revoke_api_key(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserWrite>,
+  Path(id): Path<Uuid>,
+) -> AppResult<Json<MessageResponse>> {
+  info!("Revoke api key user_id: {} api_key_id: {id}", user.uid);
+  match service::api_key::revoke(&state, user.uid, id).await {
+    Ok(_) => {
+      info!(
+        "Success revoke api key user_id: {} api_key_id: {id}",
+        user.uid
+      );
+      Ok(Json(MessageResponse::new("The api key has been revoked.")))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully revoke api key user: {e:?}");
+      Err(e)
+    }
+  }
+}
+
+/// Start passkey registration.
+#[utoipa::path(
+    post,
+    path = "/api/v1/user/passkeys",
+    responses(
+        (status = 200, description = "Success create passkey challenge", body = [PasskeyCreationOptionsResponse]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn start_passkey_registration(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserWrite>,
+) -> AppResult<Json<PasskeyCreationOptionsResponse>> {
+  info!("Start passkey registration user_id: {}.", user.uid);
+  match service::passkey::start_registration(&state, user.uid).await {
+    Ok(resp) => {
+      info!("Success start passkey registration user_id: {}.", user.uid);
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully start passkey registration user: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Register a passkey of user.
+#[utoipa::path(
+    put,
+    path = "/api/v1/user/passkeys",
+    request_body = RegisterPasskeyRequest,
+    responses(
+        (status = 200, description = "Success register passkey", body = [PasskeyResponse]),
+        (status = 400, description = "Invalid data input", body = [AppResponseError]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 409, description = "Passkey already registered", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn register_passkey(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserWrite>,
+  Json(req): Json<RegisterPasskeyRequest>,
+) -> AppResult<Json<PasskeyResponse>> {
+  info!("Register passkey user_id: {}.", user.uid);
+  req.validate()?;
+  match service::passkey::register(&state, user.uid, req).await {
+    Ok(resp) => {
+      info!(
+        "Success register passkey user_id: {} passkey_id: {}.",
+        user.uid, resp.id
+      );
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully register passkey user: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// List passkeys of user.
+#[utoipa::path(
+    get,
+    path = "/api/v1/user/passkeys",
+    responses(
+        (status = 200, description = "Success list passkeys", body = [PasskeyListResponse]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn list_passkeys(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserRead>,
+) -> AppResult<Json<PasskeyListResponse>> {
+  info!("List passkeys user_id: {}", user.uid);
+  match service::passkey::list(&state, user.uid).await {
+    Ok(resp) => {
+      info!("Success list passkeys user_id: {}", user.uid);
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully list passkeys user: {e:?}");
+      Err(e)
+    }
+  }
+}
+
+/// Delete a passkey of user.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/user/passkeys/{id}",
+    params(("id" = Uuid, Path, description = "Passkey id")),
+    responses(
+        (status = 200, description = "Success delete passkey", body = [MessageResponse]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 404, description = "Passkey not found", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn delete_passkey(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserWrite>,
+  Path(id): Path<Uuid>,
+) -> AppResult<Json<MessageResponse>> {
+  info!("Delete passkey user_id: {} passkey_id: {id}", user.uid);
+  match service::passkey::delete(&state, user.uid, id).await {
+    Ok(_) => {
+      info!(
+        "Success delete passkey user_id: {} passkey_id: {id}",
+        user.uid
+      );
+      Ok(Json(MessageResponse::new("The passkey has been deleted.")))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully delete passkey user: {e:?}");
+      Err(e)
+    }
+  }
+}
+
 /// Forgot user password.
 #[utoipa::path(
     get,
@@ -227,7 +897,7 @@ pub async fn reset_password(
 )]
 pub async fn get_profile(
   State(state): State<AppState>,
-  user: UserClaims,
+  user: ScopedClaims<UserRead>,
 ) -> AppResult<Json<ProfileResponse>> {
   info!("Get profile user id: {}.", user.uid);
   match service::user::get_profile(&state, user.uid).await {
@@ -248,7 +918,7 @@ pub async fn get_profile(
     path = "/api/v1/user/profile",
     request_body = UpdateProfileRequest,
     responses(
-        (status = 200, description = "Success update profile information", body = [MessageResponse]),
+        (status = 200, description = "Success update profile information", body = [UpdateProfileResponse]),
         (status = 400, description = "Invalid data input", body = [AppResponseError]),
         (status = 401, description = "Unauthorized user", body = [AppResponseError]),
         (status = 500, description = "Internal server error", body = [AppResponseError])
@@ -257,14 +927,17 @@ pub async fn get_profile(
 )]
 pub async fn update_profile(
   State(state): State<AppState>,
-  user: UserClaims,
+  user: ScopedClaims<UserWrite>,
   Json(req): Json<UpdateProfileRequest>,
-) -> AppResult<Json<MessageResponse>> {
+) -> AppResult<Json<UpdateProfileResponse>> {
   info!("Update profile user_id: {}.", user.uid);
   match service::user::update_profile(&state, user.uid, req).await {
-    Ok(_) => {
+    Ok(recovery_codes) => {
       info!("Success update profile user user_id: {}.", user.uid);
-      Ok(Json(MessageResponse::new("User profile updated.")))
+      Ok(Json(UpdateProfileResponse::new(
+        "User profile updated.",
+        recovery_codes,
+      )))
     }
     Err(e) => {
       info!("Unsuccessful update profile user: {e:?}");
@@ -272,3 +945,107 @@ pub async fn update_profile(
     }
   }
 }
+
+/// Change password of user, every other session is ended.
+#[utoipa::path(
+    put,
+    path = "/api/v1/user/profile/password",
+    request_body = ChangePasswordRequest,
+    responses(
+        (status = 200, description = "Success change password", body = [MessageResponse]),
+        (status = 400, description = "Invalid data input", body = [AppResponseError]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 423, description = "User is locked", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn change_password(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserWrite>,
+  Json(req): Json<ChangePasswordRequest>,
+) -> AppResult<Json<MessageResponse>> {
+  info!("Change password user_id: {}.", user.uid);
+  req.validate()?;
+  match service::user::change_password(&state, &user, req).await {
+    Ok(count) => {
+      info!(
+        "Success change password user_id: {} revoked sessions: {count}.",
+        user.uid
+      );
+      Ok(Json(MessageResponse::new(
+        "The password has been changed, other sessions have been ended.",
+      )))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully change password user: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Request to change the email of user, a code is sent to the new address.
+#[utoipa::path(
+    post,
+    path = "/api/v1/user/email",
+    request_body = ChangeEmailRequest,
+    responses(
+        (status = 200, description = "Success send email change code", body = [ChangeEmailResponse]),
+        (status = 400, description = "Invalid data input", body = [AppResponseError]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 409, description = "Email already exists", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn request_email_change(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserWrite>,
+  Json(req): Json<ChangeEmailRequest>,
+) -> AppResult<Json<ChangeEmailResponse>> {
+  info!("Request email change user_id: {}.", user.uid);
+  req.validate()?;
+  match service::user::request_email_change(&state, user.uid, req).await {
+    Ok(resp) => {
+      info!("Success request email change user_id: {}.", user.uid);
+      Ok(Json(resp))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully request email change user: {e:?}.");
+      Err(e)
+    }
+  }
+}
+
+/// Confirm the email change with the code sent to the new address.
+#[utoipa::path(
+    put,
+    path = "/api/v1/user/email",
+    request_body = ConfirmEmailRequest,
+    responses(
+        (status = 200, description = "Success change email", body = [MessageResponse]),
+        (status = 400, description = "Invalid data input", body = [AppResponseError]),
+        (status = 401, description = "Unauthorized user", body = [AppResponseError]),
+        (status = 409, description = "Email already exists", body = [AppResponseError]),
+        (status = 500, description = "Internal server error", body = [AppResponseError])
+    ),
+    security(("jwt" = []))
+)]
+pub async fn confirm_email_change(
+  State(state): State<AppState>,
+  user: ScopedClaims<UserWrite>,
+  Json(req): Json<ConfirmEmailRequest>,
+) -> AppResult<Json<MessageResponse>> {
+  info!("Confirm email change user_id: {}.", user.uid);
+  req.validate()?;
+  match service::user::confirm_email_change(&state, user.uid, req).await {
+    Ok(_) => {
+      info!("Success change email user_id: {}.", user.uid);
+      Ok(Json(MessageResponse::new("The email has been changed.")))
+    }
+    Err(e) => {
+      warn!("Unsuccessfully confirm email change user: {e:?}.");
+      Err(e)
+    }
+  }
+}
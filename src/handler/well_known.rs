@@ -0,0 +1,47 @@
+use axum::Json;
+use axum::extract::State;
+use jsonwebtoken::jwk::JwkSet;
+
+use crate::constant::ACCESS_TOKEN_KEY_RING;
+use crate::dto::OpenIdConfigurationResponse;
+use crate::error::AppResult;
+use crate::server::state::AppState;
+use crate::service;
+
+// Public keys for verifying access tokens.
+#[utoipa::path(
+    get,
+    path = "/.well-known/jwks.json",
+    responses(
+        (status = 200, description = "json web key set", body = Object)
+    )
+)]
+pub async fn jwks() -> AppResult<Json<JwkSet>> {
+  Ok(Json(ACCESS_TOKEN_KEY_RING.jwks().clone()))
+}
+
+// Metadata of this server as an OpenID provider.
+#[utoipa::path(
+    get,
+    path = "/.well-known/openid-configuration",
+    responses(
+        (status = 200, description = "OpenID provider metadata", body = OpenIdConfigurationResponse)
+    )
+)]
+pub async fn openid_configuration(
+  State(state): State<AppState>,
+) -> AppResult<Json<OpenIdConfigurationResponse>> {
+  Ok(Json(service::oauth::configuration(&state)))
+}
+
+#[cfg(test)]
+pub mod tests {
+
+  use super::*;
+
+  #[tokio::test]
+  async fn test_jwks_handler() {
+    let jwks = jwks().await.unwrap().0;
+    assert!(jwks.find(ACCESS_TOKEN_KEY_RING.kid().unwrap()).is_some());
+  }
+}
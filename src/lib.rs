@@ -5,6 +5,7 @@ pub mod dto;
 pub mod entity;
 pub mod error;
 pub mod handler;
+pub mod middleware;
 pub mod migration;
 pub mod repo;
 pub mod router;
@@ -0,0 +1,40 @@
+use axum::extract::{MatchedPath, Request, State};
+use axum::http::Method;
+use axum::middleware::Next;
+use axum::response::Response;
+use axum_extra::extract::CookieJar;
+
+use crate::error::AppResult;
+use crate::server::state::AppState;
+use crate::util::session_cookie;
+
+// routes that change state on a GET request
+const STATE_CHANGING_GET_ROUTES: [&str; 1] = ["/api/v1/user/logout"];
+
+/// Requires the CSRF token on every state-changing request sent with the session
+/// cookies, which the browser attaches to a request forged by another site too.
+pub async fn csrf(
+  State(state): State<AppState>,
+  request: Request,
+  next: Next,
+) -> AppResult<Response> {
+  if !state.config.session_cookie.enable || !is_state_changing(&request) {
+    return Ok(next.run(request).await);
+  }
+  let jar = CookieJar::from_headers(request.headers());
+  if session_cookie::has_session(&jar) {
+    session_cookie::check_csrf(&jar, request.headers())?;
+  }
+  Ok(next.run(request).await)
+}
+
+fn is_state_changing(request: &Request) -> bool {
+  let method = request.method();
+  if method == Method::GET || method == Method::HEAD {
+    return request
+      .extensions()
+      .get::<MatchedPath>()
+      .is_some_and(|path| STATE_CHANGING_GET_ROUTES.contains(&path.as_str()));
+  }
+  method != Method::OPTIONS && method != Method::TRACE
+}
@@ -0,0 +1,130 @@
+use axum::body::{Body, to_bytes};
+use axum::extract::{FromRequestParts, MatchedPath, Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use uuid::Uuid;
+
+use crate::configure::rate_limit::RateLimitKeyKind;
+use crate::constant::RATE_LIMIT_MAX_BODY_SIZE;
+use crate::error::{AppError, AppResult};
+use crate::server::state::AppState;
+use crate::service::{self, rate_limit::RateLimitKey};
+use crate::util::client_info::ClientInfo;
+
+const ACCOUNT_FIELDS: [&str; 2] = ["email", "user_id"];
+
+/// Applies every configured policy matching the route before the handler runs.
+pub async fn rate_limit(
+  State(state): State<AppState>,
+  request: Request,
+  next: Next,
+) -> AppResult<Response> {
+  let config = &state.config.rate_limit;
+  let Some(route) = request
+    .extensions()
+    .get::<MatchedPath>()
+    .map(|path| path.as_str().to_string())
+  else {
+    return Ok(next.run(request).await);
+  };
+  let method = request.method().to_string();
+  let policies = config
+    .policies
+    .iter()
+    .filter(|policy| config.enable && policy.matches(&route, &method))
+    .collect::<Vec<_>>();
+  if policies.is_empty() {
+    return Ok(next.run(request).await);
+  }
+  let (mut parts, body) = request.into_parts();
+  let client = ClientInfo::from_request_parts(&mut parts, &state).await?;
+  let (body, account) = if policies.iter().any(|p| p.key == RateLimitKeyKind::Account) {
+    let bytes = to_bytes(body, RATE_LIMIT_MAX_BODY_SIZE)
+      .await
+      .map_err(|e| AppError::BadRequestError(e.to_string()))?;
+    let account = get_account(parts.uri.query(), &bytes);
+    (Body::from(bytes), account)
+  } else {
+    (body, None)
+  };
+  for policy in policies {
+    let id = match policy.key {
+      RateLimitKeyKind::Ip => client.ip.as_deref(),
+      RateLimitKeyKind::Account => account.as_deref(),
+      RateLimitKeyKind::Route => Some(""),
+    };
+    let Some(id) = id else {
+      continue;
+    };
+    let key = RateLimitKey {
+      route: &route,
+      method: &method,
+      kind: policy.key,
+      id,
+    };
+    service::rate_limit::check(&state.redis, policy, &key).await?;
+  }
+  Ok(next.run(Request::from_parts(parts, body)).await)
+}
+
+// The account is the email or user id sent in the query string, the json body or the form body.
+fn get_account(query: Option<&str>, body: &[u8]) -> Option<String> {
+  let from_urlencoded = |input: &[u8]| {
+    url::form_urlencoded::parse(input)
+      .find(|(name, _)| ACCOUNT_FIELDS.contains(&name.as_ref()))
+      .map(|(_, value)| value.into_owned())
+  };
+  let from_query = query.and_then(|query| from_urlencoded(query.as_bytes()));
+  let account = from_query
+    .or_else(|| {
+      let value = serde_json::from_slice::<serde_json::Value>(body).ok()?;
+      ACCOUNT_FIELDS
+        .iter()
+        .find_map(|field| value.get(field)?.as_str().map(ToString::to_string))
+    })
+    .or_else(|| from_urlencoded(body))?;
+  let account = account.trim();
+  // a user id has several spellings, each would get a bucket of its own
+  match Uuid::parse_str(account) {
+    Ok(id) => Some(id.hyphenated().to_string()),
+    Err(_) => Some(account.to_lowercase()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_get_account() {
+    assert_eq!(
+      get_account(Some("email=User%40Mail.com"), b""),
+      Some("user@mail.com".to_string())
+    );
+    assert_eq!(
+      get_account(None, br#"{"user_id":"abc","code":"12345"}"#),
+      Some("abc".to_string())
+    );
+    assert_eq!(
+      get_account(None, b"client_id=app&email=User%40Mail.com&password=x"),
+      Some("user@mail.com".to_string())
+    );
+    assert_eq!(get_account(Some("page=1"), br#"{"code":"1"}"#), None);
+    assert_eq!(get_account(None, b"not json"), None);
+  }
+
+  #[test]
+  fn test_get_account_of_uuid_forms() {
+    let id = "67e55044-10b1-426f-9247-bb680e5fe0c8";
+    for form in [
+      id.to_string(),
+      id.to_uppercase(),
+      id.replace('-', ""),
+      format!("{{{id}}}"),
+      format!("urn:uuid:{id}"),
+    ] {
+      let body = serde_json::json!({ "user_id": form }).to_string();
+      assert_eq!(get_account(None, body.as_bytes()), Some(id.to_string()));
+    }
+  }
+}
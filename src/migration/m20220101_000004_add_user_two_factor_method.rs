@@ -0,0 +1,35 @@
+use sea_orm_migration::{prelude::*, sea_orm::TransactionTrait};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    let tx = db.begin().await?;
+    tx.execute_unprepared(r#"CREATE TYPE TWO_FACTOR_METHOD AS ENUM ('Email', 'Totp')"#)
+      .await?;
+    tx.execute_unprepared(
+      r#"ALTER TABLE users
+            ADD COLUMN two_factor_method TWO_FACTOR_METHOD NOT NULL DEFAULT 'Email',
+            ADD COLUMN totp_secret TEXT"#,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(())
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let conn = manager.get_connection();
+    let tx = conn.begin().await?;
+    tx.execute_unprepared(
+      "ALTER TABLE users DROP COLUMN IF EXISTS two_factor_method, DROP COLUMN IF EXISTS totp_secret",
+    )
+    .await?;
+    tx.execute_unprepared("DROP TYPE IF EXISTS TWO_FACTOR_METHOD")
+      .await?;
+    tx.commit().await?;
+    Ok(())
+  }
+}
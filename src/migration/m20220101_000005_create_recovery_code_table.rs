@@ -0,0 +1,47 @@
+use sea_orm_migration::{prelude::*, sea_orm::TransactionTrait};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    let tx = db.begin().await?;
+    tx.execute_unprepared(r#"ALTER TYPE MESSAGE_KIND ADD VALUE 'RecoveryCodeUsed'"#)
+      .await?;
+    tx.execute_unprepared(
+      r#"CREATE TABLE recovery_code (
+            id UUID NOT NULL PRIMARY KEY,
+            user_id UUID NOT NULL,
+            code_hash TEXT NOT NULL,
+            used_at TIMESTAMPTZ,
+            create_at TIMESTAMPTZ DEFAULT current_timestamp,
+            CONSTRAINT fk_recovery_code_user FOREIGN KEY(user_id) REFERENCES users(id)
+        )"#,
+    )
+    .await?;
+    tx.execute_unprepared(r#"CREATE INDEX idx_recovery_code_user_id ON recovery_code(user_id)"#)
+      .await?;
+    tx.commit().await?;
+    Ok(())
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let conn = manager.get_connection();
+    let tx = conn.begin().await?;
+    tx.execute_unprepared("DROP TABLE IF EXISTS recovery_code")
+      .await?;
+    tx.execute_unprepared("DELETE FROM message WHERE kind = 'RecoveryCodeUsed'")
+      .await?;
+    tx.execute_unprepared(
+      r#"ALTER TYPE MESSAGE_KIND RENAME TO MESSAGE_KIND_OLD;
+         CREATE TYPE MESSAGE_KIND AS ENUM ('ActiveCode', 'LoginCode', 'ForgetPasswordCode');
+         ALTER TABLE message ALTER COLUMN kind TYPE MESSAGE_KIND USING kind::text::MESSAGE_KIND;
+         DROP TYPE MESSAGE_KIND_OLD"#,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(())
+  }
+}
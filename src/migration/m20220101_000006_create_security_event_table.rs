@@ -0,0 +1,42 @@
+use sea_orm_migration::{prelude::*, sea_orm::TransactionTrait};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    let tx = db.begin().await?;
+    tx.execute_unprepared(r#"CREATE TYPE SECURITY_EVENT_KIND AS ENUM ('RefreshTokenReuse')"#)
+      .await?;
+    tx.execute_unprepared(
+      r#"CREATE TABLE security_event (
+            id UUID NOT NULL PRIMARY KEY,
+            kind SECURITY_EVENT_KIND NOT NULL,
+            user_id UUID NOT NULL,
+            session_id UUID,
+            ip TEXT,
+            user_agent TEXT,
+            create_at TIMESTAMPTZ DEFAULT current_timestamp,
+            CONSTRAINT fk_security_event_user FOREIGN KEY(user_id) REFERENCES users(id)
+        )"#,
+    )
+    .await?;
+    tx.execute_unprepared(r#"CREATE INDEX idx_security_event_user_id ON security_event(user_id)"#)
+      .await?;
+    tx.commit().await?;
+    Ok(())
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let conn = manager.get_connection();
+    let tx = conn.begin().await?;
+    tx.execute_unprepared("DROP TABLE IF EXISTS security_event")
+      .await?;
+    tx.execute_unprepared("DROP TYPE IF EXISTS SECURITY_EVENT_KIND")
+      .await?;
+    tx.commit().await?;
+    Ok(())
+  }
+}
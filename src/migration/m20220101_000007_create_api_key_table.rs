@@ -0,0 +1,40 @@
+use sea_orm_migration::{prelude::*, sea_orm::TransactionTrait};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    let tx = db.begin().await?;
+    tx.execute_unprepared(
+      r#"CREATE TABLE api_key (
+            id UUID NOT NULL PRIMARY KEY,
+            user_id UUID NOT NULL,
+            name TEXT NOT NULL,
+            prefix TEXT NOT NULL UNIQUE,
+            key_hash TEXT NOT NULL,
+            scope TEXT NOT NULL,
+            expire_at TIMESTAMPTZ,
+            last_used_at TIMESTAMPTZ,
+            create_at TIMESTAMPTZ DEFAULT current_timestamp,
+            CONSTRAINT fk_api_key_user FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE
+        )"#,
+    )
+    .await?;
+    tx.execute_unprepared(r#"CREATE INDEX idx_api_key_user_id ON api_key(user_id)"#)
+      .await?;
+    tx.commit().await?;
+    Ok(())
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let conn = manager.get_connection();
+    let tx = conn.begin().await?;
+    tx.execute_unprepared("DROP TABLE IF EXISTS api_key")
+      .await?;
+    tx.commit().await?;
+    Ok(())
+  }
+}
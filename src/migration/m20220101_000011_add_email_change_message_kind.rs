@@ -0,0 +1,36 @@
+use sea_orm_migration::{prelude::*, sea_orm::TransactionTrait};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    let tx = db.begin().await?;
+    tx.execute_unprepared(r#"ALTER TYPE MESSAGE_KIND ADD VALUE 'EmailChangeCode'"#)
+      .await?;
+    tx.execute_unprepared(r#"ALTER TYPE MESSAGE_KIND ADD VALUE 'EmailChangeNotice'"#)
+      .await?;
+    tx.commit().await?;
+    Ok(())
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let conn = manager.get_connection();
+    let tx = conn.begin().await?;
+    tx.execute_unprepared(
+      "DELETE FROM message WHERE kind IN ('EmailChangeCode', 'EmailChangeNotice')",
+    )
+    .await?;
+    tx.execute_unprepared(
+      r#"ALTER TYPE MESSAGE_KIND RENAME TO MESSAGE_KIND_OLD;
+         CREATE TYPE MESSAGE_KIND AS ENUM ('ActiveCode', 'LoginCode', 'ForgetPasswordCode', 'RecoveryCodeUsed', 'AccountLocked', 'PasswordChanged', 'AccountExists');
+         ALTER TABLE message ALTER COLUMN kind TYPE MESSAGE_KIND USING kind::text::MESSAGE_KIND;
+         DROP TYPE MESSAGE_KIND_OLD"#,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(())
+  }
+}
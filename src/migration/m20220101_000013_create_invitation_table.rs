@@ -0,0 +1,51 @@
+use sea_orm_migration::{prelude::*, sea_orm::TransactionTrait};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    let tx = db.begin().await?;
+    tx.execute_unprepared(
+      r#"CREATE TABLE invitation (
+            id UUID NOT NULL PRIMARY KEY,
+            user_id UUID NOT NULL,
+            code_hash TEXT NOT NULL UNIQUE,
+            email TEXT,
+            role ROLE_USER NOT NULL,
+            max_uses INTEGER NOT NULL,
+            use_count INTEGER NOT NULL DEFAULT 0,
+            expire_at TIMESTAMPTZ NOT NULL,
+            create_at TIMESTAMPTZ DEFAULT current_timestamp,
+            CONSTRAINT fk_invitation_user FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE
+        )"#,
+    )
+    .await?;
+    tx.execute_unprepared(r#"CREATE INDEX idx_invitation_user_id ON invitation(user_id)"#)
+      .await?;
+    tx.execute_unprepared(r#"ALTER TYPE MESSAGE_KIND ADD VALUE 'Invitation'"#)
+      .await?;
+    tx.commit().await?;
+    Ok(())
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let conn = manager.get_connection();
+    let tx = conn.begin().await?;
+    tx.execute_unprepared("DROP TABLE IF EXISTS invitation")
+      .await?;
+    tx.execute_unprepared("DELETE FROM message WHERE kind = 'Invitation'")
+      .await?;
+    tx.execute_unprepared(
+      r#"ALTER TYPE MESSAGE_KIND RENAME TO MESSAGE_KIND_OLD;
+         CREATE TYPE MESSAGE_KIND AS ENUM ('ActiveCode', 'LoginCode', 'ForgetPasswordCode', 'RecoveryCodeUsed', 'AccountLocked', 'PasswordChanged', 'AccountExists', 'EmailChangeCode', 'EmailChangeNotice', 'MagicLink');
+         ALTER TABLE message ALTER COLUMN kind TYPE MESSAGE_KIND USING kind::text::MESSAGE_KIND;
+         DROP TYPE MESSAGE_KIND_OLD"#,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(())
+  }
+}
@@ -0,0 +1,37 @@
+use sea_orm_migration::{prelude::*, sea_orm::TransactionTrait};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    let tx = db.begin().await?;
+    tx.execute_unprepared(
+      r#"CREATE TABLE identity (
+            id UUID NOT NULL PRIMARY KEY,
+            user_id UUID NOT NULL,
+            provider TEXT NOT NULL,
+            subject TEXT NOT NULL,
+            email TEXT,
+            create_at TIMESTAMPTZ DEFAULT current_timestamp,
+            CONSTRAINT fk_identity_user FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE,
+            CONSTRAINT uq_identity_provider_subject UNIQUE(provider, subject),
+            CONSTRAINT uq_identity_user_provider UNIQUE(user_id, provider)
+        )"#,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(())
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let conn = manager.get_connection();
+    let tx = conn.begin().await?;
+    tx.execute_unprepared("DROP TABLE IF EXISTS identity")
+      .await?;
+    tx.commit().await?;
+    Ok(())
+  }
+}
@@ -0,0 +1,35 @@
+use sea_orm_migration::{prelude::*, sea_orm::TransactionTrait};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    let tx = db.begin().await?;
+    tx.execute_unprepared(
+      r#"CREATE TABLE oauth_client (
+            id UUID NOT NULL PRIMARY KEY,
+            client_id TEXT NOT NULL UNIQUE,
+            secret_hash TEXT,
+            name TEXT NOT NULL,
+            redirect_uris TEXT NOT NULL,
+            scope TEXT NOT NULL,
+            create_at TIMESTAMPTZ DEFAULT current_timestamp
+        )"#,
+    )
+    .await?;
+    tx.commit().await?;
+    Ok(())
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let conn = manager.get_connection();
+    let tx = conn.begin().await?;
+    tx.execute_unprepared("DROP TABLE IF EXISTS oauth_client")
+      .await?;
+    tx.commit().await?;
+    Ok(())
+  }
+}
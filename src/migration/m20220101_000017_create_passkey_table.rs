@@ -0,0 +1,41 @@
+use sea_orm_migration::{prelude::*, sea_orm::TransactionTrait};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+  async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let db = manager.get_connection();
+    let tx = db.begin().await?;
+    tx.execute_unprepared(
+      r#"CREATE TABLE passkey (
+            id UUID NOT NULL PRIMARY KEY,
+            user_id UUID NOT NULL,
+            name TEXT NOT NULL,
+            credential_id TEXT NOT NULL,
+            public_key BYTEA NOT NULL,
+            algorithm INTEGER NOT NULL,
+            sign_count BIGINT NOT NULL DEFAULT 0,
+            last_used_at TIMESTAMPTZ,
+            create_at TIMESTAMPTZ DEFAULT current_timestamp,
+            CONSTRAINT fk_passkey_user FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE,
+            CONSTRAINT uq_passkey_credential_id UNIQUE(credential_id)
+        )"#,
+    )
+    .await?;
+    tx.execute_unprepared(r#"CREATE INDEX idx_passkey_user_id ON passkey(user_id)"#)
+      .await?;
+    tx.commit().await?;
+    Ok(())
+  }
+
+  async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+    let conn = manager.get_connection();
+    let tx = conn.begin().await?;
+    tx.execute_unprepared("DROP TABLE IF EXISTS passkey")
+      .await?;
+    tx.commit().await?;
+    Ok(())
+  }
+}
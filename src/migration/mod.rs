@@ -3,6 +3,21 @@ pub use sea_orm_migration::prelude::*;
 mod m20220101_000001_create_role_type;
 mod m20220101_000002_create_user_table;
 mod m20220101_000003_create_message_table;
+mod m20220101_000004_add_user_two_factor_method;
+mod m20220101_000005_create_recovery_code_table;
+mod m20220101_000006_create_security_event_table;
+mod m20220101_000007_create_api_key_table;
+mod m20220101_000008_add_account_locked_message_kind;
+mod m20220101_000009_add_password_changed_message_kind;
+mod m20220101_000010_add_account_exists_message_kind;
+mod m20220101_000011_add_email_change_message_kind;
+mod m20220101_000012_add_magic_link_message_kind;
+mod m20220101_000013_create_invitation_table;
+mod m20220101_000014_add_user_approval;
+mod m20220101_000015_create_identity_table;
+mod m20220101_000016_create_oauth_client_table;
+mod m20220101_000017_create_passkey_table;
+mod m20220101_000018_add_message_receiver;
 
 pub struct Migrator;
 
@@ -13,6 +28,21 @@ impl MigratorTrait for Migrator {
       Box::new(m20220101_000001_create_role_type::Migration),
       Box::new(m20220101_000002_create_user_table::Migration),
       Box::new(m20220101_000003_create_message_table::Migration),
+      Box::new(m20220101_000004_add_user_two_factor_method::Migration),
+      Box::new(m20220101_000005_create_recovery_code_table::Migration),
+      Box::new(m20220101_000006_create_security_event_table::Migration),
+      Box::new(m20220101_000007_create_api_key_table::Migration),
+      Box::new(m20220101_000008_add_account_locked_message_kind::Migration),
+      Box::new(m20220101_000009_add_password_changed_message_kind::Migration),
+      Box::new(m20220101_000010_add_account_exists_message_kind::Migration),
+      Box::new(m20220101_000011_add_email_change_message_kind::Migration),
+      Box::new(m20220101_000012_add_magic_link_message_kind::Migration),
+      Box::new(m20220101_000013_create_invitation_table::Migration),
+      Box::new(m20220101_000014_add_user_approval::Migration),
+      Box::new(m20220101_000015_create_identity_table::Migration),
+      Box::new(m20220101_000016_create_oauth_client_table::Migration),
+      Box::new(m20220101_000017_create_passkey_table::Migration),
+      Box::new(m20220101_000018_add_message_receiver::Migration),
     ]
   }
 }
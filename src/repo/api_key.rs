@@ -0,0 +1,146 @@
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use sea_orm::{
+  ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use uuid::Uuid;
+
+use crate::{
+  entity::{self, scope::Scope},
+  error::AppResult,
+};
+
+#[tracing::instrument(skip_all)]
+pub async fn save<C>(
+  conn: &C,
+  user_id: Uuid,
+  name: String,
+  prefix: String,
+  key_hash: String,
+  scope: &[Scope],
+  expire_at: Option<DateTime<Utc>>,
+) -> AppResult<entity::api_key::Model>
+where
+  C: ConnectionTrait,
+{
+  let model = entity::api_key::ActiveModel {
+    id: Set(Uuid::new_v4()),
+    user_id: Set(user_id),
+    name: Set(name),
+    prefix: Set(prefix),
+    key_hash: Set(key_hash),
+    scope: Set(scope.iter().join(" ")),
+    expire_at: Set(expire_at),
+    last_used_at: Set(None),
+    create_at: Set(Utc::now()),
+  }
+  .insert(conn)
+  .await?;
+  Ok(model)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn find_by_prefix<C>(conn: &C, prefix: &str) -> AppResult<Option<entity::api_key::Model>>
+where
+  C: ConnectionTrait,
+{
+  let model = entity::api_key::Entity::find()
+    .filter(entity::api_key::Column::Prefix.eq(prefix))
+    .one(conn)
+    .await?;
+  Ok(model)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn find_by_user<C>(conn: &C, user_id: Uuid) -> AppResult<Vec<entity::api_key::Model>>
+where
+  C: ConnectionTrait,
+{
+  let models = entity::api_key::Entity::find()
+    .filter(entity::api_key::Column::UserId.eq(user_id))
+    .order_by_desc(entity::api_key::Column::CreateAt)
+    .all(conn)
+    .await?;
+  Ok(models)
+}
+
+/// Deletes the key if it belongs to the user, returns false if nothing was deleted.
+#[tracing::instrument(skip_all)]
+pub async fn delete_by_user<C>(conn: &C, user_id: Uuid, id: Uuid) -> AppResult<bool>
+where
+  C: ConnectionTrait,
+{
+  let result = entity::api_key::Entity::delete_many()
+    .filter(
+      entity::api_key::Column::Id
+        .eq(id)
+        .and(entity::api_key::Column::UserId.eq(user_id)),
+    )
+    .exec(conn)
+    .await?;
+  Ok(result.rows_affected == 1)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn update_last_used<C>(conn: &C, id: Uuid) -> AppResult
+where
+  C: ConnectionTrait,
+{
+  entity::api_key::Entity::update_many()
+    .col_expr(
+      entity::api_key::Column::LastUsedAt,
+      sea_orm::sea_query::Expr::value(Utc::now()),
+    )
+    .filter(entity::api_key::Column::Id.eq(id))
+    .exec(conn)
+    .await?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use test_context::test_context;
+
+  use crate::entity::TransactionTestContext;
+
+  #[test_context(TransactionTestContext)]
+  #[tokio::test]
+  async fn test_save_find_and_delete_api_key(ctx: &mut TransactionTestContext) {
+    let user_id = crate::entity::user::Entity::find()
+      .one(&**ctx)
+      .await
+      .unwrap()
+      .unwrap()
+      .id;
+    let prefix = crate::util::random::generate_random_string(8);
+    let model = save(
+      &**ctx,
+      user_id,
+      "ci".to_string(),
+      prefix.clone(),
+      "hash".to_string(),
+      &[Scope::UserRead, Scope::TokenInfo],
+      None,
+    )
+    .await
+    .unwrap();
+    let found = find_by_prefix(&**ctx, &prefix).await.unwrap().unwrap();
+    assert_eq!(found.id, model.id);
+    assert_eq!(found.scopes(), vec![Scope::UserRead, Scope::TokenInfo]);
+    update_last_used(&**ctx, model.id).await.unwrap();
+    let list = find_by_user(&**ctx, user_id).await.unwrap();
+    assert!(
+      list
+        .iter()
+        .any(|m| m.id == model.id && m.last_used_at.is_some())
+    );
+    assert!(
+      !delete_by_user(&**ctx, Uuid::new_v4(), model.id)
+        .await
+        .unwrap()
+    );
+    assert!(delete_by_user(&**ctx, user_id, model.id).await.unwrap());
+    assert!(find_by_prefix(&**ctx, &prefix).await.unwrap().is_none());
+  }
+}
@@ -0,0 +1,125 @@
+use chrono::Utc;
+use sea_orm::{
+  ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use uuid::Uuid;
+
+use crate::{entity, error::AppResult};
+
+#[tracing::instrument(skip_all)]
+pub async fn save<C>(
+  conn: &C,
+  user_id: Uuid,
+  provider: String,
+  subject: String,
+  email: Option<String>,
+) -> AppResult<entity::identity::Model>
+where
+  C: ConnectionTrait,
+{
+  let model = entity::identity::ActiveModel {
+    id: Set(Uuid::new_v4()),
+    user_id: Set(user_id),
+    provider: Set(provider),
+    subject: Set(subject),
+    email: Set(email),
+    create_at: Set(Utc::now()),
+  }
+  .insert(conn)
+  .await?;
+  Ok(model)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn find_by_subject<C>(
+  conn: &C,
+  provider: &str,
+  subject: &str,
+) -> AppResult<Option<entity::identity::Model>>
+where
+  C: ConnectionTrait,
+{
+  let model = entity::identity::Entity::find()
+    .filter(
+      entity::identity::Column::Provider
+        .eq(provider)
+        .and(entity::identity::Column::Subject.eq(subject)),
+    )
+    .one(conn)
+    .await?;
+  Ok(model)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn find_by_user<C>(conn: &C, user_id: Uuid) -> AppResult<Vec<entity::identity::Model>>
+where
+  C: ConnectionTrait,
+{
+  let models = entity::identity::Entity::find()
+    .filter(entity::identity::Column::UserId.eq(user_id))
+    .order_by_asc(entity::identity::Column::CreateAt)
+    .all(conn)
+    .await?;
+  Ok(models)
+}
+
+/// Removes the link to the provider, returns false if the user had none.
+#[tracing::instrument(skip_all)]
+pub async fn delete_by_user<C>(conn: &C, user_id: Uuid, provider: &str) -> AppResult<bool>
+where
+  C: ConnectionTrait,
+{
+  let result = entity::identity::Entity::delete_many()
+    .filter(
+      entity::identity::Column::UserId
+        .eq(user_id)
+        .and(entity::identity::Column::Provider.eq(provider)),
+    )
+    .exec(conn)
+    .await?;
+  Ok(result.rows_affected == 1)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use test_context::test_context;
+
+  use crate::entity::TransactionTestContext;
+
+  #[test_context(TransactionTestContext)]
+  #[tokio::test]
+  async fn test_save_find_and_delete_identity(ctx: &mut TransactionTestContext) {
+    let user_id = crate::entity::user::Entity::find()
+      .one(&**ctx)
+      .await
+      .unwrap()
+      .unwrap()
+      .id;
+    let subject = Uuid::new_v4().to_string();
+    let model = save(&**ctx, user_id, "test".to_string(), subject.clone(), None)
+      .await
+      .unwrap();
+    let found = find_by_subject(&**ctx, "test", &subject)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(found.id, model.id);
+    assert!(
+      find_by_subject(&**ctx, "other", &subject)
+        .await
+        .unwrap()
+        .is_none()
+    );
+    let list = find_by_user(&**ctx, user_id).await.unwrap();
+    assert!(list.iter().any(|m| m.id == model.id));
+    assert!(!delete_by_user(&**ctx, user_id, "other").await.unwrap());
+    assert!(delete_by_user(&**ctx, user_id, "test").await.unwrap());
+    assert!(
+      find_by_subject(&**ctx, "test", &subject)
+        .await
+        .unwrap()
+        .is_none()
+    );
+  }
+}
@@ -0,0 +1,184 @@
+use chrono::{DateTime, Utc};
+use sea_orm::{
+  ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, Set,
+  sea_query::Expr,
+};
+use uuid::Uuid;
+
+use crate::{
+  entity::{self, role::RoleUser},
+  error::AppResult,
+};
+
+#[tracing::instrument(skip_all)]
+pub async fn save<C>(
+  conn: &C,
+  user_id: Uuid,
+  code_hash: String,
+  email: Option<String>,
+  role: RoleUser,
+  max_uses: i32,
+  expire_at: DateTime<Utc>,
+) -> AppResult<entity::invitation::Model>
+where
+  C: ConnectionTrait,
+{
+  let model = entity::invitation::ActiveModel {
+    id: Set(Uuid::new_v4()),
+    user_id: Set(user_id),
+    code_hash: Set(code_hash),
+    email: Set(email),
+    role: Set(role),
+    max_uses: Set(max_uses),
+    use_count: Set(0),
+    expire_at: Set(expire_at),
+    create_at: Set(Utc::now()),
+  }
+  .insert(conn)
+  .await?;
+  Ok(model)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn find_by_code_hash<C>(
+  conn: &C,
+  code_hash: &str,
+) -> AppResult<Option<entity::invitation::Model>>
+where
+  C: ConnectionTrait,
+{
+  let model = entity::invitation::Entity::find()
+    .filter(entity::invitation::Column::CodeHash.eq(code_hash))
+    .one(conn)
+    .await?;
+  Ok(model)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn find_by_user<C>(conn: &C, user_id: Uuid) -> AppResult<Vec<entity::invitation::Model>>
+where
+  C: ConnectionTrait,
+{
+  let models = entity::invitation::Entity::find()
+    .filter(entity::invitation::Column::UserId.eq(user_id))
+    .order_by_desc(entity::invitation::Column::CreateAt)
+    .all(conn)
+    .await?;
+  Ok(models)
+}
+
+/// Deletes the invitation if it belongs to the user, returns false if nothing was deleted.
+#[tracing::instrument(skip_all)]
+pub async fn delete_by_user<C>(conn: &C, user_id: Uuid, id: Uuid) -> AppResult<bool>
+where
+  C: ConnectionTrait,
+{
+  let result = entity::invitation::Entity::delete_many()
+    .filter(
+      entity::invitation::Column::Id
+        .eq(id)
+        .and(entity::invitation::Column::UserId.eq(user_id)),
+    )
+    .exec(conn)
+    .await?;
+  Ok(result.rows_affected == 1)
+}
+
+/// Counts one use unless the invitation is expired or used up, returns false in that case.
+#[tracing::instrument(skip_all)]
+pub async fn increment_use<C>(conn: &C, id: Uuid) -> AppResult<bool>
+where
+  C: ConnectionTrait,
+{
+  let result = entity::invitation::Entity::update_many()
+    .col_expr(
+      entity::invitation::Column::UseCount,
+      Expr::col(entity::invitation::Column::UseCount).add(1),
+    )
+    .filter(entity::invitation::Column::Id.eq(id))
+    .filter(
+      Expr::col(entity::invitation::Column::UseCount)
+        .lt(Expr::col(entity::invitation::Column::MaxUses)),
+    )
+    .filter(entity::invitation::Column::ExpireAt.gt(Utc::now()))
+    .exec(conn)
+    .await?;
+  Ok(result.rows_affected == 1)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use test_context::test_context;
+
+  use crate::entity::TransactionTestContext;
+
+  #[test_context(TransactionTestContext)]
+  #[tokio::test]
+  async fn test_save_and_increment_use_invitation(ctx: &mut TransactionTestContext) {
+    let user_id = crate::entity::user::Entity::find()
+      .one(&**ctx)
+      .await
+      .unwrap()
+      .unwrap()
+      .id;
+    let code_hash = crate::util::random::generate_random_string(16);
+    let model = save(
+      &**ctx,
+      user_id,
+      code_hash.clone(),
+      None,
+      RoleUser::User,
+      2,
+      Utc::now() + chrono::Duration::seconds(100),
+    )
+    .await
+    .unwrap();
+    let found = find_by_code_hash(&**ctx, &code_hash)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(found.id, model.id);
+    assert!(increment_use(&**ctx, model.id).await.unwrap());
+    assert!(increment_use(&**ctx, model.id).await.unwrap());
+    assert!(!increment_use(&**ctx, model.id).await.unwrap());
+    let found = find_by_code_hash(&**ctx, &code_hash)
+      .await
+      .unwrap()
+      .unwrap();
+    assert!(found.is_used_up());
+    let list = find_by_user(&**ctx, user_id).await.unwrap();
+    assert!(list.iter().any(|m| m.id == model.id));
+    assert!(delete_by_user(&**ctx, user_id, model.id).await.unwrap());
+    assert!(
+      find_by_code_hash(&**ctx, &code_hash)
+        .await
+        .unwrap()
+        .is_none()
+    );
+  }
+
+  #[test_context(TransactionTestContext)]
+  #[tokio::test]
+  async fn test_expired_invitation_is_not_used(ctx: &mut TransactionTestContext) {
+    let user_id = crate::entity::user::Entity::find()
+      .one(&**ctx)
+      .await
+      .unwrap()
+      .unwrap()
+      .id;
+    let model = save(
+      &**ctx,
+      user_id,
+      crate::util::random::generate_random_string(16),
+      None,
+      RoleUser::User,
+      1,
+      Utc::now() - chrono::Duration::seconds(1),
+    )
+    .await
+    .unwrap();
+    assert!(model.is_expired());
+    assert!(!increment_use(&**ctx, model.id).await.unwrap());
+  }
+}
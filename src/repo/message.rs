@@ -1,11 +1,12 @@
 use chrono::Utc;
 use sea_orm::{
   ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, EntityTrait,
-  QueryFilter, Set, TransactionTrait,
+  QueryFilter, QueryOrder, Set, TransactionTrait,
 };
 use uuid::Uuid;
 
 use crate::{
+  constant::MESSAGE_ENCRYPT_KEY,
   entity::{
     self,
     message::{MessageKind, MessageStatus},
@@ -13,6 +14,7 @@ use crate::{
   error::AppResult,
 };
 
+/// Returns the newest message of the kind.
 #[tracing::instrument(skip_all)]
 pub async fn find_by_user_and_kind<C>(
   conn: &C,
@@ -28,6 +30,7 @@ where
         .eq(user_id)
         .and(entity::message::Column::Kind.eq(kind)),
     )
+    .order_by_desc(entity::message::Column::CreateAt)
     .one(conn)
     .await?;
   Ok(model)
@@ -35,15 +38,78 @@ where
 
 #[tracing::instrument(skip_all)]
 pub async fn save<C>(conn: &C, user_id: Uuid, content: String, kind: MessageKind) -> AppResult<Uuid>
+where
+  C: ConnectionTrait,
+{
+  insert(conn, user_id, None, content, kind, MessageStatus::Pending).await
+}
+
+/// Saves a message sent to `receiver` instead of the user, e.g. to an account already deleted.
+#[tracing::instrument(skip_all)]
+pub async fn save_to<C>(
+  conn: &C,
+  user_id: Uuid,
+  receiver: String,
+  content: String,
+  kind: MessageKind,
+) -> AppResult<Uuid>
+where
+  C: ConnectionTrait,
+{
+  insert(
+    conn,
+    user_id,
+    Some(receiver),
+    content,
+    kind,
+    MessageStatus::Pending,
+  )
+  .await
+}
+
+/// Saves a message whose content is a secret, the messenger decrypts it to send it
+/// to `receiver`, or to the user when not given.
+#[tracing::instrument(skip_all)]
+pub async fn save_secret<C>(
+  conn: &C,
+  user_id: Uuid,
+  receiver: Option<String>,
+  content: &str,
+  kind: MessageKind,
+) -> AppResult<Uuid>
+where
+  C: ConnectionTrait,
+{
+  let content = crate::util::cipher::encrypt(&MESSAGE_ENCRYPT_KEY, content)?;
+  insert(
+    conn,
+    user_id,
+    receiver,
+    content,
+    kind,
+    MessageStatus::Pending,
+  )
+  .await
+}
+
+async fn insert<C>(
+  conn: &C,
+  user_id: Uuid,
+  receiver: Option<String>,
+  content: String,
+  kind: MessageKind,
+  status: MessageStatus,
+) -> AppResult<Uuid>
 where
   C: ConnectionTrait,
 {
   let model = crate::entity::message::ActiveModel {
     id: Set(Uuid::new_v4()),
     content: Set(content),
-    status: Set(crate::entity::message::MessageStatus::Pending),
+    status: Set(status),
     kind: Set(kind),
     user_id: Set(user_id),
+    receiver: Set(receiver),
     create_at: Set(Utc::now()),
     update_at: Set(Utc::now()),
   }
@@ -116,7 +182,12 @@ pub async fn update_status(
   model: entity::message::Model,
   status: MessageStatus,
 ) -> AppResult {
+  let secret = model.kind.is_secret();
   let mut model: entity::message::ActiveModel = model.into();
+  if secret && status == MessageStatus::Success {
+    // the credential is not needed once delivered, so it is not kept for backups to leak
+    model.content = Set(String::new());
+  }
   model.status = Set(status);
   model.update(conn).await?;
   Ok(())
@@ -145,6 +216,7 @@ mod tests {
       status: Set(MessageStatus::Pending),
       content: Set("code1".to_string()),
       user_id: Set(user_id),
+      receiver: Set(None),
       create_at: Set(Utc::now() - Duration::seconds(100)),
       update_at: Set(Utc::now() - Duration::seconds(100)),
     }
@@ -157,6 +229,7 @@ mod tests {
       status: Set(MessageStatus::Pending),
       content: Set("code2".to_string()),
       user_id: Set(user_id),
+      receiver: Set(None),
       create_at: Set(Utc::now() - Duration::seconds(10)),
       update_at: Set(Utc::now() - Duration::seconds(10)),
     }
@@ -169,6 +242,7 @@ mod tests {
       status: Set(MessageStatus::Pending),
       content: Set("code3".to_string()),
       user_id: Set(user_id),
+      receiver: Set(None),
       create_at: Set(Utc::now()),
       update_at: Set(Utc::now()),
     }
@@ -183,4 +257,62 @@ mod tests {
     let list = get_list_and_update(&**ctx, 100, 2).await.unwrap();
     assert_eq!(list.len(), 0);
   }
+
+  #[test_context(TransactionTestContext)]
+  #[tokio::test]
+  async fn test_find_newest_message_by_user_and_kind(ctx: &mut TransactionTestContext) {
+    let user_id = crate::entity::user::Entity::find()
+      .one(&**ctx)
+      .await
+      .unwrap()
+      .unwrap()
+      .id;
+    for (content, secs) in [("code1", 100), ("code2", 0), ("code3", 200)] {
+      entity::message::ActiveModel {
+        id: Set(Uuid::new_v4()),
+        kind: Set(MessageKind::ActiveCode),
+        status: Set(MessageStatus::Success),
+        content: Set(content.to_string()),
+        user_id: Set(user_id),
+        receiver: Set(None),
+        create_at: Set(Utc::now() - Duration::seconds(secs)),
+        update_at: Set(Utc::now() - Duration::seconds(secs)),
+      }
+      .insert(&**ctx)
+      .await
+      .unwrap();
+    }
+    let message = find_by_user_and_kind(&**ctx, user_id, MessageKind::ActiveCode)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(message.content, "code2");
+  }
+
+  #[test_context(TransactionTestContext)]
+  #[tokio::test]
+  async fn test_save_secret_message_encrypted(ctx: &mut TransactionTestContext) {
+    let user_id = crate::entity::user::Entity::find()
+      .one(&**ctx)
+      .await
+      .unwrap()
+      .unwrap()
+      .id;
+    save_secret(
+      &**ctx,
+      user_id,
+      None,
+      "code",
+      MessageKind::ForgetPasswordCode,
+    )
+    .await
+    .unwrap();
+    let message = find_by_user_and_kind(&**ctx, user_id, MessageKind::ForgetPasswordCode)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_ne!(message.content, "code");
+    let plain = crate::util::cipher::decrypt(&MESSAGE_ENCRYPT_KEY, &message.content).unwrap();
+    assert_eq!(plain, "code");
+  }
 }
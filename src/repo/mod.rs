@@ -1,2 +1,9 @@
+pub mod api_key;
+pub mod identity;
+pub mod invitation;
 pub mod message;
+pub mod oauth_client;
+pub mod passkey;
+pub mod recovery_code;
+pub mod security_event;
 pub mod user;
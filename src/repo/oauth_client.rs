@@ -0,0 +1,118 @@
+use chrono::Utc;
+use itertools::Itertools;
+use sea_orm::{
+  ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use uuid::Uuid;
+
+use crate::{
+  entity::{self, scope::Scope},
+  error::AppResult,
+};
+
+#[tracing::instrument(skip_all)]
+pub async fn save<C>(
+  conn: &C,
+  client_id: String,
+  secret_hash: Option<String>,
+  name: String,
+  redirect_uris: &[String],
+  scope: &[Scope],
+) -> AppResult<entity::oauth_client::Model>
+where
+  C: ConnectionTrait,
+{
+  let model = entity::oauth_client::ActiveModel {
+    id: Set(Uuid::new_v4()),
+    client_id: Set(client_id),
+    secret_hash: Set(secret_hash),
+    name: Set(name),
+    redirect_uris: Set(redirect_uris.join(" ")),
+    scope: Set(scope.iter().join(" ")),
+    create_at: Set(Utc::now()),
+  }
+  .insert(conn)
+  .await?;
+  Ok(model)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn find_by_client_id<C>(
+  conn: &C,
+  client_id: &str,
+) -> AppResult<Option<entity::oauth_client::Model>>
+where
+  C: ConnectionTrait,
+{
+  let model = entity::oauth_client::Entity::find()
+    .filter(entity::oauth_client::Column::ClientId.eq(client_id))
+    .one(conn)
+    .await?;
+  Ok(model)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn find_all<C>(conn: &C) -> AppResult<Vec<entity::oauth_client::Model>>
+where
+  C: ConnectionTrait,
+{
+  let models = entity::oauth_client::Entity::find()
+    .order_by_desc(entity::oauth_client::Column::CreateAt)
+    .all(conn)
+    .await?;
+  Ok(models)
+}
+
+/// Returns false if no client has the id.
+#[tracing::instrument(skip_all)]
+pub async fn delete<C>(conn: &C, id: Uuid) -> AppResult<bool>
+where
+  C: ConnectionTrait,
+{
+  let result = entity::oauth_client::Entity::delete_by_id(id)
+    .exec(conn)
+    .await?;
+  Ok(result.rows_affected == 1)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use test_context::test_context;
+
+  use crate::entity::TransactionTestContext;
+
+  #[test_context(TransactionTestContext)]
+  #[tokio::test]
+  async fn test_save_find_and_delete_oauth_client(ctx: &mut TransactionTestContext) {
+    let client_id = crate::util::random::generate_random_string(16);
+    let model = save(
+      &**ctx,
+      client_id.clone(),
+      None,
+      "spa".to_string(),
+      &["http://localhost/callback".to_string()],
+      &[Scope::UserRead],
+    )
+    .await
+    .unwrap();
+    let found = find_by_client_id(&**ctx, &client_id)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(found.id, model.id);
+    assert!(found.is_public());
+    assert!(found.has_redirect_uri("http://localhost/callback"));
+    assert!(!found.has_redirect_uri("http://localhost"));
+    assert_eq!(found.scopes(), vec![Scope::UserRead]);
+    assert!(
+      find_all(&**ctx)
+        .await
+        .unwrap()
+        .iter()
+        .any(|m| m.id == model.id)
+    );
+    assert!(delete(&**ctx, model.id).await.unwrap());
+    assert!(!delete(&**ctx, model.id).await.unwrap());
+  }
+}
@@ -0,0 +1,170 @@
+use chrono::Utc;
+use sea_orm::{
+  ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use uuid::Uuid;
+
+use crate::{entity, error::AppResult};
+
+#[tracing::instrument(skip_all)]
+pub async fn save<C>(
+  conn: &C,
+  user_id: Uuid,
+  name: String,
+  credential_id: String,
+  public_key: Vec<u8>,
+  algorithm: i32,
+  sign_count: i64,
+) -> AppResult<entity::passkey::Model>
+where
+  C: ConnectionTrait,
+{
+  let model = entity::passkey::ActiveModel {
+    id: Set(Uuid::new_v4()),
+    user_id: Set(user_id),
+    name: Set(name),
+    credential_id: Set(credential_id),
+    public_key: Set(public_key),
+    algorithm: Set(algorithm),
+    sign_count: Set(sign_count),
+    last_used_at: Set(None),
+    create_at: Set(Utc::now()),
+  }
+  .insert(conn)
+  .await?;
+  Ok(model)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn find_by_credential_id<C>(
+  conn: &C,
+  credential_id: &str,
+) -> AppResult<Option<entity::passkey::Model>>
+where
+  C: ConnectionTrait,
+{
+  let model = entity::passkey::Entity::find()
+    .filter(entity::passkey::Column::CredentialId.eq(credential_id))
+    .one(conn)
+    .await?;
+  Ok(model)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn find_by_user<C>(conn: &C, user_id: Uuid) -> AppResult<Vec<entity::passkey::Model>>
+where
+  C: ConnectionTrait,
+{
+  let models = entity::passkey::Entity::find()
+    .filter(entity::passkey::Column::UserId.eq(user_id))
+    .order_by_asc(entity::passkey::Column::CreateAt)
+    .all(conn)
+    .await?;
+  Ok(models)
+}
+
+/// Deletes the passkey if it belongs to the user, returns false if nothing was deleted.
+#[tracing::instrument(skip_all)]
+pub async fn delete_by_user<C>(conn: &C, user_id: Uuid, id: Uuid) -> AppResult<bool>
+where
+  C: ConnectionTrait,
+{
+  let result = entity::passkey::Entity::delete_many()
+    .filter(
+      entity::passkey::Column::Id
+        .eq(id)
+        .and(entity::passkey::Column::UserId.eq(user_id)),
+    )
+    .exec(conn)
+    .await?;
+  Ok(result.rows_affected == 1)
+}
+
+/// Stores the counter of the last assertion, returns false if a concurrent
+/// assertion already moved it past `current`.
+#[tracing::instrument(skip_all)]
+pub async fn update_sign_count<C>(
+  conn: &C,
+  id: Uuid,
+  current: i64,
+  sign_count: i64,
+) -> AppResult<bool>
+where
+  C: ConnectionTrait,
+{
+  let result = entity::passkey::Entity::update_many()
+    .col_expr(
+      entity::passkey::Column::SignCount,
+      sea_orm::sea_query::Expr::value(sign_count),
+    )
+    .col_expr(
+      entity::passkey::Column::LastUsedAt,
+      sea_orm::sea_query::Expr::value(Utc::now()),
+    )
+    .filter(
+      entity::passkey::Column::Id
+        .eq(id)
+        .and(entity::passkey::Column::SignCount.eq(current)),
+    )
+    .exec(conn)
+    .await?;
+  Ok(result.rows_affected == 1)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use test_context::test_context;
+
+  use crate::entity::TransactionTestContext;
+
+  #[test_context(TransactionTestContext)]
+  #[tokio::test]
+  async fn test_save_find_update_and_delete_passkey(ctx: &mut TransactionTestContext) {
+    let user_id = crate::entity::user::Entity::find()
+      .one(&**ctx)
+      .await
+      .unwrap()
+      .unwrap()
+      .id;
+    let credential_id = crate::util::random::generate_random_string(16);
+    let model = save(
+      &**ctx,
+      user_id,
+      "laptop".to_string(),
+      credential_id.clone(),
+      vec![1, 2, 3],
+      -7,
+      0,
+    )
+    .await
+    .unwrap();
+    let found = find_by_credential_id(&**ctx, &credential_id)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(found, model);
+    let list = find_by_user(&**ctx, user_id).await.unwrap();
+    assert!(list.iter().any(|m| m.id == model.id));
+    assert!(update_sign_count(&**ctx, model.id, 0, 5).await.unwrap());
+    assert!(!update_sign_count(&**ctx, model.id, 0, 6).await.unwrap());
+    let found = find_by_credential_id(&**ctx, &credential_id)
+      .await
+      .unwrap()
+      .unwrap();
+    assert_eq!(found.sign_count, 5);
+    assert!(found.last_used_at.is_some());
+    assert!(
+      !delete_by_user(&**ctx, Uuid::new_v4(), model.id)
+        .await
+        .unwrap()
+    );
+    assert!(delete_by_user(&**ctx, user_id, model.id).await.unwrap());
+    assert!(
+      find_by_credential_id(&**ctx, &credential_id)
+        .await
+        .unwrap()
+        .is_none()
+    );
+  }
+}
@@ -0,0 +1,120 @@
+use chrono::Utc;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, PaginatorTrait, QueryFilter, Set};
+use uuid::Uuid;
+
+use crate::{entity, error::AppResult};
+
+#[tracing::instrument(skip_all)]
+pub async fn replace_all<C>(conn: &C, user_id: Uuid, code_hashes: Vec<String>) -> AppResult
+where
+  C: ConnectionTrait,
+{
+  delete_by_user(conn, user_id).await?;
+  let models = code_hashes
+    .into_iter()
+    .map(|code_hash| entity::recovery_code::ActiveModel {
+      id: Set(Uuid::new_v4()),
+      user_id: Set(user_id),
+      code_hash: Set(code_hash),
+      used_at: Set(None),
+      create_at: Set(Utc::now()),
+    });
+  entity::recovery_code::Entity::insert_many(models)
+    .exec(conn)
+    .await?;
+  Ok(())
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn delete_by_user<C>(conn: &C, user_id: Uuid) -> AppResult
+where
+  C: ConnectionTrait,
+{
+  entity::recovery_code::Entity::delete_many()
+    .filter(entity::recovery_code::Column::UserId.eq(user_id))
+    .exec(conn)
+    .await?;
+  Ok(())
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn find_unused_by_user<C>(
+  conn: &C,
+  user_id: Uuid,
+) -> AppResult<Vec<entity::recovery_code::Model>>
+where
+  C: ConnectionTrait,
+{
+  let models = entity::recovery_code::Entity::find()
+    .filter(
+      entity::recovery_code::Column::UserId
+        .eq(user_id)
+        .and(entity::recovery_code::Column::UsedAt.is_null()),
+    )
+    .all(conn)
+    .await?;
+  Ok(models)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn count_unused_by_user<C>(conn: &C, user_id: Uuid) -> AppResult<u64>
+where
+  C: ConnectionTrait,
+{
+  let count = entity::recovery_code::Entity::find()
+    .filter(
+      entity::recovery_code::Column::UserId
+        .eq(user_id)
+        .and(entity::recovery_code::Column::UsedAt.is_null()),
+    )
+    .count(conn)
+    .await?;
+  Ok(count)
+}
+
+/// Marks the code as used, returns false if it was already consumed concurrently.
+#[tracing::instrument(skip_all)]
+pub async fn mark_used<C>(conn: &C, id: Uuid) -> AppResult<bool>
+where
+  C: ConnectionTrait,
+{
+  let result = entity::recovery_code::Entity::update_many()
+    .col_expr(
+      entity::recovery_code::Column::UsedAt,
+      sea_orm::sea_query::Expr::value(Utc::now()),
+    )
+    .filter(
+      entity::recovery_code::Column::Id
+        .eq(id)
+        .and(entity::recovery_code::Column::UsedAt.is_null()),
+    )
+    .exec(conn)
+    .await?;
+  Ok(result.rows_affected == 1)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use test_context::test_context;
+
+  use crate::entity::TransactionTestContext;
+
+  #[test_context(TransactionTestContext)]
+  #[tokio::test]
+  async fn test_replace_and_mark_used_recovery_code(ctx: &mut TransactionTestContext) {
+    let user_id = crate::entity::user::Entity::find()
+      .one(&**ctx)
+      .await
+      .unwrap()
+      .unwrap()
+      .id;
+    let hashes = vec!["hash1".to_string(), "hash2".to_string()];
+    replace_all(&**ctx, user_id, hashes).await.unwrap();
+    let codes = find_unused_by_user(&**ctx, user_id).await.unwrap();
+    assert_eq!(codes.len(), 2);
+    assert!(mark_used(&**ctx, codes[0].id).await.unwrap());
+    assert!(!mark_used(&**ctx, codes[0].id).await.unwrap());
+    assert_eq!(count_unused_by_user(&**ctx, user_id).await.unwrap(), 1);
+  }
+}
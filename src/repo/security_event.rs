@@ -0,0 +1,83 @@
+use chrono::Utc;
+use sea_orm::{
+  ActiveModelTrait, ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder, Set,
+};
+use uuid::Uuid;
+
+use crate::{
+  entity::{self, security_event::SecurityEventKind},
+  error::AppResult,
+  util::client_info::ClientInfo,
+};
+
+#[tracing::instrument(skip_all)]
+pub async fn save<C>(
+  conn: &C,
+  user_id: Uuid,
+  kind: SecurityEventKind,
+  session_id: Option<Uuid>,
+  client: ClientInfo,
+) -> AppResult<Uuid>
+where
+  C: ConnectionTrait,
+{
+  let model = entity::security_event::ActiveModel {
+    id: Set(Uuid::new_v4()),
+    kind: Set(kind),
+    user_id: Set(user_id),
+    session_id: Set(session_id),
+    ip: Set(client.ip),
+    user_agent: Set(client.user_agent),
+    create_at: Set(Utc::now()),
+  }
+  .insert(conn)
+  .await?;
+  Ok(model.id)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn find_by_user<C>(
+  conn: &C,
+  user_id: Uuid,
+) -> AppResult<Vec<entity::security_event::Model>>
+where
+  C: ConnectionTrait,
+{
+  let models = entity::security_event::Entity::find()
+    .filter(entity::security_event::Column::UserId.eq(user_id))
+    .order_by_desc(entity::security_event::Column::CreateAt)
+    .all(conn)
+    .await?;
+  Ok(models)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use test_context::test_context;
+
+  use crate::entity::TransactionTestContext;
+
+  #[test_context(TransactionTestContext)]
+  #[tokio::test]
+  async fn test_save_and_find_security_event(ctx: &mut TransactionTestContext) {
+    let user_id = crate::entity::user::Entity::find()
+      .one(&**ctx)
+      .await
+      .unwrap()
+      .unwrap()
+      .id;
+    let session_id = Uuid::new_v4();
+    save(
+      &**ctx,
+      user_id,
+      SecurityEventKind::RefreshTokenReuse,
+      Some(session_id),
+      ClientInfo::default(),
+    )
+    .await
+    .unwrap();
+    let events = find_by_user(&**ctx, user_id).await.unwrap();
+    assert!(events.iter().any(|e| e.session_id == Some(session_id)));
+  }
+}
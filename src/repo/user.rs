@@ -1,13 +1,14 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use sea_orm::{
   ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection,
-  DatabaseTransaction, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, Set, sea_query::Expr,
+  DatabaseTransaction, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, Set, TransactionTrait,
+  sea_query::Expr,
 };
 use uuid::Uuid;
 
 use crate::{
   dto::{Direction, PageQueryParam},
-  entity,
+  entity::{self, role::RoleUser},
   error::{AppResult, ToAppResult},
   util,
 };
@@ -18,15 +19,20 @@ pub async fn save(
   username: String,
   password: String,
   email: String,
+  role: RoleUser,
+  is_approved: bool,
 ) -> AppResult<Uuid> {
   let user = crate::entity::user::ActiveModel {
     id: Set(Uuid::new_v4()),
     username: Set(username),
     password: Set(util::password::hash(password).await?),
     email: Set(email),
-    role: Set(crate::entity::role::RoleUser::User),
+    role: Set(role),
     is_active: Set(false),
     is_2fa: Set(false),
+    is_approved: Set(is_approved),
+    two_factor_method: Set(crate::entity::two_factor::TwoFactorMethod::Email),
+    totp_secret: Set(None),
     create_at: Set(Utc::now()),
     update_at: Set(Utc::now()),
   }
@@ -57,6 +63,104 @@ pub async fn update_password(
   Ok(())
 }
 
+/// Deletes accounts never activated and created before `create_before`, with their messages.
+#[tracing::instrument(skip_all)]
+pub async fn delete_inactive_before<C>(conn: &C, create_before: DateTime<Utc>) -> AppResult<u64>
+where
+  C: TransactionTrait,
+{
+  let tx = conn.begin().await?;
+  let ids = entity::user::Entity::find()
+    .filter(
+      entity::user::Column::IsActive
+        .eq(false)
+        .and(entity::user::Column::CreateAt.lt(create_before)),
+    )
+    .all(&tx)
+    .await?
+    .into_iter()
+    .map(|user| user.id)
+    .collect::<Vec<_>>();
+  let count = delete_by_ids(&tx, ids).await?;
+  tx.commit().await?;
+  Ok(count)
+}
+
+/// Deletes the users with their messages.
+#[tracing::instrument(skip_all)]
+pub async fn delete_by_ids(tx: &DatabaseTransaction, ids: Vec<Uuid>) -> AppResult<u64> {
+  if ids.is_empty() {
+    return Ok(0);
+  }
+  entity::message::Entity::delete_many()
+    .filter(entity::message::Column::UserId.is_in(ids.clone()))
+    .exec(tx)
+    .await?;
+  entity::recovery_code::Entity::delete_many()
+    .filter(entity::recovery_code::Column::UserId.is_in(ids.clone()))
+    .exec(tx)
+    .await?;
+  entity::security_event::Entity::delete_many()
+    .filter(entity::security_event::Column::UserId.is_in(ids.clone()))
+    .exec(tx)
+    .await?;
+  let result = entity::user::Entity::delete_many()
+    .filter(entity::user::Column::Id.is_in(ids))
+    .exec(tx)
+    .await?;
+  Ok(result.rows_affected)
+}
+
+/// Returns the users waiting for an approval decision, oldest first.
+#[tracing::instrument(skip_all)]
+pub async fn find_pending_approval<C>(conn: &C) -> AppResult<Vec<entity::user::Model>>
+where
+  C: ConnectionTrait,
+{
+  let models = entity::user::Entity::find()
+    .filter(entity::user::Column::IsApproved.eq(false))
+    .order_by_asc(entity::user::Column::CreateAt)
+    .all(conn)
+    .await?;
+  Ok(models)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn approve<C>(conn: &C, user: entity::user::Model) -> AppResult
+where
+  C: ConnectionTrait,
+{
+  let mut model: entity::user::ActiveModel = user.into();
+  model.is_approved = Set(true);
+  model.update_at = Set(Utc::now());
+  model.update(conn).await?;
+  Ok(())
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn find_by_email<C>(conn: &C, email: &str) -> AppResult<Option<entity::user::Model>>
+where
+  C: ConnectionTrait,
+{
+  let model = entity::user::Entity::find()
+    .filter(entity::user::Column::Email.eq(email))
+    .one(conn)
+    .await?;
+  Ok(model)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn find_by_username<C>(conn: &C, username: &str) -> AppResult<Option<entity::user::Model>>
+where
+  C: ConnectionTrait,
+{
+  let model = entity::user::Entity::find()
+    .filter(entity::user::Column::Username.eq(username))
+    .one(conn)
+    .await?;
+  Ok(model)
+}
+
 #[tracing::instrument(skip_all)]
 pub async fn find_by_id<C>(conn: &C, id: Uuid) -> AppResult<Option<entity::user::Model>>
 where
@@ -160,3 +264,64 @@ pub async fn exist_by_username_and_status(
       .is_some(),
   )
 }
+
+#[cfg(test)]
+mod tests {
+  use chrono::Duration;
+  use fake::{Fake, Faker};
+  use test_context::test_context;
+
+  use super::*;
+  use crate::entity::TransactionTestContext;
+  use crate::entity::message::MessageKind;
+
+  async fn insert_user(
+    conn: &DatabaseTransaction,
+    is_active: bool,
+    create_at: DateTime<Utc>,
+  ) -> Uuid {
+    let id = Uuid::new_v4();
+    entity::user::ActiveModel {
+      id: Set(id),
+      username: Set(Faker.fake()),
+      password: Set(Faker.fake()),
+      email: Set(Faker.fake()),
+      role: Set(Faker.fake()),
+      is_active: Set(is_active),
+      is_2fa: Set(false),
+      is_approved: Set(true),
+      two_factor_method: Set(Faker.fake()),
+      totp_secret: Set(None),
+      create_at: Set(create_at),
+      update_at: Set(create_at),
+    }
+    .insert(conn)
+    .await
+    .unwrap();
+    id
+  }
+
+  #[test_context(TransactionTestContext)]
+  #[tokio::test]
+  async fn test_delete_inactive_users_before(ctx: &mut TransactionTestContext) {
+    let old = Utc::now() - Duration::days(10);
+    let stale_id = insert_user(ctx, false, old).await;
+    let active_id = insert_user(ctx, true, old).await;
+    let recent_id = insert_user(ctx, false, Utc::now()).await;
+    crate::repo::message::save(
+      &**ctx,
+      stale_id,
+      "code".to_string(),
+      MessageKind::ActiveCode,
+    )
+    .await
+    .unwrap();
+    let count = delete_inactive_before(&**ctx, Utc::now() - Duration::days(1))
+      .await
+      .unwrap();
+    assert!(count >= 1);
+    assert!(find_by_id(&**ctx, stale_id).await.unwrap().is_none());
+    assert!(find_by_id(&**ctx, active_id).await.unwrap().is_some());
+    assert!(find_by_id(&**ctx, recent_id).await.unwrap().is_some());
+  }
+}
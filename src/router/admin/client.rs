@@ -0,0 +1,13 @@
+use axum::routing::{delete, get};
+
+use crate::handler::admin;
+use crate::server::state::AppState;
+
+pub fn add_routers(router: axum::Router<AppState>) -> axum::Router<AppState> {
+  router
+    .route(
+      "/api/v1/admin/clients",
+      get(admin::client::list).post(admin::client::create),
+    )
+    .route("/api/v1/admin/clients/{id}", delete(admin::client::delete))
+}
@@ -1,8 +1,13 @@
-use axum::routing::get;
+use axum::routing::{delete, get, put};
 
 use crate::handler::admin;
 use crate::server::state::AppState;
 
 pub fn add_routers(router: axum::Router<AppState>) -> axum::Router<AppState> {
-  router.route("/api/v1/admin/user/list", get(admin::user::list))
+  router
+    .route("/api/v1/admin/user/list", get(admin::user::list))
+    .route("/api/v1/admin/user/{id}/lock", delete(admin::user::unlock))
+    .route("/api/v1/admin/user/pending", get(admin::user::list_pending))
+    .route("/api/v1/admin/user/{id}/approve", put(admin::user::approve))
+    .route("/api/v1/admin/user/{id}/reject", put(admin::user::reject))
 }
@@ -0,0 +1,11 @@
+use axum::routing::{delete, get, post};
+
+use crate::handler::invitation;
+use crate::server::state::AppState;
+
+pub fn add_routers(router: axum::Router<AppState>) -> axum::Router<AppState> {
+  router
+    .route("/api/v1/invitations", post(invitation::create))
+    .route("/api/v1/invitations", get(invitation::list))
+    .route("/api/v1/invitations/{id}", delete(invitation::revoke))
+}
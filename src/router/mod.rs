@@ -1,12 +1,16 @@
-use crate::{handler::openapi::ApiDoc, server::state::AppState};
+use crate::{handler::openapi::ApiDoc, middleware, server::state::AppState};
 use axum::Router;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 pub mod admin;
+pub mod invitation;
+pub mod oauth;
+pub mod oidc;
 pub mod server;
 pub mod token;
 pub mod user;
+pub mod well_known;
 
 pub fn create_router_app(state: AppState) -> Router {
   let router = Router::new()
@@ -14,6 +18,20 @@ pub fn create_router_app(state: AppState) -> Router {
   let router = server::add_routers(router);
   let router = user::add_routers(router);
   let router = token::add_routers(router);
+  let router = oidc::add_routers(router);
+  let router = invitation::add_routers(router);
+  let router = oauth::add_routers(router);
   let router = admin::user::add_routers(router);
-  router.with_state(state)
+  let router = admin::client::add_routers(router);
+  let router = well_known::add_routers(router);
+  router
+    .layer(axum::middleware::from_fn_with_state(
+      state.clone(),
+      middleware::rate_limit::rate_limit,
+    ))
+    .layer(axum::middleware::from_fn_with_state(
+      state.clone(),
+      middleware::csrf::csrf,
+    ))
+    .with_state(state)
 }
@@ -0,0 +1,22 @@
+use axum::routing::{get, post};
+
+use crate::handler::oauth;
+use crate::server::state::AppState;
+
+pub fn add_routers(router: axum::Router<AppState>) -> axum::Router<AppState> {
+  router
+    .route(
+      "/oauth2/authorize",
+      get(oauth::authorize).post(oauth::consent),
+    )
+    .route("/oauth2/token", post(oauth::token))
+    .route(
+      "/oauth2/device_authorization",
+      post(oauth::device_authorization),
+    )
+    .route(
+      "/oauth2/device",
+      get(oauth::device_page).post(oauth::device_verify),
+    )
+    .route("/oauth2/userinfo", get(oauth::userinfo))
+}
@@ -0,0 +1,25 @@
+use axum::routing::{get, post};
+
+use crate::handler::oidc;
+use crate::server::state::AppState;
+
+pub fn add_routers(router: axum::Router<AppState>) -> axum::Router<AppState> {
+  router
+    .route(
+      "/api/v1/user/oidc/{provider}/authorize",
+      get(oidc::authorize),
+    )
+    .route("/api/v1/user/oidc/{provider}/login", post(oidc::login))
+    .route(
+      "/api/v1/user/profile/identities",
+      get(oidc::list_identities),
+    )
+    .route(
+      "/api/v1/user/profile/identities/{provider}/authorize",
+      post(oidc::authorize_link),
+    )
+    .route(
+      "/api/v1/user/profile/identities/{provider}",
+      post(oidc::link_identity).delete(oidc::unlink_identity),
+    )
+}
@@ -1,4 +1,4 @@
-use axum::routing::{get, post, put};
+use axum::routing::{delete, get, post, put};
 
 use crate::handler::user;
 use crate::server::state::AppState;
@@ -7,11 +7,49 @@ pub fn add_routers(router: axum::Router<AppState>) -> axum::Router<AppState> {
   router
     .route("/api/v1/user/register", post(user::register))
     .route("/api/v1/user/active", put(user::active))
+    .route("/api/v1/user/active/resend", post(user::resend_active_code))
     .route("/api/v1/user/login", post(user::login))
+    .route(
+      "/api/v1/user/login/magic-link",
+      post(user::request_magic_link).get(user::magic_link_page),
+    )
+    .route(
+      "/api/v1/user/login/magic-link/verify",
+      post(user::login_magic_link),
+    )
+    .route(
+      "/api/v1/user/login/passkey/options",
+      post(user::login_passkey_options),
+    )
+    .route("/api/v1/user/login/passkey", post(user::login_passkey))
     .route("/api/v1/user/login2fa", post(user::login2fa))
+    .route("/api/v1/user/login2fa/email", post(user::login2fa_email))
+    .route("/api/v1/user/2fa/totp", post(user::enroll_totp))
+    .route("/api/v1/user/2fa/totp", put(user::confirm_totp))
+    .route("/api/v1/user/2fa/totp", delete(user::disable_totp))
+    .route(
+      "/api/v1/user/2fa/recovery_codes",
+      post(user::regenerate_recovery_codes),
+    )
     .route("/api/v1/user/logout", get(user::logout))
+    .route("/api/v1/user/sessions", get(user::list_sessions))
+    .route("/api/v1/user/sessions", delete(user::revoke_other_sessions))
+    .route("/api/v1/user/sessions/{sid}", delete(user::revoke_session))
+    .route("/api/v1/user/api-keys", post(user::create_api_key))
+    .route("/api/v1/user/api-keys", get(user::list_api_keys))
+    .route("/api/v1/user/api-keys/{id}", delete(user::revoke_api_key))
+    .route(
+      "/api/v1/user/passkeys",
+      post(user::start_passkey_registration),
+    )
+    .route("/api/v1/user/passkeys", put(user::register_passkey))
+    .route("/api/v1/user/passkeys", get(user::list_passkeys))
+    .route("/api/v1/user/passkeys/{id}", delete(user::delete_passkey))
     .route("/api/v1/user/password", get(user::forget_password))
     .route("/api/v1/user/password", put(user::reset_password))
     .route("/api/v1/user/profile", get(user::get_profile))
     .route("/api/v1/user/profile", put(user::update_profile))
+    .route("/api/v1/user/profile/password", put(user::change_password))
+    .route("/api/v1/user/email", post(user::request_email_change))
+    .route("/api/v1/user/email", put(user::confirm_email_change))
 }
@@ -0,0 +1,12 @@
+use axum::routing::get;
+
+use crate::{handler::well_known, server::state::AppState};
+
+pub fn add_routers(router: axum::Router<AppState>) -> axum::Router<AppState> {
+  router
+    .route("/.well-known/jwks.json", get(well_known::jwks))
+    .route(
+      "/.well-known/openid-configuration",
+      get(well_known::openid_configuration),
+    )
+}
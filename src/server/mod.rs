@@ -1,8 +1,11 @@
+use std::net::SocketAddr;
+
 use self::state::AppState;
 use crate::configure::AppConfig;
 use crate::error::AppResult;
 use crate::router::create_router_app;
 pub mod state;
+pub mod tls;
 pub mod worker;
 
 pub struct AppServer {
@@ -20,8 +23,25 @@ impl AppServer {
   }
 
   pub async fn run(self) -> AppResult<()> {
+    let tls = self.state.config.server.tls.clone();
     let router = create_router_app(self.state);
-    axum::serve(self.tcp, router).await?;
+    if tls.enabled {
+      let listener = tls::TlsListener::new(self.tcp, tls::acceptor(&tls)?)?;
+      let router = router.layer(axum::middleware::map_request(move |req| {
+        tls::expose_peer(tls.certificate_bound_tokens, req)
+      }));
+      axum::serve(
+        listener,
+        router.into_make_service_with_connect_info::<tls::TlsPeer>(),
+      )
+      .await?;
+    } else {
+      axum::serve(
+        self.tcp,
+        router.into_make_service_with_connect_info::<SocketAddr>(),
+      )
+      .await?;
+    }
     Ok(())
   }
 }
@@ -0,0 +1,172 @@
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use axum::extract::{ConnectInfo, Request, connect_info::Connected};
+use axum::serve::{IncomingStream, Listener};
+use openssl::pkey::PKey;
+use openssl::ssl::{Ssl, SslAcceptor, SslMethod, SslVerifyMode};
+use openssl::x509::X509;
+use openssl::x509::store::X509StoreBuilder;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio_openssl::SslStream;
+use tracing::{info, warn};
+
+use crate::configure::secret::KeySource;
+use crate::configure::server::TlsConfig;
+use crate::constant::{TLS_ACCEPT_QUEUE_LEN, TLS_ACCEPT_RETRY_DELAY, TLS_HANDSHAKE_TIMEOUT};
+use crate::error::{AppError, AppResult};
+use crate::util::certificate::{ClientCertificate, TlsSession};
+
+/// Accepts TLS connections for `axum::serve`. Handshakes run in their own tasks,
+/// so a slow client does not hold up the others.
+pub struct TlsListener {
+  local_addr: SocketAddr,
+  connections: mpsc::Receiver<(SslStream<TcpStream>, TlsPeer)>,
+}
+
+/// The remote end of a TLS connection and the certificate it presented.
+#[derive(Debug, Clone)]
+pub struct TlsPeer {
+  pub addr: SocketAddr,
+  pub certificate: Option<ClientCertificate>,
+}
+
+impl TlsListener {
+  pub fn new(tcp: TcpListener, acceptor: SslAcceptor) -> AppResult<Self> {
+    let local_addr = tcp.local_addr()?;
+    let (sender, connections) = mpsc::channel(TLS_ACCEPT_QUEUE_LEN);
+    tokio::spawn(accept_loop(tcp, Arc::new(acceptor), sender));
+    Ok(Self {
+      local_addr,
+      connections,
+    })
+  }
+}
+
+impl Listener for TlsListener {
+  type Io = SslStream<TcpStream>;
+  type Addr = TlsPeer;
+
+  async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+    match self.connections.recv().await {
+      Some(connection) => connection,
+      // the accept loop only stops once the listener is dropped
+      None => std::future::pending().await,
+    }
+  }
+
+  fn local_addr(&self) -> std::io::Result<Self::Addr> {
+    Ok(TlsPeer {
+      addr: self.local_addr,
+      certificate: None,
+    })
+  }
+}
+
+impl Connected<IncomingStream<'_, TlsListener>> for TlsPeer {
+  fn connect_info(stream: IncomingStream<'_, TlsListener>) -> Self {
+    stream.remote_addr().clone()
+  }
+}
+
+async fn accept_loop(
+  tcp: TcpListener,
+  acceptor: Arc<SslAcceptor>,
+  sender: mpsc::Sender<(SslStream<TcpStream>, TlsPeer)>,
+) {
+  while !sender.is_closed() {
+    let (stream, addr) = match tcp.accept().await {
+      Ok(connection) => connection,
+      Err(e) => {
+        warn!("Failed to accept tcp connection: {e}");
+        tokio::time::sleep(TLS_ACCEPT_RETRY_DELAY).await;
+        continue;
+      }
+    };
+    let acceptor = acceptor.clone();
+    let sender = sender.clone();
+    tokio::spawn(async move {
+      let stream =
+        match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, handshake(&acceptor, stream)).await {
+          Ok(Ok(stream)) => stream,
+          Ok(Err(e)) => {
+            info!("TLS handshake with {addr} failed: {e}");
+            return;
+          }
+          Err(_) => {
+            info!("TLS handshake with {addr} timed out.");
+            return;
+          }
+        };
+      let certificate = stream
+        .ssl()
+        .peer_certificate()
+        .and_then(|certificate| ClientCertificate::from_x509(&certificate).ok());
+      let _ = sender.send((stream, TlsPeer { addr, certificate })).await;
+    });
+  }
+}
+
+async fn handshake(
+  acceptor: &SslAcceptor,
+  stream: TcpStream,
+) -> Result<SslStream<TcpStream>, openssl::ssl::Error> {
+  let mut stream = SslStream::new(Ssl::new(acceptor.context())?, stream)?;
+  Pin::new(&mut stream).accept().await?;
+  Ok(stream)
+}
+
+/// Builds the acceptor of the server certificate. With a client CA a client
+/// certificate is requested but not required, people still sign in with tokens.
+pub fn acceptor(config: &TlsConfig) -> AppResult<SslAcceptor> {
+  let mut builder = SslAcceptor::mozilla_intermediate_v5(SslMethod::tls_server())?;
+  let mut chain =
+    X509::stack_from_pem(read(&config.certificate, "certificate")?.as_bytes())?.into_iter();
+  let certificate = chain.next().ok_or_else(|| missing_setting("certificate"))?;
+  builder.set_certificate(&certificate)?;
+  for certificate in chain {
+    builder.add_extra_chain_cert(certificate)?;
+  }
+  let private_key =
+    PKey::private_key_from_pem(read(&config.private_key, "private_key")?.as_bytes())?;
+  builder.set_private_key(&private_key)?;
+  builder.check_private_key()?;
+  if let Some(client_ca) = &config.client_ca {
+    let mut store = X509StoreBuilder::new()?;
+    for certificate in X509::stack_from_pem(client_ca.read()?.as_bytes())? {
+      builder.add_client_ca(&certificate)?;
+      store.add_cert(certificate)?;
+    }
+    builder.set_verify_cert_store(store.build())?;
+    builder.set_verify(SslVerifyMode::PEER);
+  }
+  Ok(builder.build())
+}
+
+/// Exposes a TLS connection to the extractors: its remote address the way a
+/// plain connection does, and the connection itself as a `TlsSession`.
+pub async fn expose_peer(certificate_bound_tokens: bool, mut req: Request) -> Request {
+  if let Some(ConnectInfo(peer)) = req.extensions().get::<ConnectInfo<TlsPeer>>().cloned() {
+    req.extensions_mut().insert(ConnectInfo(peer.addr));
+    req.extensions_mut().insert(TlsSession {
+      certificate: peer.certificate,
+      certificate_bound_tokens,
+    });
+  }
+  req
+}
+
+fn read(source: &Option<KeySource>, name: &str) -> AppResult<String> {
+  Ok(
+    source
+      .as_ref()
+      .ok_or_else(|| missing_setting(name))?
+      .read()?,
+  )
+}
+
+fn missing_setting(name: &str) -> AppError {
+  AppError::ConfigError(config::ConfigError::NotFound(format!("server.tls.{name}")))
+}
@@ -1,8 +1,9 @@
+use chrono::Utc;
 use tracing::info;
 
 use crate::{
   client::email::EmailClientExt,
-  constant::{APP_EMAIL_ADDR, TEMPLATE_ENGIN},
+  constant::{APP_EMAIL_ADDR, MESSAGE_ENCRYPT_KEY, TEMPLATE_ENGIN},
   continue_if_fail,
   dto::{Email, Template},
   entity::{self, message::MessageStatus},
@@ -58,9 +59,10 @@ impl MessengerTask {
             continue;
           }
         };
+        let receiver = message.receiver.clone().unwrap_or(user.email);
         let email = Email::new(
           APP_EMAIL_ADDR.to_string(),
-          user.email,
+          receiver,
           message.kind.to_string(),
           message_content,
         );
@@ -77,26 +79,101 @@ impl MessengerTask {
   }
 }
 
+/// Deletes accounts that were never activated within the configured period.
+pub struct PurgeTask {
+  state: AppState,
+}
+
+impl PurgeTask {
+  pub fn new(state: AppState) -> Self {
+    Self { state }
+  }
+
+  pub async fn run(self) -> AppResult {
+    info!("The purge task has started.");
+    let config = &self.state.config.worker;
+    loop {
+      let before = Utc::now() - chrono::Duration::seconds(config.purge_inactive_user_after as i64);
+      match repo::user::delete_inactive_before(&*self.state.db, before).await {
+        Ok(count) => info!("The purge task deleted {count} inactive users."),
+        Err(err) => tracing::error!("Deleting inactive users failed: {err}"),
+      }
+      tokio::time::sleep(std::time::Duration::from_secs(config.purge_task_interval)).await;
+    }
+  }
+}
+
 pub fn render_template(
   message: &entity::message::Model,
   user: &entity::user::Model,
 ) -> AppResult<String> {
+  let content = if message.kind.is_secret() {
+    crate::util::cipher::decrypt(&MESSAGE_ENCRYPT_KEY, &message.content)?
+  } else {
+    message.content.clone()
+  };
   let template = match message.kind {
     entity::message::MessageKind::ActiveCode => Template::ActiveUser {
       username: user.username.clone(),
       user_id: user.id,
-      code: message.content.clone(),
+      code: content,
     },
     entity::message::MessageKind::LoginCode => Template::Login2fa {
       username: user.username.clone(),
       user_id: user.id,
-      code: message.content.clone(),
+      code: content,
     },
     entity::message::MessageKind::ForgetPasswordCode => Template::ForgetPassword {
       username: user.username.clone(),
       user_id: user.id,
-      code: message.content.clone(),
+      code: content,
+    },
+    entity::message::MessageKind::RecoveryCodeUsed => Template::RecoveryCodeUsed {
+      username: user.username.clone(),
+      user_id: user.id,
+      remaining: content,
+    },
+    entity::message::MessageKind::AccountLocked => Template::AccountLocked {
+      username: user.username.clone(),
+      user_id: user.id,
+      locked_until: content,
+    },
+    entity::message::MessageKind::PasswordChanged => Template::PasswordChanged {
+      username: user.username.clone(),
+      user_id: user.id,
+      changed_at: content,
     },
+    entity::message::MessageKind::AccountExists => Template::AccountExists {
+      username: user.username.clone(),
+      user_id: user.id,
+    },
+    entity::message::MessageKind::EmailChangeCode => Template::EmailChangeCode {
+      username: user.username.clone(),
+      user_id: user.id,
+      code: content,
+    },
+    entity::message::MessageKind::EmailChangeNotice => Template::EmailChangeNotice {
+      username: user.username.clone(),
+      user_id: user.id,
+      new_email: content,
+    },
+    entity::message::MessageKind::MagicLink => Template::MagicLink {
+      username: user.username.clone(),
+      user_id: user.id,
+      link: content,
+    },
+    entity::message::MessageKind::Invitation => Template::Invitation {
+      username: user.username.clone(),
+      user_id: user.id,
+      code: content,
+    },
+    entity::message::MessageKind::RegistrationApproved => Template::RegistrationApproved {
+      username: user.username.clone(),
+      user_id: user.id,
+    },
+    entity::message::MessageKind::RegistrationRejected => {
+      Template::RegistrationRejected { username: content }
+    }
   };
   Ok(TEMPLATE_ENGIN.render(&template)?)
 }
@@ -106,7 +183,9 @@ mod tests {
   use fake::Fake;
 
   use super::render_template;
+  use crate::constant::MESSAGE_ENCRYPT_KEY;
   use crate::entity::{self, message::MessageKind};
+  use crate::util::cipher::encrypt;
 
   #[test]
   fn test_render_template() {
@@ -116,4 +195,15 @@ mod tests {
     let result = render_template(&message, &user).unwrap();
     assert!(result.to_lowercase().contains("login"))
   }
+
+  #[test]
+  fn test_render_template_of_secret() {
+    let mut message: entity::message::Model = fake::Faker.fake();
+    message.kind = MessageKind::ForgetPasswordCode;
+    message.content = encrypt(&MESSAGE_ENCRYPT_KEY, "reset-code").unwrap();
+    let user: entity::user::Model = fake::Faker.fake();
+    let result = render_template(&message, &user).unwrap();
+    assert!(result.contains("reset-code"));
+    assert!(!result.contains(&message.content));
+  }
 }
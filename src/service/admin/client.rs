@@ -0,0 +1,76 @@
+use tracing::info;
+use uuid::Uuid;
+
+use crate::constant::{OAUTH_CLIENT_ID_LEN, OAUTH_CLIENT_SECRET_LEN};
+use crate::dto::*;
+use crate::entity::role::RoleUser;
+use crate::entity::scope::Scope;
+use crate::error::{AppError, AppResult, Resource, ResourceType, invalid_input_error};
+use crate::repo;
+use crate::server::state::AppState;
+use crate::service::admin::check_admin;
+use crate::util;
+use crate::util::claim::UserClaims;
+
+pub async fn create(
+  state: &AppState,
+  user: &UserClaims,
+  req: CreateOauthClientRequest,
+) -> AppResult<CreateOauthClientResponse> {
+  check_admin(user)?;
+  info!(
+    "Create oauth client: {} by admin id: {}",
+    req.name, user.uid
+  );
+  // Redirect uris are matched exactly and stored space separated.
+  for uri in req.redirect_uris.iter() {
+    let url = url::Url::parse(uri)
+      .map_err(|_| invalid_input_error("redirect_uris", "Redirect uri is not valid."))?;
+    if url.fragment().is_some() || uri.contains(char::is_whitespace) {
+      return Err(invalid_input_error(
+        "redirect_uris",
+        "Redirect uri must not contain a fragment or whitespace.",
+      ));
+    }
+  }
+  let scope = req.scope.unwrap_or_else(|| Scope::for_role(RoleUser::User));
+  let client_secret = req
+    .confidential
+    .then(|| util::random::generate_random_string(OAUTH_CLIENT_SECRET_LEN));
+  let model = repo::oauth_client::save(
+    &*state.db,
+    util::random::generate_random_string(OAUTH_CLIENT_ID_LEN),
+    client_secret.as_ref().map(util::hash::sha256_hash),
+    req.name,
+    &req.redirect_uris,
+    &scope,
+  )
+  .await?;
+  Ok(CreateOauthClientResponse {
+    client: OauthClientResponse::from(model),
+    client_secret,
+  })
+}
+
+pub async fn list(state: &AppState, user: &UserClaims) -> AppResult<OauthClientListResponse> {
+  check_admin(user)?;
+  info!("Get list of oauth clients by admin id: {}", user.uid);
+  let list = repo::oauth_client::find_all(&*state.db)
+    .await?
+    .into_iter()
+    .map(OauthClientResponse::from)
+    .collect();
+  Ok(OauthClientListResponse { list })
+}
+
+pub async fn delete(state: &AppState, user: &UserClaims, id: Uuid) -> AppResult {
+  check_admin(user)?;
+  info!("Delete oauth client id: {id} by admin id: {}", user.uid);
+  if !repo::oauth_client::delete(&*state.db, id).await? {
+    return Err(AppError::NotFoundError(Resource {
+      details: vec![("id".to_string(), id.to_string())],
+      resource_type: ResourceType::OauthClient,
+    }));
+  }
+  Ok(())
+}
@@ -1 +1,15 @@
+use crate::entity::role::RoleUser;
+use crate::error::{AppError, AppResult};
+use crate::util::claim::UserClaims;
+
+pub mod client;
 pub mod user;
+
+pub(crate) fn check_admin(user: &UserClaims) -> AppResult {
+  if user.rol != RoleUser::Admin {
+    return Err(AppError::PermissionDeniedError(
+      "This user does not have permission to use this resource.".to_string(),
+    ));
+  }
+  Ok(())
+}
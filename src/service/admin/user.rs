@@ -1,10 +1,17 @@
+use chrono::Utc;
+use sea_orm::TransactionTrait;
 use tracing::info;
+use uuid::Uuid;
 
 use crate::dto::*;
+use crate::entity::message::MessageKind;
 use crate::entity::role::RoleUser;
-use crate::error::{AppError, AppResult};
+use crate::error::{AppError, AppResult, ToAppResult};
 use crate::repo;
 use crate::server::state::AppState;
+use crate::service;
+use crate::service::admin::check_admin;
+use crate::service::redis::BlockedEmailKey;
 use crate::util::claim::UserClaims;
 
 pub async fn list(
@@ -25,3 +32,84 @@ pub async fn list(
     .collect::<Vec<_>>();
   Ok(GetUserListResponse { list })
 }
+
+pub async fn unlock(state: &AppState, user: &UserClaims, user_id: Uuid) -> AppResult<bool> {
+  if user.rol != RoleUser::Admin {
+    return Err(AppError::PermissionDeniedError(
+      "This user does not have permission to use this resource.".to_string(),
+    ));
+  }
+  info!("Unlock user id: {user_id} by admin id: {}", user.uid);
+  repo::user::find_by_id(&*state.db, user_id)
+    .await?
+    .to_result()?;
+  service::lockout::unlock(&state.redis, user_id).await
+}
+
+pub async fn list_pending(state: &AppState, user: &UserClaims) -> AppResult<GetUserListResponse> {
+  check_admin(user)?;
+  info!(
+    "Get list of users waiting for approval by admin id: {}",
+    user.uid
+  );
+  let list = repo::user::find_pending_approval(&*state.db)
+    .await?
+    .into_iter()
+    .map(GetUserResponse::from)
+    .collect::<Vec<_>>();
+  Ok(GetUserListResponse { list })
+}
+
+pub async fn approve(state: &AppState, user: &UserClaims, user_id: Uuid) -> AppResult {
+  check_admin(user)?;
+  info!("Approve user id: {user_id} by admin id: {}", user.uid);
+  let tx = state.db.begin().await?;
+  let target = repo::user::find_by_id(&tx, user_id).await?.to_result()?;
+  if target.is_approved {
+    return Err(AppError::BadRequestError(
+      "User is already approved.".to_string(),
+    ));
+  }
+  repo::user::approve(&tx, target).await?;
+  repo::message::save(
+    &tx,
+    user_id,
+    String::new(),
+    MessageKind::RegistrationApproved,
+  )
+  .await?;
+  tx.commit().await?;
+  state.messenger_notify.notify_one();
+  Ok(())
+}
+
+/// Deletes the pending account and blocks its email from registering again for a while.
+pub async fn reject(state: &AppState, user: &UserClaims, user_id: Uuid) -> AppResult {
+  check_admin(user)?;
+  info!("Reject user id: {user_id} by admin id: {}", user.uid);
+  let tx = state.db.begin().await?;
+  let target = repo::user::find_by_id(&tx, user_id).await?.to_result()?;
+  if target.is_approved {
+    return Err(AppError::BadRequestError(
+      "User is already approved.".to_string(),
+    ));
+  }
+  repo::user::delete_by_ids(&tx, vec![user_id]).await?;
+  // The account is gone once committed, so the notice is queued under the admin
+  // and carries the address and username of the rejected user.
+  repo::message::save_to(
+    &tx,
+    user.uid,
+    target.email.clone(),
+    target.username,
+    MessageKind::RegistrationRejected,
+  )
+  .await?;
+  tx.commit().await?;
+  state.messenger_notify.notify_one();
+  let key = BlockedEmailKey {
+    email: target.email,
+  };
+  service::redis::set(&state.redis, (&key, &Utc::now())).await?;
+  Ok(())
+}
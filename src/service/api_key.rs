@@ -0,0 +1,138 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::constant::{
+  API_KEY_ID_LEN, API_KEY_PREFIX, API_KEY_SECRET_LEN, EXPIRE_BEARER_TOKEN_SECS,
+};
+use crate::dto::{ApiKeyListResponse, ApiKeyResponse, CreateApiKeyRequest, CreateApiKeyResponse};
+use crate::entity::scope::Scope;
+use crate::error::{AppError, AppResult, invalid_input_error};
+use crate::repo;
+use crate::server::state::AppState;
+use crate::util;
+use crate::util::claim::UserClaims;
+
+/// Creates a key limited to scopes the caller already holds, the plain key is returned only once.
+pub async fn create(
+  state: &AppState,
+  user: &UserClaims,
+  req: CreateApiKeyRequest,
+) -> AppResult<CreateApiKeyResponse> {
+  info!("Create api key user id: {} name: {}", user.uid, req.name);
+  let scope = req.scope.unwrap_or_else(|| user.scope.clone());
+  if scope.is_empty() || scope.iter().any(|s| !user.has_scope(*s)) {
+    return Err(invalid_input_error(
+      "scope",
+      "Scope is not granted to this user.",
+    ));
+  }
+  let prefix = util::random::generate_random_string(API_KEY_ID_LEN);
+  let secret = util::random::generate_random_string(API_KEY_SECRET_LEN);
+  let key = format!("{API_KEY_PREFIX}_{prefix}_{secret}");
+  let expire_at = req
+    .expire_secs
+    .map(|secs| Utc::now() + chrono::Duration::seconds(secs as i64));
+  let model = repo::api_key::save(
+    &*state.db,
+    user.uid,
+    req.name,
+    prefix,
+    util::hash::sha256_hash(&key),
+    &scope,
+    expire_at,
+  )
+  .await?;
+  Ok(CreateApiKeyResponse {
+    key,
+    api_key: ApiKeyResponse::from(model),
+  })
+}
+
+pub async fn list(state: &AppState, user_id: Uuid) -> AppResult<ApiKeyListResponse> {
+  info!("List api keys user id: {user_id}");
+  let list = repo::api_key::find_by_user(&*state.db, user_id)
+    .await?
+    .into_iter()
+    .map(ApiKeyResponse::from)
+    .collect();
+  Ok(ApiKeyListResponse { list })
+}
+
+pub async fn revoke(state: &AppState, user_id: Uuid, id: Uuid) -> AppResult {
+  info!("Revoke api key user id: {user_id} api key id: {id}");
+  if !repo::api_key::delete_by_user(&*state.db, user_id, id).await? {
+    return Err(AppError::NotFoundError(crate::error::Resource {
+      details: vec![("api_key_id".to_string(), id.to_string())],
+      resource_type: crate::error::ResourceType::ApiKey,
+    }));
+  }
+  Ok(())
+}
+
+/// Resolves an api key to claims equivalent to an access token of its owner.
+pub async fn authenticate(state: &AppState, key: &str) -> AppResult<UserClaims> {
+  let prefix = parse_prefix(key).ok_or_else(unauthorized)?;
+  let model = repo::api_key::find_by_prefix(&*state.db, prefix)
+    .await?
+    .ok_or_else(unauthorized)?;
+  let hash = util::hash::sha256_hash(key);
+  if !openssl::memcmp::eq(hash.as_bytes(), model.key_hash.as_bytes()) || model.is_expired() {
+    return Err(unauthorized());
+  }
+  let user = repo::user::find_by_id(&*state.db, model.user_id)
+    .await?
+    .filter(|user| user.is_active)
+    .ok_or_else(unauthorized)?;
+  repo::api_key::update_last_used(&*state.db, model.id).await?;
+  let duration = model
+    .expire_at
+    .and_then(|expire_at| (expire_at - Utc::now()).to_std().ok())
+    .unwrap_or(EXPIRE_BEARER_TOKEN_SECS)
+    .min(EXPIRE_BEARER_TOKEN_SECS)
+    .max(Duration::from_secs(1));
+  // The owner may have lost a role since the key was created.
+  let role_scope = Scope::for_role(user.role);
+  let scope = model
+    .scopes()
+    .into_iter()
+    .filter(|s| role_scope.contains(s))
+    .collect();
+  Ok(
+    UserClaims::new(duration, user.id, model.id, user.role)
+      .with_jti(model.id)
+      .with_scope(scope),
+  )
+}
+
+fn parse_prefix(key: &str) -> Option<&str> {
+  let (prefix, secret) = key
+    .strip_prefix(API_KEY_PREFIX)?
+    .strip_prefix('_')?
+    .split_once('_')?;
+  (prefix.len() == API_KEY_ID_LEN && secret.len() == API_KEY_SECRET_LEN).then_some(prefix)
+}
+
+fn unauthorized() -> AppError {
+  AppError::UnauthorizedError("Invalid api key.".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_parse_api_key_prefix() {
+    let prefix = util::random::generate_random_string(API_KEY_ID_LEN);
+    let secret = util::random::generate_random_string(API_KEY_SECRET_LEN);
+    let key = format!("{API_KEY_PREFIX}_{prefix}_{secret}");
+    assert_eq!(parse_prefix(&key), Some(prefix.as_str()));
+    assert_eq!(parse_prefix(&format!("{prefix}_{secret}")), None);
+    assert_eq!(
+      parse_prefix(&format!("{API_KEY_PREFIX}_{prefix}_short")),
+      None
+    );
+  }
+}
@@ -0,0 +1,66 @@
+use tracing::info;
+
+use crate::constant::EXPIRE_BEARER_TOKEN_SECS;
+use crate::entity;
+use crate::entity::role::RoleUser;
+use crate::error::{AppError, AppResult};
+use crate::repo;
+use crate::server::state::AppState;
+use crate::util::certificate::ClientCertificate;
+use crate::util::claim::UserClaims;
+
+/// Signs in the service account named by a client certificate. The email
+/// addresses of the certificate are matched first, then its DNS names and
+/// common name against usernames. Only an active `System` user is accepted.
+pub async fn authenticate(
+  state: &AppState,
+  certificate: &ClientCertificate,
+) -> AppResult<UserClaims> {
+  let user = find_user(state, certificate)
+    .await?
+    .ok_or_else(unauthorized)?;
+  info!(
+    "Client certificate {} signed in user id: {}",
+    certificate.thumbprint, user.id
+  );
+  // no session backs the claims, the certificate is presented on every connection
+  Ok(UserClaims::sessionless(
+    EXPIRE_BEARER_TOKEN_SECS,
+    user.id,
+    user.role,
+  ))
+}
+
+/// The first service account the certificate names, a name of any other user is skipped.
+async fn find_user(
+  state: &AppState,
+  certificate: &ClientCertificate,
+) -> AppResult<Option<entity::user::Model>> {
+  let is_service_account =
+    |user: &entity::user::Model| user.is_active && user.role == RoleUser::System;
+  for email in &certificate.emails {
+    if let Some(user) = repo::user::find_by_email(&*state.db, email)
+      .await?
+      .filter(is_service_account)
+    {
+      return Ok(Some(user));
+    }
+  }
+  let usernames = certificate
+    .dns_names
+    .iter()
+    .chain(certificate.common_name.as_ref());
+  for username in usernames {
+    if let Some(user) = repo::user::find_by_username(&*state.db, username)
+      .await?
+      .filter(is_service_account)
+    {
+      return Ok(Some(user));
+    }
+  }
+  Ok(None)
+}
+
+fn unauthorized() -> AppError {
+  AppError::UnauthorizedError("The client certificate is not a service account.".to_string())
+}
@@ -0,0 +1,297 @@
+use chrono::Utc;
+use tracing::info;
+
+use crate::constant::{
+  DEVICE_CODE_LEN, DEVICE_POLL_INTERVAL_SECS, EXPIRE_DEVICE_CODE_SECS, TEMPLATE_ENGIN,
+  USER_CODE_CHARSET, USER_CODE_LEN,
+};
+use crate::dto::*;
+use crate::entity;
+use crate::error::oauth::{OauthError, OauthResult};
+use crate::error::{AppError, AppResult};
+use crate::repo;
+use crate::server::state::AppState;
+use crate::service;
+use crate::service::oauth::SignIn;
+use crate::service::redis::{
+  DeviceCodeKey, DeviceCodeStatus, DeviceCodeValue, DevicePollKey, UserCodeKey,
+};
+use crate::util;
+use crate::util::client_info::ClientInfo;
+
+/// Starts a device authorization, the user approves the user code on the verification page.
+pub async fn authorize(
+  state: &AppState,
+  credentials: Option<(String, String)>,
+  req: DeviceAuthorizationRequest,
+) -> OauthResult<DeviceAuthorizationResponse> {
+  let client =
+    service::oauth::authenticate_client(state, credentials, req.client_id, req.client_secret)
+      .await?;
+  let scope = service::oauth::resolve_scope(req.scope.as_deref(), &client.scopes())?;
+  let user_code = util::random::generate_random_string_from(USER_CODE_CHARSET, USER_CODE_LEN);
+  let device_code = util::random::generate_random_string(DEVICE_CODE_LEN);
+  let value = DeviceCodeValue {
+    client_id: client.client_id.clone(),
+    user_code: user_code.clone(),
+    scope: scope.join(" "),
+    status: DeviceCodeStatus::Pending,
+    expire_at: Utc::now().timestamp() + EXPIRE_DEVICE_CODE_SECS.as_secs() as i64,
+  };
+  let key = DeviceCodeKey {
+    device_code: device_code.clone(),
+  };
+  service::redis::set(&state.redis, (&key, &value)).await?;
+  let user_code_key = UserCodeKey {
+    user_code: user_code.clone(),
+  };
+  service::redis::set(&state.redis, (&user_code_key, &device_code)).await?;
+  info!("Start device authorization of client: {}", client.client_id);
+  let user_code = format_user_code(&user_code);
+  let verification_uri = format!("{}/oauth2/device", state.config.server.get_public_url());
+  Ok(DeviceAuthorizationResponse {
+    device_code,
+    verification_uri_complete: format!("{verification_uri}?user_code={user_code}"),
+    verification_uri,
+    user_code,
+    expires_in: EXPIRE_DEVICE_CODE_SECS.as_secs(),
+    interval: DEVICE_POLL_INTERVAL_SECS.as_secs(),
+  })
+}
+
+/// The verification page, it names the requesting client when the user code is known.
+pub async fn page(state: &AppState, params: DeviceVerificationParams) -> AppResult<String> {
+  let user_code = params.user_code.unwrap_or_default();
+  let mut page = VerificationPage::new(user_code.clone());
+  if let Some((_, value)) = find(state, &user_code).await? {
+    page = page.with_request(state, &value).await?;
+  }
+  page.render()
+}
+
+/// Signs the user in on the verification page and answers the device authorization.
+pub async fn verify(state: &AppState, req: DeviceVerificationRequest) -> AppResult<String> {
+  let mut page = VerificationPage::new(req.user_code.clone());
+  page.email = req.email.clone();
+  let Some((key, value)) = find(state, &req.user_code).await? else {
+    return page
+      .error("The code is invalid or expired.".to_string())
+      .render();
+  };
+  let page = page.with_request(state, &value).await?;
+  // A denial needs the sign in too, else anyone reading the code off a screen could cancel it.
+  let user =
+    match service::oauth::sign_in(state, &req.email, req.password, req.code.as_deref()).await? {
+      SignIn::User(user) => user,
+      SignIn::Retry {
+        error,
+        code_required,
+      } => {
+        let mut page = page.error(error);
+        page.code_required = code_required;
+        return page.render();
+      }
+    };
+  let mut answered = value.clone();
+  answered.status = if req.decision == AuthorizeDecision::Deny {
+    DeviceCodeStatus::Denied
+  } else {
+    let scope = value
+      .scope
+      .split_whitespace()
+      .map(String::from)
+      .collect::<Vec<_>>();
+    answered.scope = service::oauth::grant_scope(&scope, user.role).join(" ");
+    DeviceCodeStatus::Approved {
+      user_id: user.id,
+      auth_time: Utc::now().timestamp(),
+    }
+  };
+  if !service::redis::compare_and_set(&state.redis, &key, &value, &answered).await? {
+    return page
+      .error("The code is invalid or expired.".to_string())
+      .render();
+  }
+  service::redis::del(
+    &state.redis,
+    &UserCodeKey {
+      user_code: value.user_code,
+    },
+  )
+  .await?;
+  info!(
+    "Device authorization of client: {} answered: {:?}",
+    value.client_id, answered.status
+  );
+  let message = match answered.status {
+    DeviceCodeStatus::Approved { .. } => "The device is connected, you can return to it.",
+    _ => "The device has been denied.",
+  };
+  page.message(message.to_string()).render()
+}
+
+/// Answers a device polling the token endpoint with the device code grant.
+pub(crate) async fn token(
+  state: &AppState,
+  client_info: ClientInfo,
+  client: &entity::oauth_client::Model,
+  req: OauthTokenRequest,
+) -> OauthResult<OauthTokenResponse> {
+  let device_code = req
+    .device_code
+    .ok_or_else(|| OauthError::InvalidRequest("The device code is required.".to_string()))?;
+  let key = DeviceCodeKey {
+    device_code: device_code.clone(),
+  };
+  let value = service::redis::get(&state.redis, &key)
+    .await?
+    .filter(|value| value.client_id == client.client_id)
+    .ok_or_else(invalid_device_code_error)?;
+  if value.expire_at <= Utc::now().timestamp() {
+    service::redis::del(&state.redis, &key)
+      .await
+      .map_err(AppError::from)?;
+    return Err(OauthError::ExpiredToken);
+  }
+  match value.status {
+    DeviceCodeStatus::Pending => {
+      let poll_key = DevicePollKey { device_code };
+      if service::redis::check_exist_key(&state.redis, &poll_key).await? {
+        return Err(OauthError::SlowDown);
+      }
+      service::redis::set(&state.redis, (&poll_key, &Utc::now())).await?;
+      Err(OauthError::AuthorizationPending)
+    }
+    DeviceCodeStatus::Denied => {
+      service::redis::del(&state.redis, &key)
+        .await
+        .map_err(AppError::from)?;
+      Err(OauthError::AccessDenied)
+    }
+    DeviceCodeStatus::Approved { user_id, auth_time } => {
+      // The device code is used once.
+      if !service::redis::compare_and_del(&state.redis, &key, &value).await? {
+        return Err(invalid_device_code_error());
+      }
+      let user = repo::user::find_by_id(&*state.db, user_id)
+        .await?
+        .filter(|user| user.is_active && user.is_approved)
+        .ok_or_else(invalid_device_code_error)?;
+      info!(
+        "Issue tokens to device of client: {} for user id: {user_id}",
+        client.client_id
+      );
+      service::oauth::issue_tokens(
+        state,
+        client_info,
+        &client.client_id,
+        user,
+        value.scope,
+        None,
+        auth_time,
+      )
+      .await
+    }
+  }
+}
+
+/// Looks up a pending authorization by the user code as typed, ignoring case and separators.
+async fn find(
+  state: &AppState,
+  user_code: &str,
+) -> AppResult<Option<(DeviceCodeKey, DeviceCodeValue)>> {
+  let user_code = user_code
+    .chars()
+    .filter(char::is_ascii_alphanumeric)
+    .collect::<String>()
+    .to_uppercase();
+  if user_code.len() != USER_CODE_LEN {
+    return Ok(None);
+  }
+  let Some(device_code) = service::redis::get(&state.redis, &UserCodeKey { user_code }).await?
+  else {
+    return Ok(None);
+  };
+  let key = DeviceCodeKey { device_code };
+  let value = service::redis::get(&state.redis, &key)
+    .await?
+    .filter(|value| {
+      value.status == DeviceCodeStatus::Pending && value.expire_at > Utc::now().timestamp()
+    });
+  Ok(value.map(|value| (key, value)))
+}
+
+fn format_user_code(user_code: &str) -> String {
+  let (head, tail) = user_code.split_at(user_code.len() / 2);
+  format!("{head}-{tail}")
+}
+
+fn invalid_device_code_error() -> OauthError {
+  OauthError::InvalidGrant("The device code is invalid.".to_string())
+}
+
+struct VerificationPage {
+  user_code: String,
+  client_name: Option<String>,
+  scope: Vec<String>,
+  email: String,
+  error: Option<String>,
+  message: Option<String>,
+  code_required: bool,
+}
+
+impl VerificationPage {
+  fn new(user_code: String) -> Self {
+    Self {
+      user_code,
+      client_name: None,
+      scope: vec![],
+      email: String::new(),
+      error: None,
+      message: None,
+      code_required: false,
+    }
+  }
+
+  async fn with_request(mut self, state: &AppState, value: &DeviceCodeValue) -> AppResult<Self> {
+    self.client_name = repo::oauth_client::find_by_client_id(&*state.db, &value.client_id)
+      .await?
+      .map(|client| client.name);
+    self.scope = value.scope.split_whitespace().map(String::from).collect();
+    self.user_code = format_user_code(&value.user_code);
+    Ok(self)
+  }
+
+  fn error(mut self, error: String) -> Self {
+    self.error = Some(error);
+    self
+  }
+
+  fn message(mut self, message: String) -> Self {
+    self.message = Some(message);
+    self
+  }
+
+  fn render(self) -> AppResult<String> {
+    let template = Template::DeviceVerification {
+      user_code: self.user_code,
+      client_name: self.client_name,
+      scope: self.scope,
+      email: self.email,
+      error: self.error,
+      message: self.message,
+      code_required: self.code_required,
+    };
+    Ok(TEMPLATE_ENGIN.render(&template)?)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_format_user_code() {
+    assert_eq!(format_user_code("BCDFGHJK"), "BCDF-GHJK");
+  }
+}
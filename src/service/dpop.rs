@@ -0,0 +1,85 @@
+use chrono::Utc;
+use tracing::info;
+
+use crate::client::redis::RedisClient;
+use crate::error::{AppError, AppResult};
+use crate::service;
+use crate::service::redis::DpopProofKey;
+use crate::util::dpop::DpopProof;
+
+/// Thumbprint of the key the tokens issued to the client are bound to, when it sent a proof.
+pub async fn bind(redis: &RedisClient, proof: Option<&DpopProof>) -> AppResult<Option<String>> {
+  let Some(proof) = proof else {
+    return Ok(None);
+  };
+  consume(redis, proof).await?;
+  Ok(Some(proof.jkt.clone()))
+}
+
+/// Checks the proof sent with a token bound to `jkt`, with an access token the
+/// proof must also be made for that token.
+pub async fn check(
+  redis: &RedisClient,
+  proof: Option<&DpopProof>,
+  jkt: &str,
+  access_token: Option<&str>,
+) -> AppResult {
+  let proof = proof
+    .filter(|proof| proof.jkt == jkt)
+    .filter(|proof| access_token.is_none_or(|token| proof.is_for_token(token)))
+    .ok_or_else(|| {
+      AppError::UnauthorizedError("The token requires a DPoP proof of its key.".to_string())
+    })?;
+  consume(redis, proof).await
+}
+
+/// Records the `jti` of the proof, each proof is accepted once.
+async fn consume(redis: &RedisClient, proof: &DpopProof) -> AppResult {
+  let key = DpopProofKey {
+    jkt: proof.jkt.clone(),
+    jti: proof.jti.clone(),
+  };
+  if !service::redis::set_if_absent(redis, (&key, &Utc::now())).await? {
+    info!("DPoP proof replayed: {key:?}");
+    return Err(AppError::UnauthorizedError(
+      "The DPoP proof was already used.".to_string(),
+    ));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use fake::{Fake, Faker};
+
+  use super::*;
+  use crate::constant::REDIS;
+  use crate::util::dpop::access_token_hash;
+
+  #[tokio::test]
+  async fn test_dpop_proof_is_accepted_once() {
+    let proof = DpopProof {
+      jkt: Faker.fake(),
+      jti: Faker.fake(),
+      ath: Some(access_token_hash("token")),
+    };
+    let jkt = proof.jkt.as_str();
+    assert!(check(&REDIS, None, jkt, None).await.is_err());
+    assert!(check(&REDIS, Some(&proof), "other", None).await.is_err());
+    assert!(
+      check(&REDIS, Some(&proof), jkt, Some("other"))
+        .await
+        .is_err()
+    );
+    check(&REDIS, Some(&proof), jkt, Some("token"))
+      .await
+      .unwrap();
+    assert!(
+      check(&REDIS, Some(&proof), jkt, Some("token"))
+        .await
+        .is_err()
+    );
+    assert!(bind(&REDIS, Some(&proof)).await.is_err());
+    assert_eq!(bind(&REDIS, None).await.unwrap(), None);
+  }
+}
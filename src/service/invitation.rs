@@ -0,0 +1,122 @@
+use chrono::Utc;
+use sea_orm::DatabaseTransaction;
+use sea_orm::TransactionTrait;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::constant::{EXPIRE_INVITATION_CODE_SECS, INVITATION_CODE_LEN};
+use crate::dto::{
+  CreateInvitationRequest, CreateInvitationResponse, InvitationListResponse, InvitationResponse,
+};
+use crate::entity::message::MessageKind;
+use crate::entity::role::RoleUser;
+use crate::error::{AppError, AppResult, ToAppResult, invalid_input_error};
+use crate::repo;
+use crate::server::state::AppState;
+use crate::util;
+use crate::util::claim::UserClaims;
+
+/// Creates an invitation code, the plain code is returned only once and emailed when an email is given.
+pub async fn create(
+  state: &AppState,
+  user: &UserClaims,
+  req: CreateInvitationRequest,
+) -> AppResult<CreateInvitationResponse> {
+  info!("Create invitation user id: {} request: {req:?}", user.uid);
+  let role = req.role.unwrap_or(RoleUser::User);
+  match user.rol {
+    RoleUser::Admin if role != RoleUser::System => {}
+    RoleUser::Admin => {
+      return Err(invalid_input_error(
+        "role",
+        "Role can not be granted by an invitation.",
+      ));
+    }
+    _ if !state.config.registration.allow_user_invitations => {
+      return Err(AppError::PermissionDeniedError(
+        "This user does not have permission to create invitations.".to_string(),
+      ));
+    }
+    _ if role != RoleUser::User => {
+      return Err(invalid_input_error(
+        "role",
+        "Role can not be granted by this user.",
+      ));
+    }
+    _ => {}
+  }
+  let tx = state.db.begin().await?;
+  let inviter = repo::user::find_by_id(&tx, user.uid).await?.to_result()?;
+  let code = util::random::generate_random_string(INVITATION_CODE_LEN);
+  let expire_secs = req
+    .expire_secs
+    .unwrap_or(EXPIRE_INVITATION_CODE_SECS.as_secs());
+  let model = repo::invitation::save(
+    &tx,
+    inviter.id,
+    util::hash::sha256_hash(&code),
+    req.email.clone(),
+    role,
+    req.max_uses.unwrap_or(1),
+    Utc::now() + chrono::Duration::seconds(expire_secs as i64),
+  )
+  .await?;
+  let notify = req.email.is_some();
+  if let Some(email) = req.email {
+    repo::message::save_secret(&tx, inviter.id, Some(email), &code, MessageKind::Invitation)
+      .await?;
+  }
+  tx.commit().await?;
+  if notify {
+    state.messenger_notify.notify_one();
+  }
+  Ok(CreateInvitationResponse {
+    code,
+    invitation: InvitationResponse::from(model),
+  })
+}
+
+pub async fn list(state: &AppState, user_id: Uuid) -> AppResult<InvitationListResponse> {
+  info!("List invitations user id: {user_id}");
+  let list = repo::invitation::find_by_user(&*state.db, user_id)
+    .await?
+    .into_iter()
+    .map(InvitationResponse::from)
+    .collect();
+  Ok(InvitationListResponse { list })
+}
+
+pub async fn revoke(state: &AppState, user_id: Uuid, id: Uuid) -> AppResult {
+  info!("Revoke invitation user id: {user_id} invitation id: {id}");
+  if !repo::invitation::delete_by_user(&*state.db, user_id, id).await? {
+    return Err(AppError::NotFoundError(crate::error::Resource {
+      details: vec![("invitation_id".to_string(), id.to_string())],
+      resource_type: crate::error::ResourceType::Invitation,
+    }));
+  }
+  Ok(())
+}
+
+/// Uses the code once for a registration with the email and returns the role it grants.
+pub async fn redeem(tx: &DatabaseTransaction, code: &str, email: &str) -> AppResult<RoleUser> {
+  let model = repo::invitation::find_by_code_hash(tx, &util::hash::sha256_hash(code))
+    .await?
+    .filter(|model| {
+      model
+        .email
+        .as_ref()
+        .is_none_or(|invited| invited.eq_ignore_ascii_case(email))
+    })
+    .ok_or_else(invalid_invitation_error)?;
+  if !repo::invitation::increment_use(tx, model.id).await? {
+    return Err(invalid_invitation_error());
+  }
+  Ok(model.role)
+}
+
+fn invalid_invitation_error() -> AppError {
+  invalid_input_error(
+    "invitation_code",
+    "Invitation code is invalid, expired or used up.",
+  )
+}
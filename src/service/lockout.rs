@@ -0,0 +1,156 @@
+use chrono::Utc;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::client::redis::RedisClient;
+use crate::constant::{
+  EXPIRE_LOCKOUT_SECS, MAX_CODE_ATTEMPTS, MAX_LOGIN_ATTEMPTS, MAX_PASSWORD_CHECK_ATTEMPTS,
+};
+use crate::entity::message::MessageKind;
+use crate::error::{AppError, AppResult};
+use crate::repo;
+use crate::server::state::AppState;
+use crate::service;
+use crate::service::redis::{CodeAttemptKey, LockoutKey, LoginAttemptKey, PasswordCheckAttemptKey};
+
+/// Fails while the account is locked, with the seconds left until it unlocks.
+pub async fn check(redis: &RedisClient, user_id: Uuid) -> AppResult {
+  let ttl = service::redis::get_tll(redis, &LockoutKey { user_id }).await?;
+  if ttl > 0 {
+    return Err(AppError::UserLockedError(ttl as u64));
+  }
+  Ok(())
+}
+
+/// Counts a wrong password, once the threshold is reached the account is
+/// locked and the user is emailed. Returns whether the account got locked.
+pub async fn record_failed_login(state: &AppState, user_id: Uuid) -> AppResult<bool> {
+  let key = LoginAttemptKey { user_id };
+  let attempts = service::redis::incr(&state.redis, &key).await?;
+  if attempts < MAX_LOGIN_ATTEMPTS {
+    return Ok(false);
+  }
+  info!("Lock user id: {user_id} after {attempts} failed login attempts");
+  let now = Utc::now();
+  service::redis::set(&state.redis, (&LockoutKey { user_id }, &now)).await?;
+  service::redis::del(&state.redis, &key).await?;
+  let locked_until = now + EXPIRE_LOCKOUT_SECS;
+  repo::message::save(
+    &*state.db,
+    user_id,
+    locked_until.to_rfc3339(),
+    MessageKind::AccountLocked,
+  )
+  .await?;
+  state.messenger_notify.notify_one();
+  Ok(true)
+}
+
+pub async fn reset_failed_login(redis: &RedisClient, user_id: Uuid) -> AppResult {
+  service::redis::del(redis, &LoginAttemptKey { user_id }).await?;
+  Ok(())
+}
+
+/// Counts a wrong guess of a pending code. Returns whether the threshold is
+/// reached and the caller has to invalidate the code.
+pub async fn record_failed_code(
+  redis: &RedisClient,
+  user_id: Uuid,
+  kind: MessageKind,
+) -> AppResult<bool> {
+  let key = CodeAttemptKey { user_id, kind };
+  let attempts = service::redis::incr(redis, &key).await?;
+  if attempts < MAX_CODE_ATTEMPTS {
+    return Ok(false);
+  }
+  info!("Invalidate {kind} of user id: {user_id} after {attempts} failed attempts");
+  service::redis::del(redis, &key).await?;
+  Ok(true)
+}
+
+pub async fn reset_failed_code(redis: &RedisClient, user_id: Uuid, kind: MessageKind) -> AppResult {
+  service::redis::del(redis, &CodeAttemptKey { user_id, kind }).await?;
+  Ok(())
+}
+
+/// Fails while the current password or a code was given wrong too often by a signed in
+/// user. It only throttles the checks, a stolen session must not lock the owner out of login.
+pub async fn check_password_attempts(redis: &RedisClient, user_id: Uuid) -> AppResult {
+  let key = PasswordCheckAttemptKey { user_id };
+  let attempts = service::redis::get(redis, &key).await?.unwrap_or_default();
+  if attempts >= MAX_PASSWORD_CHECK_ATTEMPTS {
+    let ttl = service::redis::get_tll(redis, &key).await?;
+    return Err(AppError::TooManyRequestsError(ttl.max(0) as u64));
+  }
+  Ok(())
+}
+
+pub async fn record_failed_password_check(redis: &RedisClient, user_id: Uuid) -> AppResult {
+  service::redis::incr(redis, &PasswordCheckAttemptKey { user_id }).await?;
+  Ok(())
+}
+
+pub async fn reset_failed_password_check(redis: &RedisClient, user_id: Uuid) -> AppResult {
+  service::redis::del(redis, &PasswordCheckAttemptKey { user_id }).await?;
+  Ok(())
+}
+
+/// Lifts a lock and forgets the failed login attempts. Returns whether the account was locked.
+pub async fn unlock(redis: &RedisClient, user_id: Uuid) -> AppResult<bool> {
+  info!("Unlock user id: {user_id}");
+  service::redis::del(redis, &LoginAttemptKey { user_id }).await?;
+  Ok(service::redis::del(redis, &LockoutKey { user_id }).await?)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::constant::REDIS;
+
+  #[tokio::test]
+  async fn test_record_failed_code_until_invalidated() {
+    let user_id = Uuid::new_v4();
+    for _ in 1..MAX_CODE_ATTEMPTS {
+      let invalidated = record_failed_code(&REDIS, user_id, MessageKind::LoginCode)
+        .await
+        .unwrap();
+      assert!(!invalidated);
+    }
+    let invalidated = record_failed_code(&REDIS, user_id, MessageKind::LoginCode)
+      .await
+      .unwrap();
+    assert!(invalidated);
+    let invalidated = record_failed_code(&REDIS, user_id, MessageKind::LoginCode)
+      .await
+      .unwrap();
+    assert!(!invalidated);
+  }
+
+  #[tokio::test]
+  async fn test_password_checks_throttled_without_lock() {
+    let user_id = Uuid::new_v4();
+    for _ in 0..MAX_PASSWORD_CHECK_ATTEMPTS {
+      check_password_attempts(&REDIS, user_id).await.unwrap();
+      record_failed_password_check(&REDIS, user_id).await.unwrap();
+    }
+    let result = check_password_attempts(&REDIS, user_id).await;
+    assert!(matches!(result, Err(AppError::TooManyRequestsError(_))));
+    check(&REDIS, user_id).await.unwrap();
+    reset_failed_password_check(&REDIS, user_id).await.unwrap();
+    check_password_attempts(&REDIS, user_id).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn test_check_and_unlock() {
+    let user_id = Uuid::new_v4();
+    check(&REDIS, user_id).await.unwrap();
+    service::redis::set(&REDIS, (&LockoutKey { user_id }, &Utc::now()))
+      .await
+      .unwrap();
+    let result = check(&REDIS, user_id).await;
+    assert!(matches!(result, Err(AppError::UserLockedError(ttl)) if ttl > 0));
+    assert!(unlock(&REDIS, user_id).await.unwrap());
+    check(&REDIS, user_id).await.unwrap();
+    assert!(!unlock(&REDIS, user_id).await.unwrap());
+  }
+}
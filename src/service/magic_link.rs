@@ -0,0 +1,72 @@
+use tracing::info;
+
+use crate::constant::{ACCESS_TOKEN_KEY_RING, EXPIRE_MAGIC_LINK_SECS, TEMPLATE_ENGIN};
+use crate::dto::{LoginResponse, MagicLinkQueryParam, MagicLinkRequest, Template};
+use crate::entity::message::MessageKind;
+use crate::error::{AppError, AppResult, ToAppResult};
+use crate::repo;
+use crate::server::state::AppState;
+use crate::service;
+use crate::service::redis::MagicLinkKey;
+use crate::util::claim::MagicLinkClaims;
+use crate::util::client_info::ClientInfo;
+
+/// Emails a sign-in link, a new link replaces any earlier one.
+pub async fn request(state: &AppState, req: MagicLinkRequest) -> AppResult {
+  info!("Magic link request: {req:?}");
+  let user = repo::user::find_by_email_and_status(&state.db, &req.email, true).await?;
+  if user.is_none() && state.config.privacy.enable {
+    return Ok(());
+  }
+  let user = user.to_result()?;
+  service::lockout::check(&state.redis, user.id).await?;
+  let claims = MagicLinkClaims::new(EXPIRE_MAGIC_LINK_SECS, user.id);
+  let token = claims.encode(&ACCESS_TOKEN_KEY_RING)?;
+  service::redis::set(
+    &state.redis,
+    (&MagicLinkKey { user_id: user.id }, &claims.jti),
+  )
+  .await?;
+  let link = format!(
+    "{}/api/v1/user/login/magic-link?token={token}",
+    state.config.server.get_public_url()
+  );
+  repo::message::save_secret(&*state.db, user.id, None, &link, MessageKind::MagicLink).await?;
+  state.messenger_notify.notify_one();
+  Ok(())
+}
+
+/// The page the link opens, it posts the token back so only the user following the
+/// link uses it up, not a mail scanner fetching it.
+pub fn page(param: MagicLinkQueryParam) -> AppResult<String> {
+  let template = Template::MagicLinkConfirm { token: param.token };
+  Ok(TEMPLATE_ENGIN.render(&template)?)
+}
+
+/// Exchanges a sign-in link for tokens, or for a second factor challenge.
+pub async fn verify(
+  state: &AppState,
+  client: ClientInfo,
+  param: MagicLinkQueryParam,
+) -> AppResult<LoginResponse> {
+  let claims = MagicLinkClaims::decode(&param.token, &ACCESS_TOKEN_KEY_RING)
+    .map_err(|_| invalid_link_error())?
+    .claims;
+  info!("Verify magic link user id: {}", claims.uid);
+  let key = MagicLinkKey {
+    user_id: claims.uid,
+  };
+  if !service::redis::compare_and_del(&state.redis, &key, &claims.jti).await? {
+    return Err(invalid_link_error());
+  }
+  let user = repo::user::find_by_id(&*state.db, claims.uid)
+    .await?
+    .filter(|user| user.is_active)
+    .ok_or_else(invalid_link_error)?;
+  service::lockout::check(&state.redis, user.id).await?;
+  service::user::complete_login(state, client, user).await
+}
+
+fn invalid_link_error() -> AppError {
+  AppError::UnauthorizedError("The sign-in link is invalid or expired.".to_string())
+}
@@ -1,7 +1,20 @@
 pub mod admin;
+pub mod api_key;
+pub mod certificate;
 pub mod code;
+pub mod device;
+pub mod dpop;
 pub mod email;
+pub mod invitation;
+pub mod lockout;
+pub mod magic_link;
+pub mod oauth;
+pub mod oidc;
+pub mod passkey;
+pub mod rate_limit;
+pub mod recovery_code;
 pub mod redis;
+pub mod registration;
 pub mod session;
 pub mod token;
 pub mod user;
@@ -0,0 +1,608 @@
+use std::str::FromStr;
+
+use chrono::Utc;
+use jsonwebtoken::Algorithm;
+use strum::IntoEnumIterator;
+use tracing::info;
+
+use crate::constant::{
+  ACCESS_TOKEN_KEY_RING, AUTHORIZATION_CODE_LEN, DEVICE_CODE_GRANT_TYPE, EXPIRE_BEARER_TOKEN_SECS,
+};
+use crate::dto::*;
+use crate::entity;
+use crate::entity::role::RoleUser;
+use crate::entity::scope::Scope;
+use crate::error::oauth::{OauthError, OauthResult};
+use crate::error::{AppError, AppResult, ToAppResult};
+use crate::repo;
+use crate::server::state::AppState;
+use crate::service;
+use crate::service::redis::{AuthorizationCodeKey, AuthorizationCodeValue};
+use crate::util;
+use crate::util::claim::IdTokenClaims;
+use crate::util::client_info::ClientInfo;
+
+/// Scopes of OpenID Connect, the others name the API scopes of the access token.
+const OPENID_SCOPES: [&str; 3] = ["openid", "profile", "email"];
+
+pub enum AuthorizeOutcome {
+  /// The login and consent page.
+  Page(String),
+  /// The redirect back to the client with a code or an error.
+  Redirect(String),
+}
+
+// The page and the redirect carry the state and the code of the client, they are kept out of logs.
+impl std::fmt::Debug for AuthorizeOutcome {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Page(_) => f.write_str("Page"),
+      Self::Redirect(_) => f.write_str("Redirect"),
+    }
+  }
+}
+
+/// Validates an authorization request and shows the login and consent page.
+pub async fn authorize(state: &AppState, params: AuthorizeParams) -> OauthResult<AuthorizeOutcome> {
+  let client = find_client(state, &params).await?;
+  let scope = match validate(&client, &params) {
+    Ok(scope) => scope,
+    Err(e) => return redirect_error(&params, e),
+  };
+  render(&client, scope, &params, String::new(), None, false)
+}
+
+/// Signs the user in from the consent page and redirects back with an authorization code.
+pub async fn consent(
+  state: &AppState,
+  req: AuthorizeConsentRequest,
+) -> OauthResult<AuthorizeOutcome> {
+  let params = &req.params;
+  let client = find_client(state, params).await?;
+  let scope = match validate(&client, params) {
+    Ok(scope) => scope,
+    Err(e) => return redirect_error(params, e),
+  };
+  if req.decision == AuthorizeDecision::Deny {
+    info!("User denied authorization of client: {}", client.client_id);
+    return redirect_error(params, OauthError::AccessDenied);
+  }
+  let user = match sign_in(state, &req.email, req.password.clone(), req.code.as_deref()).await? {
+    SignIn::User(user) => user,
+    SignIn::Retry {
+      error,
+      code_required,
+    } => {
+      return render(
+        &client,
+        scope,
+        params,
+        req.email.clone(),
+        Some(error),
+        code_required,
+      );
+    }
+  };
+  let value = AuthorizationCodeValue {
+    client_id: client.client_id.clone(),
+    user_id: user.id,
+    redirect_uri: params.redirect_uri.clone(),
+    scope: grant_scope(&scope, user.role).join(" "),
+    nonce: params.nonce.clone(),
+    code_challenge: params.code_challenge.clone(),
+    auth_time: Utc::now().timestamp(),
+  };
+  let key = AuthorizationCodeKey {
+    code: util::random::generate_random_string(AUTHORIZATION_CODE_LEN),
+  };
+  service::redis::set(&state.redis, (&key, &value)).await?;
+  info!(
+    "Issue authorization code to client: {} for user id: {}",
+    client.client_id, user.id
+  );
+  redirect(params, &[("code", &key.code)])
+}
+
+/// The result of signing in on a page of the authorization server.
+pub(crate) enum SignIn {
+  User(entity::user::Model),
+  /// The page is shown again with the error, asking for a code when the second factor started.
+  Retry {
+    error: String,
+    code_required: bool,
+  },
+}
+
+/// Checks the password and, when enabled, the second factor of a user signing in on a page.
+pub(crate) async fn sign_in(
+  state: &AppState,
+  email: &str,
+  password: String,
+  code: Option<&str>,
+) -> AppResult<SignIn> {
+  let retry = |error: String, code_required: bool| {
+    Ok(SignIn::Retry {
+      error,
+      code_required,
+    })
+  };
+  let user = match service::user::authenticate(state, email, password).await {
+    Ok(user) => user,
+    Err(e) if is_user_error(&e) => return retry(e.to_string(), false),
+    Err(e) => return Err(e),
+  };
+  if !user.is_approved {
+    return retry("The account is waiting for approval.".to_string(), false);
+  }
+  if user.is_2fa {
+    match code.filter(|code| !code.is_empty()) {
+      None => {
+        let message = match service::user::start_second_factor(state, &user).await? {
+          LoginResponse::Code { message, .. } => message,
+          LoginResponse::Token(_) => String::new(),
+        };
+        return retry(message, true);
+      }
+      Some(code) => match service::user::verify_second_factor(state, &user, code).await {
+        Ok(()) => {}
+        Err(e) if is_user_error(&e) => return retry(e.to_string(), true),
+        Err(e) => return Err(e),
+      },
+    }
+  }
+  Ok(SignIn::User(user))
+}
+
+/// Exchanges an authorization code, a device code or a refresh token for tokens.
+pub async fn token(
+  state: &AppState,
+  client_info: ClientInfo,
+  credentials: Option<(String, String)>,
+  req: OauthTokenRequest,
+) -> OauthResult<OauthTokenResponse> {
+  let client = authenticate_client(
+    state,
+    credentials,
+    req.client_id.clone(),
+    req.client_secret.clone(),
+  )
+  .await?;
+  info!(
+    "Token request of client: {} with grant: {}",
+    client.client_id, req.grant_type
+  );
+  match req.grant_type.as_str() {
+    "authorization_code" => exchange_code(state, client_info, &client, req).await,
+    "refresh_token" => refresh(state, client_info, &client, req).await,
+    DEVICE_CODE_GRANT_TYPE => service::device::token(state, client_info, &client, req).await,
+    _ => Err(OauthError::UnsupportedGrantType(
+      "Only the authorization_code, device_code and refresh_token grants are supported."
+        .to_string(),
+    )),
+  }
+}
+
+pub async fn userinfo(state: &AppState, user_id: uuid::Uuid) -> AppResult<UserInfoResponse> {
+  info!("Get user info user id: {user_id}");
+  let user = repo::user::find_by_id(&*state.db, user_id)
+    .await?
+    .to_result()?;
+  Ok(UserInfoResponse {
+    sub: user.id.to_string(),
+    email: user.email,
+    email_verified: user.is_active,
+    preferred_username: user.username,
+  })
+}
+
+pub fn configuration(state: &AppState) -> OpenIdConfigurationResponse {
+  let addr = state.config.server.get_public_url();
+  let algorithm: Algorithm = state.config.secret.algorithm.into();
+  let to_strings = |items: &[&str]| items.iter().map(ToString::to_string).collect();
+  OpenIdConfigurationResponse {
+    issuer: state.config.secret.issuer.clone(),
+    authorization_endpoint: format!("{addr}/oauth2/authorize"),
+    token_endpoint: format!("{addr}/oauth2/token"),
+    userinfo_endpoint: format!("{addr}/oauth2/userinfo"),
+    device_authorization_endpoint: format!("{addr}/oauth2/device_authorization"),
+    jwks_uri: format!("{addr}/.well-known/jwks.json"),
+    response_types_supported: to_strings(&["code"]),
+    grant_types_supported: to_strings(&[
+      "authorization_code",
+      DEVICE_CODE_GRANT_TYPE,
+      "refresh_token",
+    ]),
+    subject_types_supported: to_strings(&["public"]),
+    id_token_signing_alg_values_supported: vec![format!("{algorithm:?}")],
+    scopes_supported: OPENID_SCOPES
+      .iter()
+      .map(ToString::to_string)
+      .chain(Scope::iter().map(|s| s.to_string()))
+      .collect(),
+    token_endpoint_auth_methods_supported: to_strings(&[
+      "client_secret_basic",
+      "client_secret_post",
+      "none",
+    ]),
+    code_challenge_methods_supported: to_strings(&["S256"]),
+    dpop_signing_alg_values_supported: util::dpop::ALGORITHMS
+      .iter()
+      .map(|alg| format!("{alg:?}"))
+      .collect(),
+    tls_client_certificate_bound_access_tokens: state.config.server.tls.enabled
+      && state.config.server.tls.certificate_bound_tokens,
+    claims_supported: to_strings(&[
+      "iss",
+      "sub",
+      "aud",
+      "iat",
+      "exp",
+      "auth_time",
+      "nonce",
+      "email",
+      "email_verified",
+      "preferred_username",
+    ]),
+  }
+}
+
+async fn exchange_code(
+  state: &AppState,
+  client_info: ClientInfo,
+  client: &entity::oauth_client::Model,
+  req: OauthTokenRequest,
+) -> OauthResult<OauthTokenResponse> {
+  let code = req
+    .code
+    .ok_or_else(|| OauthError::InvalidRequest("The code is required.".to_string()))?;
+  let key = AuthorizationCodeKey { code };
+  let value = service::redis::get(&state.redis, &key)
+    .await?
+    .ok_or_else(invalid_code_error)?;
+  // A code is used once, even when the exchange fails.
+  if !service::redis::del(&state.redis, &key)
+    .await
+    .map_err(AppError::from)?
+    || value.client_id != client.client_id
+    || req.redirect_uri.as_deref() != Some(value.redirect_uri.as_str())
+  {
+    return Err(invalid_code_error());
+  }
+  if let Some(challenge) = value.code_challenge.as_deref() {
+    let verifier = req.code_verifier.as_deref().unwrap_or_default();
+    if service::oidc::code_challenge(verifier) != challenge {
+      return Err(OauthError::InvalidGrant(
+        "The code verifier does not match the code challenge.".to_string(),
+      ));
+    }
+  }
+  let user = repo::user::find_by_id(&*state.db, value.user_id)
+    .await?
+    .filter(|user| user.is_active && user.is_approved)
+    .ok_or_else(invalid_code_error)?;
+  issue_tokens(
+    state,
+    client_info,
+    &client.client_id,
+    user,
+    value.scope,
+    value.nonce,
+    value.auth_time,
+  )
+  .await
+}
+
+/// Opens a session for the user limited to the granted scope, with an id token when
+/// `openid` is granted.
+pub(crate) async fn issue_tokens(
+  state: &AppState,
+  client_info: ClientInfo,
+  client_id: &str,
+  user: entity::user::Model,
+  scope: String,
+  nonce: Option<String>,
+  auth_time: i64,
+) -> OauthResult<OauthTokenResponse> {
+  let scopes = scope.split_whitespace().collect::<Vec<_>>();
+  let ids = service::session::set(
+    &state.redis,
+    user.id,
+    client_info,
+    Some(client_id.to_string()),
+  )
+  .await?;
+  let tokens = service::token::generate_scoped_tokens(user.id, user.role, ids, api_scope(&scopes))?;
+  let id_token = if scopes.contains(&"openid") {
+    let mut claims = IdTokenClaims::new(
+      EXPIRE_BEARER_TOKEN_SECS,
+      user.id,
+      client_id.to_string(),
+      auth_time,
+      nonce,
+    );
+    if scopes.contains(&"email") {
+      claims = claims.with_email(user.email);
+    }
+    if scopes.contains(&"profile") {
+      claims = claims.with_profile(user.username);
+    }
+    Some(
+      claims
+        .encode(&ACCESS_TOKEN_KEY_RING)
+        .map_err(AppError::from)?,
+    )
+  } else {
+    None
+  };
+  Ok(OauthTokenResponse {
+    id_token,
+    scope: Some(scope),
+    ..OauthTokenResponse::from(tokens)
+  })
+}
+
+async fn refresh(
+  state: &AppState,
+  client_info: ClientInfo,
+  client: &entity::oauth_client::Model,
+  req: OauthTokenRequest,
+) -> OauthResult<OauthTokenResponse> {
+  let token = req
+    .refresh_token
+    .ok_or_else(|| OauthError::InvalidRequest("The refresh token is required.".to_string()))?;
+  let tokens = match service::token::refresh(
+    state,
+    client_info,
+    Some(&client.client_id),
+    RefreshTokenRequest { token },
+  )
+  .await
+  {
+    Ok(tokens) => tokens,
+    Err(
+      AppError::InvalidSessionError(_)
+      | AppError::UnauthorizedError(_)
+      | AppError::JwtError(_)
+      | AppError::NotFoundError(_),
+    ) => {
+      return Err(OauthError::InvalidGrant(
+        "The refresh token is invalid or expired.".to_string(),
+      ));
+    }
+    Err(e) => return Err(e.into()),
+  };
+  Ok(OauthTokenResponse::from(tokens))
+}
+
+/// Authenticates a client by HTTP Basic or by its form parameters, a public client
+/// is identified by its id alone.
+pub(crate) async fn authenticate_client(
+  state: &AppState,
+  credentials: Option<(String, String)>,
+  client_id: Option<String>,
+  client_secret: Option<String>,
+) -> OauthResult<entity::oauth_client::Model> {
+  let (client_id, secret) = match credentials {
+    Some(_) if client_secret.is_some() => {
+      return Err(OauthError::InvalidRequest(
+        "Only one client authentication method may be used.".to_string(),
+      ));
+    }
+    Some((client_id, secret)) => (client_id, Some(secret)),
+    None => (
+      client_id.ok_or_else(|| {
+        OauthError::InvalidClient("Client authentication is required.".to_string())
+      })?,
+      client_secret,
+    ),
+  };
+  let client = repo::oauth_client::find_by_client_id(&*state.db, &client_id)
+    .await?
+    .ok_or_else(invalid_client_error)?;
+  match (client.secret_hash.as_deref(), secret.as_deref()) {
+    (None, _) => Ok(client),
+    (Some(hash), Some(secret))
+      if openssl::memcmp::eq(hash.as_bytes(), util::hash::sha256_hash(secret).as_bytes()) =>
+    {
+      Ok(client)
+    }
+    _ => Err(invalid_client_error()),
+  }
+}
+
+/// Looks up the client and its redirect uri, errors here are never sent to the redirect uri.
+async fn find_client(
+  state: &AppState,
+  params: &AuthorizeParams,
+) -> OauthResult<entity::oauth_client::Model> {
+  let client = repo::oauth_client::find_by_client_id(&*state.db, &params.client_id)
+    .await?
+    .ok_or_else(|| OauthError::InvalidRequest("The client is unknown.".to_string()))?;
+  if !client.has_redirect_uri(&params.redirect_uri) {
+    return Err(OauthError::InvalidRequest(
+      "The redirect uri is not registered for the client.".to_string(),
+    ));
+  }
+  Ok(client)
+}
+
+/// Checks the response type and PKCE parameters and resolves the requested scope.
+fn validate(
+  client: &entity::oauth_client::Model,
+  params: &AuthorizeParams,
+) -> OauthResult<Vec<String>> {
+  if params.response_type != "code" {
+    return Err(OauthError::UnsupportedResponseType(
+      "Only the code response type is supported.".to_string(),
+    ));
+  }
+  match (
+    params.code_challenge.as_deref(),
+    params.code_challenge_method.as_deref(),
+  ) {
+    (Some(challenge), Some("S256")) if !challenge.is_empty() => {}
+    (None, None) if !client.is_public() => {}
+    (None, None) => {
+      return Err(OauthError::InvalidRequest(
+        "A public client must use PKCE.".to_string(),
+      ));
+    }
+    _ => {
+      return Err(OauthError::InvalidRequest(
+        "Only the S256 code challenge method is supported.".to_string(),
+      ));
+    }
+  }
+  resolve_scope(params.scope.as_deref(), &client.scopes())
+}
+
+/// The requested scope, or every API scope of the client when none is requested.
+pub(crate) fn resolve_scope(
+  requested: Option<&str>,
+  allowed: &[Scope],
+) -> OauthResult<Vec<String>> {
+  let Some(requested) = requested.filter(|s| !s.trim().is_empty()) else {
+    return Ok(allowed.iter().map(ToString::to_string).collect());
+  };
+  let mut scope: Vec<String> = Vec::new();
+  for item in requested.split_whitespace() {
+    let known =
+      OPENID_SCOPES.contains(&item) || Scope::from_str(item).is_ok_and(|s| allowed.contains(&s));
+    if !known {
+      return Err(OauthError::InvalidScope(format!(
+        "The scope {item} is not allowed for this client."
+      )));
+    }
+    if !scope.iter().any(|s| s == item) {
+      scope.push(item.to_string());
+    }
+  }
+  Ok(scope)
+}
+
+/// Drops the API scopes the role of the user does not have.
+pub(crate) fn grant_scope(scope: &[String], role: RoleUser) -> Vec<String> {
+  let role_scope = Scope::for_role(role);
+  scope
+    .iter()
+    .filter(|s| {
+      OPENID_SCOPES.contains(&s.as_str())
+        || Scope::from_str(s).is_ok_and(|s| role_scope.contains(&s))
+    })
+    .cloned()
+    .collect()
+}
+
+fn api_scope(scope: &[&str]) -> Vec<Scope> {
+  scope
+    .iter()
+    .filter_map(|s| Scope::from_str(s).ok())
+    .collect()
+}
+
+fn render(
+  client: &entity::oauth_client::Model,
+  scope: Vec<String>,
+  params: &AuthorizeParams,
+  email: String,
+  error: Option<String>,
+  code_required: bool,
+) -> OauthResult<AuthorizeOutcome> {
+  let template = Template::Authorize {
+    client_name: client.name.clone(),
+    scope,
+    params: Box::new(params.clone()),
+    email,
+    error,
+    code_required,
+  };
+  let page = crate::constant::TEMPLATE_ENGIN
+    .render(&template)
+    .map_err(AppError::from)?;
+  Ok(AuthorizeOutcome::Page(page))
+}
+
+fn redirect(params: &AuthorizeParams, pairs: &[(&str, &str)]) -> OauthResult<AuthorizeOutcome> {
+  let mut url =
+    url::Url::parse(&params.redirect_uri).map_err(|e| AppError::UnknownError(e.into()))?;
+  {
+    let mut query = url.query_pairs_mut();
+    query.extend_pairs(pairs);
+    if let Some(state) = params.state.as_deref() {
+      query.append_pair("state", state);
+    }
+  }
+  Ok(AuthorizeOutcome::Redirect(url.to_string()))
+}
+
+fn redirect_error(params: &AuthorizeParams, err: OauthError) -> OauthResult<AuthorizeOutcome> {
+  if let OauthError::ServerError(e) = err {
+    return Err(OauthError::ServerError(e));
+  }
+  let description = err.to_string();
+  redirect(
+    params,
+    &[("error", err.code()), ("error_description", &description)],
+  )
+}
+
+/// Errors of the user signing in, shown on the page instead of failing the request.
+fn is_user_error(err: &AppError) -> bool {
+  matches!(
+    err,
+    AppError::UnauthorizedError(_)
+      | AppError::NotFoundError(_)
+      | AppError::UserLockedError(_)
+      | AppError::UserNotActiveError(_)
+      | AppError::PermissionDeniedError(_)
+      | AppError::InvalidInputError(_)
+  )
+}
+
+fn invalid_code_error() -> OauthError {
+  OauthError::InvalidGrant("The authorization code is invalid or expired.".to_string())
+}
+
+fn invalid_client_error() -> OauthError {
+  OauthError::InvalidClient("Client authentication failed.".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_resolve_scope() {
+    let allowed = vec![Scope::UserRead, Scope::UserWrite];
+    assert_eq!(
+      resolve_scope(None, &allowed).unwrap(),
+      vec!["user:read", "user:write"]
+    );
+    assert_eq!(
+      resolve_scope(Some("openid email user:read openid"), &allowed).unwrap(),
+      vec!["openid", "email", "user:read"]
+    );
+    assert!(matches!(
+      resolve_scope(Some("openid admin:read"), &allowed),
+      Err(OauthError::InvalidScope(_))
+    ));
+    assert!(matches!(
+      resolve_scope(Some("unknown"), &allowed),
+      Err(OauthError::InvalidScope(_))
+    ));
+  }
+
+  #[test]
+  fn test_grant_scope_by_role() {
+    let scope = vec![
+      "openid".to_string(),
+      "user:read".to_string(),
+      "admin:read".to_string(),
+    ];
+    assert_eq!(
+      grant_scope(&scope, RoleUser::User),
+      vec!["openid", "user:read"]
+    );
+    assert_eq!(grant_scope(&scope, RoleUser::Admin), scope);
+  }
+}
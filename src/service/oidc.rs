@@ -0,0 +1,456 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::jwk::JwkSet;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use sea_orm::TransactionTrait;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::configure::oidc::OidcProviderConfig;
+use crate::configure::registration::RegistrationMode;
+use crate::constant::{OIDC_STATE_LEN, OIDC_USERNAME_ATTEMPTS, PKCE_VERIFIER_LEN};
+use crate::dto::{
+  IdentityListResponse, IdentityResponse, LoginResponse, OidcAuthorizeResponse, OidcCallbackRequest,
+};
+use crate::entity;
+use crate::entity::role::RoleUser;
+use crate::error::{AppError, AppResult, Resource, ResourceType, ToAppResult};
+use crate::repo;
+use crate::server::state::AppState;
+use crate::service;
+use crate::service::redis::{OidcStateKey, OidcStateValue};
+use crate::util;
+use crate::util::client_info::ClientInfo;
+
+/// The part of the discovery document this server uses.
+#[derive(Debug, Deserialize)]
+pub struct ProviderMetadata {
+  pub issuer: String,
+  pub authorization_endpoint: String,
+  pub token_endpoint: String,
+  pub jwks_uri: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenEndpointResponse {
+  id_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IdTokenClaims {
+  pub sub: String,
+  pub nonce: Option<String>,
+  pub email: Option<String>,
+  pub email_verified: Option<bool>,
+  pub preferred_username: Option<String>,
+}
+
+/// Starts an authorization code flow with PKCE, the identity is linked to
+/// `user_id` when given instead of used to sign in.
+pub async fn authorize(
+  state: &AppState,
+  provider: &str,
+  user_id: Option<Uuid>,
+) -> AppResult<OidcAuthorizeResponse> {
+  info!("Authorize with provider: {provider} user id: {user_id:?}");
+  let provider = find_provider(state, provider)?;
+  let metadata = discover(state, provider).await?;
+  let value = OidcStateValue {
+    provider: provider.name.clone(),
+    nonce: util::random::generate_random_string(OIDC_STATE_LEN),
+    code_verifier: util::random::generate_random_string(PKCE_VERIFIER_LEN),
+    user_id,
+  };
+  let oidc_state = util::random::generate_random_string(OIDC_STATE_LEN);
+  let mut url = url::Url::parse(&metadata.authorization_endpoint)
+    .map_err(|e| AppError::UnknownError(e.into()))?;
+  url
+    .query_pairs_mut()
+    .append_pair("response_type", "code")
+    .append_pair("client_id", &provider.client_id)
+    .append_pair("redirect_uri", &provider.redirect_uri)
+    .append_pair("scope", &provider.scope.join(" "))
+    .append_pair("state", &oidc_state)
+    .append_pair("nonce", &value.nonce)
+    .append_pair("code_challenge", &code_challenge(&value.code_verifier))
+    .append_pair("code_challenge_method", "S256");
+  let key = OidcStateKey {
+    state: oidc_state.clone(),
+  };
+  service::redis::set(&state.redis, (&key, &value)).await?;
+  Ok(OidcAuthorizeResponse {
+    authorization_url: url.to_string(),
+    state: oidc_state,
+  })
+}
+
+/// Signs in the owner of the identity, a new user is registered for an unknown identity.
+pub async fn login(
+  state: &AppState,
+  client: ClientInfo,
+  provider: &str,
+  req: OidcCallbackRequest,
+) -> AppResult<LoginResponse> {
+  let provider = find_provider(state, provider)?;
+  let claims = exchange(state, provider, req, None).await?;
+  info!(
+    "Login with provider: {} subject: {}",
+    provider.name, claims.sub
+  );
+  let user = match repo::identity::find_by_subject(&*state.db, &provider.name, &claims.sub).await? {
+    Some(identity) => {
+      let user = repo::user::find_by_id(&*state.db, identity.user_id)
+        .await?
+        .to_result()?;
+      if !user.is_active {
+        return Err(AppError::UserNotActiveError(
+          "User is not active.".to_string(),
+        ));
+      }
+      user
+    }
+    None => register(state, provider, claims).await?,
+  };
+  service::lockout::check(&state.redis, user.id).await?;
+  service::user::complete_login(state, client, user).await
+}
+
+/// Links the identity to the signed in user that started the authorization.
+pub async fn link(
+  state: &AppState,
+  user_id: Uuid,
+  provider: &str,
+  req: OidcCallbackRequest,
+) -> AppResult<IdentityResponse> {
+  let provider = find_provider(state, provider)?;
+  let claims = exchange(state, provider, req, Some(user_id)).await?;
+  info!(
+    "Link provider: {} subject: {} to user id: {user_id}",
+    provider.name, claims.sub
+  );
+  let tx = state.db.begin().await?;
+  let linked = repo::identity::find_by_subject(&tx, &provider.name, &claims.sub).await?;
+  let has_provider = repo::identity::find_by_user(&tx, user_id)
+    .await?
+    .iter()
+    .any(|identity| identity.provider == provider.name);
+  if linked.is_some() || has_provider {
+    return Err(AppError::ResourceExistsError(Resource {
+      details: vec![("provider".to_string(), provider.name.clone())],
+      resource_type: ResourceType::Identity,
+    }));
+  }
+  let model = repo::identity::save(
+    &tx,
+    user_id,
+    provider.name.clone(),
+    claims.sub,
+    claims.email,
+  )
+  .await?;
+  tx.commit().await?;
+  Ok(IdentityResponse::from(model))
+}
+
+pub async fn list(state: &AppState, user_id: Uuid) -> AppResult<IdentityListResponse> {
+  info!("List identities user id: {user_id}");
+  let list = repo::identity::find_by_user(&*state.db, user_id)
+    .await?
+    .into_iter()
+    .map(IdentityResponse::from)
+    .collect();
+  Ok(IdentityListResponse { list })
+}
+
+pub async fn unlink(state: &AppState, user_id: Uuid, provider: &str) -> AppResult {
+  info!("Unlink provider: {provider} user id: {user_id}");
+  if !repo::identity::delete_by_user(&*state.db, user_id, provider).await? {
+    return Err(AppError::NotFoundError(Resource {
+      details: vec![("provider".to_string(), provider.to_string())],
+      resource_type: ResourceType::Identity,
+    }));
+  }
+  Ok(())
+}
+
+pub async fn discover(
+  state: &AppState,
+  provider: &OidcProviderConfig,
+) -> AppResult<ProviderMetadata> {
+  let metadata: ProviderMetadata = state
+    .http
+    .get(provider.discovery_url())
+    .send()
+    .await?
+    .error_for_status()?
+    .json()
+    .await?;
+  if metadata.issuer.trim_end_matches('/') != provider.issuer.trim_end_matches('/') {
+    return Err(AppError::BadRequestError(format!(
+      "Issuer of provider {} does not match its discovery document.",
+      provider.name
+    )));
+  }
+  Ok(metadata)
+}
+
+/// Consumes the authorization state and exchanges the code for a verified id token.
+async fn exchange(
+  state: &AppState,
+  provider: &OidcProviderConfig,
+  req: OidcCallbackRequest,
+  user_id: Option<Uuid>,
+) -> AppResult<IdTokenClaims> {
+  let key = OidcStateKey { state: req.state };
+  let value = service::redis::get(&state.redis, &key)
+    .await?
+    .ok_or_else(invalid_state_error)?;
+  if !service::redis::del(&state.redis, &key).await?
+    || value.provider != provider.name
+    || value.user_id != user_id
+  {
+    return Err(invalid_state_error());
+  }
+  let metadata = discover(state, provider).await?;
+  let resp = state
+    .http
+    .post(&metadata.token_endpoint)
+    .form(&[
+      ("grant_type", "authorization_code"),
+      ("code", &req.code),
+      ("redirect_uri", &provider.redirect_uri),
+      ("client_id", &provider.client_id),
+      ("client_secret", &provider.client_secret),
+      ("code_verifier", &value.code_verifier),
+    ])
+    .send()
+    .await?;
+  if !resp.status().is_success() {
+    info!(
+      "Token endpoint of {} answered: {}",
+      provider.name,
+      resp.status()
+    );
+    return Err(AppError::UnauthorizedError(
+      "The identity provider rejected the authorization code.".to_string(),
+    ));
+  }
+  let token: TokenEndpointResponse = resp.json().await?;
+  let jwks: JwkSet = state
+    .http
+    .get(&metadata.jwks_uri)
+    .send()
+    .await?
+    .error_for_status()?
+    .json()
+    .await?;
+  let claims = verify_id_token(
+    &jwks,
+    &token.id_token,
+    &metadata.issuer,
+    &provider.client_id,
+  )?;
+  if claims.nonce.as_deref() != Some(value.nonce.as_str()) {
+    return Err(invalid_id_token_error());
+  }
+  Ok(claims)
+}
+
+async fn register(
+  state: &AppState,
+  provider: &OidcProviderConfig,
+  claims: IdTokenClaims,
+) -> AppResult<entity::user::Model> {
+  if state.config.registration.mode != RegistrationMode::Open {
+    return Err(AppError::PermissionDeniedError(
+      "Registration is closed.".to_string(),
+    ));
+  }
+  let email = claims
+    .email
+    .clone()
+    .filter(|_| claims.email_verified == Some(true))
+    .ok_or_else(|| {
+      AppError::BadRequestError(
+        "The identity provider did not return a verified email.".to_string(),
+      )
+    })?;
+  service::registration::check_email(state, &email).await?;
+  let tx = state.db.begin().await?;
+  if repo::user::find_by_email(&tx, &email).await?.is_some() {
+    // Taking over an account by email is left to its owner, who may link the provider.
+    return Err(AppError::ResourceExistsError(Resource {
+      details: vec![("email".to_string(), email)],
+      resource_type: ResourceType::User,
+    }));
+  }
+  let username = available_username(&tx, &claims, &email).await?;
+  info!("Register user: {username} with provider: {}", provider.name);
+  let user_id = repo::user::save(
+    &tx,
+    username,
+    // never shown, the user may set a password through the forget password flow
+    util::random::generate_random_string(PKCE_VERIFIER_LEN),
+    email.clone(),
+    RoleUser::User,
+    !state.config.registration.require_approval,
+  )
+  .await?;
+  let user = repo::user::find_by_id(&tx, user_id).await?.to_result()?;
+  repo::user::active(&tx, user).await?;
+  repo::identity::save(&tx, user_id, provider.name.clone(), claims.sub, Some(email)).await?;
+  let user = repo::user::find_by_id(&tx, user_id).await?.to_result()?;
+  tx.commit().await?;
+  Ok(user)
+}
+
+async fn available_username(
+  tx: &sea_orm::DatabaseTransaction,
+  claims: &IdTokenClaims,
+  email: &str,
+) -> AppResult<String> {
+  let base = claims
+    .preferred_username
+    .as_deref()
+    .unwrap_or_else(|| email.split('@').next().unwrap_or_default())
+    .chars()
+    .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '.')
+    .take(20)
+    .collect::<String>();
+  let base = if base.len() < 3 {
+    format!("user_{base}")
+  } else {
+    base
+  };
+  let mut username = base.clone();
+  for _ in 0..OIDC_USERNAME_ATTEMPTS {
+    match repo::user::check_unique_by_username(tx, &username).await {
+      Err(AppError::ResourceExistsError(_)) => {
+        username = format!(
+          "{base}_{}",
+          util::random::generate_random_string(4).to_lowercase()
+        );
+      }
+      result => return result.map(|_| username),
+    }
+  }
+  repo::user::check_unique_by_username(tx, &username)
+    .await
+    .map(|_| username)
+}
+
+/// Verifies the signature, issuer, audience and expiry of an id token.
+pub fn verify_id_token(
+  jwks: &JwkSet,
+  token: &str,
+  issuer: &str,
+  client_id: &str,
+) -> AppResult<IdTokenClaims> {
+  let header = jsonwebtoken::decode_header(token).map_err(|_| invalid_id_token_error())?;
+  if !matches!(
+    header.alg,
+    Algorithm::RS256
+      | Algorithm::RS384
+      | Algorithm::RS512
+      | Algorithm::PS256
+      | Algorithm::PS384
+      | Algorithm::PS512
+      | Algorithm::ES256
+      | Algorithm::ES384
+      | Algorithm::EdDSA
+  ) {
+    return Err(invalid_id_token_error());
+  }
+  let jwk = match header.kid.as_deref() {
+    Some(kid) => jwks.find(kid),
+    None if jwks.keys.len() == 1 => jwks.keys.first(),
+    None => None,
+  }
+  .ok_or_else(invalid_id_token_error)?;
+  let key = DecodingKey::from_jwk(jwk).map_err(|_| invalid_id_token_error())?;
+  let mut validation = Validation::new(header.alg);
+  validation.set_issuer(&[issuer]);
+  validation.set_audience(&[client_id]);
+  validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+  let data = jsonwebtoken::decode::<IdTokenClaims>(token, &key, &validation)
+    .map_err(|_| invalid_id_token_error())?;
+  Ok(data.claims)
+}
+
+/// The S256 PKCE challenge of a code verifier.
+pub fn code_challenge(code_verifier: &str) -> String {
+  URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+fn find_provider<'a>(state: &'a AppState, name: &str) -> AppResult<&'a OidcProviderConfig> {
+  state.config.oidc.provider(name).ok_or_else(|| {
+    AppError::NotFoundError(Resource {
+      details: vec![("provider".to_string(), name.to_string())],
+      resource_type: ResourceType::OidcProvider,
+    })
+  })
+}
+
+fn invalid_state_error() -> AppError {
+  AppError::UnauthorizedError("The authorization state is invalid or expired.".to_string())
+}
+
+fn invalid_id_token_error() -> AppError {
+  AppError::UnauthorizedError("The identity token is invalid.".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use jsonwebtoken::Header;
+  use serde::Serialize;
+
+  use super::*;
+  use crate::util::jwk::KeyRing;
+  use crate::util::key::PairKey;
+
+  #[derive(Serialize)]
+  struct Claims<'a> {
+    iss: &'a str,
+    aud: &'a str,
+    sub: &'a str,
+    exp: i64,
+    nonce: &'a str,
+  }
+
+  fn key_ring() -> KeyRing {
+    let pair = PairKey::rsa(2048).unwrap();
+    let private_key = String::from_utf8(pair.private_key).unwrap();
+    let public_key = String::from_utf8(pair.public_key).unwrap();
+    KeyRing::new(&Header::new(Algorithm::RS256), &private_key, &[public_key]).unwrap()
+  }
+
+  #[test]
+  fn test_code_challenge() {
+    // RFC 7636 appendix B
+    assert_eq!(
+      code_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+      "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+    );
+  }
+
+  #[test]
+  fn test_verify_id_token() {
+    let ring = key_ring();
+    let exp = chrono::Utc::now().timestamp() + 60;
+    let claims = Claims {
+      iss: "https://issuer",
+      aud: "client",
+      sub: "subject",
+      exp,
+      nonce: "nonce",
+    };
+    let token = ring.encode(&claims).unwrap();
+    let result = verify_id_token(ring.jwks(), &token, "https://issuer", "client").unwrap();
+    assert_eq!(result.sub, "subject");
+    assert_eq!(result.nonce.as_deref(), Some("nonce"));
+    assert!(verify_id_token(ring.jwks(), &token, "https://other", "client").is_err());
+    assert!(verify_id_token(ring.jwks(), &token, "https://issuer", "other").is_err());
+    assert!(verify_id_token(key_ring().jwks(), &token, "https://issuer", "client").is_err());
+  }
+}
@@ -0,0 +1,318 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::constant::EXPIRE_PASSKEY_CHALLENGE_SECS;
+use crate::dto::*;
+use crate::entity;
+use crate::entity::message::MessageKind;
+use crate::error::{AppError, AppResult, Resource, ResourceType, ToAppResult, invalid_input_error};
+use crate::repo;
+use crate::server::state::AppState;
+use crate::service;
+use crate::service::redis::{LoginKey, PasskeyCeremony, PasskeyChallengeKey};
+use crate::util;
+use crate::util::client_info::ClientInfo;
+use crate::util::webauthn::{AuthenticatorData, ClientData};
+
+const PUBLIC_KEY_TYPE: &str = "public-key";
+
+/// Starts registering a passkey of the signed in user.
+pub async fn start_registration(
+  state: &AppState,
+  user_id: Uuid,
+) -> AppResult<PasskeyCreationOptionsResponse> {
+  info!("Start passkey registration user id: {user_id}");
+  let user = repo::user::find_by_id(&*state.db, user_id)
+    .await?
+    .to_result()?;
+  let exclude_credentials = repo::passkey::find_by_user(&*state.db, user_id)
+    .await?
+    .into_iter()
+    .map(descriptor)
+    .collect();
+  let challenge = issue_challenge(state, PasskeyCeremony::Register { user_id }).await?;
+  let config = &state.config.webauthn;
+  Ok(PasskeyCreationOptionsResponse {
+    challenge,
+    rp: PasskeyRelyingParty {
+      id: config.rp_id.clone(),
+      name: config.rp_name.clone(),
+    },
+    user: PasskeyUser {
+      id: URL_SAFE_NO_PAD.encode(user.id.as_bytes()),
+      name: user.email,
+      display_name: user.username,
+    },
+    pub_key_cred_params: util::webauthn::ALGORITHMS
+      .into_iter()
+      .map(|alg| PasskeyCredentialParameter {
+        kind: PUBLIC_KEY_TYPE.to_string(),
+        alg,
+      })
+      .collect(),
+    timeout: EXPIRE_PASSKEY_CHALLENGE_SECS.as_millis() as u64,
+    exclude_credentials,
+    // discoverable, so the passkey can sign in without an email
+    authenticator_selection: PasskeyAuthenticatorSelection {
+      resident_key: "required".to_string(),
+      user_verification: "preferred".to_string(),
+    },
+    attestation: "none".to_string(),
+  })
+}
+
+/// Verifies the new credential against the challenge of the registration and stores it.
+pub async fn register(
+  state: &AppState,
+  user_id: Uuid,
+  req: RegisterPasskeyRequest,
+) -> AppResult<PasskeyResponse> {
+  info!("Register passkey user id: {user_id} name: {}", req.name);
+  let response = req.credential.response;
+  let client_data_json = util::webauthn::decode("client_data_json", &response.client_data_json)?;
+  let ceremony = check_client_data(state, &client_data_json, "webauthn.create").await?;
+  if ceremony != (PasskeyCeremony::Register { user_id }) {
+    return Err(invalid_input_error(
+      "credential",
+      "The challenge is invalid or expired.",
+    ));
+  }
+  let attestation_object =
+    util::webauthn::decode("attestation_object", &response.attestation_object)?;
+  let auth_data =
+    AuthenticatorData::parse(&util::webauthn::attestation_auth_data(&attestation_object)?)?;
+  if !auth_data.is_rp_id(&state.config.webauthn.rp_id) || !auth_data.user_present() {
+    return Err(invalid_input_error(
+      "credential",
+      "The credential is not valid for this site.",
+    ));
+  }
+  let credential = auth_data
+    .credential
+    .ok_or_else(|| invalid_input_error("credential", "The credential has no public key."))?;
+  let credential_id = URL_SAFE_NO_PAD.encode(&credential.credential_id);
+  if credential_id != req.credential.id.trim_end_matches('=') {
+    return Err(invalid_input_error(
+      "credential",
+      "The credential id does not match.",
+    ));
+  }
+  if repo::passkey::find_by_credential_id(&*state.db, &credential_id)
+    .await?
+    .is_some()
+  {
+    return Err(AppError::ResourceExistsError(Resource {
+      details: vec![("credential_id".to_string(), credential_id)],
+      resource_type: ResourceType::Passkey,
+    }));
+  }
+  let model = repo::passkey::save(
+    &*state.db,
+    user_id,
+    req.name,
+    credential_id,
+    credential.public_key,
+    credential.algorithm as i32,
+    auth_data.sign_count.into(),
+  )
+  .await?;
+  Ok(PasskeyResponse::from(model))
+}
+
+pub async fn list(state: &AppState, user_id: Uuid) -> AppResult<PasskeyListResponse> {
+  info!("List passkeys user id: {user_id}");
+  let list = repo::passkey::find_by_user(&*state.db, user_id)
+    .await?
+    .into_iter()
+    .map(PasskeyResponse::from)
+    .collect();
+  Ok(PasskeyListResponse { list })
+}
+
+pub async fn delete(state: &AppState, user_id: Uuid, id: Uuid) -> AppResult {
+  info!("Delete passkey user id: {user_id} passkey id: {id}");
+  if !repo::passkey::delete_by_user(&*state.db, user_id, id).await? {
+    return Err(AppError::NotFoundError(Resource {
+      details: vec![("passkey_id".to_string(), id.to_string())],
+      resource_type: ResourceType::Passkey,
+    }));
+  }
+  Ok(())
+}
+
+/// Issues a challenge for a passwordless login, or for the second factor of a
+/// pending password login when a user id is given.
+pub async fn login_options(
+  state: &AppState,
+  req: PasskeyLoginOptionsRequest,
+) -> AppResult<PasskeyRequestOptionsResponse> {
+  info!("Passkey login options request: {req:?}");
+  let allow_credentials = match req.user_id {
+    Some(user_id) => {
+      if !service::redis::check_exist_key(&state.redis, &LoginKey { user_id }).await? {
+        return Err(AppError::InvalidSessionError(
+          "There is no pending two factor login.".to_string(),
+        ));
+      }
+      repo::passkey::find_by_user(&*state.db, user_id)
+        .await?
+        .into_iter()
+        .map(descriptor)
+        .collect()
+    }
+    // the authenticator offers the discoverable passkeys it holds for the site
+    None => vec![],
+  };
+  let user_verification = if req.user_id.is_some() {
+    "discouraged"
+  } else {
+    "required"
+  };
+  let challenge = issue_challenge(
+    state,
+    PasskeyCeremony::Login {
+      user_id: req.user_id,
+    },
+  )
+  .await?;
+  Ok(PasskeyRequestOptionsResponse {
+    challenge,
+    rp_id: state.config.webauthn.rp_id.clone(),
+    timeout: EXPIRE_PASSKEY_CHALLENGE_SECS.as_millis() as u64,
+    allow_credentials,
+    user_verification: user_verification.to_string(),
+  })
+}
+
+/// Signs in with a passkey alone, the verified user makes it a second factor by itself.
+pub async fn login(
+  state: &AppState,
+  client: ClientInfo,
+  req: PasskeyLoginRequest,
+) -> AppResult<LoginResponse> {
+  info!("Passkey login credential id: {}", req.credential.id);
+  let (user, auth_data) = verify_assertion(state, req.credential, None).await?;
+  if !auth_data.user_verified() {
+    return Err(invalid_passkey_error());
+  }
+  service::lockout::check(&state.redis, user.id).await?;
+  let resp = service::user::open_session(state, client, user).await?;
+  Ok(LoginResponse::Token(resp))
+}
+
+/// Accepts a passkey of the user as the second factor of the pending login.
+pub(crate) async fn verify_second_factor(
+  state: &AppState,
+  user: &entity::user::Model,
+  credential: PasskeyAssertionCredential,
+) -> AppResult {
+  let key = LoginKey { user_id: user.id };
+  verify_assertion(state, credential, Some(user.id)).await?;
+  if !service::redis::del(&state.redis, &key).await? {
+    return Err(invalid_input_error("code", "Code is invalid."));
+  }
+  service::lockout::reset_failed_code(&state.redis, user.id, MessageKind::LoginCode).await?;
+  Ok(())
+}
+
+/// Checks an assertion against the stored public key and moves its signature counter forward.
+async fn verify_assertion(
+  state: &AppState,
+  credential: PasskeyAssertionCredential,
+  user_id: Option<Uuid>,
+) -> AppResult<(entity::user::Model, AuthenticatorData)> {
+  let response = credential.response;
+  let client_data_json = util::webauthn::decode("client_data_json", &response.client_data_json)?;
+  let ceremony = check_client_data(state, &client_data_json, "webauthn.get").await?;
+  if ceremony != (PasskeyCeremony::Login { user_id }) {
+    return Err(invalid_passkey_error());
+  }
+  let passkey =
+    repo::passkey::find_by_credential_id(&*state.db, credential.id.trim_end_matches('='))
+      .await?
+      .filter(|passkey| user_id.is_none_or(|user_id| passkey.user_id == user_id))
+      .ok_or_else(invalid_passkey_error)?;
+  if let Some(user_handle) = response.user_handle.filter(|handle| !handle.is_empty())
+    && util::webauthn::decode("user_handle", &user_handle)? != passkey.user_id.as_bytes()
+  {
+    return Err(invalid_passkey_error());
+  }
+  let raw_auth_data = util::webauthn::decode("authenticator_data", &response.authenticator_data)?;
+  let signature = util::webauthn::decode("signature", &response.signature)?;
+  let auth_data = AuthenticatorData::parse(&raw_auth_data)?;
+  if !auth_data.is_rp_id(&state.config.webauthn.rp_id)
+    || !auth_data.user_present()
+    || !util::webauthn::verify_signature(
+      passkey.algorithm.into(),
+      &passkey.public_key,
+      &raw_auth_data,
+      &client_data_json,
+      &signature,
+    )?
+  {
+    return Err(invalid_passkey_error());
+  }
+  // Authenticators without a counter always send zero, otherwise a counter that
+  // does not grow points to a cloned authenticator.
+  let sign_count = i64::from(auth_data.sign_count);
+  if (sign_count != 0 || passkey.sign_count != 0) && sign_count <= passkey.sign_count {
+    return Err(invalid_passkey_error());
+  }
+  if !repo::passkey::update_sign_count(&*state.db, passkey.id, passkey.sign_count, sign_count)
+    .await?
+  {
+    return Err(invalid_passkey_error());
+  }
+  let user = repo::user::find_by_id(&*state.db, passkey.user_id)
+    .await?
+    .filter(|user| user.is_active)
+    .ok_or_else(invalid_passkey_error)?;
+  Ok((user, auth_data))
+}
+
+/// Checks the client data of a ceremony and consumes its challenge.
+async fn check_client_data(
+  state: &AppState,
+  client_data_json: &[u8],
+  kind: &str,
+) -> AppResult<PasskeyCeremony> {
+  let client_data = ClientData::parse(client_data_json)?;
+  if client_data.kind != kind || !state.config.webauthn.origins.contains(&client_data.origin) {
+    return Err(invalid_input_error(
+      "client_data_json",
+      "Client data is not valid for this site.",
+    ));
+  }
+  let key = PasskeyChallengeKey {
+    challenge: client_data.challenge,
+  };
+  let ceremony = service::redis::get(&state.redis, &key).await?;
+  match ceremony {
+    Some(ceremony) if service::redis::del(&state.redis, &key).await? => Ok(ceremony),
+    _ => Err(invalid_input_error(
+      "client_data_json",
+      "The challenge is invalid or expired.",
+    )),
+  }
+}
+
+async fn issue_challenge(state: &AppState, ceremony: PasskeyCeremony) -> AppResult<String> {
+  let challenge = util::webauthn::generate_challenge();
+  let key = PasskeyChallengeKey {
+    challenge: challenge.clone(),
+  };
+  service::redis::set(&state.redis, (&key, &ceremony)).await?;
+  Ok(challenge)
+}
+
+fn descriptor(passkey: entity::passkey::Model) -> PasskeyCredentialDescriptor {
+  PasskeyCredentialDescriptor {
+    kind: PUBLIC_KEY_TYPE.to_string(),
+    id: passkey.credential_id,
+  }
+}
+
+fn invalid_passkey_error() -> AppError {
+  AppError::UnauthorizedError("The passkey is not valid.".to_string())
+}
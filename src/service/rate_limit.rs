@@ -0,0 +1,78 @@
+use std::fmt::Display;
+
+use tracing::warn;
+
+use crate::client::redis::{RedisClient, RedisClientExt};
+use crate::configure::rate_limit::{RateLimitKeyKind, RateLimitPolicy};
+use crate::error::{AppError, AppResult};
+
+#[derive(Debug, Clone)]
+pub struct RateLimitKey<'a> {
+  pub route: &'a str,
+  pub method: &'a str,
+  pub kind: RateLimitKeyKind,
+  pub id: &'a str,
+}
+
+impl Display for RateLimitKey<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(
+      f,
+      "RATE_LIMIT_{}_{}_{}_{}",
+      self.method, self.route, self.kind, self.id
+    )
+  }
+}
+
+/// Consumes one request from the policy budget, fails with the seconds to wait when exhausted.
+pub async fn check(
+  redis: &RedisClient,
+  policy: &RateLimitPolicy,
+  key: &RateLimitKey<'_>,
+) -> AppResult {
+  let retry_after = redis
+    .gcra(
+      &key.to_string(),
+      policy.emission_interval(),
+      policy.burst_tolerance(),
+    )
+    .await?;
+  if let Some(retry_after) = retry_after {
+    warn!("Rate limit exceeded key: {key}");
+    let secs = retry_after.as_millis().div_ceil(1000).max(1) as u64;
+    return Err(AppError::TooManyRequestsError(secs));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use uuid::Uuid;
+
+  use super::*;
+  use crate::constant::REDIS;
+
+  #[tokio::test]
+  async fn test_rate_limit_check() {
+    let policy = RateLimitPolicy {
+      route: "/api/v1/user/login".to_string(),
+      method: None,
+      key: RateLimitKeyKind::Ip,
+      limit: 2,
+      period: Duration::from_secs(60),
+    };
+    let id = Uuid::new_v4().to_string();
+    let key = RateLimitKey {
+      route: &policy.route,
+      method: "POST",
+      kind: policy.key,
+      id: &id,
+    };
+    check(&REDIS, &policy, &key).await.unwrap();
+    check(&REDIS, &policy, &key).await.unwrap();
+    let err = check(&REDIS, &policy, &key).await.unwrap_err();
+    assert!(matches!(err, AppError::TooManyRequestsError(secs) if secs > 0 && secs <= 30));
+  }
+}
@@ -0,0 +1,63 @@
+use sea_orm::ConnectionTrait;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::constant::{RECOVERY_CODE_COUNT, RECOVERY_CODE_LEN};
+use crate::entity::message::MessageKind;
+use crate::error::AppResult;
+use crate::repo;
+use crate::server::state::AppState;
+use crate::util;
+
+/// Replaces the user's recovery codes and returns the new plain codes.
+pub async fn generate<C>(conn: &C, user_id: Uuid) -> AppResult<Vec<String>>
+where
+  C: ConnectionTrait,
+{
+  info!("Generate recovery codes user id: {user_id}");
+  let codes = (0..RECOVERY_CODE_COUNT)
+    .map(|_| util::random::generate_random_string(RECOVERY_CODE_LEN))
+    .collect::<Vec<_>>();
+  let plain = codes.clone();
+  let hashes = tokio::task::spawn_blocking(move || {
+    plain
+      .iter()
+      .map(util::hash::argon_hash)
+      .collect::<Result<Vec<_>, _>>()
+  })
+  .await??;
+  repo::recovery_code::replace_all(conn, user_id, hashes).await?;
+  Ok(codes)
+}
+
+/// Consumes a matching unused recovery code and notifies the user by email.
+pub async fn consume(state: &AppState, user_id: Uuid, code: &str) -> AppResult<bool> {
+  if code.len() != RECOVERY_CODE_LEN {
+    return Ok(false);
+  }
+  let models = repo::recovery_code::find_unused_by_user(&*state.db, user_id).await?;
+  let code = code.to_string();
+  let matched = tokio::task::spawn_blocking(move || {
+    models
+      .into_iter()
+      .find(|m| util::hash::argon_verify(&code, &m.code_hash).is_ok())
+  })
+  .await?;
+  let Some(model) = matched else {
+    return Ok(false);
+  };
+  if !repo::recovery_code::mark_used(&*state.db, model.id).await? {
+    return Ok(false);
+  }
+  info!("Recovery code used by user id: {user_id}");
+  let remaining = repo::recovery_code::count_unused_by_user(&*state.db, user_id).await?;
+  repo::message::save(
+    &*state.db,
+    user_id,
+    remaining.to_string(),
+    MessageKind::RecoveryCodeUsed,
+  )
+  .await?;
+  state.messenger_notify.notify_one();
+  Ok(true)
+}
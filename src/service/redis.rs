@@ -4,6 +4,7 @@ use std::time::Duration;
 
 use crate::client::redis::RedisClientExt;
 use crate::constant::*;
+use chrono::{DateTime, Utc};
 use fake::Dummy;
 
 use serde::de::DeserializeOwned;
@@ -12,6 +13,7 @@ use tracing::info;
 use uuid::Uuid;
 
 use crate::client::redis::RedisClient;
+use crate::entity::message::MessageKind;
 use crate::error::AppResult;
 
 pub trait RedisKey: Debug + Display {
@@ -25,19 +27,69 @@ pub trait RedisKey: Debug + Display {
 #[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
 pub struct SessionKey {
   pub user_id: Uuid,
+  pub session_id: Uuid,
 }
 
 impl RedisKey for SessionKey {
-  type Value = Uuid;
+  type Value = SessionValue;
   const EXPIRE_TIME: Duration = EXPIRE_SESSION_CODE_SECS;
 }
 
 impl Display for SessionKey {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    write!(f, "SESSION_KEY_{}", self.user_id)
+    write!(f, "SESSION_KEY_{}_{}", self.user_id, self.session_id)
   }
 }
 
+/// The set of the session ids of a user, so listing them does not scan the keyspace.
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct SessionIndexKey {
+  pub user_id: Uuid,
+}
+
+impl RedisKey for SessionIndexKey {
+  type Value = Uuid;
+  // outlives the newest refresh token, creating or refreshing a session resets it
+  const EXPIRE_TIME: Duration = EXPIRE_REFRESH_TOKEN_SECS;
+}
+
+impl Display for SessionIndexKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "SESSION_INDEX_KEY_{}", self.user_id)
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct SessionValue {
+  pub session_id: Uuid,
+  pub user_agent: Option<String>,
+  pub ip: Option<String>,
+  pub create_at: DateTime<Utc>,
+  pub last_seen_at: DateTime<Utc>,
+  // the OAuth client the session was opened for, only it may refresh the tokens
+  #[serde(default)]
+  pub client_id: Option<String>,
+}
+
+/// Holds the `jti` of the only refresh token still valid for a session (token family).
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct RefreshTokenKey {
+  pub user_id: Uuid,
+  pub session_id: Uuid,
+}
+
+impl RedisKey for RefreshTokenKey {
+  type Value = Uuid;
+  const EXPIRE_TIME: Duration = EXPIRE_REFRESH_TOKEN_SECS;
+}
+
+impl Display for RefreshTokenKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "REFRESH_TOKEN_KEY_{}_{}", self.user_id, self.session_id)
+  }
+}
+
+/// Holds the sha256 hash of the pending password reset code.
 #[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
 pub struct ForgetPasswordKey {
   pub user_id: Uuid,
@@ -70,6 +122,331 @@ impl Display for LoginKey {
   }
 }
 
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct TotpEnrollKey {
+  pub user_id: Uuid,
+}
+
+impl RedisKey for TotpEnrollKey {
+  type Value = String;
+  const EXPIRE_TIME: Duration = EXPIRE_TOTP_ENROLL_SECS;
+}
+
+impl Display for TotpEnrollKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "TOTP_ENROLL_KEY_{}", self.user_id)
+  }
+}
+
+/// Present while an email rejected by an admin can not register again.
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct BlockedEmailKey {
+  pub email: String,
+}
+
+impl RedisKey for BlockedEmailKey {
+  type Value = DateTime<Utc>;
+  const EXPIRE_TIME: Duration = EXPIRE_BLOCKED_EMAIL_SECS;
+}
+
+impl Display for BlockedEmailKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "BLOCKED_EMAIL_KEY_{}", self.email.to_lowercase())
+  }
+}
+
+/// Present during the cooldown after an activation code was resent.
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct ActiveResendKey {
+  pub user_id: Uuid,
+}
+
+impl RedisKey for ActiveResendKey {
+  type Value = DateTime<Utc>;
+  const EXPIRE_TIME: Duration = EXPIRE_ACTIVE_RESEND_SECS;
+}
+
+impl Display for ActiveResendKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "ACTIVE_RESEND_KEY_{}", self.user_id)
+  }
+}
+
+/// Holds the id of the only sign-in link of the user that is still valid.
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct MagicLinkKey {
+  pub user_id: Uuid,
+}
+
+impl RedisKey for MagicLinkKey {
+  type Value = Uuid;
+  const EXPIRE_TIME: Duration = EXPIRE_MAGIC_LINK_SECS;
+}
+
+impl Display for MagicLinkKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "MAGIC_LINK_KEY_{}", self.user_id)
+  }
+}
+
+/// Holds the grant an authorization code is exchanged for at the token endpoint.
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct AuthorizationCodeKey {
+  pub code: String,
+}
+
+impl RedisKey for AuthorizationCodeKey {
+  type Value = AuthorizationCodeValue;
+  const EXPIRE_TIME: Duration = EXPIRE_AUTHORIZATION_CODE_SECS;
+}
+
+impl Display for AuthorizationCodeKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "AUTHORIZATION_CODE_KEY_{}", self.code)
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct AuthorizationCodeValue {
+  pub client_id: String,
+  pub user_id: Uuid,
+  pub redirect_uri: String,
+  // space separated scopes granted by the user
+  pub scope: String,
+  pub nonce: Option<String>,
+  pub code_challenge: Option<String>,
+  pub auth_time: i64,
+}
+
+/// Holds a device authorization while the device polls the token endpoint.
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct DeviceCodeKey {
+  pub device_code: String,
+}
+
+impl RedisKey for DeviceCodeKey {
+  type Value = DeviceCodeValue;
+  const EXPIRE_TIME: Duration = EXPIRE_DEVICE_CODE_SECS;
+}
+
+impl Display for DeviceCodeKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "DEVICE_CODE_KEY_{}", self.device_code)
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct DeviceCodeValue {
+  pub client_id: String,
+  pub user_code: String,
+  // space separated scopes, narrowed to the role of the user once approved
+  pub scope: String,
+  pub status: DeviceCodeStatus,
+  // updating the status resets the key expire time, so the deadline is kept here
+  pub expire_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub enum DeviceCodeStatus {
+  Pending,
+  Approved { user_id: Uuid, auth_time: i64 },
+  Denied,
+}
+
+/// Maps the code a user types on the verification page to its device code.
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct UserCodeKey {
+  pub user_code: String,
+}
+
+impl RedisKey for UserCodeKey {
+  type Value = String;
+  const EXPIRE_TIME: Duration = EXPIRE_DEVICE_CODE_SECS;
+}
+
+impl Display for UserCodeKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "USER_CODE_KEY_{}", self.user_code)
+  }
+}
+
+/// Present for the polling interval after a device polled the token endpoint.
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct DevicePollKey {
+  pub device_code: String,
+}
+
+impl RedisKey for DevicePollKey {
+  type Value = DateTime<Utc>;
+  const EXPIRE_TIME: Duration = DEVICE_POLL_INTERVAL_SECS;
+}
+
+impl Display for DevicePollKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "DEVICE_POLL_KEY_{}", self.device_code)
+  }
+}
+
+/// Holds what is needed to finish an authorization started with an identity provider.
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct OidcStateKey {
+  pub state: String,
+}
+
+impl RedisKey for OidcStateKey {
+  type Value = OidcStateValue;
+  const EXPIRE_TIME: Duration = EXPIRE_OIDC_STATE_SECS;
+}
+
+impl Display for OidcStateKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "OIDC_STATE_KEY_{}", self.state)
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct OidcStateValue {
+  pub provider: String,
+  pub nonce: String,
+  pub code_verifier: String,
+  // set when the identity is linked to a signed in user instead of used to sign in
+  pub user_id: Option<Uuid>,
+}
+
+/// A challenge handed to the browser, it is accepted once and only by the ceremony it was issued for.
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct PasskeyChallengeKey {
+  pub challenge: String,
+}
+
+impl RedisKey for PasskeyChallengeKey {
+  type Value = PasskeyCeremony;
+  const EXPIRE_TIME: Duration = EXPIRE_PASSKEY_CHALLENGE_SECS;
+}
+
+impl Display for PasskeyChallengeKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "PASSKEY_CHALLENGE_KEY_{}", self.challenge)
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub enum PasskeyCeremony {
+  Register { user_id: Uuid },
+  // the user id is set when the passkey is the second factor of a password login
+  Login { user_id: Option<Uuid> },
+}
+
+/// Present while a DPoP proof could still be accepted, holds the time it was first used.
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct DpopProofKey {
+  pub jkt: String,
+  pub jti: String,
+}
+
+impl RedisKey for DpopProofKey {
+  type Value = DateTime<Utc>;
+  const EXPIRE_TIME: Duration = EXPIRE_DPOP_PROOF_SECS;
+}
+
+impl Display for DpopProofKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "DPOP_PROOF_KEY_{}_{}", self.jkt, self.jti)
+  }
+}
+
+/// Holds the requested email and the sha256 hash of the code sent to it.
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct EmailChangeKey {
+  pub user_id: Uuid,
+}
+
+impl RedisKey for EmailChangeKey {
+  type Value = EmailChangeValue;
+  const EXPIRE_TIME: Duration = EXPIRE_EMAIL_CHANGE_CODE_SECS;
+}
+
+impl Display for EmailChangeKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "EMAIL_CHANGE_KEY_{}", self.user_id)
+  }
+}
+
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct EmailChangeValue {
+  pub email: String,
+  pub code_hash: String,
+}
+
+/// Counts wrong passwords of a user until the account gets locked.
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct LoginAttemptKey {
+  pub user_id: Uuid,
+}
+
+impl RedisKey for LoginAttemptKey {
+  type Value = u64;
+  const EXPIRE_TIME: Duration = EXPIRE_LOGIN_ATTEMPT_SECS;
+}
+
+impl Display for LoginAttemptKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "LOGIN_ATTEMPT_KEY_{}", self.user_id)
+  }
+}
+
+/// Counts wrong guesses of the pending code of the given kind until it gets invalidated.
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct CodeAttemptKey {
+  pub user_id: Uuid,
+  pub kind: MessageKind,
+}
+
+impl RedisKey for CodeAttemptKey {
+  type Value = u64;
+  const EXPIRE_TIME: Duration = EXPIRE_CODE_ATTEMPT_SECS;
+}
+
+impl Display for CodeAttemptKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "CODE_ATTEMPT_KEY_{}_{}", self.kind, self.user_id)
+  }
+}
+
+/// Counts wrong current passwords or codes given by a signed in user, apart from the failed logins.
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct PasswordCheckAttemptKey {
+  pub user_id: Uuid,
+}
+
+impl RedisKey for PasswordCheckAttemptKey {
+  type Value = u64;
+  const EXPIRE_TIME: Duration = EXPIRE_PASSWORD_CHECK_ATTEMPT_SECS;
+}
+
+impl Display for PasswordCheckAttemptKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "PASSWORD_CHECK_ATTEMPT_KEY_{}", self.user_id)
+  }
+}
+
+/// Present while the account is locked, holds the time it was locked.
+#[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub struct LockoutKey {
+  pub user_id: Uuid,
+}
+
+impl RedisKey for LockoutKey {
+  type Value = DateTime<Utc>;
+  const EXPIRE_TIME: Duration = EXPIRE_LOCKOUT_SECS;
+}
+
+impl Display for LockoutKey {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "LOCKOUT_KEY_{}", self.user_id)
+  }
+}
+
 #[derive(Debug, Serialize, Deserialize, Dummy, Ord, PartialOrd, Eq, PartialEq, Clone)]
 pub struct LoginValue {
   pub code: String,
@@ -98,6 +475,101 @@ where
       .transpose()?,
   )
 }
+/// Sets the value only if the key does not exist yet, returns whether it was set.
+pub async fn set_if_absent<K>(
+  client: &RedisClient,
+  (key, value): (&K, &K::Value),
+) -> AppResult<bool>
+where
+  K: RedisKey,
+{
+  info!("Set value to redis key if absent :{key:?} value :{value:?}");
+  let value = serde_json::to_string(value)?;
+  Ok(
+    client
+      .set_nx(&key.to_string(), &value, K::EXPIRE_TIME)
+      .await?,
+  )
+}
+
+/// Atomically replaces the value of the key only if it still holds `current`.
+pub async fn compare_and_set<K>(
+  client: &RedisClient,
+  key: &K,
+  current: &K::Value,
+  value: &K::Value,
+) -> AppResult<bool>
+where
+  K: RedisKey,
+{
+  info!("Compare and set redis key :{key:?} value :{value:?}");
+  let current = serde_json::to_string(current)?;
+  let value = serde_json::to_string(value)?;
+  Ok(
+    client
+      .set_if_eq(&key.to_string(), &current, &value, K::EXPIRE_TIME)
+      .await?,
+  )
+}
+
+/// Atomically deletes the key only if it still holds `current`.
+pub async fn compare_and_del<K>(
+  client: &RedisClient,
+  key: &K,
+  current: &K::Value,
+) -> AppResult<bool>
+where
+  K: RedisKey,
+{
+  info!("Compare and delete redis key :{key:?}");
+  let current = serde_json::to_string(current)?;
+  Ok(client.del_if_eq(&key.to_string(), &current).await?)
+}
+
+/// Increments a counter key, its expire time starts with the first increment.
+pub async fn incr<K>(client: &RedisClient, key: &K) -> AppResult<u64>
+where
+  K: RedisKey<Value = u64>,
+{
+  info!("Increment redis key :{key:?}");
+  Ok(client.incr_expire(&key.to_string(), K::EXPIRE_TIME).await?)
+}
+
+/// Adds a member to the set of the key and resets its expire time.
+pub async fn add_member<K>(client: &RedisClient, key: &K, member: &K::Value) -> AppResult
+where
+  K: RedisKey,
+{
+  info!("Add member to redis set key :{key:?} member :{member:?}");
+  let member = serde_json::to_string(member)?;
+  client
+    .sadd(&key.to_string(), &member, K::EXPIRE_TIME)
+    .await?;
+  Ok(())
+}
+
+pub async fn remove_member<K>(client: &RedisClient, key: &K, member: &K::Value) -> AppResult<bool>
+where
+  K: RedisKey,
+{
+  info!("Remove member from redis set key :{key:?} member :{member:?}");
+  let member = serde_json::to_string(member)?;
+  Ok(client.srem(&key.to_string(), &member).await?)
+}
+
+pub async fn members<K>(client: &RedisClient, key: &K) -> AppResult<Vec<K::Value>>
+where
+  K: RedisKey,
+{
+  info!("Get members of redis set key :{key:?}");
+  client
+    .smembers(&key.to_string())
+    .await?
+    .iter()
+    .map(|member| Ok(serde_json::from_str::<K::Value>(member)?))
+    .collect()
+}
+
 pub async fn del(client: &RedisClient, key: &impl RedisKey) -> Result<bool, redis::RedisError> {
   info!("Delete key in redis :{key:?}");
   client.del(&key.to_string()).await
@@ -120,7 +592,7 @@ mod tests {
 
   #[tokio::test]
   async fn test_set_and_get_str_redis_service() {
-    let key: SessionKey = Faker.fake();
+    let key: RefreshTokenKey = Faker.fake();
     let value = Uuid::new_v4();
     set(&REDIS, (&key, &value)).await.unwrap();
     let actual_value = get(&REDIS, &key).await.unwrap().unwrap();
@@ -140,6 +612,54 @@ mod tests {
     assert!(actual_value.is_none());
   }
 
+  #[tokio::test]
+  async fn test_compare_and_set_redis_service() {
+    let key: RefreshTokenKey = Faker.fake();
+    let value = Uuid::new_v4();
+    set(&REDIS, (&key, &value)).await.unwrap();
+    let next = Uuid::new_v4();
+    assert!(compare_and_set(&REDIS, &key, &value, &next).await.unwrap());
+    assert!(!compare_and_set(&REDIS, &key, &value, &next).await.unwrap());
+    assert_eq!(get(&REDIS, &key).await.unwrap(), Some(next));
+  }
+
+  #[tokio::test]
+  async fn test_compare_and_del_redis_service() {
+    let key: ForgetPasswordKey = Faker.fake();
+    let value: String = Faker.fake();
+    set(&REDIS, (&key, &value)).await.unwrap();
+    assert!(
+      !compare_and_del(&REDIS, &key, &"other".to_string())
+        .await
+        .unwrap()
+    );
+    assert!(compare_and_del(&REDIS, &key, &value).await.unwrap());
+    assert!(get(&REDIS, &key).await.unwrap().is_none());
+  }
+
+  #[tokio::test]
+  async fn test_incr_redis_service() {
+    let key: CodeAttemptKey = Faker.fake();
+    assert_eq!(incr(&REDIS, &key).await.unwrap(), 1);
+    assert_eq!(incr(&REDIS, &key).await.unwrap(), 2);
+    assert_eq!(get(&REDIS, &key).await.unwrap(), Some(2));
+  }
+
+  #[tokio::test]
+  async fn test_members_redis_service() {
+    let key: SessionIndexKey = Faker.fake();
+    let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+    add_member(&REDIS, &key, &first).await.unwrap();
+    add_member(&REDIS, &key, &second).await.unwrap();
+    let mut values = members(&REDIS, &key).await.unwrap();
+    values.sort();
+    let mut expected = vec![first, second];
+    expected.sort();
+    assert_eq!(values, expected);
+    assert!(remove_member(&REDIS, &key, &first).await.unwrap());
+    assert_eq!(members(&REDIS, &key).await.unwrap(), vec![second]);
+  }
+
   #[tokio::test]
   async fn test_set_and_get_value_redis_service() {
     let key: LoginKey = Faker.fake();
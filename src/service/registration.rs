@@ -0,0 +1,94 @@
+use tracing::info;
+
+use crate::configure::registration::RegistrationConfig;
+use crate::constant::DISPOSABLE_EMAIL_DOMAINS;
+use crate::error::{AppResult, invalid_input_error};
+use crate::server::state::AppState;
+use crate::service;
+use crate::service::redis::BlockedEmailKey;
+
+/// Rejects emails the registration policy does not accept.
+pub async fn check_email(state: &AppState, email: &str) -> AppResult {
+  check_domain(&state.config.registration, email)
+    .map_err(|message| invalid_input_error("email", message))?;
+  let key = BlockedEmailKey {
+    email: email.to_string(),
+  };
+  if service::redis::check_exist_key(&state.redis, &key).await? {
+    info!("Registration of a blocked email: {email}");
+    return Err(invalid_input_error(
+      "email",
+      "Email can not be registered at this time.",
+    ));
+  }
+  Ok(())
+}
+
+fn check_domain(config: &RegistrationConfig, email: &str) -> Result<(), &'static str> {
+  let domain = email
+    .rsplit_once('@')
+    .map(|(_, domain)| domain.trim().to_lowercase())
+    .ok_or("Email is invalid.")?;
+  if !config.allowed_domains.is_empty() && !matches_any(&domain, &config.allowed_domains) {
+    return Err("Email domain is not allowed.");
+  }
+  if matches_any(&domain, &config.denied_domains) {
+    return Err("Email domain is not allowed.");
+  }
+  if config.block_disposable_email && parents(&domain).any(|d| DISPOSABLE_EMAIL_DOMAINS.contains(d))
+  {
+    return Err("Disposable email addresses are not allowed.");
+  }
+  Ok(())
+}
+
+fn matches_any(domain: &str, list: &[String]) -> bool {
+  parents(domain).any(|d| list.iter().any(|l| l.eq_ignore_ascii_case(d)))
+}
+
+// The domain itself followed by every parent domain, e.g. a.b.com, b.com, com.
+fn parents(domain: &str) -> impl Iterator<Item = &str> {
+  std::iter::successors(Some(domain), |d| d.split_once('.').map(|(_, rest)| rest))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::configure::registration::RegistrationMode;
+
+  fn config() -> RegistrationConfig {
+    RegistrationConfig {
+      mode: RegistrationMode::Open,
+      allow_user_invitations: false,
+      allowed_domains: vec![],
+      denied_domains: vec![],
+      block_disposable_email: true,
+      require_approval: false,
+    }
+  }
+
+  #[test]
+  fn test_check_domain_deny_and_allow_lists() {
+    let mut config = config();
+    assert!(check_domain(&config, "user@example.com").is_ok());
+    config.denied_domains = vec!["example.com".to_string()];
+    assert!(check_domain(&config, "user@example.com").is_err());
+    assert!(check_domain(&config, "user@mail.Example.com").is_err());
+    assert!(check_domain(&config, "user@example.org").is_ok());
+    config.denied_domains.clear();
+    config.allowed_domains = vec!["company.com".to_string()];
+    assert!(check_domain(&config, "user@company.com").is_ok());
+    assert!(check_domain(&config, "user@dev.company.com").is_ok());
+    assert!(check_domain(&config, "user@notcompany.com").is_err());
+    assert!(check_domain(&config, "user@example.org").is_err());
+  }
+
+  #[test]
+  fn test_check_domain_disposable_email() {
+    let mut config = config();
+    assert!(check_domain(&config, "user@mailinator.com").is_err());
+    assert!(check_domain(&config, "user@YOPMAIL.com").is_err());
+    config.block_disposable_email = false;
+    assert!(check_domain(&config, "user@mailinator.com").is_ok());
+  }
+}
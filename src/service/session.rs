@@ -1,41 +1,240 @@
-use crate::client::redis::RedisClient;
-use crate::error::{AppError, AppResult};
-use crate::util::claim::UserClaims;
+use chrono::Utc;
 use tracing::info;
 use uuid::Uuid;
 
-use crate::service::redis::SessionKey;
+use crate::client::redis::RedisClient;
+use crate::error::{AppError, AppResult};
+use crate::service::redis::{RefreshTokenKey, SessionIndexKey, SessionKey, SessionValue};
+use crate::util::claim::{Confirmation, UserClaims};
+use crate::util::client_info::ClientInfo;
 
 pub async fn check(redis: &RedisClient, claims: &UserClaims) -> AppResult<Uuid> {
   let session_key = SessionKey {
     user_id: claims.uid,
+    session_id: claims.sid,
+  };
+  let mut session = crate::service::redis::get(redis, &session_key)
+    .await?
+    .ok_or_else(|| not_found(claims.sid))?;
+  session.last_seen_at = Utc::now();
+  crate::service::redis::set(redis, (&session_key, &session)).await?;
+  Ok(claims.uid)
+}
+
+/// Identifiers of a session and of the refresh token currently valid for it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionIds {
+  pub session_id: Uuid,
+  pub refresh_id: Uuid,
+  // the DPoP key or client certificate the tokens of the session are bound to
+  pub cnf: Option<Confirmation>,
+}
+
+/// Opens a session, `client_id` is the OAuth client it is opened for, if any.
+pub async fn set(
+  redis: &RedisClient,
+  user_id: Uuid,
+  client: ClientInfo,
+  client_id: Option<String>,
+) -> AppResult<SessionIds> {
+  let jkt = crate::service::dpop::bind(redis, client.dpop.as_ref()).await?;
+  let cnf = Confirmation::new(jkt, client.certificate.clone());
+  let (key, value) = generate(user_id, client, client_id);
+  crate::service::redis::set(redis, (&key, &value)).await?;
+  let refresh_id = Uuid::new_v4();
+  let refresh_key = RefreshTokenKey {
+    user_id,
+    session_id: value.session_id,
+  };
+  crate::service::redis::set(redis, (&refresh_key, &refresh_id)).await?;
+  crate::service::redis::add_member(redis, &SessionIndexKey { user_id }, &value.session_id).await?;
+  Ok(SessionIds {
+    session_id: value.session_id,
+    refresh_id,
+    cnf,
+  })
+}
+
+pub fn generate(
+  user_id: Uuid,
+  client: ClientInfo,
+  client_id: Option<String>,
+) -> (SessionKey, SessionValue) {
+  let now = Utc::now();
+  let session_id = Uuid::new_v4();
+  let key = SessionKey {
+    user_id,
+    session_id,
+  };
+  let value = SessionValue {
+    session_id,
+    user_agent: client.user_agent,
+    ip: client.ip,
+    create_at: now,
+    last_seen_at: now,
+    client_id,
+  };
+  (key, value)
+}
+
+/// Checks the session presented by `claims` was opened for the OAuth client, `None`
+/// being a sign in to the API itself.
+pub async fn check_client(
+  redis: &RedisClient,
+  claims: &UserClaims,
+  client_id: Option<&str>,
+) -> AppResult {
+  let session_key = SessionKey {
+    user_id: claims.uid,
+    session_id: claims.sid,
+  };
+  let session = crate::service::redis::get(redis, &session_key)
+    .await?
+    .ok_or_else(|| not_found(claims.sid))?;
+  if session.client_id.as_deref() != client_id {
+    return Err(AppError::UnauthorizedError(
+      "The token was issued to another client.".to_string(),
+    ));
+  }
+  Ok(())
+}
+
+/// Rotates the refresh token of the session presented by `claims` and returns the new token id.
+/// A refresh token that was already rotated revokes the whole session.
+pub async fn rotate(redis: &RedisClient, claims: &UserClaims) -> AppResult<SessionIds> {
+  check(redis, claims).await?;
+  let refresh_key = RefreshTokenKey {
+    user_id: claims.uid,
+    session_id: claims.sid,
   };
-  let session_id = crate::service::redis::get(redis, &session_key)
+  let current = crate::service::redis::get(redis, &refresh_key)
     .await?
-    .ok_or_else(|| {
-      AppError::NotFoundError(crate::error::Resource {
-        details: vec![("session_key".to_string(), claims.sid.to_string())],
-        resource_type: crate::error::ResourceType::Session,
-      })
-    })?;
-  if claims.sid != session_id {
-    info!("Session id invalid so deleting it: {session_key:?}.");
-    crate::service::redis::del(redis, &session_key).await?;
+    .ok_or_else(|| not_found(claims.sid))?;
+  let refresh_id = Uuid::new_v4();
+  if current != claims.jti
+    || !crate::service::redis::compare_and_set(redis, &refresh_key, &claims.jti, &refresh_id)
+      .await?
+  {
+    info!("Refresh token reused so revoking session: {refresh_key:?}.");
+    revoke(redis, claims.uid, claims.sid).await?;
     return Err(AppError::InvalidSessionError(
       "Session is Invalid".to_string(),
     ));
   }
-  Ok(claims.uid)
+  let index_key = SessionIndexKey {
+    user_id: claims.uid,
+  };
+  crate::service::redis::add_member(redis, &index_key, &claims.sid).await?;
+  Ok(SessionIds {
+    session_id: claims.sid,
+    refresh_id,
+    cnf: claims.cnf.clone(),
+  })
 }
 
-pub async fn set(redis: &RedisClient, user_id: Uuid) -> AppResult<Uuid> {
-  let (key, value) = generate(user_id);
-  crate::service::redis::set(redis, (&key, &value)).await?;
-  Ok(value)
+pub async fn list(redis: &RedisClient, user_id: Uuid) -> AppResult<Vec<SessionValue>> {
+  let index_key = SessionIndexKey { user_id };
+  let mut sessions = vec![];
+  for session_id in crate::service::redis::members(redis, &index_key).await? {
+    let key = SessionKey {
+      user_id,
+      session_id,
+    };
+    match crate::service::redis::get(redis, &key).await? {
+      Some(session) => sessions.push(session),
+      // the session expired without being revoked
+      None => {
+        crate::service::redis::remove_member(redis, &index_key, &session_id).await?;
+      }
+    }
+  }
+  sessions.sort_by_key(|s| std::cmp::Reverse(s.last_seen_at));
+  Ok(sessions)
 }
 
-pub fn generate(user_id: Uuid) -> (SessionKey, Uuid) {
-  let session_id = Uuid::new_v4();
-  let key = SessionKey { user_id };
-  (key, session_id)
+pub async fn revoke(redis: &RedisClient, user_id: Uuid, session_id: Uuid) -> AppResult {
+  let key = SessionKey {
+    user_id,
+    session_id,
+  };
+  let refresh_key = RefreshTokenKey {
+    user_id,
+    session_id,
+  };
+  crate::service::redis::del(redis, &refresh_key).await?;
+  crate::service::redis::remove_member(redis, &SessionIndexKey { user_id }, &session_id).await?;
+  if !crate::service::redis::del(redis, &key).await? {
+    return Err(not_found(session_id));
+  }
+  Ok(())
+}
+
+/// Revokes every session of the user except `keep`, returns the number of revoked sessions.
+pub async fn revoke_all(redis: &RedisClient, user_id: Uuid, keep: Option<Uuid>) -> AppResult<u64> {
+  let mut count = 0;
+  for session in list(redis, user_id).await? {
+    if Some(session.session_id) == keep {
+      continue;
+    }
+    if revoke(redis, user_id, session.session_id).await.is_ok() {
+      count += 1;
+    }
+  }
+  Ok(count)
+}
+
+fn not_found(session_id: Uuid) -> AppError {
+  AppError::NotFoundError(crate::error::Resource {
+    details: vec![("session_key".to_string(), session_id.to_string())],
+    resource_type: crate::error::ResourceType::Session,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use fake::{Fake, Faker};
+
+  use super::*;
+  use crate::constant::EXPIRE_REFRESH_TOKEN_SECS;
+  use crate::constant::REDIS;
+  use crate::entity::role::RoleUser;
+
+  #[tokio::test]
+  async fn test_multiple_sessions_and_revoke_all() {
+    let user_id = Uuid::new_v4();
+    let first = set(&REDIS, user_id, Faker.fake(), None)
+      .await
+      .unwrap()
+      .session_id;
+    let second = set(&REDIS, user_id, Faker.fake(), None)
+      .await
+      .unwrap()
+      .session_id;
+    assert_eq!(list(&REDIS, user_id).await.unwrap().len(), 2);
+    let count = revoke_all(&REDIS, user_id, Some(first)).await.unwrap();
+    assert_eq!(count, 1);
+    let sessions = list(&REDIS, user_id).await.unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].session_id, first);
+    assert!(revoke(&REDIS, user_id, second).await.is_err());
+  }
+
+  #[tokio::test]
+  async fn test_rotate_refresh_token_reuse() {
+    let user_id = Uuid::new_v4();
+    let ids = set(&REDIS, user_id, Faker.fake(), None).await.unwrap();
+    let claims = UserClaims::new(
+      EXPIRE_REFRESH_TOKEN_SECS,
+      user_id,
+      ids.session_id,
+      RoleUser::User,
+    )
+    .with_jti(ids.refresh_id);
+    let next = rotate(&REDIS, &claims).await.unwrap();
+    assert_eq!(next.session_id, ids.session_id);
+    assert_ne!(next.refresh_id, ids.refresh_id);
+    let result = rotate(&REDIS, &claims).await;
+    assert!(matches!(result, Err(AppError::InvalidSessionError(_))));
+    assert!(list(&REDIS, user_id).await.unwrap().is_empty());
+  }
 }
@@ -2,10 +2,14 @@ use crate::constant::*;
 use crate::dto::response::TokenResponse;
 use crate::dto::{RefreshTokenRequest, TokenInfoRequest};
 use crate::entity::role::RoleUser;
+use crate::entity::scope::Scope;
+use crate::entity::security_event::SecurityEventKind;
 use crate::error::{AppError, AppResult, ToAppResult};
 use crate::server::state::AppState;
 use crate::service;
+use crate::service::session::SessionIds;
 use crate::util::claim::UserClaims;
+use crate::util::client_info::ClientInfo;
 use tracing::info;
 use uuid::Uuid;
 
@@ -20,37 +24,95 @@ pub async fn info(
       "This user does not have permission to use this resource.".to_string(),
     ));
   }
-  let token_data = UserClaims::decode(&req.token, &ACCESS_TOKEN_DECODE_KEY)?;
+  let token_data = UserClaims::decode(&req.token, &ACCESS_TOKEN_KEY_RING)?;
   service::session::check(&state.redis, &token_data.claims).await?;
   Ok(token_data.claims)
 }
 
-pub async fn refresh(state: &AppState, req: RefreshTokenRequest) -> AppResult<TokenResponse> {
-  let user_claims = UserClaims::decode(&req.token, &REFRESH_TOKEN_DECODE_KEY)?.claims;
+/// Rotates the refresh token, `client_id` is the OAuth client refreshing it or `None`
+/// for the API itself, it must be the one the session was opened for.
+pub async fn refresh(
+  state: &AppState,
+  client: ClientInfo,
+  client_id: Option<&str>,
+  req: RefreshTokenRequest,
+) -> AppResult<TokenResponse> {
+  let user_claims = UserClaims::decode(&req.token, &REFRESH_TOKEN_KEY_RING)?.claims;
   info!("Refresh token: {user_claims:?}");
-  let user_id = service::session::check(&state.redis, &user_claims).await?;
-  let user = crate::repo::user::find_by_id(&*state.db, user_id)
+  // checked before the rotation, so a stolen token without its key cannot revoke the session
+  if let Some(cnf) = &user_claims.cnf {
+    if let Some(x5t_s256) = &cnf.x5t_s256
+      && client.certificate.as_ref() != Some(x5t_s256)
+    {
+      return Err(AppError::UnauthorizedError(
+        "The token is bound to another client certificate.".to_string(),
+      ));
+    }
+    if let Some(jkt) = &cnf.jkt {
+      service::dpop::check(&state.redis, client.dpop.as_ref(), jkt, None).await?;
+    }
+  }
+  service::session::check_client(&state.redis, &user_claims, client_id).await?;
+  let ids = match service::session::rotate(&state.redis, &user_claims).await {
+    Ok(ids) => ids,
+    Err(AppError::InvalidSessionError(msg)) => {
+      crate::repo::security_event::save(
+        &*state.db,
+        user_claims.uid,
+        SecurityEventKind::RefreshTokenReuse,
+        Some(user_claims.sid),
+        client,
+      )
+      .await?;
+      return Err(AppError::InvalidSessionError(msg));
+    }
+    Err(e) => return Err(e),
+  };
+  let user = crate::repo::user::find_by_id(&*state.db, user_claims.uid)
     .await?
     .to_result()?;
-  let session_id = service::session::set(&state.redis, user.id).await?;
-  info!("Set new session for user: {}", user.id);
-  let resp = generate_tokens(user.id, user.role, session_id)?;
+  info!("Rotate refresh token for user: {}", user.id);
+  // Keep the scope granted at login, less any the user lost with a role change.
+  let role_scope = Scope::for_role(user.role);
+  let scope = user_claims
+    .scope
+    .iter()
+    .copied()
+    .filter(|s| role_scope.contains(s))
+    .collect();
+  let resp = generate_scoped_tokens(user.id, user.role, ids, scope)?;
   info!("Refresh token success: {user_claims:?}");
   Ok(resp)
 }
 
-pub fn generate_tokens(
+pub fn generate_tokens(user_id: Uuid, role: RoleUser, ids: SessionIds) -> AppResult<TokenResponse> {
+  generate_scoped_tokens(user_id, role, ids, Scope::for_role(role))
+}
+
+/// Tokens limited to `scope`, which is kept when the refresh token is used. Both
+/// tokens are bound to the DPoP key or client certificate of the session, if it has one.
+pub fn generate_scoped_tokens(
   user_id: Uuid,
   role: RoleUser,
-  session_id: Uuid,
+  ids: SessionIds,
+  scope: Vec<Scope>,
 ) -> AppResult<TokenResponse> {
-  let access_token = UserClaims::new(EXPIRE_BEARER_TOKEN_SECS, user_id, session_id, role)
-    .encode(&ACCESS_TOKEN_ENCODE_KEY)?;
-  let refresh_token = UserClaims::new(EXPIRE_REFRESH_TOKEN_SECS, user_id, session_id, role)
-    .encode(&REFRESH_TOKEN_ENCODE_KEY)?;
-  Ok(TokenResponse::new(
+  let access_token = UserClaims::new(EXPIRE_BEARER_TOKEN_SECS, user_id, ids.session_id, role)
+    .with_scope(scope.clone())
+    .with_cnf(ids.cnf.clone())
+    .encode(&ACCESS_TOKEN_KEY_RING)?;
+  let refresh_token = UserClaims::new(EXPIRE_REFRESH_TOKEN_SECS, user_id, ids.session_id, role)
+    .with_jti(ids.refresh_id)
+    .with_scope(scope)
+    .with_cnf(ids.cnf.clone())
+    .encode(&REFRESH_TOKEN_KEY_RING)?;
+  let mut resp = TokenResponse::new(
     access_token,
     refresh_token,
     EXPIRE_BEARER_TOKEN_SECS.as_secs(),
-  ))
+  );
+  if ids.cnf.as_ref().is_some_and(|cnf| cnf.jkt.is_some()) {
+    resp.token_type = DPOP_SCHEME.to_string();
+  }
+  Ok(resp)
 }
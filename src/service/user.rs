@@ -1,3 +1,4 @@
+use chrono::Utc;
 use sea_orm::ActiveModelTrait;
 use sea_orm::DatabaseTransaction;
 use sea_orm::Set;
@@ -5,29 +6,86 @@ use sea_orm::TransactionTrait;
 use tracing::info;
 use uuid::Uuid;
 
+use crate::configure::registration::RegistrationMode;
+use crate::constant::CHECK_AUTHENTICATOR_MESSAGE;
 use crate::constant::CHECK_EMAIL_MESSAGE;
 use crate::constant::CODE_LEN;
+use crate::constant::DUMMY_PASSWORD_HASH;
+use crate::constant::EXPIRE_ACTIVE_CODE_SECS;
+use crate::constant::EXPIRE_EMAIL_CHANGE_CODE_SECS;
 use crate::constant::EXPIRE_FORGET_PASS_CODE_SECS;
+use crate::constant::EXPIRE_LOCKOUT_SECS;
+use crate::constant::EXPIRE_TOTP_ENROLL_SECS;
 use crate::constant::EXPIRE_TWO_FACTOR_CODE_SECS;
+use crate::constant::TOTP_ENCRYPT_KEY;
 use crate::dto::*;
 use crate::entity;
 use crate::entity::message::MessageKind;
+use crate::entity::role::RoleUser;
+use crate::entity::two_factor::TwoFactorMethod;
+use crate::error::AppError;
 use crate::error::AppResult;
 use crate::error::ToAppResult;
 use crate::error::invalid_input_error;
 use crate::repo;
 use crate::server::state::AppState;
 use crate::service;
+use crate::service::redis::ActiveResendKey;
+use crate::service::redis::EmailChangeKey;
+use crate::service::redis::EmailChangeValue;
 use crate::service::redis::ForgetPasswordKey;
 use crate::service::redis::LoginKey;
-use crate::service::redis::SessionKey;
+use crate::service::redis::TotpEnrollKey;
 use crate::util;
+use crate::util::claim::UserClaims;
+use crate::util::client_info::ClientInfo;
 
 pub async fn register(state: AppState, req: RegisterRequest) -> AppResult<Uuid> {
   info!("Register a new user request: {req:?}.");
+  match state.config.registration.mode {
+    RegistrationMode::Closed => {
+      return Err(AppError::PermissionDeniedError(
+        "Registration is closed.".to_string(),
+      ));
+    }
+    RegistrationMode::InviteOnly if req.invitation_code.is_none() => {
+      return Err(invalid_input_error(
+        "invitation_code",
+        "An invitation code is required.",
+      ));
+    }
+    _ => {}
+  }
+  service::registration::check_email(&state, &req.email).await?;
   let tx = state.db.begin().await?;
-  check_unique_username_or_email(&tx, &req.username, &req.email).await?;
-  let user_id = crate::repo::user::save(&tx, req.username, req.password, req.email).await?;
+  if state.config.privacy.enable {
+    repo::user::check_unique_by_username(&tx, &req.username).await?;
+    if let Some(user) = repo::user::find_by_email(&tx, &req.email).await? {
+      // Tell the owner of the email instead of the caller and answer like a new registration.
+      util::password::hash(req.password).await?;
+      repo::message::save(&tx, user.id, String::new(), MessageKind::AccountExists).await?;
+      tx.commit().await?;
+      state.messenger_notify.notify_one();
+      return Ok(Uuid::new_v4());
+    }
+  } else {
+    check_unique_username_or_email(&tx, &req.username, &req.email).await?;
+  }
+  let role = match req.invitation_code.as_deref() {
+    Some(code) => service::invitation::redeem(&tx, code, &req.email).await?,
+    None => RoleUser::User,
+  };
+  // Invited users were already vetted by whoever invited them.
+  let is_approved = !state.config.registration.require_approval || req.invitation_code.is_some();
+  let user_id = crate::repo::user::save(
+    &tx,
+    req.username,
+    req.password,
+    req.email,
+    role,
+    is_approved,
+  )
+  .await?;
   let code = generate_active_code();
   repo::message::save(&tx, user_id, code, MessageKind::ActiveCode).await?;
   tx.commit().await?;
@@ -51,119 +109,589 @@ pub async fn active(state: &AppState, req: ActiveRequest) -> AppResult {
     crate::repo::message::find_by_user_and_kind(&tx, req.user_id, MessageKind::ActiveCode)
       .await?
       .to_result()?;
+  if (Utc::now() - message.create_at)
+    .to_std()
+    .is_ok_and(|age| age > EXPIRE_ACTIVE_CODE_SECS)
+  {
+    return Err(invalid_input_error(
+      "code",
+      "Code is expired, please request a new code.",
+    ));
+  }
   if message.content != req.code {
+    if service::lockout::record_failed_code(&state.redis, user.id, MessageKind::ActiveCode).await? {
+      repo::message::save(
+        &tx,
+        user.id,
+        generate_active_code(),
+        MessageKind::ActiveCode,
+      )
+      .await?;
+      tx.commit().await?;
+      state.messenger_notify.notify_one();
+      return Err(invalid_input_error(
+        "code",
+        "Too many invalid codes, a new code has been sent.",
+      ));
+    }
     return Err(invalid_input_error("code", "Code is Invalid"));
   }
   crate::repo::user::active(&tx, user).await?;
   tx.commit().await?;
+  service::lockout::reset_failed_code(&state.redis, req.user_id, MessageKind::ActiveCode).await?;
   Ok(())
 }
 
-pub async fn login(state: &AppState, req: LoginRequest) -> AppResult<LoginResponse> {
+/// Sends a new activation code, at most once per cooldown period.
+pub async fn resend_active_code(state: &AppState, req: ResendActiveCodeRequest) -> AppResult {
+  info!("Resend active code request: {req:?}");
+  let privacy = state.config.privacy.enable;
+  let user = repo::user::find_by_email(&*state.db, &req.email).await?;
+  let user = match user {
+    Some(user) if !user.is_active => user,
+    _ if privacy => return Ok(()),
+    Some(_) => {
+      return Err(AppError::BadRequestError(
+        "User is already active.".to_string(),
+      ));
+    }
+    None => {
+      return Err(AppError::NotFoundError(crate::error::Resource {
+        details: vec![],
+        resource_type: crate::error::ResourceType::User,
+      }));
+    }
+  };
+  let key = ActiveResendKey { user_id: user.id };
+  let ttl = service::redis::get_tll(&state.redis, &key).await?;
+  if ttl > 0 {
+    return if privacy {
+      Ok(())
+    } else {
+      Err(AppError::TooManyRequestsError(ttl as u64))
+    };
+  }
+  service::redis::set(&state.redis, (&key, &Utc::now())).await?;
+  repo::message::save(
+    &*state.db,
+    user.id,
+    generate_active_code(),
+    MessageKind::ActiveCode,
+  )
+  .await?;
+  service::lockout::reset_failed_code(&state.redis, user.id, MessageKind::ActiveCode).await?;
+  state.messenger_notify.notify_one();
+  Ok(())
+}
+
+pub async fn login(
+  state: &AppState,
+  client: ClientInfo,
+  req: LoginRequest,
+) -> AppResult<LoginResponse> {
   info!("User login request :{req:?}.");
-  let user = crate::repo::user::find_by_email_and_status(&state.db, &req.email, true)
-    .await?
-    .to_result()?;
-  util::password::verify(req.password.clone(), user.password.clone()).await?;
-  if user.is_2fa {
-    let key = LoginKey { user_id: user.id };
-    let ttl = service::redis::get_tll(&state.redis, &key).await?;
-    if ttl > 0 {
-      return Ok(LoginResponse::Code {
-        expire_in: ttl as u64,
-        message: CHECK_EMAIL_MESSAGE.to_string(),
-      });
+  let user = authenticate(state, &req.email, req.password).await?;
+  complete_login(state, client, user).await
+}
+
+/// Checks the password of an active user, counting failures toward a lockout.
+pub(crate) async fn authenticate(
+  state: &AppState,
+  email: &str,
+  password: String,
+) -> AppResult<entity::user::Model> {
+  let privacy = state.config.privacy.enable;
+  let user = crate::repo::user::find_by_email_and_status(&state.db, email, true).await?;
+  if user.is_none() && privacy {
+    // Spend the same time a wrong password would take.
+    let _ = util::password::verify(password, DUMMY_PASSWORD_HASH.clone()).await;
+    return Err(invalid_credentials_error());
+  }
+  let user = user.to_result()?;
+  if let Err(e) = service::lockout::check(&state.redis, user.id).await {
+    if !privacy {
+      return Err(e);
     }
-    let login_code = util::random::generate_random_string(CODE_LEN);
-    crate::repo::message::save(
-      &*state.db,
-      user.id,
-      login_code.clone(),
-      MessageKind::LoginCode,
-    )
-    .await?;
-    crate::service::redis::set(&state.redis, (&key, &login_code)).await?;
-    state.messenger_notify.notify_one();
-    return Ok(LoginResponse::Code {
-      expire_in: EXPIRE_TWO_FACTOR_CODE_SECS.as_secs(),
-      message: CHECK_EMAIL_MESSAGE.to_string(),
+    // Only an existing account can be locked, so it fails like an unknown one.
+    let _ = util::password::verify(password, DUMMY_PASSWORD_HASH.clone()).await;
+    return Err(invalid_credentials_error());
+  }
+  if let Err(e) = util::password::verify(password, user.password.clone()).await {
+    let locked = service::lockout::record_failed_login(state, user.id).await?;
+    return Err(match (privacy, locked) {
+      (true, _) => invalid_credentials_error(),
+      (false, true) => AppError::UserLockedError(EXPIRE_LOCKOUT_SECS.as_secs()),
+      (false, false) => e,
     });
   }
-  let session_id = service::session::set(&state.redis, user.id).await?;
-  let resp = service::token::generate_tokens(user.id, user.role, session_id)?;
+  service::lockout::reset_failed_login(&state.redis, user.id).await?;
+  Ok(user)
+}
+
+/// Starts the second factor when enabled, otherwise opens a session for the authenticated user.
+pub(crate) async fn complete_login(
+  state: &AppState,
+  client: ClientInfo,
+  user: entity::user::Model,
+) -> AppResult<LoginResponse> {
+  if user.is_approved && user.is_2fa {
+    return start_second_factor(state, &user).await;
+  }
+  let resp = open_session(state, client, user).await?;
   Ok(LoginResponse::Token(resp))
 }
 
-pub async fn login2fa(state: &AppState, req: Login2faRequest) -> AppResult<TokenResponse> {
+/// Opens a session for a user who passed every factor the login asks for.
+pub(crate) async fn open_session(
+  state: &AppState,
+  client: ClientInfo,
+  user: entity::user::Model,
+) -> AppResult<TokenResponse> {
+  if !user.is_approved {
+    return Err(AppError::PermissionDeniedError(
+      "The account is waiting for approval.".to_string(),
+    ));
+  }
+  let ids = service::session::set(&state.redis, user.id, client, None).await?;
+  service::token::generate_tokens(user.id, user.role, ids)
+}
+
+/// Issues a login code, it is emailed unless the user has an authenticator app.
+pub(crate) async fn start_second_factor(
+  state: &AppState,
+  user: &entity::user::Model,
+) -> AppResult<LoginResponse> {
+  let message = if use_totp(user) {
+    CHECK_AUTHENTICATOR_MESSAGE
+  } else {
+    CHECK_EMAIL_MESSAGE
+  };
+  let key = LoginKey { user_id: user.id };
+  let ttl = service::redis::get_tll(&state.redis, &key).await?;
+  if ttl > 0 {
+    return Ok(LoginResponse::Code {
+      expire_in: ttl as u64,
+      message: message.to_string(),
+    });
+  }
+  let login_code = util::random::generate_random_string(CODE_LEN);
+  crate::service::redis::set(&state.redis, (&key, &login_code)).await?;
+  service::lockout::reset_failed_code(&state.redis, user.id, MessageKind::LoginCode).await?;
+  if !use_totp(user) {
+    send_login_code(state, user.id, login_code).await?;
+  }
+  Ok(LoginResponse::Code {
+    expire_in: EXPIRE_TWO_FACTOR_CODE_SECS.as_secs(),
+    message: message.to_string(),
+  })
+}
+
+fn invalid_credentials_error() -> AppError {
+  AppError::UnauthorizedError("The email or password is not correct.".to_string())
+}
+
+fn use_totp(user: &entity::user::Model) -> bool {
+  user.two_factor_method == TwoFactorMethod::Totp && user.totp_secret.is_some()
+}
+
+async fn send_login_code(state: &AppState, user_id: Uuid, code: String) -> AppResult {
+  crate::repo::message::save(&*state.db, user_id, code, MessageKind::LoginCode).await?;
+  state.messenger_notify.notify_one();
+  Ok(())
+}
+
+pub async fn login2fa(
+  state: &AppState,
+  client: ClientInfo,
+  req: Login2faRequest,
+) -> AppResult<TokenResponse> {
   info!("User two factor login request: {req:?}");
   let key = LoginKey {
     user_id: req.user_id,
   };
-  let code = service::redis::get(&state.redis, &key).await?;
-  if code != Some(req.code) {
+  if !service::redis::check_exist_key(&state.redis, &key).await? {
     return Err(invalid_input_error("code", "Code is invalid."));
   }
   let user = crate::repo::user::find_by_id(&*state.db, req.user_id)
     .await?
     .to_result()?;
-  let session_id = service::session::set(&state.redis, user.id).await?;
-  service::token::generate_tokens(req.user_id, user.role, session_id)
+  match (req.passkey, req.code) {
+    (Some(credential), _) => {
+      service::passkey::verify_second_factor(state, &user, credential).await?
+    }
+    (None, Some(code)) => verify_second_factor(state, &user, &code).await?,
+    (None, None) => return Err(invalid_input_error("code", "Code is required.")),
+  }
+  let ids = service::session::set(&state.redis, user.id, client, None).await?;
+  service::token::generate_tokens(req.user_id, user.role, ids)
+}
+
+/// Accepts the pending login code, a code of the authenticator app or a recovery code.
+pub(crate) async fn verify_second_factor(
+  state: &AppState,
+  user: &entity::user::Model,
+  code: &str,
+) -> AppResult {
+  let key = LoginKey { user_id: user.id };
+  let Some(login_code) = service::redis::get(&state.redis, &key).await? else {
+    return Err(invalid_input_error("code", "Code is invalid."));
+  };
+  if login_code != code
+    && !verify_totp(user, code)?
+    && !service::recovery_code::consume(state, user.id, code).await?
+  {
+    if service::lockout::record_failed_code(&state.redis, user.id, MessageKind::LoginCode).await? {
+      service::redis::del(&state.redis, &key).await?;
+      return Err(invalid_input_error(
+        "code",
+        "Too many invalid codes, please login again.",
+      ));
+    }
+    return Err(invalid_input_error("code", "Code is invalid."));
+  }
+  service::redis::del(&state.redis, &key).await?;
+  service::lockout::reset_failed_code(&state.redis, user.id, MessageKind::LoginCode).await?;
+  Ok(())
+}
+
+fn verify_totp(user: &entity::user::Model, code: &str) -> AppResult<bool> {
+  match user.totp_secret.as_ref() {
+    Some(secret) if use_totp(user) => {
+      let secret = util::cipher::decrypt(&TOTP_ENCRYPT_KEY, secret)?;
+      util::totp::verify_code(&secret, code)
+    }
+    _ => Ok(false),
+  }
+}
+
+pub async fn login2fa_email(
+  state: &AppState,
+  req: Login2faEmailRequest,
+) -> AppResult<LoginResponse> {
+  info!("User two factor email fallback request: {req:?}");
+  let key = LoginKey {
+    user_id: req.user_id,
+  };
+  let ttl = service::redis::get_tll(&state.redis, &key).await?;
+  let Some(code) = service::redis::get(&state.redis, &key).await? else {
+    return Err(AppError::InvalidSessionError(
+      "There is no pending two factor login.".to_string(),
+    ));
+  };
+  send_login_code(state, req.user_id, code).await?;
+  Ok(LoginResponse::Code {
+    expire_in: ttl.max(0) as u64,
+    message: CHECK_EMAIL_MESSAGE.to_string(),
+  })
+}
+
+pub async fn enroll_totp(state: &AppState, user_id: Uuid) -> AppResult<TotpEnrollResponse> {
+  info!("Enroll totp user id: {user_id}");
+  let user = crate::repo::user::find_by_id(&*state.db, user_id)
+    .await?
+    .to_result()?;
+  let secret = util::totp::generate_secret();
+  let encrypted = util::cipher::encrypt(&TOTP_ENCRYPT_KEY, &secret)?;
+  service::redis::set(&state.redis, (&TotpEnrollKey { user_id }, &encrypted)).await?;
+  Ok(TotpEnrollResponse {
+    uri: util::totp::generate_uri(&secret, &user.email),
+    secret,
+    expire_in: EXPIRE_TOTP_ENROLL_SECS.as_secs(),
+  })
 }
 
-pub async fn logout(state: &AppState, user_id: Uuid) -> AppResult {
-  info!("Logout user id: {user_id}");
-  let key = SessionKey { user_id };
+pub async fn confirm_totp(
+  state: &AppState,
+  user_id: Uuid,
+  req: ConfirmTotpRequest,
+) -> AppResult<Option<Vec<String>>> {
+  info!("Confirm totp user id: {user_id}");
+  let key = TotpEnrollKey { user_id };
+  let Some(encrypted) = service::redis::get(&state.redis, &key).await? else {
+    return Err(invalid_input_error(
+      "code",
+      "There is no pending enrollment.",
+    ));
+  };
+  let secret = util::cipher::decrypt(&TOTP_ENCRYPT_KEY, &encrypted)?;
+  if !util::totp::verify_code(&secret, &req.code)? {
+    return Err(invalid_input_error("code", "Code is invalid."));
+  }
+  let tx = state.db.begin().await?;
+  let model = repo::user::find_by_id(&tx, user_id).await?.to_result()?;
+  let recovery_codes = if model.is_2fa {
+    None
+  } else {
+    Some(service::recovery_code::generate(&tx, user_id).await?)
+  };
+  let mut user: entity::user::ActiveModel = model.into();
+  user.totp_secret = Set(Some(encrypted));
+  user.two_factor_method = Set(TwoFactorMethod::Totp);
+  user.is_2fa = Set(true);
+  user.update(&tx).await?;
+  tx.commit().await?;
   service::redis::del(&state.redis, &key).await?;
+  Ok(recovery_codes)
+}
+
+/// Removes the authenticator app once a code of it or a recovery code proves the
+/// caller holds the factor, a stolen token alone does not downgrade it.
+pub async fn disable_totp(state: &AppState, user_id: Uuid, req: DisableTotpRequest) -> AppResult {
+  info!("Disable totp user id: {user_id}");
+  let model = repo::user::find_by_id(&*state.db, user_id)
+    .await?
+    .to_result()?;
+  if !use_totp(&model) {
+    return Err(invalid_input_error(
+      "code",
+      "Authenticator app is not enabled.",
+    ));
+  }
+  service::lockout::check_password_attempts(&state.redis, user_id).await?;
+  if !verify_totp(&model, &req.code)?
+    && !service::recovery_code::consume(state, user_id, &req.code).await?
+  {
+    service::lockout::record_failed_password_check(&state.redis, user_id).await?;
+    return Err(invalid_input_error("code", "Code is invalid."));
+  }
+  service::lockout::reset_failed_password_check(&state.redis, user_id).await?;
+  let tx = state.db.begin().await?;
+  let mut user: entity::user::ActiveModel = model.into();
+  user.totp_secret = Set(None);
+  user.two_factor_method = Set(TwoFactorMethod::Email);
+  user.update(&tx).await?;
+  tx.commit().await?;
   Ok(())
 }
 
+pub async fn logout(state: &AppState, user: &UserClaims) -> AppResult {
+  info!("Logout user id: {} session id: {}", user.uid, user.sid);
+  service::session::revoke(&state.redis, user.uid, current_session(user)?).await
+}
+
+pub async fn list_sessions(state: &AppState, user: &UserClaims) -> AppResult<SessionListResponse> {
+  info!("List sessions user id: {}", user.uid);
+  let list = service::session::list(&state.redis, user.uid)
+    .await?
+    .into_iter()
+    .map(|session| SessionResponse::new(session, user.sid))
+    .collect();
+  Ok(SessionListResponse { list })
+}
+
+pub async fn revoke_session(state: &AppState, user_id: Uuid, session_id: Uuid) -> AppResult {
+  info!("Revoke session user id: {user_id} session id: {session_id}");
+  service::session::revoke(&state.redis, user_id, session_id).await
+}
+
+pub async fn revoke_other_sessions(state: &AppState, user: &UserClaims) -> AppResult<u64> {
+  info!("Revoke other sessions user id: {}", user.uid);
+  service::session::revoke_all(&state.redis, user.uid, Some(current_session(user)?)).await
+}
+
+fn current_session(user: &UserClaims) -> AppResult<Uuid> {
+  user
+    .session_id()
+    .ok_or_else(|| AppError::BadRequestError("The request is not made in a session.".to_string()))
+}
+
 pub async fn forget_password(
   state: &AppState,
   req: ForgetPasswordQueryParam,
 ) -> AppResult<ForgetPasswordResponse> {
   info!("Forget password request: {req:?}");
-  let user = repo::user::find_by_email_and_status(&state.db, &req.email, true)
-    .await?
-    .to_result()?;
-  let key = ForgetPasswordKey { user_id: user.id };
-  let ttl = service::redis::get_tll(&state.redis, &key).await?;
-  if ttl > 0 {
+  let user = repo::user::find_by_email_and_status(&state.db, &req.email, true).await?;
+  if state.config.privacy.enable {
+    // The code is issued in the background, so an existing account answers as fast as an
+    // unknown email, and a pending code is not revealed by a shorter expire time.
+    if let Some(user) = user {
+      let state = state.clone();
+      tokio::spawn(async move {
+        if let Err(err) = send_reset_code(&state, &user).await {
+          tracing::error!("Sending the reset code of user {} failed: {err}", user.id);
+        }
+      });
+    }
     return Ok(ForgetPasswordResponse {
-      expire_in: ttl as u64,
+      expire_in: EXPIRE_FORGET_PASS_CODE_SECS.as_secs(),
       message: CHECK_EMAIL_MESSAGE.to_string(),
     });
   }
-  let code = util::random::generate_random_string(CODE_LEN);
-  repo::message::save(
-    &*state.db,
-    user.id,
-    code.clone(),
-    MessageKind::ForgetPasswordCode,
-  )
-  .await?;
-  crate::service::redis::set(&state.redis, (&key, &code)).await?;
-  state.messenger_notify.notify_one();
+  let user = user.to_result()?;
+  let expire_in = match send_reset_code(state, &user).await? {
+    Some(ttl) => ttl,
+    None => EXPIRE_FORGET_PASS_CODE_SECS.as_secs(),
+  };
   Ok(ForgetPasswordResponse {
-    expire_in: EXPIRE_FORGET_PASS_CODE_SECS.as_secs(),
+    expire_in,
     message: CHECK_EMAIL_MESSAGE.to_string(),
   })
 }
 
+/// Queues a new reset code unless one is pending, returns the remaining time of the pending one.
+async fn send_reset_code(state: &AppState, user: &entity::user::Model) -> AppResult<Option<u64>> {
+  let key = ForgetPasswordKey { user_id: user.id };
+  let ttl = service::redis::get_tll(&state.redis, &key).await?;
+  if ttl > 0 {
+    return Ok(Some(ttl as u64));
+  }
+  // The message keeps the code encrypted until it is sent, redis only its hash.
+  let code = util::random::generate_random_string(CODE_LEN);
+  let kind = MessageKind::ForgetPasswordCode;
+  repo::message::save_secret(&*state.db, user.id, None, &code, kind).await?;
+  let code_hash = util::hash::sha256_hash(&code);
+  crate::service::redis::set(&state.redis, (&key, &code_hash)).await?;
+  state.messenger_notify.notify_one();
+  service::lockout::reset_failed_code(&state.redis, user.id, kind).await?;
+  Ok(None)
+}
+
 pub async fn reset_password(state: &AppState, req: SetPasswordRequest) -> AppResult {
   info!("Reset password user: {}", req.user_id);
-  let code = service::redis::get(
-    &state.redis,
-    &ForgetPasswordKey {
-      user_id: req.user_id,
-    },
-  )
-  .await?;
-  if code != Some(req.code) {
+  let key = ForgetPasswordKey {
+    user_id: req.user_id,
+  };
+  let code_hash = service::redis::get(&state.redis, &key).await?;
+  let Some(code_hash) = code_hash else {
+    return Err(invalid_input_error("code", "Code is invalid"));
+  };
+  let req_hash = util::hash::sha256_hash(&req.code);
+  if !openssl::memcmp::eq(req_hash.as_bytes(), code_hash.as_bytes()) {
+    let kind = MessageKind::ForgetPasswordCode;
+    if service::lockout::record_failed_code(&state.redis, req.user_id, kind).await? {
+      service::redis::del(&state.redis, &key).await?;
+      return Err(invalid_input_error(
+        "code",
+        "Too many invalid codes, please request a new code.",
+      ));
+    }
+    return Err(invalid_input_error("code", "Code is invalid"));
+  }
+  // Deleting only if unchanged makes the code single use under concurrent requests.
+  if !service::redis::compare_and_del(&state.redis, &key, &code_hash).await? {
     return Err(invalid_input_error("code", "Code is invalid"));
   }
+  service::lockout::reset_failed_code(&state.redis, req.user_id, MessageKind::ForgetPasswordCode)
+    .await?;
   let password =
     tokio::task::spawn_blocking(move || crate::util::hash::argon_hash(req.new_password)).await??;
   repo::user::update_password(&state.db, req.user_id, password).await?;
+  let count = service::session::revoke_all(&state.redis, req.user_id, None).await?;
+  info!(
+    "Revoked {count} sessions after reset password user: {}",
+    req.user_id
+  );
+  Ok(())
+}
+
+/// Replaces the password after checking the current one, every other session is ended.
+pub async fn change_password(
+  state: &AppState,
+  user: &UserClaims,
+  req: ChangePasswordRequest,
+) -> AppResult<u64> {
+  info!("Change password user id: {}", user.uid);
+  let model = repo::user::find_by_id(&*state.db, user.uid)
+    .await?
+    .to_result()?;
+  service::lockout::check(&state.redis, model.id).await?;
+  // counted apart from failed logins, the holder of a stolen token could lock the owner out
+  service::lockout::check_password_attempts(&state.redis, model.id).await?;
+  if let Err(e) = util::password::verify(req.current_password, model.password).await {
+    service::lockout::record_failed_password_check(&state.redis, model.id).await?;
+    return Err(e);
+  }
+  service::lockout::reset_failed_password_check(&state.redis, model.id).await?;
+  let password = util::password::hash(req.new_password).await?;
+  repo::user::update_password(&state.db, model.id, password).await?;
+  let count = service::session::revoke_all(&state.redis, model.id, user.session_id()).await?;
+  repo::message::save(
+    &*state.db,
+    model.id,
+    Utc::now().to_rfc3339(),
+    MessageKind::PasswordChanged,
+  )
+  .await?;
+  state.messenger_notify.notify_one();
+  Ok(count)
+}
+
+/// Sends a code to the new address and a notice to the current one, the
+/// email is replaced only once the code is confirmed.
+pub async fn request_email_change(
+  state: &AppState,
+  user_id: Uuid,
+  req: ChangeEmailRequest,
+) -> AppResult<ChangeEmailResponse> {
+  info!("Request email change user id: {user_id}");
+  let tx = state.db.begin().await?;
+  repo::user::find_by_id(&tx, user_id).await?.to_result()?;
+  repo::user::check_unique_by_email(&tx, &req.email).await?;
+  // The messenger sends the code to the new address, encrypted in the message until then.
+  let code = util::random::generate_random_string(CODE_LEN);
+  let kind = MessageKind::EmailChangeCode;
+  repo::message::save_secret(&tx, user_id, Some(req.email.clone()), &code, kind).await?;
+  repo::message::save(
+    &tx,
+    user_id,
+    req.email.clone(),
+    MessageKind::EmailChangeNotice,
+  )
+  .await?;
+  let value = EmailChangeValue {
+    email: req.email,
+    code_hash: util::hash::sha256_hash(&code),
+  };
+  service::redis::set(&state.redis, (&EmailChangeKey { user_id }, &value)).await?;
+  tx.commit().await?;
+  service::lockout::reset_failed_code(&state.redis, user_id, kind).await?;
+  state.messenger_notify.notify_one();
+  Ok(ChangeEmailResponse {
+    expire_in: EXPIRE_EMAIL_CHANGE_CODE_SECS.as_secs(),
+    message: CHECK_EMAIL_MESSAGE.to_string(),
+  })
+}
+
+pub async fn confirm_email_change(
+  state: &AppState,
+  user_id: Uuid,
+  req: ConfirmEmailRequest,
+) -> AppResult {
+  info!("Confirm email change user id: {user_id}");
+  let key = EmailChangeKey { user_id };
+  let Some(value) = service::redis::get(&state.redis, &key).await? else {
+    return Err(invalid_input_error(
+      "code",
+      "There is no pending email change.",
+    ));
+  };
+  let req_hash = util::hash::sha256_hash(&req.code);
+  if !openssl::memcmp::eq(req_hash.as_bytes(), value.code_hash.as_bytes()) {
+    let kind = MessageKind::EmailChangeCode;
+    if service::lockout::record_failed_code(&state.redis, user_id, kind).await? {
+      service::redis::del(&state.redis, &key).await?;
+      return Err(invalid_input_error(
+        "code",
+        "Too many invalid codes, please request a new code.",
+      ));
+    }
+    return Err(invalid_input_error("code", "Code is invalid."));
+  }
+  if !service::redis::compare_and_del(&state.redis, &key, &value).await? {
+    return Err(invalid_input_error("code", "Code is invalid."));
+  }
+  service::lockout::reset_failed_code(&state.redis, user_id, MessageKind::EmailChangeCode).await?;
+  let tx = state.db.begin().await?;
+  // The address may have been taken since the change was requested.
+  repo::user::check_unique_by_email(&tx, &value.email).await?;
+  let mut user: entity::user::ActiveModel = repo::user::find_by_id(&tx, user_id)
+    .await?
+    .to_result()?
+    .into();
+  user.email = Set(value.email);
+  user.update_at = Set(Utc::now());
+  user.update(&tx).await?;
+  tx.commit().await?;
   Ok(())
 }
 
@@ -175,32 +703,61 @@ pub async fn get_profile(state: &AppState, user_id: Uuid) -> AppResult<ProfileRe
   Ok(ProfileResponse::from(user))
 }
 
+pub async fn regenerate_recovery_codes(
+  state: &AppState,
+  user_id: Uuid,
+) -> AppResult<RecoveryCodesResponse> {
+  info!("Regenerate recovery codes user id: {user_id}");
+  let tx = state.db.begin().await?;
+  let user = repo::user::find_by_id(&tx, user_id).await?.to_result()?;
+  if !user.is_2fa {
+    return Err(AppError::BadRequestError(
+      "Two factor authentication is not enabled.".to_string(),
+    ));
+  }
+  let recovery_codes = service::recovery_code::generate(&tx, user_id).await?;
+  tx.commit().await?;
+  Ok(RecoveryCodesResponse { recovery_codes })
+}
+
 pub async fn update_profile(
   state: &AppState,
   user_id: Uuid,
   req: UpdateProfileRequest,
-) -> AppResult {
+) -> AppResult<Option<Vec<String>>> {
   info!("Update user profile with id: {user_id} request: {req:?}");
   let tx = state.db.begin().await?;
   if let Some(username) = req.username.as_ref() {
     repo::user::check_unique_by_username(&tx, username).await?;
   }
-  let mut user: entity::user::ActiveModel = repo::user::find_by_id(&tx, user_id)
-    .await?
-    .to_result()?
-    .into();
+  let model = repo::user::find_by_id(&tx, user_id).await?.to_result()?;
+  if req.two_factor_method == Some(TwoFactorMethod::Totp) && model.totp_secret.is_none() {
+    return Err(invalid_input_error(
+      "two_factor_method",
+      "Authenticator app is not enrolled.",
+    ));
+  }
+  let recovery_codes = match req.is_2fa {
+    Some(true) if !model.is_2fa => Some(service::recovery_code::generate(&tx, user_id).await?),
+    Some(false) if model.is_2fa => {
+      repo::recovery_code::delete_by_user(&tx, user_id).await?;
+      None
+    }
+    _ => None,
+  };
+  let mut user: entity::user::ActiveModel = model.into();
+  if let Some(method) = req.two_factor_method {
+    user.two_factor_method = Set(method);
+  }
   if let Some(is_2fa) = req.is_2fa {
     user.is_2fa = Set(is_2fa);
   }
   if let Some(username) = req.username {
     user.username = Set(username);
   }
-  if let Some(password) = req.password {
-    user.password = Set(password);
-  }
   user.update(&tx).await?;
   tx.commit().await?;
-  Ok(())
+  Ok(recovery_codes)
 }
 
 pub async fn check_unique_username_or_email(
@@ -0,0 +1,123 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use openssl::nid::Nid;
+use openssl::x509::X509Ref;
+use sha2::{Digest, Sha256};
+
+use crate::error::AppResult;
+
+/// A client certificate verified during the TLS handshake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientCertificate {
+  // base64url sha256 of the DER encoding, the `x5t#S256` of RFC 8705
+  pub thumbprint: String,
+  pub common_name: Option<String>,
+  pub dns_names: Vec<String>,
+  pub emails: Vec<String>,
+}
+
+impl ClientCertificate {
+  pub fn from_x509(certificate: &X509Ref) -> AppResult<Self> {
+    let thumbprint = URL_SAFE_NO_PAD.encode(Sha256::digest(certificate.to_der()?));
+    let common_name = certificate
+      .subject_name()
+      .entries_by_nid(Nid::COMMONNAME)
+      .next()
+      .and_then(|entry| entry.data().to_string().ok());
+    let mut dns_names = vec![];
+    let mut emails = vec![];
+    for name in certificate.subject_alt_names().into_iter().flatten() {
+      if let Some(dns_name) = name.dnsname() {
+        dns_names.push(dns_name.to_string());
+      }
+      if let Some(email) = name.email() {
+        emails.push(email.to_string());
+      }
+    }
+    Ok(Self {
+      thumbprint,
+      common_name,
+      dns_names,
+      emails,
+    })
+  }
+}
+
+/// The TLS connection a request came over.
+#[derive(Debug, Clone, Default)]
+pub struct TlsSession {
+  pub certificate: Option<ClientCertificate>,
+  // whether tokens issued over the connection are bound to its certificate
+  pub certificate_bound_tokens: bool,
+}
+
+impl TlsSession {
+  /// Thumbprint of the certificate the tokens issued over the connection are bound to.
+  pub fn binding(&self) -> Option<&str> {
+    self
+      .certificate
+      .as_ref()
+      .filter(|_| self.certificate_bound_tokens)
+      .map(|certificate| certificate.thumbprint.as_str())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use openssl::asn1::Asn1Time;
+  use openssl::hash::MessageDigest;
+  use openssl::pkey::PKey;
+  use openssl::x509::extension::SubjectAlternativeName;
+  use openssl::x509::{X509, X509NameBuilder};
+
+  use super::*;
+  use crate::util::key::PairKey;
+
+  #[test]
+  fn test_client_certificate_identities() {
+    let pair = PairKey::ec().unwrap();
+    let key = PKey::private_key_from_pem(&pair.private_key).unwrap();
+    let mut name = X509NameBuilder::new().unwrap();
+    name
+      .append_entry_by_nid(Nid::COMMONNAME, "billing")
+      .unwrap();
+    let name = name.build();
+    let mut builder = X509::builder().unwrap();
+    builder.set_version(2).unwrap();
+    builder.set_subject_name(&name).unwrap();
+    builder.set_issuer_name(&name).unwrap();
+    builder.set_pubkey(&key).unwrap();
+    builder
+      .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+      .unwrap();
+    builder
+      .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+      .unwrap();
+    let san = SubjectAlternativeName::new()
+      .dns("billing.internal")
+      .email("billing@example.com")
+      .build(&builder.x509v3_context(None, None))
+      .unwrap();
+    builder.append_extension(san).unwrap();
+    builder.sign(&key, MessageDigest::sha256()).unwrap();
+    let x509 = builder.build();
+
+    let certificate = ClientCertificate::from_x509(&x509).unwrap();
+    assert_eq!(certificate.common_name.as_deref(), Some("billing"));
+    assert_eq!(certificate.dns_names, vec!["billing.internal"]);
+    assert_eq!(certificate.emails, vec!["billing@example.com"]);
+    assert_eq!(
+      certificate.thumbprint,
+      URL_SAFE_NO_PAD.encode(Sha256::digest(x509.to_der().unwrap()))
+    );
+    let mut session = TlsSession {
+      certificate: Some(certificate),
+      certificate_bound_tokens: false,
+    };
+    assert_eq!(session.binding(), None);
+    session.certificate_bound_tokens = true;
+    assert_eq!(
+      session.binding(),
+      session.certificate.as_ref().map(|c| c.thumbprint.as_str())
+    );
+  }
+}
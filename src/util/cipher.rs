@@ -0,0 +1,70 @@
+use base64::{Engine, engine::general_purpose::STANDARD};
+use openssl::symm::{Cipher, decrypt_aead, encrypt_aead};
+
+use crate::error::{AppError, AppResult};
+
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// Encrypts `plain` with AES-256-GCM and returns base64 of `nonce || ciphertext || tag`.
+pub fn encrypt(key: &[u8], plain: &str) -> AppResult<String> {
+  let nonce: [u8; NONCE_LEN] = rand::random();
+  let mut tag = [0u8; TAG_LEN];
+  let cipher_text = encrypt_aead(
+    Cipher::aes_256_gcm(),
+    key,
+    Some(&nonce),
+    &[],
+    plain.as_bytes(),
+    &mut tag,
+  )?;
+  let mut buf = Vec::with_capacity(NONCE_LEN + cipher_text.len() + TAG_LEN);
+  buf.extend_from_slice(&nonce);
+  buf.extend_from_slice(&cipher_text);
+  buf.extend_from_slice(&tag);
+  Ok(STANDARD.encode(buf))
+}
+
+pub fn decrypt(key: &[u8], encoded: &str) -> AppResult<String> {
+  let buf = STANDARD.decode(encoded)?;
+  if buf.len() < NONCE_LEN + TAG_LEN {
+    return Err(AppError::BadRequestError(
+      "Encrypted value is too short.".to_string(),
+    ));
+  }
+  let (nonce, rest) = buf.split_at(NONCE_LEN);
+  let (cipher_text, tag) = rest.split_at(rest.len() - TAG_LEN);
+  let plain = decrypt_aead(
+    Cipher::aes_256_gcm(),
+    key,
+    Some(nonce),
+    &[],
+    cipher_text,
+    tag,
+  )?;
+  String::from_utf8(plain).map_err(|e| AppError::UnknownError(e.into()))
+}
+
+#[cfg(test)]
+mod tests {
+  use fake::{Fake, Faker};
+
+  use super::*;
+
+  #[test]
+  fn test_encrypt_and_decrypt() {
+    let key: [u8; 32] = rand::random();
+    let plain: String = Faker.fake();
+    let encrypted = encrypt(&key, &plain).unwrap();
+    assert_ne!(encrypted, plain);
+    assert_eq!(decrypt(&key, &encrypted).unwrap(), plain);
+  }
+
+  #[test]
+  fn test_decrypt_with_wrong_key() {
+    let key: [u8; 32] = rand::random();
+    let encrypted = encrypt(&key, "secret").unwrap();
+    let other: [u8; 32] = rand::random();
+    assert!(decrypt(&other, &encrypted).is_err());
+  }
+}
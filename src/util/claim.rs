@@ -1,4 +1,4 @@
-use std::{sync::LazyLock, time::Duration};
+use std::{marker::PhantomData, sync::LazyLock, time::Duration};
 
 use axum_extra::{
   TypedHeader,
@@ -7,28 +7,55 @@ use axum_extra::{
 
 use axum::RequestPartsExt;
 use axum::extract::FromRequestParts;
+use axum::http::header::AUTHORIZATION;
 use axum::http::request::Parts;
 use chrono::Utc;
 use fake::Dummy;
 use jsonwebtoken::Header;
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, TokenData, Validation};
+use jsonwebtoken::{TokenData, Validation};
 use serde::Deserialize;
 use serde::Serialize;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::error::{AppError, AppResult};
-use crate::{constant::ACCESS_TOKEN_DECODE_KEY, server::state::AppState};
-use crate::{entity::role::RoleUser, service};
+use crate::util::certificate::TlsSession;
+use crate::util::dpop::DpopProof;
+use crate::util::jwk::KeyRing;
+use crate::util::session_cookie;
+use crate::{
+  constant::{ACCESS_TOKEN_KEY_RING, API_KEY_SCHEME, CONFIG, DPOP_SCHEME, MAGIC_LINK_AUDIENCE},
+  server::state::AppState,
+};
+use crate::{
+  entity::{
+    role::RoleUser,
+    scope::{RequiredScope, Scope},
+  },
+  service,
+};
 
-pub static DECODE_HEADER: LazyLock<Validation> =
-  LazyLock::new(|| Validation::new(Algorithm::RS256));
-pub static ENCODE_HEADER: LazyLock<Header> = LazyLock::new(|| Header::new(Algorithm::RS256));
+pub static DECODE_HEADER: LazyLock<Validation> = LazyLock::new(|| {
+  let mut validation = Validation::new(CONFIG.secret.algorithm.into());
+  validation.set_issuer(&[&CONFIG.secret.issuer]);
+  validation.set_audience(&CONFIG.secret.audience);
+  validation.set_required_spec_claims(&["exp", "nbf", "iss", "aud"]);
+  validation.validate_nbf = true;
+  validation
+});
+pub static ENCODE_HEADER: LazyLock<Header> =
+  LazyLock::new(|| Header::new(CONFIG.secret.algorithm.into()));
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Dummy, ToSchema)]
 pub struct UserClaims {
+  // issuer
+  pub iss: String,
+  // audience
+  pub aud: Vec<String>,
   // issued at
   pub iat: i64,
+  // not before
+  pub nbf: i64,
   // expiration
   pub exp: i64,
   // user id
@@ -37,29 +64,206 @@ pub struct UserClaims {
   pub sid: Uuid,
   // role user
   pub rol: RoleUser,
+  // token id
+  pub jti: Uuid,
+  // granted scopes
+  pub scope: Vec<Scope>,
+  // confirmation of the DPoP key or client certificate the token is bound to
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  #[dummy(default)]
+  pub cnf: Option<Confirmation>,
+}
+
+/// Key confirmation of a sender-constrained token (RFC 9449, RFC 8705).
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Clone, Dummy, ToSchema)]
+pub struct Confirmation {
+  // thumbprint of the DPoP key
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub jkt: Option<String>,
+  // thumbprint of the client certificate
+  #[serde(rename = "x5t#S256", default, skip_serializing_if = "Option::is_none")]
+  pub x5t_s256: Option<String>,
+}
+
+impl Confirmation {
+  pub fn new(jkt: Option<String>, x5t_s256: Option<String>) -> Option<Self> {
+    (jkt.is_some() || x5t_s256.is_some()).then_some(Self { jkt, x5t_s256 })
+  }
 }
 
 impl UserClaims {
   pub fn new(duration: Duration, user_id: Uuid, session_id: Uuid, role: RoleUser) -> Self {
     let now = Utc::now().timestamp();
     Self {
+      iss: CONFIG.secret.issuer.clone(),
+      aud: CONFIG.secret.audience.clone(),
       iat: now,
+      nbf: now,
       exp: now + (duration.as_secs() as i64),
       uid: user_id,
       sid: session_id,
       rol: role,
+      jti: Uuid::new_v4(),
+      scope: Scope::for_role(role),
+      cnf: None,
+    }
+  }
+
+  /// Claims of a principal signed in without a session, e.g. by a client certificate.
+  pub fn sessionless(duration: Duration, user_id: Uuid, role: RoleUser) -> Self {
+    Self::new(duration, user_id, Uuid::nil(), role)
+  }
+
+  /// The session the claims were issued in, `None` when no session backs them.
+  pub fn session_id(&self) -> Option<Uuid> {
+    (!self.sid.is_nil()).then_some(self.sid)
+  }
+
+  pub fn with_scope(mut self, scope: Vec<Scope>) -> Self {
+    self.scope = scope;
+    self
+  }
+
+  pub fn has_scope(&self, scope: Scope) -> bool {
+    self.scope.contains(&scope)
+  }
+
+  pub fn with_jti(mut self, jti: Uuid) -> Self {
+    self.jti = jti;
+    self
+  }
+
+  pub fn with_cnf(mut self, cnf: Option<Confirmation>) -> Self {
+    self.cnf = cnf;
+    self
+  }
+
+  pub fn decode(
+    token: &str,
+    key_ring: &KeyRing,
+  ) -> Result<TokenData<Self>, jsonwebtoken::errors::Error> {
+    key_ring.decode::<UserClaims>(token, &DECODE_HEADER)
+  }
+
+  pub fn encode(&self, key_ring: &KeyRing) -> Result<String, jsonwebtoken::errors::Error> {
+    key_ring.encode(self)
+  }
+}
+
+pub static MAGIC_LINK_DECODE_HEADER: LazyLock<Validation> = LazyLock::new(|| {
+  let mut validation = Validation::new(CONFIG.secret.algorithm.into());
+  validation.set_issuer(&[&CONFIG.secret.issuer]);
+  validation.set_audience(&[MAGIC_LINK_AUDIENCE]);
+  validation.set_required_spec_claims(&["exp", "nbf", "iss", "aud"]);
+  validation.validate_nbf = true;
+  validation
+});
+
+/// Claims of a sign-in link, its own audience keeps it from being used as an access token.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct MagicLinkClaims {
+  pub iss: String,
+  pub aud: Vec<String>,
+  pub iat: i64,
+  pub nbf: i64,
+  pub exp: i64,
+  pub uid: Uuid,
+  pub jti: Uuid,
+}
+
+impl MagicLinkClaims {
+  pub fn new(duration: Duration, user_id: Uuid) -> Self {
+    let now = Utc::now().timestamp();
+    Self {
+      iss: CONFIG.secret.issuer.clone(),
+      aud: vec![MAGIC_LINK_AUDIENCE.to_string()],
+      iat: now,
+      nbf: now,
+      exp: now + (duration.as_secs() as i64),
+      uid: user_id,
+      jti: Uuid::new_v4(),
     }
   }
 
   pub fn decode(
     token: &str,
-    key: &DecodingKey,
+    key_ring: &KeyRing,
   ) -> Result<TokenData<Self>, jsonwebtoken::errors::Error> {
-    jsonwebtoken::decode::<UserClaims>(token, key, &DECODE_HEADER)
+    key_ring.decode::<MagicLinkClaims>(token, &MAGIC_LINK_DECODE_HEADER)
   }
 
-  pub fn encode(&self, key: &EncodingKey) -> Result<String, jsonwebtoken::errors::Error> {
-    jsonwebtoken::encode(&ENCODE_HEADER, self, key)
+  pub fn encode(&self, key_ring: &KeyRing) -> Result<String, jsonwebtoken::errors::Error> {
+    key_ring.encode(self)
+  }
+}
+
+/// Claims of an OpenID Connect id token, its audience is the client it was issued to.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Clone)]
+pub struct IdTokenClaims {
+  pub iss: String,
+  pub sub: String,
+  pub aud: String,
+  pub iat: i64,
+  pub exp: i64,
+  pub auth_time: i64,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub nonce: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub email: Option<String>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub email_verified: Option<bool>,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub preferred_username: Option<String>,
+}
+
+impl IdTokenClaims {
+  pub fn new(
+    duration: Duration,
+    user_id: Uuid,
+    client_id: String,
+    auth_time: i64,
+    nonce: Option<String>,
+  ) -> Self {
+    let now = Utc::now().timestamp();
+    Self {
+      iss: CONFIG.secret.issuer.clone(),
+      sub: user_id.to_string(),
+      aud: client_id,
+      iat: now,
+      exp: now + (duration.as_secs() as i64),
+      auth_time,
+      nonce,
+      email: None,
+      email_verified: None,
+      preferred_username: None,
+    }
+  }
+
+  pub fn with_email(mut self, email: String) -> Self {
+    self.email = Some(email);
+    self.email_verified = Some(true);
+    self
+  }
+
+  pub fn with_profile(mut self, username: String) -> Self {
+    self.preferred_username = Some(username);
+    self
+  }
+
+  pub fn decode(
+    token: &str,
+    key_ring: &KeyRing,
+    client_id: &str,
+  ) -> Result<TokenData<Self>, jsonwebtoken::errors::Error> {
+    let mut validation = Validation::new(CONFIG.secret.algorithm.into());
+    validation.set_issuer(&[&CONFIG.secret.issuer]);
+    validation.set_audience(&[client_id]);
+    validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+    key_ring.decode::<IdTokenClaims>(token, &validation)
+  }
+
+  pub fn encode(&self, key_ring: &KeyRing) -> Result<String, jsonwebtoken::errors::Error> {
+    key_ring.encode(self)
   }
 }
 
@@ -70,15 +274,110 @@ impl FromRequestParts<AppState> for UserClaims {
     parts: &mut Parts,
     state: &AppState,
   ) -> Result<Self, Self::Rejection> {
-    let TypedHeader(Authorization(bearer)) = parts
-      .extract::<TypedHeader<Authorization<Bearer>>>()
-      .await?;
-    let user_claims = UserClaims::decode(bearer.token(), &ACCESS_TOKEN_DECODE_KEY)?.claims;
+    if let Some(key) = authorization(parts, API_KEY_SCHEME) {
+      return service::api_key::authenticate(state, key).await;
+    }
+    let certificate = parts
+      .extensions
+      .get::<TlsSession>()
+      .and_then(|tls| tls.certificate.clone());
+    let cookie = if parts.headers.contains_key(AUTHORIZATION) {
+      None
+    } else {
+      session_cookie::access_token(&state.config.session_cookie, &parts.headers)
+    };
+    // a service account presents its certificate instead of a token
+    if !parts.headers.contains_key(AUTHORIZATION)
+      && cookie.is_none()
+      && let Some(certificate) = &certificate
+    {
+      return service::certificate::authenticate(state, certificate).await;
+    }
+    let (token, is_dpop) = match (authorization(parts, DPOP_SCHEME), cookie) {
+      (Some(token), _) => (token.to_string(), true),
+      (None, Some(token)) => (token, false),
+      (None, None) => {
+        let TypedHeader(Authorization(bearer)) = parts
+          .extract::<TypedHeader<Authorization<Bearer>>>()
+          .await?;
+        (bearer.token().to_string(), false)
+      }
+    };
+    let user_claims = UserClaims::decode(&token, &ACCESS_TOKEN_KEY_RING)?.claims;
+    let cnf = user_claims.cnf.as_ref();
+    if let Some(x5t_s256) = cnf.and_then(|cnf| cnf.x5t_s256.as_ref())
+      && certificate
+        .map(|certificate| certificate.thumbprint)
+        .as_ref()
+        != Some(x5t_s256)
+    {
+      return Err(AppError::UnauthorizedError(
+        "The token is bound to another client certificate.".to_string(),
+      ));
+    }
+    match (cnf.and_then(|cnf| cnf.jkt.as_ref()), is_dpop) {
+      (Some(jkt), true) => {
+        let proof = DpopProof::from_parts(parts, &state.config.server.get_public_url())?;
+        service::dpop::check(&state.redis, proof.as_ref(), jkt, Some(&token)).await?;
+      }
+      (None, false) => {}
+      _ => {
+        return Err(AppError::UnauthorizedError(
+          "The authorization scheme does not match the token.".to_string(),
+        ));
+      }
+    }
     service::session::check(&state.redis, &user_claims).await?;
     Ok(user_claims)
   }
 }
 
+/// The credentials of the authorization header when it uses `scheme`.
+fn authorization<'a>(parts: &'a Parts, scheme: &str) -> Option<&'a str> {
+  parts
+    .headers
+    .get(AUTHORIZATION)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.strip_prefix(scheme))
+    .and_then(|value| value.strip_prefix(' '))
+    .map(str::trim)
+}
+
+/// User claims that must carry the scope named by `S`.
+pub struct ScopedClaims<S>(pub UserClaims, PhantomData<S>);
+
+impl<S> ScopedClaims<S> {
+  pub fn into_inner(self) -> UserClaims {
+    self.0
+  }
+}
+
+impl<S> std::ops::Deref for ScopedClaims<S> {
+  type Target = UserClaims;
+
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+impl<S: RequiredScope + Send + Sync> FromRequestParts<AppState> for ScopedClaims<S> {
+  type Rejection = AppError;
+
+  async fn from_request_parts(
+    parts: &mut Parts,
+    state: &AppState,
+  ) -> Result<Self, Self::Rejection> {
+    let user_claims = UserClaims::from_request_parts(parts, state).await?;
+    if !user_claims.has_scope(S::SCOPE) {
+      return Err(AppError::PermissionDeniedError(format!(
+        "Token does not have the required scope: {}.",
+        S::SCOPE
+      )));
+    }
+    Ok(Self(user_claims, PhantomData))
+  }
+}
+
 pub trait UserClaimsRequest {
   fn get_user_id(&self) -> AppResult<Uuid>;
   fn get_user_claims(&self) -> AppResult<UserClaims>;
@@ -104,7 +403,7 @@ impl UserClaimsRequest for axum::extract::Request {
 
 #[cfg(test)]
 mod tests {
-  use crate::util::key::RsaPairKey;
+  use crate::util::key::PairKey;
   use fake::{Fake, Faker};
 
   use super::*;
@@ -113,7 +412,7 @@ mod tests {
   fn test_user_claims() {
     let user_id: Uuid = Faker.fake();
     let session_id: Uuid = Faker.fake();
-    let pair_key = RsaPairKey::new(2048).unwrap();
+    let pair_key = PairKey::new(CONFIG.secret.algorithm).unwrap();
     let claims = UserClaims::new(
       Duration::from_secs(100),
       user_id,
@@ -128,15 +427,103 @@ mod tests {
     //     "public key: {}",
     //     String::from_utf8(pair_key.public_key.clone()).unwrap()
     // );
-    let token = claims
-      .encode(&EncodingKey::from_rsa_pem(&pair_key.private_key).unwrap())
-      .unwrap();
-    let actual_claims = UserClaims::decode(
-      &token,
-      &DecodingKey::from_rsa_pem(&pair_key.public_key).unwrap(),
+    let key_ring = KeyRing::new(
+      &ENCODE_HEADER,
+      &String::from_utf8(pair_key.private_key).unwrap(),
+      &[String::from_utf8(pair_key.public_key).unwrap()],
     )
-    .unwrap()
-    .claims;
+    .unwrap();
+    let token = claims.encode(&key_ring).unwrap();
+    let actual_claims = UserClaims::decode(&token, &key_ring).unwrap().claims;
     assert_eq!(actual_claims, claims)
   }
+
+  #[test]
+  fn test_id_token_claims() {
+    let user_id: Uuid = Faker.fake();
+    let claims = IdTokenClaims::new(
+      Duration::from_secs(100),
+      user_id,
+      "client".to_string(),
+      Utc::now().timestamp(),
+      Some("nonce".to_string()),
+    )
+    .with_email("user@example.com".to_string());
+    let token = claims.encode(&ACCESS_TOKEN_KEY_RING).unwrap();
+    let actual_claims = IdTokenClaims::decode(&token, &ACCESS_TOKEN_KEY_RING, "client")
+      .unwrap()
+      .claims;
+    assert_eq!(actual_claims, claims);
+    assert!(IdTokenClaims::decode(&token, &ACCESS_TOKEN_KEY_RING, "other").is_err());
+    assert!(UserClaims::decode(&token, &ACCESS_TOKEN_KEY_RING).is_err());
+  }
+
+  #[test]
+  fn test_user_claims_strict_validation() {
+    let pair_key = PairKey::new(CONFIG.secret.algorithm).unwrap();
+    let key_ring = KeyRing::new(
+      &ENCODE_HEADER,
+      &String::from_utf8(pair_key.private_key).unwrap(),
+      &[String::from_utf8(pair_key.public_key).unwrap()],
+    )
+    .unwrap();
+    let claims = UserClaims::new(
+      Duration::from_secs(100),
+      Faker.fake(),
+      Faker.fake(),
+      RoleUser::User,
+    );
+    let mut invalid = claims.clone();
+    invalid.iss = "other-issuer".to_string();
+    let token = invalid.encode(&key_ring).unwrap();
+    assert!(UserClaims::decode(&token, &key_ring).is_err());
+    let mut invalid = claims.clone();
+    invalid.aud = vec!["other-service".to_string()];
+    let token = invalid.encode(&key_ring).unwrap();
+    assert!(UserClaims::decode(&token, &key_ring).is_err());
+    let mut invalid = claims.clone();
+    invalid.nbf = claims.iat + 3600;
+    let token = invalid.encode(&key_ring).unwrap();
+    assert!(UserClaims::decode(&token, &key_ring).is_err());
+  }
+
+  #[test]
+  fn test_magic_link_claims() {
+    let pair_key = PairKey::new(CONFIG.secret.algorithm).unwrap();
+    let key_ring = KeyRing::new(
+      &ENCODE_HEADER,
+      &String::from_utf8(pair_key.private_key).unwrap(),
+      &[String::from_utf8(pair_key.public_key).unwrap()],
+    )
+    .unwrap();
+    let claims = MagicLinkClaims::new(Duration::from_secs(100), Faker.fake());
+    let token = claims.encode(&key_ring).unwrap();
+    let actual_claims = MagicLinkClaims::decode(&token, &key_ring).unwrap().claims;
+    assert_eq!(actual_claims, claims);
+    assert!(UserClaims::decode(&token, &key_ring).is_err());
+    let token = UserClaims::new(
+      Duration::from_secs(100),
+      Faker.fake(),
+      Faker.fake(),
+      RoleUser::User,
+    )
+    .encode(&key_ring)
+    .unwrap();
+    assert!(MagicLinkClaims::decode(&token, &key_ring).is_err());
+  }
+
+  #[test]
+  fn test_user_claims_scope() {
+    let claims = UserClaims::new(
+      Duration::from_secs(100),
+      Faker.fake(),
+      Faker.fake(),
+      RoleUser::User,
+    );
+    assert!(claims.has_scope(Scope::UserRead));
+    assert!(!claims.has_scope(Scope::TokenInfo));
+    let claims = claims.with_scope(vec![Scope::TokenInfo]);
+    assert!(claims.has_scope(Scope::TokenInfo));
+    assert!(!claims.has_scope(Scope::UserRead));
+  }
 }
@@ -0,0 +1,153 @@
+use std::net::{IpAddr, SocketAddr};
+
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::header::USER_AGENT;
+use axum::http::request::Parts;
+use fake::Dummy;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, AppResult};
+use crate::server::state::AppState;
+use crate::util::certificate::TlsSession;
+use crate::util::dpop::DpopProof;
+
+pub const X_FORWARDED_FOR: &str = "x-forwarded-for";
+
+/// Device details of the caller recorded on its session.
+#[derive(Debug, Serialize, Deserialize, Dummy, Clone, Default, PartialEq, Eq)]
+pub struct ClientInfo {
+  pub user_agent: Option<String>,
+  pub ip: Option<String>,
+  // proof of possession of the key the issued tokens are bound to
+  #[serde(skip)]
+  #[dummy(default)]
+  pub dpop: Option<DpopProof>,
+  // thumbprint of the client certificate the issued tokens are bound to
+  #[serde(skip)]
+  #[dummy(default)]
+  pub certificate: Option<String>,
+}
+
+impl FromRequestParts<AppState> for ClientInfo {
+  type Rejection = AppError;
+
+  async fn from_request_parts(
+    parts: &mut Parts,
+    state: &AppState,
+  ) -> Result<Self, Self::Rejection> {
+    Self::from_parts(
+      parts,
+      &state.config.server.trusted_proxies,
+      &state.config.server.get_public_url(),
+    )
+  }
+}
+
+impl ClientInfo {
+  pub fn from_parts(
+    parts: &Parts,
+    trusted_proxies: &[IpAddr],
+    public_url: &str,
+  ) -> AppResult<Self> {
+    let user_agent = parts
+      .headers
+      .get(USER_AGENT)
+      .and_then(|v| v.to_str().ok())
+      .map(ToString::to_string);
+    let ip = client_ip(parts, trusted_proxies).map(|ip| ip.to_string());
+    let dpop = DpopProof::from_parts(parts, public_url)?;
+    let certificate = parts
+      .extensions
+      .get::<TlsSession>()
+      .and_then(TlsSession::binding)
+      .map(ToString::to_string);
+    Ok(Self {
+      user_agent,
+      ip,
+      dpop,
+      certificate,
+    })
+  }
+}
+
+/// The address of the peer, or the one it forwarded the request for when it is
+/// a trusted proxy. Any client can send `X-Forwarded-For`, so the header is read
+/// from the nearest hop and only as far as the hops are trusted proxies.
+fn client_ip(parts: &Parts, trusted_proxies: &[IpAddr]) -> Option<IpAddr> {
+  let ConnectInfo(peer) = parts.extensions.get::<ConnectInfo<SocketAddr>>()?;
+  let mut client = peer.ip();
+  let hops = parts
+    .headers
+    .get_all(X_FORWARDED_FOR)
+    .iter()
+    .filter_map(|v| v.to_str().ok())
+    .flat_map(|v| v.split(','))
+    .collect::<Vec<_>>();
+  for hop in hops.into_iter().rev() {
+    if !trusted_proxies.contains(&client) {
+      break;
+    }
+    match hop.trim().parse() {
+      Ok(ip) => client = ip,
+      Err(_) => break,
+    }
+  }
+  Some(client)
+}
+
+#[cfg(test)]
+mod tests {
+  use axum::http::Request;
+
+  use super::*;
+
+  const PUBLIC_URL: &str = "http://localhost";
+
+  fn parts(peer: &str, forwarded_for: &str) -> Parts {
+    let (mut parts, _) = Request::builder()
+      .header(USER_AGENT, "test-agent")
+      .header(X_FORWARDED_FOR, forwarded_for)
+      .body(())
+      .unwrap()
+      .into_parts();
+    let peer = SocketAddr::new(peer.parse().unwrap(), 4000);
+    parts.extensions.insert(ConnectInfo(peer));
+    parts
+  }
+
+  #[test]
+  fn test_extract_client_info() {
+    let proxy: IpAddr = "10.0.0.2".parse().unwrap();
+    let parts = parts("10.0.0.2", "10.0.0.1");
+    let info = ClientInfo::from_parts(&parts, &[proxy], PUBLIC_URL).unwrap();
+    assert_eq!(info.user_agent.as_deref(), Some("test-agent"));
+    assert_eq!(info.ip.as_deref(), Some("10.0.0.1"));
+    assert!(info.dpop.is_none());
+  }
+
+  #[test]
+  fn test_forwarded_for_only_from_trusted_proxies() {
+    let proxy: IpAddr = "10.0.0.2".parse().unwrap();
+    // a client that is not a trusted proxy can not choose its address
+    let info =
+      ClientInfo::from_parts(&parts("203.0.113.9", "10.0.0.1"), &[proxy], PUBLIC_URL).unwrap();
+    assert_eq!(info.ip.as_deref(), Some("203.0.113.9"));
+    // the addresses the client prepends before the proxy are ignored
+    let parts = parts("10.0.0.2", "1.1.1.1, 203.0.113.9");
+    let info = ClientInfo::from_parts(&parts, &[proxy], PUBLIC_URL).unwrap();
+    assert_eq!(info.ip.as_deref(), Some("203.0.113.9"));
+    let info = ClientInfo::from_parts(&parts, &[], PUBLIC_URL).unwrap();
+    assert_eq!(info.ip.as_deref(), Some("10.0.0.2"));
+  }
+
+  #[test]
+  fn test_reject_invalid_dpop_proof() {
+    let (parts, _) = Request::builder()
+      .header(crate::constant::DPOP_HEADER, "invalid")
+      .body(())
+      .unwrap()
+      .into_parts();
+    let result = ClientInfo::from_parts(&parts, &[], PUBLIC_URL);
+    assert!(matches!(result, Err(AppError::UnauthorizedError(_))));
+  }
+}
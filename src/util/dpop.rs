@@ -0,0 +1,206 @@
+use axum::http::request::Parts;
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::Utc;
+use jsonwebtoken::{Algorithm, DecodingKey, Validation};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::constant::{DPOP_HEADER, DPOP_JTI_MAX_LEN, DPOP_PROOF_LEEWAY_SECS, DPOP_PROOF_TYPE};
+use crate::error::{AppError, AppResult};
+
+// asymmetric algorithms only, a proof is signed by the key it carries
+pub const ALGORITHMS: [Algorithm; 4] = [
+  Algorithm::ES256,
+  Algorithm::RS256,
+  Algorithm::PS256,
+  Algorithm::EdDSA,
+];
+
+/// Claims of a DPoP proof (RFC 9449).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DpopClaims {
+  // proof id
+  pub jti: String,
+  // method of the request
+  pub htm: String,
+  // URL of the request without query and fragment
+  pub htu: String,
+  // issued at
+  pub iat: i64,
+  // hash of the access token sent with the proof
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub ath: Option<String>,
+}
+
+/// A proof whose signature, request and age were checked, its `jti` still has to be
+/// recorded to stop a replay.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DpopProof {
+  // thumbprint of the key that signed the proof
+  pub jkt: String,
+  pub jti: String,
+  pub ath: Option<String>,
+}
+
+impl DpopProof {
+  /// Reads the proof sent with the request, a request may carry at most one. The
+  /// request URL is the path on `public_url`, the headers of the request can not
+  /// be trusted to name it.
+  pub fn from_parts(parts: &Parts, public_url: &str) -> AppResult<Option<Self>> {
+    let mut values = parts.headers.get_all(DPOP_HEADER).iter();
+    let Some(value) = values.next() else {
+      return Ok(None);
+    };
+    if values.next().is_some() {
+      return Err(invalid_proof_error());
+    }
+    let proof = value.to_str().map_err(|_| invalid_proof_error())?;
+    let url = format!("{public_url}{}", parts.uri.path());
+    Self::verify(proof, parts.method.as_str(), &url).map(Some)
+  }
+
+  pub fn verify(proof: &str, method: &str, url: &str) -> AppResult<Self> {
+    Self::read(proof, method, url).ok_or_else(invalid_proof_error)
+  }
+
+  fn read(proof: &str, method: &str, url: &str) -> Option<Self> {
+    let header = jsonwebtoken::decode_header(proof).ok()?;
+    if header.typ.as_deref() != Some(DPOP_PROOF_TYPE) || !ALGORITHMS.contains(&header.alg) {
+      return None;
+    }
+    let jwk = header.jwk?;
+    let jkt = crate::util::jwk::thumbprint(&jwk.algorithm)?;
+    let mut validation = Validation::new(header.alg);
+    validation.required_spec_claims.clear();
+    validation.validate_exp = false;
+    let claims =
+      jsonwebtoken::decode::<DpopClaims>(proof, &DecodingKey::from_jwk(&jwk).ok()?, &validation)
+        .ok()?
+        .claims;
+    let htu = claims.htu.split(['?', '#']).next()?;
+    let age = Utc::now().timestamp().abs_diff(claims.iat);
+    if claims.htm != method
+      || htu != url
+      || age > DPOP_PROOF_LEEWAY_SECS.as_secs()
+      || claims.jti.is_empty()
+      || claims.jti.len() > DPOP_JTI_MAX_LEN
+    {
+      return None;
+    }
+    Some(Self {
+      jkt,
+      jti: claims.jti,
+      ath: claims.ath,
+    })
+  }
+
+  /// Whether the proof was made for this access token.
+  pub fn is_for_token(&self, access_token: &str) -> bool {
+    self.ath.as_deref() == Some(access_token_hash(access_token).as_str())
+  }
+}
+
+/// Value of the `ath` claim of a proof sent with the access token.
+pub fn access_token_hash(access_token: &str) -> String {
+  URL_SAFE_NO_PAD.encode(Sha256::digest(access_token.as_bytes()))
+}
+
+fn invalid_proof_error() -> AppError {
+  AppError::UnauthorizedError("The DPoP proof is not valid.".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use jsonwebtoken::{EncodingKey, Header};
+
+  use crate::util::jwk::KeyRing;
+  use crate::util::key::PairKey;
+
+  use super::*;
+
+  const URL: &str = "http://localhost/api/v1/user/profile";
+
+  fn key_ring(pair: &PairKey) -> KeyRing {
+    let private_key = String::from_utf8(pair.private_key.clone()).unwrap();
+    let public_key = String::from_utf8(pair.public_key.clone()).unwrap();
+    KeyRing::new(&Header::new(Algorithm::ES256), &private_key, &[public_key]).unwrap()
+  }
+
+  fn sign(pair: &PairKey, typ: &str, claims: &DpopClaims) -> String {
+    let ring = key_ring(pair);
+    let mut header = Header::new(Algorithm::ES256);
+    header.typ = Some(typ.to_string());
+    header.jwk = Some(ring.jwks().keys[0].clone());
+    let key = EncodingKey::from_ec_pem(&pair.private_key).unwrap();
+    jsonwebtoken::encode(&header, claims, &key).unwrap()
+  }
+
+  fn claims() -> DpopClaims {
+    DpopClaims {
+      jti: uuid::Uuid::new_v4().to_string(),
+      htm: "GET".to_string(),
+      htu: format!("{URL}?page=1"),
+      iat: Utc::now().timestamp(),
+      ath: Some(access_token_hash("token")),
+    }
+  }
+
+  #[test]
+  fn test_verify_dpop_proof() {
+    let pair = PairKey::ec().unwrap();
+    let sent = claims();
+    let proof = DpopProof::verify(&sign(&pair, DPOP_PROOF_TYPE, &sent), "GET", URL).unwrap();
+    assert_eq!(proof.jti, sent.jti);
+    assert!(proof.is_for_token("token"));
+    assert!(!proof.is_for_token("other"));
+    let other = DpopProof::verify(&sign(&pair, DPOP_PROOF_TYPE, &claims()), "GET", URL);
+    assert_eq!(other.unwrap().jkt, proof.jkt);
+    // the thumbprint is the kid of the same key in a key ring
+    assert_eq!(Some(proof.jkt.as_str()), key_ring(&pair).kid());
+  }
+
+  #[test]
+  fn test_reject_invalid_dpop_proof() {
+    let pair = PairKey::ec().unwrap();
+    let proof = sign(&pair, DPOP_PROOF_TYPE, &claims());
+    assert!(DpopProof::verify(&proof, "POST", URL).is_err());
+    assert!(DpopProof::verify(&proof, "GET", "http://localhost/api/v1/user/sessions").is_err());
+    let proof = sign(&pair, "JWT", &claims());
+    assert!(DpopProof::verify(&proof, "GET", URL).is_err());
+    let mut old = claims();
+    old.iat -= 2 * DPOP_PROOF_LEEWAY_SECS.as_secs() as i64;
+    let proof = sign(&pair, DPOP_PROOF_TYPE, &old);
+    assert!(DpopProof::verify(&proof, "GET", URL).is_err());
+    let mut header = Header::new(Algorithm::HS256);
+    header.typ = Some(DPOP_PROOF_TYPE.to_string());
+    let proof =
+      jsonwebtoken::encode(&header, &claims(), &EncodingKey::from_secret(b"key")).unwrap();
+    assert!(DpopProof::verify(&proof, "GET", URL).is_err());
+  }
+
+  #[test]
+  fn test_proof_url_from_public_url() {
+    let pair = PairKey::ec().unwrap();
+    let request = |proof: &str| {
+      axum::http::Request::builder()
+        .uri("/api/v1/user/profile?page=1")
+        .header(axum::http::header::HOST, "attacker.example")
+        .header(DPOP_HEADER, proof)
+        .body(())
+        .unwrap()
+        .into_parts()
+        .0
+    };
+    // a proof for the host the client chose is not one for the server
+    let mut spoofed = claims();
+    spoofed.htu = "http://attacker.example/api/v1/user/profile".to_string();
+    let parts = request(&sign(&pair, DPOP_PROOF_TYPE, &spoofed));
+    assert!(DpopProof::from_parts(&parts, "http://localhost").is_err());
+    let parts = request(&sign(&pair, DPOP_PROOF_TYPE, &claims()));
+    assert!(
+      DpopProof::from_parts(&parts, "http://localhost")
+        .unwrap()
+        .is_some()
+    );
+  }
+}
@@ -2,6 +2,7 @@ use argon2::{
   Argon2,
   password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString, rand_core::OsRng},
 };
+use sha2::{Digest, Sha256};
 
 pub fn argon_hash(content: impl AsRef<str>) -> Result<String, argon2::password_hash::Error> {
   let salt = SaltString::generate(&mut OsRng);
@@ -21,6 +22,11 @@ pub fn argon_verify(
   Argon2::default().verify_password(content.as_ref().as_bytes(), &parsed_hash)
 }
 
+/// Fast digest for high entropy secrets such as api keys, never for passwords.
+pub fn sha256_hash(content: impl AsRef<str>) -> String {
+  data_encoding::HEXLOWER.encode(&Sha256::digest(content.as_ref().as_bytes()))
+}
+
 #[cfg(test)]
 mod tests {
   use fake::{Fake, Faker};
@@ -41,4 +47,12 @@ mod tests {
     let result = argon_verify(password, hash_pass);
     assert!(result.is_ok());
   }
+
+  #[test]
+  pub fn test_sha256_hash() {
+    assert_eq!(
+      sha256_hash("abc"),
+      "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+  }
 }
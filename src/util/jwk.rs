@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::jwk::{
+  AlgorithmParameters, CommonParameters, EllipticCurve, EllipticCurveKeyParameters,
+  EllipticCurveKeyType, Jwk, JwkSet, KeyAlgorithm, OctetKeyPairParameters, OctetKeyPairType,
+  PublicKeyUse, RSAKeyParameters, RSAKeyType,
+};
+use jsonwebtoken::{
+  Algorithm, DecodingKey, EncodingKey, Header, TokenData, Validation,
+  errors::{Error, ErrorKind},
+};
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::pkey::{Id, PKey, Public};
+use openssl::rsa::Rsa;
+use serde::{Serialize, de::DeserializeOwned};
+use sha2::{Digest, Sha256};
+
+use crate::error::AppResult;
+
+/// One active signing key plus every public key still accepted for verification.
+pub struct KeyRing {
+  header: Header,
+  encoding_key: EncodingKey,
+  decoding_keys: HashMap<String, DecodingKey>,
+  jwks: JwkSet,
+}
+
+impl KeyRing {
+  /// The first public key must belong to the private key, the rest are retired
+  /// keys kept only to verify tokens issued before a rotation.
+  pub fn new(header: &Header, private_key: &str, public_keys: &[String]) -> AppResult<Self> {
+    let encoding_key = match header.alg {
+      Algorithm::RS256 | Algorithm::PS256 => EncodingKey::from_rsa_pem(private_key.as_bytes())?,
+      Algorithm::ES256 => EncodingKey::from_ec_pem(private_key.as_bytes())?,
+      Algorithm::EdDSA => EncodingKey::from_ed_pem(private_key.as_bytes())?,
+      _ => return Err(Error::from(ErrorKind::InvalidAlgorithm).into()),
+    };
+    let mut decoding_keys = HashMap::new();
+    let mut keys = Vec::new();
+    for public_key in public_keys {
+      let jwk = public_jwk(header.alg, public_key)?;
+      let kid = jwk.common.key_id.clone().unwrap_or_default();
+      decoding_keys.insert(kid, DecodingKey::from_jwk(&jwk)?);
+      keys.push(jwk);
+    }
+    let active = keys
+      .first()
+      .ok_or_else(|| Error::from(ErrorKind::InvalidKeyFormat))?;
+    let mut header = header.clone();
+    header.kid = active.common.key_id.clone();
+    Ok(Self {
+      header,
+      encoding_key,
+      decoding_keys,
+      jwks: JwkSet { keys },
+    })
+  }
+
+  pub fn kid(&self) -> Option<&str> {
+    self.header.kid.as_deref()
+  }
+
+  pub fn jwks(&self) -> &JwkSet {
+    &self.jwks
+  }
+
+  pub fn encode<T: Serialize>(&self, claims: &T) -> Result<String, Error> {
+    jsonwebtoken::encode(&self.header, claims, &self.encoding_key)
+  }
+
+  /// Tokens without a `kid` are checked against the active key.
+  pub fn decode<T: DeserializeOwned>(
+    &self,
+    token: &str,
+    validation: &Validation,
+  ) -> Result<TokenData<T>, Error> {
+    let header = jsonwebtoken::decode_header(token)?;
+    let kid = header
+      .kid
+      .as_deref()
+      .or(self.kid())
+      .ok_or_else(|| Error::from(ErrorKind::InvalidToken))?;
+    let key = self
+      .decoding_keys
+      .get(kid)
+      .ok_or_else(|| Error::from(ErrorKind::InvalidToken))?;
+    jsonwebtoken::decode::<T>(token, key, validation)
+  }
+}
+
+fn public_jwk(algorithm: Algorithm, public_key: &str) -> AppResult<Jwk> {
+  let pkey = read_public_key(public_key)?;
+  let (key_algorithm, parameters) = match (algorithm, pkey.id()) {
+    (Algorithm::RS256 | Algorithm::PS256, Id::RSA) => {
+      let rsa = pkey.rsa()?;
+      let key_algorithm = if algorithm == Algorithm::PS256 {
+        KeyAlgorithm::PS256
+      } else {
+        KeyAlgorithm::RS256
+      };
+      let parameters = AlgorithmParameters::RSA(RSAKeyParameters {
+        key_type: RSAKeyType::RSA,
+        n: URL_SAFE_NO_PAD.encode(rsa.n().to_vec()),
+        e: URL_SAFE_NO_PAD.encode(rsa.e().to_vec()),
+      });
+      (key_algorithm, parameters)
+    }
+    (Algorithm::ES256, Id::EC) => {
+      let ec = pkey.ec_key()?;
+      let mut ctx = BigNumContext::new()?;
+      let mut x = BigNum::new()?;
+      let mut y = BigNum::new()?;
+      ec.public_key()
+        .affine_coordinates(ec.group(), &mut x, &mut y, &mut ctx)?;
+      let parameters = AlgorithmParameters::EllipticCurve(EllipticCurveKeyParameters {
+        key_type: EllipticCurveKeyType::EC,
+        curve: EllipticCurve::P256,
+        x: URL_SAFE_NO_PAD.encode(x.to_vec_padded(32)?),
+        y: URL_SAFE_NO_PAD.encode(y.to_vec_padded(32)?),
+      });
+      (KeyAlgorithm::ES256, parameters)
+    }
+    (Algorithm::EdDSA, Id::ED25519) => {
+      let parameters = AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+        key_type: OctetKeyPairType::OctetKeyPair,
+        curve: EllipticCurve::Ed25519,
+        x: URL_SAFE_NO_PAD.encode(pkey.raw_public_key()?),
+      });
+      (KeyAlgorithm::EdDSA, parameters)
+    }
+    _ => return Err(Error::from(ErrorKind::InvalidKeyFormat).into()),
+  };
+  let key_id = thumbprint(&parameters);
+  Ok(Jwk {
+    common: CommonParameters {
+      public_key_use: Some(PublicKeyUse::Signature),
+      key_algorithm: Some(key_algorithm),
+      key_id,
+      ..Default::default()
+    },
+    algorithm: parameters,
+  })
+}
+
+/// JWK thumbprint (RFC 7638) of a public key, `None` for symmetric keys.
+pub fn thumbprint(parameters: &AlgorithmParameters) -> Option<String> {
+  let members = match parameters {
+    AlgorithmParameters::RSA(rsa) => format!(r#"{{"e":"{}","kty":"RSA","n":"{}"}}"#, rsa.e, rsa.n),
+    AlgorithmParameters::EllipticCurve(ec) => format!(
+      r#"{{"crv":"{}","kty":"EC","x":"{}","y":"{}"}}"#,
+      curve_name(&ec.curve),
+      ec.x,
+      ec.y
+    ),
+    AlgorithmParameters::OctetKeyPair(okp) => format!(
+      r#"{{"crv":"{}","kty":"OKP","x":"{}"}}"#,
+      curve_name(&okp.curve),
+      okp.x
+    ),
+    AlgorithmParameters::OctetKey(_) => return None,
+  };
+  Some(URL_SAFE_NO_PAD.encode(Sha256::digest(members.as_bytes())))
+}
+
+fn curve_name(curve: &EllipticCurve) -> &'static str {
+  match curve {
+    EllipticCurve::P256 => "P-256",
+    EllipticCurve::P384 => "P-384",
+    EllipticCurve::P521 => "P-521",
+    EllipticCurve::Ed25519 => "Ed25519",
+  }
+}
+
+// RSA keys may be stored as PKCS#1, every other key type as SPKI.
+fn read_public_key(public_key: &str) -> Result<PKey<Public>, openssl::error::ErrorStack> {
+  match Rsa::public_key_from_pem_pkcs1(public_key.as_bytes()) {
+    Ok(rsa) => PKey::from_rsa(rsa),
+    Err(_) => PKey::public_key_from_pem(public_key.as_bytes()),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use serde::Deserialize;
+
+  use crate::configure::secret::JwtAlgorithm;
+  use crate::util::key::PairKey;
+
+  use super::*;
+
+  #[derive(Debug, Serialize, Deserialize, PartialEq)]
+  struct Claims {
+    exp: i64,
+    sub: String,
+  }
+
+  fn claims() -> Claims {
+    Claims {
+      exp: 4_102_444_800,
+      sub: "subject".to_string(),
+    }
+  }
+
+  fn pem(key: &[u8]) -> String {
+    String::from_utf8(key.to_vec()).unwrap()
+  }
+
+  #[test]
+  fn test_key_ring_encode_and_decode() {
+    let pair = PairKey::rsa(2048).unwrap();
+    let header = Header::new(Algorithm::RS256);
+    let ring = KeyRing::new(&header, &pem(&pair.private_key), &[pem(&pair.public_key)]).unwrap();
+    let token = ring.encode(&claims()).unwrap();
+    let token_header = jsonwebtoken::decode_header(&token).unwrap();
+    assert_eq!(token_header.kid.as_deref(), ring.kid());
+    let data = ring
+      .decode::<Claims>(&token, &Validation::new(Algorithm::RS256))
+      .unwrap();
+    assert_eq!(data.claims, claims());
+  }
+
+  #[test]
+  fn test_key_ring_rotation() {
+    let old = PairKey::rsa(2048).unwrap();
+    let new = PairKey::rsa(2048).unwrap();
+    let header = Header::new(Algorithm::RS256);
+    let validation = Validation::new(Algorithm::RS256);
+    let old_ring = KeyRing::new(&header, &pem(&old.private_key), &[pem(&old.public_key)]).unwrap();
+    let token = old_ring.encode(&claims()).unwrap();
+    let new_ring = KeyRing::new(&header, &pem(&new.private_key), &[pem(&new.public_key)]).unwrap();
+    assert!(new_ring.decode::<Claims>(&token, &validation).is_err());
+    let rotated_ring = KeyRing::new(
+      &header,
+      &pem(&new.private_key),
+      &[pem(&new.public_key), pem(&old.public_key)],
+    )
+    .unwrap();
+    assert_ne!(rotated_ring.kid(), old_ring.kid());
+    assert_eq!(rotated_ring.jwks().keys.len(), 2);
+    assert!(rotated_ring.decode::<Claims>(&token, &validation).is_ok());
+  }
+
+  #[test]
+  fn test_key_ring_algorithms() {
+    for algorithm in [
+      JwtAlgorithm::RS256,
+      JwtAlgorithm::PS256,
+      JwtAlgorithm::ES256,
+      JwtAlgorithm::EdDSA,
+    ] {
+      let pair = PairKey::new(algorithm).unwrap();
+      let header = Header::new(algorithm.into());
+      let ring = KeyRing::new(&header, &pem(&pair.private_key), &[pem(&pair.public_key)]).unwrap();
+      let token = ring.encode(&claims()).unwrap();
+      let data = ring
+        .decode::<Claims>(&token, &Validation::new(algorithm.into()))
+        .unwrap();
+      assert_eq!(data.claims, claims());
+      let jwk = ring.jwks().find(ring.kid().unwrap()).unwrap();
+      let key = DecodingKey::from_jwk(jwk).unwrap();
+      assert!(
+        jsonwebtoken::decode::<Claims>(&token, &key, &Validation::new(algorithm.into())).is_ok()
+      );
+    }
+  }
+
+  #[test]
+  fn test_key_ring_rejects_mismatched_key() {
+    let pair = PairKey::ed25519().unwrap();
+    let header = Header::new(Algorithm::RS256);
+    assert!(KeyRing::new(&header, &pem(&pair.private_key), &[pem(&pair.public_key)]).is_err());
+  }
+
+  #[test]
+  fn test_public_jwk_kid_is_stable() {
+    let pair = PairKey::rsa(2048).unwrap();
+    let first = public_jwk(Algorithm::RS256, &pem(&pair.public_key)).unwrap();
+    let second = public_jwk(Algorithm::RS256, &pem(&pair.public_key)).unwrap();
+    assert_eq!(first.common.key_id, second.common.key_id);
+    assert!(first.common.key_id.is_some());
+  }
+}
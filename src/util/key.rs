@@ -1,14 +1,28 @@
+use openssl::ec::{EcGroup, EcKey};
 use openssl::error::ErrorStack;
+use openssl::nid::Nid;
+use openssl::pkey::PKey;
 use openssl::rsa::Rsa;
 
+use crate::configure::secret::JwtAlgorithm;
+
 #[derive(Debug)]
-pub struct RsaPairKey {
+pub struct PairKey {
   pub private_key: Vec<u8>,
   pub public_key: Vec<u8>,
 }
 
-impl RsaPairKey {
-  pub fn new(bits: u32) -> Result<Self, ErrorStack> {
+impl PairKey {
+  /// Generate a PEM pair usable with the given signing algorithm.
+  pub fn new(algorithm: JwtAlgorithm) -> Result<Self, ErrorStack> {
+    match algorithm {
+      JwtAlgorithm::RS256 | JwtAlgorithm::PS256 => Self::rsa(2048),
+      JwtAlgorithm::ES256 => Self::ec(),
+      JwtAlgorithm::EdDSA => Self::ed25519(),
+    }
+  }
+
+  pub fn rsa(bits: u32) -> Result<Self, ErrorStack> {
     let rsa = Rsa::generate(bits)?;
     let private_key = rsa.private_key_to_pem()?;
     let public_key = rsa.public_key_to_pem_pkcs1()?;
@@ -17,6 +31,23 @@ impl RsaPairKey {
       public_key,
     })
   }
+
+  pub fn ec() -> Result<Self, ErrorStack> {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+    let key = PKey::from_ec_key(EcKey::generate(&group)?)?;
+    Ok(Self {
+      private_key: key.private_key_to_pem_pkcs8()?,
+      public_key: key.public_key_to_pem()?,
+    })
+  }
+
+  pub fn ed25519() -> Result<Self, ErrorStack> {
+    let key = PKey::generate_ed25519()?;
+    Ok(Self {
+      private_key: key.private_key_to_pem_pkcs8()?,
+      public_key: key.public_key_to_pem()?,
+    })
+  }
 }
 
 #[cfg(test)]
@@ -25,7 +56,21 @@ mod tests {
 
   #[test]
   fn pair_key_test() {
-    assert!(RsaPairKey::new(2048).is_ok());
-    assert!(RsaPairKey::new(1024).is_ok());
+    assert!(PairKey::rsa(2048).is_ok());
+    assert!(PairKey::rsa(1024).is_ok());
+  }
+
+  #[test]
+  fn pair_key_algorithm_test() {
+    for algorithm in [
+      JwtAlgorithm::RS256,
+      JwtAlgorithm::PS256,
+      JwtAlgorithm::ES256,
+      JwtAlgorithm::EdDSA,
+    ] {
+      let pair = PairKey::new(algorithm).unwrap();
+      assert!(!pair.private_key.is_empty());
+      assert!(!pair.public_key.is_empty());
+    }
   }
 }
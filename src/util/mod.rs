@@ -1,8 +1,13 @@
 pub mod assertion;
+pub mod certificate;
+pub mod cipher;
 pub mod claim;
+pub mod client_info;
 pub mod dir;
+pub mod dpop;
 pub mod file;
 pub mod hash;
+pub mod jwk;
 pub mod key;
 pub mod password;
 pub mod path;
@@ -10,5 +15,8 @@ pub mod random;
 pub mod regex;
 pub mod result;
 pub mod retry;
+pub mod session_cookie;
 pub mod task;
+pub mod totp;
+pub mod webauthn;
 pub mod ws;
@@ -8,6 +8,13 @@ pub fn generate_random_string(len: usize) -> String {
     .collect()
 }
 
+pub fn generate_random_string_from(charset: &[u8], len: usize) -> String {
+  let mut rng = rand::rng();
+  (0..len)
+    .map(|_| char::from(charset[rng.random_range(0..charset.len())]))
+    .collect()
+}
+
 pub fn generate_random_string_with_prefix(prefix: &str) -> String {
   format!("{prefix}_{}", generate_random_string(10))
 }
@@ -25,6 +32,13 @@ mod tests {
     assert!(result.starts_with(&prefix));
   }
 
+  #[test]
+  fn test_generate_random_string_from() {
+    let result = generate_random_string_from(b"AB", 16);
+    assert_eq!(result.len(), 16);
+    assert!(result.chars().all(|c| c == 'A' || c == 'B'));
+  }
+
   #[test]
   fn test_generate_random_string() {
     let len = 4;
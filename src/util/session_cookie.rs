@@ -0,0 +1,229 @@
+use std::time::Duration;
+
+use axum::http::HeaderMap;
+use axum_extra::extract::CookieJar;
+use axum_extra::extract::cookie::Cookie;
+
+use crate::configure::session_cookie::SessionCookieConfig;
+use crate::constant::{
+  ACCESS_TOKEN_COOKIE, ACCESS_TOKEN_COOKIE_PATH, CSRF_COOKIE, CSRF_HEADER, CSRF_TOKEN_LEN,
+  DPOP_SCHEME, EXPIRE_BEARER_TOKEN_SECS, EXPIRE_REFRESH_TOKEN_SECS, REFRESH_TOKEN_COOKIE,
+  REFRESH_TOKEN_COOKIE_PATH,
+};
+use crate::dto::{LoginResponse, TokenResponse};
+use crate::error::{AppError, AppResult};
+use crate::util::random::generate_random_string;
+
+const CSRF_COOKIE_PATH: &str = "/";
+
+/// A response that may carry the tokens of a new session.
+pub trait SessionTokens {
+  fn tokens_mut(&mut self) -> Option<&mut TokenResponse>;
+}
+
+impl SessionTokens for TokenResponse {
+  fn tokens_mut(&mut self) -> Option<&mut TokenResponse> {
+    Some(self)
+  }
+}
+
+impl SessionTokens for LoginResponse {
+  fn tokens_mut(&mut self) -> Option<&mut TokenResponse> {
+    match self {
+      LoginResponse::Token(tokens) => Some(tokens),
+      LoginResponse::Code { .. } => None,
+    }
+  }
+}
+
+/// Moves the tokens of `resp` into cookies when the browser session mode is
+/// enabled, with a new CSRF token the page reads and sends back in a header.
+pub fn issue<T: SessionTokens>(
+  config: &SessionCookieConfig,
+  jar: CookieJar,
+  resp: &mut T,
+) -> CookieJar {
+  // a DPoP bound token is sent with a proof, so it stays with the client holding the key
+  let Some(tokens) = resp
+    .tokens_mut()
+    .filter(|tokens| config.enable && tokens.token_type != DPOP_SCHEME)
+  else {
+    return jar;
+  };
+  let refresh_token = std::mem::take(&mut tokens.refresh_token);
+  let mut jar = jar.add(cookie(
+    config,
+    REFRESH_TOKEN_COOKIE,
+    refresh_token,
+    REFRESH_TOKEN_COOKIE_PATH,
+    EXPIRE_REFRESH_TOKEN_SECS,
+  ));
+  if config.access_token {
+    let access_token = std::mem::take(&mut tokens.access_token);
+    jar = jar.add(cookie(
+      config,
+      ACCESS_TOKEN_COOKIE,
+      access_token,
+      ACCESS_TOKEN_COOKIE_PATH,
+      EXPIRE_BEARER_TOKEN_SECS,
+    ));
+  }
+  let mut csrf = cookie(
+    config,
+    CSRF_COOKIE,
+    generate_random_string(CSRF_TOKEN_LEN),
+    CSRF_COOKIE_PATH,
+    EXPIRE_REFRESH_TOKEN_SECS,
+  );
+  csrf.set_http_only(false);
+  jar.add(csrf)
+}
+
+/// Removes the cookies of the browser session mode.
+pub fn clear(config: &SessionCookieConfig, jar: CookieJar) -> CookieJar {
+  if !config.enable {
+    return jar;
+  }
+  [
+    (ACCESS_TOKEN_COOKIE, ACCESS_TOKEN_COOKIE_PATH),
+    (REFRESH_TOKEN_COOKIE, REFRESH_TOKEN_COOKIE_PATH),
+    (CSRF_COOKIE, CSRF_COOKIE_PATH),
+  ]
+  .into_iter()
+  .fold(jar, |jar, (name, path)| {
+    let mut removal = cookie(config, name, String::new(), path, Duration::ZERO);
+    removal.make_removal();
+    jar.add(removal)
+  })
+}
+
+/// The access token of the cookie, when the browser session mode keeps it there.
+pub fn access_token(config: &SessionCookieConfig, headers: &HeaderMap) -> Option<String> {
+  if !config.enable || !config.access_token {
+    return None;
+  }
+  value(&CookieJar::from_headers(headers), ACCESS_TOKEN_COOKIE)
+}
+
+pub fn refresh_token(config: &SessionCookieConfig, jar: &CookieJar) -> Option<String> {
+  if !config.enable {
+    return None;
+  }
+  value(jar, REFRESH_TOKEN_COOKIE)
+}
+
+/// Whether the request carries a token cookie the browser sends by itself.
+pub fn has_session(jar: &CookieJar) -> bool {
+  value(jar, ACCESS_TOKEN_COOKIE).is_some() || value(jar, REFRESH_TOKEN_COOKIE).is_some()
+}
+
+/// Double-submit check: the header has to repeat the CSRF cookie, which a page
+/// of another site cannot read.
+pub fn check_csrf(jar: &CookieJar, headers: &HeaderMap) -> AppResult {
+  let cookie = value(jar, CSRF_COOKIE);
+  let header = headers
+    .get(CSRF_HEADER)
+    .and_then(|value| value.to_str().ok());
+  match (cookie, header) {
+    (Some(cookie), Some(header))
+      if cookie.len() == header.len()
+        && openssl::memcmp::eq(cookie.as_bytes(), header.as_bytes()) =>
+    {
+      Ok(())
+    }
+    _ => Err(AppError::PermissionDeniedError(
+      "The CSRF token is missing or invalid.".to_string(),
+    )),
+  }
+}
+
+fn cookie(
+  config: &SessionCookieConfig,
+  name: &'static str,
+  value: String,
+  path: &'static str,
+  max_age: Duration,
+) -> Cookie<'static> {
+  let mut builder = Cookie::build((name, value))
+    .path(path)
+    .http_only(true)
+    .secure(config.secure)
+    .same_site(config.same_site.into())
+    .max_age(max_age.try_into().unwrap_or_default());
+  if let Some(domain) = &config.domain {
+    builder = builder.domain(domain.clone());
+  }
+  builder.build()
+}
+
+fn value(jar: &CookieJar, name: &str) -> Option<String> {
+  jar
+    .get(name)
+    .map(|cookie| cookie.value().to_string())
+    .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+  use axum::http::header::{COOKIE, HeaderValue};
+
+  use super::*;
+  use crate::configure::session_cookie::CookieSameSite;
+
+  fn config(enable: bool, access_token: bool) -> SessionCookieConfig {
+    SessionCookieConfig {
+      enable,
+      access_token,
+      secure: true,
+      same_site: CookieSameSite::Strict,
+      domain: None,
+    }
+  }
+
+  fn tokens() -> TokenResponse {
+    TokenResponse::new("access".to_string(), "refresh".to_string(), 600)
+  }
+
+  #[test]
+  fn test_issue_session_cookies() {
+    let mut resp = tokens();
+    let jar = issue(&config(true, false), CookieJar::new(), &mut resp);
+    assert_eq!(resp.access_token, "access");
+    assert!(resp.refresh_token.is_empty());
+    let refresh = jar.get(REFRESH_TOKEN_COOKIE).unwrap();
+    assert_eq!(refresh.value(), "refresh");
+    assert_eq!(refresh.http_only(), Some(true));
+    assert_eq!(refresh.secure(), Some(true));
+    assert_eq!(refresh.path(), Some(REFRESH_TOKEN_COOKIE_PATH));
+    assert!(jar.get(ACCESS_TOKEN_COOKIE).is_none());
+    let csrf = jar.get(CSRF_COOKIE).unwrap();
+    assert_eq!(csrf.value().len(), CSRF_TOKEN_LEN);
+    assert_eq!(csrf.http_only(), Some(false));
+
+    let mut resp = LoginResponse::Token(tokens());
+    let jar = issue(&config(true, true), CookieJar::new(), &mut resp);
+    assert_eq!(value(&jar, ACCESS_TOKEN_COOKIE).as_deref(), Some("access"));
+    assert!(matches!(resp, LoginResponse::Token(t) if t.access_token.is_empty()));
+
+    let mut resp = tokens();
+    let jar = issue(&config(false, true), CookieJar::new(), &mut resp);
+    assert_eq!(jar.iter().count(), 0);
+    assert_eq!(resp.refresh_token, "refresh");
+  }
+
+  #[test]
+  fn test_check_csrf() {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+      COOKIE,
+      HeaderValue::from_static("csrf_token=abc; refresh_token=r"),
+    );
+    let jar = CookieJar::from_headers(&headers);
+    assert!(has_session(&jar));
+    assert!(check_csrf(&jar, &headers).is_err());
+    headers.insert(CSRF_HEADER, HeaderValue::from_static("abd"));
+    assert!(check_csrf(&jar, &headers).is_err());
+    headers.insert(CSRF_HEADER, HeaderValue::from_static("abc"));
+    assert!(check_csrf(&jar, &headers).is_ok());
+  }
+}
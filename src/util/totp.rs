@@ -0,0 +1,88 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use data_encoding::BASE32_NOPAD;
+use openssl::{hash::MessageDigest, pkey::PKey, sign::Signer};
+
+use crate::constant::{APP_DOMAIN, TOTP_DIGITS, TOTP_SECRET_LEN, TOTP_SKEW_STEPS, TOTP_STEP_SECS};
+use crate::error::{AppResult, invalid_input_error};
+
+pub fn generate_secret() -> String {
+  let bytes: [u8; TOTP_SECRET_LEN] = rand::random();
+  BASE32_NOPAD.encode(&bytes)
+}
+
+pub fn generate_uri(secret: &str, account: &str) -> String {
+  let label: String = url::form_urlencoded::byte_serialize(account.as_bytes()).collect();
+  format!(
+    "otpauth://totp/{APP_DOMAIN}:{label}?secret={secret}&issuer={APP_DOMAIN}&algorithm=SHA1&digits={TOTP_DIGITS}&period={TOTP_STEP_SECS}"
+  )
+}
+
+pub fn generate_code(secret: &str, counter: u64) -> AppResult<String> {
+  let key = BASE32_NOPAD
+    .decode(secret.as_bytes())
+    .map_err(|_| invalid_input_error("secret", "Secret is not valid base32."))?;
+  let pkey = PKey::hmac(&key)?;
+  let mut signer = Signer::new(MessageDigest::sha1(), &pkey)?;
+  signer.update(&counter.to_be_bytes())?;
+  let hmac = signer.sign_to_vec()?;
+  let offset = (hmac[hmac.len() - 1] & 0x0f) as usize;
+  let binary = u32::from_be_bytes([
+    hmac[offset] & 0x7f,
+    hmac[offset + 1],
+    hmac[offset + 2],
+    hmac[offset + 3],
+  ]);
+  let code = binary % 10_u32.pow(TOTP_DIGITS);
+  Ok(format!("{code:0width$}", width = TOTP_DIGITS as usize))
+}
+
+pub fn verify_code_at(secret: &str, code: &str, unix_time: u64) -> AppResult<bool> {
+  let counter = unix_time / TOTP_STEP_SECS;
+  for step in counter.saturating_sub(TOTP_SKEW_STEPS)..=counter + TOTP_SKEW_STEPS {
+    if generate_code(secret, step)? == code {
+      return Ok(true);
+    }
+  }
+  Ok(false)
+}
+
+pub fn verify_code(secret: &str, code: &str) -> AppResult<bool> {
+  let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+  verify_code_at(secret, code, now)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // RFC 6238 appendix B secret "12345678901234567890".
+  const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+  #[test]
+  fn test_generate_code_rfc_vector() {
+    assert_eq!(generate_code(RFC_SECRET, 59 / 30).unwrap(), "287082");
+    assert_eq!(
+      generate_code(RFC_SECRET, 1111111109 / 30).unwrap(),
+      "081804"
+    );
+  }
+
+  #[test]
+  fn test_verify_code_with_drift() {
+    let secret = generate_secret();
+    let now = 1_700_000_000;
+    let previous = generate_code(&secret, now / TOTP_STEP_SECS - 1).unwrap();
+    assert!(verify_code_at(&secret, &previous, now).unwrap());
+    let stale = generate_code(&secret, now / TOTP_STEP_SECS - 3).unwrap();
+    assert!(!verify_code_at(&secret, &stale, now).unwrap());
+  }
+
+  #[test]
+  fn test_generate_uri() {
+    let uri = generate_uri("ABC", "foo@bar.com");
+    assert!(uri.starts_with("otpauth://totp/"));
+    assert!(uri.contains("secret=ABC"));
+    assert!(uri.contains("foo%40bar.com"));
+  }
+}
@@ -0,0 +1,244 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use ciborium::Value;
+use openssl::bn::BigNum;
+use openssl::ec::{EcGroup, EcKey};
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::{Id, PKey};
+use openssl::rsa::Rsa;
+use openssl::sign::Verifier;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::constant::PASSKEY_CHALLENGE_LEN;
+use crate::error::{AppResult, invalid_input_error};
+
+// COSE algorithm identifiers offered at registration, in order of preference
+pub const ES256: i64 = -7;
+pub const EDDSA: i64 = -8;
+pub const RS256: i64 = -257;
+pub const ALGORITHMS: [i64; 3] = [ES256, EDDSA, RS256];
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+const AAGUID_LEN: usize = 16;
+
+pub fn generate_challenge() -> String {
+  let bytes: [u8; PASSKEY_CHALLENGE_LEN] = rand::random();
+  URL_SAFE_NO_PAD.encode(bytes)
+}
+
+pub fn decode(field: &'static str, value: &str) -> AppResult<Vec<u8>> {
+  URL_SAFE_NO_PAD
+    .decode(value.trim_end_matches('='))
+    .map_err(|_| invalid_input_error(field, "Value is not valid base64url."))
+}
+
+/// The members of the client data the relying party has to check.
+#[derive(Debug, Deserialize)]
+pub struct ClientData {
+  #[serde(rename = "type")]
+  pub kind: String,
+  pub challenge: String,
+  pub origin: String,
+}
+
+impl ClientData {
+  pub fn parse(json: &[u8]) -> AppResult<Self> {
+    serde_json::from_slice(json)
+      .map_err(|_| invalid_input_error("client_data_json", "Client data is not valid."))
+  }
+}
+
+/// The public key of a new credential.
+#[derive(Debug)]
+pub struct AttestedCredential {
+  pub credential_id: Vec<u8>,
+  pub algorithm: i64,
+  // DER encoded subject public key info
+  pub public_key: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub struct AuthenticatorData {
+  pub rp_id_hash: Vec<u8>,
+  pub flags: u8,
+  pub sign_count: u32,
+  pub credential: Option<AttestedCredential>,
+}
+
+impl AuthenticatorData {
+  pub fn parse(data: &[u8]) -> AppResult<Self> {
+    Self::read(data)
+      .ok_or_else(|| invalid_input_error("authenticator_data", "Authenticator data is not valid."))
+  }
+
+  fn read(mut data: &[u8]) -> Option<Self> {
+    let rp_id_hash = take(&mut data, 32)?.to_vec();
+    let flags = *take(&mut data, 1)?.first()?;
+    let sign_count = u32::from_be_bytes(take(&mut data, 4)?.try_into().ok()?);
+    let credential = if flags & FLAG_ATTESTED_CREDENTIAL != 0 {
+      take(&mut data, AAGUID_LEN)?;
+      let len = u16::from_be_bytes(take(&mut data, 2)?.try_into().ok()?);
+      let credential_id = take(&mut data, len as usize)?.to_vec();
+      let key: Value = ciborium::from_reader(data).ok()?;
+      let (algorithm, public_key) = cose_public_key(&key)?;
+      Some(AttestedCredential {
+        credential_id,
+        algorithm,
+        public_key,
+      })
+    } else {
+      None
+    };
+    Some(Self {
+      rp_id_hash,
+      flags,
+      sign_count,
+      credential,
+    })
+  }
+
+  pub fn is_rp_id(&self, rp_id: &str) -> bool {
+    self.rp_id_hash == Sha256::digest(rp_id.as_bytes()).as_slice()
+  }
+
+  pub fn user_present(&self) -> bool {
+    self.flags & FLAG_USER_PRESENT != 0
+  }
+
+  pub fn user_verified(&self) -> bool {
+    self.flags & FLAG_USER_VERIFIED != 0
+  }
+}
+
+/// Returns the authenticator data of an attestation object, the attestation
+/// statement is not checked because registration asks for none.
+pub fn attestation_auth_data(attestation_object: &[u8]) -> AppResult<Vec<u8>> {
+  let value: Value = ciborium::from_reader(attestation_object)
+    .map_err(|_| invalid_input_error("attestation_object", "Attestation object is not valid."))?;
+  value
+    .as_map()
+    .and_then(|map| map.iter().find(|(k, _)| k.as_text() == Some("authData")))
+    .and_then(|(_, v)| v.as_bytes())
+    .cloned()
+    .ok_or_else(|| invalid_input_error("attestation_object", "Attestation object is not valid."))
+}
+
+/// Checks an assertion signed over the authenticator data and the hash of the client data.
+pub fn verify_signature(
+  algorithm: i64,
+  public_key: &[u8],
+  auth_data: &[u8],
+  client_data_json: &[u8],
+  signature: &[u8],
+) -> AppResult<bool> {
+  let pkey = PKey::public_key_from_der(public_key)?;
+  let mut message = auth_data.to_vec();
+  message.extend_from_slice(&Sha256::digest(client_data_json));
+  let is_valid = match algorithm {
+    ES256 | RS256 => {
+      let mut verifier = Verifier::new(MessageDigest::sha256(), &pkey)?;
+      verifier.update(&message)?;
+      verifier.verify(signature).unwrap_or(false)
+    }
+    EDDSA => Verifier::new_without_digest(&pkey)?
+      .verify_oneshot(signature, &message)
+      .unwrap_or(false),
+    _ => false,
+  };
+  Ok(is_valid)
+}
+
+/// Converts a COSE key of one of the offered algorithms to DER.
+fn cose_public_key(key: &Value) -> Option<(i64, Vec<u8>)> {
+  let map = key.as_map()?;
+  let get = |label: i64| {
+    map
+      .iter()
+      .find(|(k, _)| k.as_integer() == Some(label.into()))
+      .map(|(_, v)| v)
+  };
+  let bytes = |label: i64| get(label).and_then(Value::as_bytes);
+  let curve = get(-1).and_then(Value::as_integer);
+  let algorithm = i64::try_from(get(3)?.as_integer()?).ok()?;
+  let pkey = match algorithm {
+    ES256 if curve == Some(1.into()) => {
+      let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).ok()?;
+      let x = BigNum::from_slice(bytes(-2)?).ok()?;
+      let y = BigNum::from_slice(bytes(-3)?).ok()?;
+      PKey::from_ec_key(EcKey::from_public_key_affine_coordinates(&group, &x, &y).ok()?).ok()?
+    }
+    EDDSA if curve == Some(6.into()) => {
+      PKey::public_key_from_raw_bytes(bytes(-2)?, Id::ED25519).ok()?
+    }
+    RS256 => {
+      let n = BigNum::from_slice(bytes(-1)?).ok()?;
+      let e = BigNum::from_slice(bytes(-2)?).ok()?;
+      PKey::from_rsa(Rsa::from_public_components(n, e).ok()?).ok()?
+    }
+    _ => return None,
+  };
+  Some((algorithm, pkey.public_key_to_der().ok()?))
+}
+
+fn take<'a>(data: &mut &'a [u8], len: usize) -> Option<&'a [u8]> {
+  let (head, rest) = data.split_at_checked(len)?;
+  *data = rest;
+  Some(head)
+}
+
+#[cfg(test)]
+mod tests {
+  use openssl::bn::BigNumContext;
+  use openssl::sign::Signer;
+
+  use super::*;
+
+  #[test]
+  fn test_parse_credential_and_verify_signature() {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    let key = EcKey::generate(&group).unwrap();
+    let mut x = BigNum::new().unwrap();
+    let mut y = BigNum::new().unwrap();
+    key
+      .public_key()
+      .affine_coordinates(&group, &mut x, &mut y, &mut BigNumContext::new().unwrap())
+      .unwrap();
+    let cose = Value::Map(vec![
+      (1.into(), 2.into()),
+      (3.into(), ES256.into()),
+      ((-1).into(), 1.into()),
+      ((-2).into(), Value::Bytes(x.to_vec_padded(32).unwrap())),
+      ((-3).into(), Value::Bytes(y.to_vec_padded(32).unwrap())),
+    ]);
+    let mut data = Sha256::digest(b"localhost").to_vec();
+    data.push(FLAG_USER_PRESENT | FLAG_ATTESTED_CREDENTIAL);
+    data.extend_from_slice(&7_u32.to_be_bytes());
+    data.extend_from_slice(&[0; AAGUID_LEN]);
+    data.extend_from_slice(&3_u16.to_be_bytes());
+    data.extend_from_slice(b"abc");
+    ciborium::into_writer(&cose, &mut data).unwrap();
+
+    let auth_data = AuthenticatorData::parse(&data).unwrap();
+    assert!(auth_data.is_rp_id("localhost"));
+    assert!(auth_data.user_present());
+    assert!(!auth_data.user_verified());
+    assert_eq!(auth_data.sign_count, 7);
+    let credential = auth_data.credential.unwrap();
+    assert_eq!(credential.credential_id, b"abc");
+    assert_eq!(credential.algorithm, ES256);
+
+    let client_data = br#"{"type":"webauthn.get","challenge":"abc","origin":"http://localhost"}"#;
+    let mut message = data[..37].to_vec();
+    message.extend_from_slice(&Sha256::digest(client_data));
+    let pkey = PKey::from_ec_key(key).unwrap();
+    let mut signer = Signer::new(MessageDigest::sha256(), &pkey).unwrap();
+    let signature = signer.sign_oneshot_to_vec(&message).unwrap();
+    let public_key = &credential.public_key;
+    assert!(verify_signature(ES256, public_key, &data[..37], client_data, &signature).unwrap());
+    assert!(!verify_signature(ES256, public_key, &data[..37], b"{}", &signature).unwrap());
+    assert!(AuthenticatorData::parse(&data[..36]).is_err());
+  }
+}
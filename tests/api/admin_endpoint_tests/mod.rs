@@ -1 +1,3 @@
+mod test_user_approval;
 mod test_user_list;
+mod test_user_unlock;
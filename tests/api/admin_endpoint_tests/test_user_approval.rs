@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use crate::context::seeder::SeedDbTestContext;
+use crate::{assert_err, assert_ok};
+use fake::{Fake, Faker};
+use rustfulapi::dto::*;
+use rustfulapi::entity::message::MessageKind;
+use rustfulapi::entity::role::RoleUser;
+use rustfulapi::error::{AppError, AppResponseError};
+use rustfulapi::server::state::AppState;
+use rustfulapi::{repo, service};
+use test_context::test_context;
+use uuid::Uuid;
+
+fn approval_state(state: &AppState) -> AppState {
+  let mut config = (*state.config).clone();
+  config.registration.require_approval = true;
+  AppState {
+    config: Arc::new(config),
+    ..state.clone()
+  }
+}
+
+async fn register_pending_user(state: &AppState, req: RegisterRequest) -> Uuid {
+  let user_id = service::user::register(state.clone(), req).await.unwrap();
+  let message = repo::message::find_by_user_and_kind(&*state.db, user_id, MessageKind::ActiveCode)
+    .await
+    .unwrap()
+    .unwrap();
+  let req = ActiveRequest {
+    user_id,
+    code: message.content,
+  };
+  service::user::active(state, req).await.unwrap();
+  user_id
+}
+
+async fn admin_token(ctx: &SeedDbTestContext) -> String {
+  let admin = ctx.users.get(&RoleUser::Admin).unwrap();
+  ctx
+    .app
+    .api
+    .get_token(&LoginRequest {
+      email: admin.email.clone(),
+      password: admin.password.clone(),
+    })
+    .await
+    .unwrap()
+    .access_token
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_register_with_disposable_email(ctx: &mut SeedDbTestContext) {
+  let mut req: RegisterRequest = Faker.fake();
+  req.email = format!("{}@mailinator.com", req.username);
+  let (status, resp) = ctx.app.api.register(&req).await.unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind == "INVALID_INPUT_ERROR");
+  assert_eq!(status, reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_register_with_denied_domain(ctx: &mut SeedDbTestContext) {
+  let mut config = (*ctx.app.state.config).clone();
+  config.registration.denied_domains = vec!["example.org".to_string()];
+  let state = AppState {
+    config: Arc::new(config),
+    ..ctx.app.state.clone()
+  };
+  let mut req: RegisterRequest = Faker.fake();
+  req.email = format!("{}@example.org", req.username);
+  let err = service::user::register(state, req).await.unwrap_err();
+  assert!(matches!(err, AppError::InvalidInputError(_)));
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_approve_pending_user(ctx: &mut SeedDbTestContext) {
+  let state = approval_state(&ctx.app.state);
+  let req: RegisterRequest = Faker.fake();
+  let login_req = LoginRequest {
+    email: req.email.clone(),
+    password: req.password.clone(),
+  };
+  let user_id = register_pending_user(&state, req).await;
+  let (status, resp) = ctx.app.api.login(&login_req).await.unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind
+    == "PERMISSION_DENIED_ERROR");
+  assert_eq!(status, reqwest::StatusCode::FORBIDDEN);
+
+  let token = admin_token(ctx).await;
+  let (status, resp) = ctx.app.api.get_pending_user_list(&token).await.unwrap();
+  assert_ok!(resp, |r: &GetUserListResponse| r
+    .list
+    .iter()
+    .any(|u| u.id == user_id && !u.is_approved));
+  assert!(status.is_success(), "status: {status}");
+  let (status, resp) = ctx.app.api.approve_user(&token, &user_id).await.unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let (status, resp) = ctx.app.api.approve_user(&token, &user_id).await.unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind == "BAD_REQUEST_ERROR");
+  assert_eq!(status, reqwest::StatusCode::BAD_REQUEST);
+  let (status, resp) = ctx.app.api.login(&login_req).await.unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_reject_pending_user(ctx: &mut SeedDbTestContext) {
+  let state = approval_state(&ctx.app.state);
+  let req: RegisterRequest = Faker.fake();
+  let email = req.email.clone();
+  let user_id = register_pending_user(&state, req).await;
+  // rows that reference the user go with it
+  service::recovery_code::generate(&*state.db, user_id)
+    .await
+    .unwrap();
+  let token = admin_token(ctx).await;
+  let (status, resp) = ctx.app.api.reject_user(&token, &user_id).await.unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let user = repo::user::find_by_id(&*state.db, user_id).await.unwrap();
+  assert!(user.is_none());
+  let admin = ctx.users.get(&RoleUser::Admin).unwrap();
+  let message =
+    repo::message::find_by_user_and_kind(&*state.db, admin.id, MessageKind::RegistrationRejected)
+      .await
+      .unwrap()
+      .unwrap();
+  assert_eq!(message.receiver.as_ref(), Some(&email));
+  let mut req: RegisterRequest = Faker.fake();
+  req.email = email;
+  let err = service::user::register(state, req).await.unwrap_err();
+  assert!(matches!(err, AppError::InvalidInputError(_)));
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_fail_approve_user_without_admin_scope(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let token = ctx
+    .app
+    .api
+    .get_token(&LoginRequest {
+      email: user.email.clone(),
+      password: user.password.clone(),
+    })
+    .await
+    .unwrap();
+  let (status, resp) = ctx
+    .app
+    .api
+    .approve_user(&token.access_token, &user.id)
+    .await
+    .unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind
+    == "PERMISSION_DENIED_ERROR");
+  assert_eq!(status, reqwest::StatusCode::FORBIDDEN);
+}
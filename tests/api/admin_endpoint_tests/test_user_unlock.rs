@@ -0,0 +1,78 @@
+use crate::context::seeder::SeedDbTestContext;
+use crate::{assert_err, assert_ok};
+use rustfulapi::constant::MAX_LOGIN_ATTEMPTS;
+use rustfulapi::dto::*;
+use rustfulapi::entity::role::RoleUser;
+use rustfulapi::error::AppResponseError;
+use test_context::test_context;
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_success_unlock_locked_user(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let wrong_req = LoginRequest {
+    email: user.email.clone(),
+    password: "wrong_password".to_string(),
+  };
+  for _ in 1..MAX_LOGIN_ATTEMPTS {
+    let (status, resp) = ctx.app.api.login(&wrong_req).await.unwrap();
+    assert_err!(resp, |e: &AppResponseError| e.kind == "INVALID_INPUT_ERROR");
+    assert!(status.is_client_error(), "status: {status}");
+  }
+  let (status, resp) = ctx.app.api.login(&wrong_req).await.unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind == "USER_LOCKED_ERROR");
+  assert_eq!(status, reqwest::StatusCode::LOCKED);
+  let req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let (status, resp) = ctx.app.api.login(&req).await.unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind == "USER_LOCKED_ERROR");
+  assert_eq!(status, reqwest::StatusCode::LOCKED);
+
+  let admin = ctx.users.get(&RoleUser::Admin).unwrap();
+  let admin_token = ctx
+    .app
+    .api
+    .get_token(&LoginRequest {
+      email: admin.email.clone(),
+      password: admin.password.clone(),
+    })
+    .await
+    .unwrap();
+  let (status, resp) = ctx
+    .app
+    .api
+    .unlock_user(&admin_token.access_token, &user.id)
+    .await
+    .unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let (status, resp) = ctx.app.api.login(&req).await.unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_fail_unlock_user_without_admin_scope(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let token = ctx
+    .app
+    .api
+    .get_token(&LoginRequest {
+      email: user.email.clone(),
+      password: user.password.clone(),
+    })
+    .await
+    .unwrap();
+  let (status, resp) = ctx
+    .app
+    .api
+    .unlock_user(&token.access_token, &user.id)
+    .await
+    .unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind
+    == "PERMISSION_DENIED_ERROR");
+  assert_eq!(status, reqwest::StatusCode::FORBIDDEN);
+}
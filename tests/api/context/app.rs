@@ -58,3 +58,11 @@ impl AsyncTestContext for AppTestContext {
     info!("Teardown done successfully.");
   }
 }
+
+/// Runs another server on `config`, sharing the database and redis of the test.
+pub async fn spawn_server(mut config: AppConfig) -> (AppState, JoinHandle<AppResult>) {
+  config.server.port = 0;
+  let server = server::AppServer::new(config).await.unwrap();
+  let state = server.state.clone();
+  (state, tokio::task::spawn(server.run()))
+}
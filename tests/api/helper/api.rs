@@ -1,14 +1,26 @@
 use crate::unwrap;
 
 use super::result::AppResponseResult;
+use jsonwebtoken::jwk::JwkSet;
 use log_derive::logfn;
 use reqwest::StatusCode;
+use reqwest::header::HeaderMap;
 use rustfulapi::client::http::HttpClientExt;
 use rustfulapi::configure::server::ServerConfig;
-use rustfulapi::constant::HTTP;
+use rustfulapi::constant::{DPOP_HEADER, HTTP};
 use rustfulapi::dto::request::*;
 use rustfulapi::dto::response::*;
 use rustfulapi::util::claim::UserClaims;
+use std::sync::LazyLock;
+use uuid::Uuid;
+
+// Redirects of the authorization endpoint are asserted instead of followed.
+static NO_REDIRECT_HTTP: LazyLock<reqwest::Client> = LazyLock::new(|| {
+  reqwest::Client::builder()
+    .redirect(reqwest::redirect::Policy::none())
+    .build()
+    .unwrap()
+});
 
 pub struct Api {
   addr: String,
@@ -21,6 +33,10 @@ impl Api {
     }
   }
 
+  pub fn url(&self, path: &str) -> String {
+    format!("{}{path}", self.addr)
+  }
+
   #[logfn(Info)]
   pub async fn server_state(
     &self,
@@ -39,6 +55,14 @@ impl Api {
     Ok((resp.status(), resp.json().await?))
   }
 
+  #[logfn(Info)]
+  pub async fn jwks(&self) -> anyhow::Result<(StatusCode, AppResponseResult<JwkSet>)> {
+    let resp = HTTP
+      .get_request(&format!("{}/.well-known/jwks.json", self.addr))
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
   #[logfn(Info)]
   pub async fn register(
     &self,
@@ -61,6 +85,17 @@ impl Api {
     Ok((resp.status(), resp.json().await?))
   }
 
+  #[logfn(Info)]
+  pub async fn resend_active_code(
+    &self,
+    req: &ResendActiveCodeRequest,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<MessageResponse>)> {
+    let resp = HTTP
+      .post_request(&format!("{}/api/v1/user/active/resend", self.addr), req)
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
   #[logfn(Info)]
   pub async fn login(
     &self,
@@ -72,6 +107,56 @@ impl Api {
     Ok((resp.status(), resp.json().await?))
   }
 
+  #[logfn(Info)]
+  pub async fn login_with_dpop(
+    &self,
+    req: &LoginRequest,
+    proof: &str,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<LoginResponse>)> {
+    let resp = HTTP
+      .post(format!("{}/api/v1/user/login", self.addr))
+      .header(DPOP_HEADER, proof)
+      .json(req)
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn request_magic_link(
+    &self,
+    req: &MagicLinkRequest,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<MessageResponse>)> {
+    let resp = HTTP
+      .post_request(&format!("{}/api/v1/user/login/magic-link", self.addr), req)
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn login_magic_link(
+    &self,
+    token: &str,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<LoginResponse>)> {
+    let resp = HTTP
+      .post(format!("{}/api/v1/user/login/magic-link/verify", self.addr))
+      .form(&[("token", token)])
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn magic_link_page(&self, token: &str) -> anyhow::Result<(StatusCode, String)> {
+    let resp = HTTP
+      .get_request(&format!(
+        "{}/api/v1/user/login/magic-link?token={token}",
+        self.addr
+      ))
+      .await?;
+    Ok((resp.status(), resp.text().await?))
+  }
+
   #[logfn(Info)]
   pub async fn get_token(&self, req: &LoginRequest) -> anyhow::Result<TokenResponse> {
     let (_, resp) = self.login(req).await?;
@@ -97,6 +182,61 @@ impl Api {
     Ok((resp.status(), resp.json().await?))
   }
 
+  #[logfn(Info)]
+  pub async fn unlock_user(
+    &self,
+    token: &str,
+    id: &Uuid,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<MessageResponse>)> {
+    let resp = HTTP
+      .delete(format!("{}/api/v1/admin/user/{id}/lock", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn get_pending_user_list(
+    &self,
+    token: &str,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<GetUserListResponse>)> {
+    let resp = HTTP
+      .get(format!("{}/api/v1/admin/user/pending", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn approve_user(
+    &self,
+    token: &str,
+    id: &Uuid,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<MessageResponse>)> {
+    let resp = HTTP
+      .put(format!("{}/api/v1/admin/user/{id}/approve", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn reject_user(
+    &self,
+    token: &str,
+    id: &Uuid,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<MessageResponse>)> {
+    let resp = HTTP
+      .put(format!("{}/api/v1/admin/user/{id}/reject", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
   #[logfn(Info)]
   pub async fn login2fa(
     &self,
@@ -108,6 +248,84 @@ impl Api {
     Ok((resp.status(), resp.json().await?))
   }
 
+  #[logfn(Info)]
+  pub async fn login2fa_with_headers(
+    &self,
+    req: &Login2faRequest,
+  ) -> anyhow::Result<(StatusCode, HeaderMap, AppResponseResult<LoginResponse>)> {
+    let resp = HTTP
+      .post_request(&format!("{}/api/v1/user/login2fa", self.addr), req)
+      .await?;
+    Ok((resp.status(), resp.headers().clone(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn login2fa_email(
+    &self,
+    req: &Login2faEmailRequest,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<LoginResponse>)> {
+    let resp = HTTP
+      .post_request(&format!("{}/api/v1/user/login2fa/email", self.addr), req)
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn enroll_totp(
+    &self,
+    token: &str,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<TotpEnrollResponse>)> {
+    let resp = HTTP
+      .post(format!("{}/api/v1/user/2fa/totp", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn confirm_totp(
+    &self,
+    token: &str,
+    req: &ConfirmTotpRequest,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<UpdateProfileResponse>)> {
+    let resp = HTTP
+      .put(format!("{}/api/v1/user/2fa/totp", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .json(req)
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn disable_totp(
+    &self,
+    token: &str,
+    req: &DisableTotpRequest,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<MessageResponse>)> {
+    let resp = HTTP
+      .delete(format!("{}/api/v1/user/2fa/totp", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .json(req)
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn regenerate_recovery_codes(
+    &self,
+    token: &str,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<RecoveryCodesResponse>)> {
+    let resp = HTTP
+      .post(format!("{}/api/v1/user/2fa/recovery_codes", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
   #[logfn(Info)]
   pub async fn logout(&self, token: &str) -> anyhow::Result<(StatusCode, AppResponseResult)> {
     let resp = HTTP
@@ -118,6 +336,46 @@ impl Api {
     Ok((resp.status(), resp.json().await?))
   }
 
+  #[logfn(Info)]
+  pub async fn list_sessions(
+    &self,
+    token: &str,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<SessionListResponse>)> {
+    let resp = HTTP
+      .get(format!("{}/api/v1/user/sessions", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn revoke_session(
+    &self,
+    token: &str,
+    session_id: &Uuid,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult)> {
+    let resp = HTTP
+      .delete(format!("{}/api/v1/user/sessions/{session_id}", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn revoke_other_sessions(
+    &self,
+    token: &str,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult)> {
+    let resp = HTTP
+      .delete(format!("{}/api/v1/user/sessions", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
   #[logfn(Info)]
   pub async fn refresh_token(
     &self,
@@ -131,6 +389,21 @@ impl Api {
     Ok((resp.status(), resp.json().await?))
   }
 
+  #[logfn(Info)]
+  pub async fn refresh_token_with_dpop(
+    &self,
+    req: &RefreshTokenRequest,
+    proof: &str,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<TokenResponse>)> {
+    let resp = HTTP
+      .post(format!("{}/api/v1/token/refresh", self.addr))
+      .header(DPOP_HEADER, proof)
+      .json(req)
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
   #[logfn(Info)]
   pub async fn forget_password(
     &self,
@@ -174,6 +447,229 @@ impl Api {
     Ok((resp.status(), resp.json().await?))
   }
 
+  #[logfn(Info)]
+  pub async fn create_api_key(
+    &self,
+    token: &str,
+    req: &CreateApiKeyRequest,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<CreateApiKeyResponse>)> {
+    let resp = HTTP
+      .post(format!("{}/api/v1/user/api-keys", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .json(req)
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn oidc_authorize(
+    &self,
+    provider: &str,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<OidcAuthorizeResponse>)> {
+    let resp = HTTP
+      .get(format!(
+        "{}/api/v1/user/oidc/{provider}/authorize",
+        self.addr
+      ))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn list_identities(
+    &self,
+    token: &str,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<IdentityListResponse>)> {
+    let resp = HTTP
+      .get(format!("{}/api/v1/user/profile/identities", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn list_api_keys(
+    &self,
+    token: &str,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<ApiKeyListResponse>)> {
+    let resp = HTTP
+      .get(format!("{}/api/v1/user/api-keys", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn revoke_api_key(
+    &self,
+    token: &str,
+    id: &Uuid,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult)> {
+    let resp = HTTP
+      .delete(format!("{}/api/v1/user/api-keys/{id}", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn start_passkey_registration(
+    &self,
+    token: &str,
+  ) -> anyhow::Result<(
+    StatusCode,
+    AppResponseResult<PasskeyCreationOptionsResponse>,
+  )> {
+    let resp = HTTP
+      .post(format!("{}/api/v1/user/passkeys", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn register_passkey(
+    &self,
+    token: &str,
+    req: &RegisterPasskeyRequest,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<PasskeyResponse>)> {
+    let resp = HTTP
+      .put(format!("{}/api/v1/user/passkeys", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .json(req)
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn list_passkeys(
+    &self,
+    token: &str,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<PasskeyListResponse>)> {
+    let resp = HTTP
+      .get(format!("{}/api/v1/user/passkeys", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn delete_passkey(
+    &self,
+    token: &str,
+    id: &Uuid,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult)> {
+    let resp = HTTP
+      .delete(format!("{}/api/v1/user/passkeys/{id}", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn passkey_login_options(
+    &self,
+    req: &PasskeyLoginOptionsRequest,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<PasskeyRequestOptionsResponse>)> {
+    let resp = HTTP
+      .post_request(
+        &format!("{}/api/v1/user/login/passkey/options", self.addr),
+        req,
+      )
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn login_passkey(
+    &self,
+    req: &PasskeyLoginRequest,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<LoginResponse>)> {
+    let resp = HTTP
+      .post_request(&format!("{}/api/v1/user/login/passkey", self.addr), req)
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn create_invitation(
+    &self,
+    token: &str,
+    req: &CreateInvitationRequest,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<CreateInvitationResponse>)> {
+    let resp = HTTP
+      .post(format!("{}/api/v1/invitations", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .json(req)
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn list_invitations(
+    &self,
+    token: &str,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<InvitationListResponse>)> {
+    let resp = HTTP
+      .get(format!("{}/api/v1/invitations", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn revoke_invitation(
+    &self,
+    token: &str,
+    id: &Uuid,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult)> {
+    let resp = HTTP
+      .delete(format!("{}/api/v1/invitations/{id}", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn token_info_with_api_key(
+    &self,
+    key: &str,
+    req: &TokenInfoRequest,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<UserClaims>)> {
+    let resp = HTTP
+      .post(format!("{}/api/v1/token/info", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("ApiKey {key}"))
+      .json(req)
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn get_profile_with_api_key(
+    &self,
+    key: &str,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<ProfileResponse>)> {
+    let resp = HTTP
+      .get(format!("{}/api/v1/user/profile", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("ApiKey {key}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
   #[logfn(Info)]
   pub async fn get_profile(
     &self,
@@ -187,12 +683,27 @@ impl Api {
     Ok((resp.status(), resp.json().await?))
   }
 
+  #[logfn(Info)]
+  pub async fn get_profile_with_dpop(
+    &self,
+    token: &str,
+    proof: &str,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<ProfileResponse>)> {
+    let resp = HTTP
+      .get(format!("{}/api/v1/user/profile", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("DPoP {token}"))
+      .header(DPOP_HEADER, proof)
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
   #[logfn(Info)]
   pub async fn update_profile(
     &self,
     token: &str,
     req: &UpdateProfileRequest,
-  ) -> reqwest::Result<(StatusCode, AppResponseResult)> {
+  ) -> reqwest::Result<(StatusCode, AppResponseResult<UpdateProfileResponse>)> {
     let resp = HTTP
       .put(format!("{}/api/v1/user/profile", self.addr))
       .json(req)
@@ -201,4 +712,191 @@ impl Api {
       .await?;
     Result::<_, reqwest::Error>::Ok((resp.status(), resp.json().await?))
   }
+
+  #[logfn(Info)]
+  pub async fn change_password(
+    &self,
+    token: &str,
+    req: &ChangePasswordRequest,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<MessageResponse>)> {
+    let resp = HTTP
+      .put(format!("{}/api/v1/user/profile/password", self.addr))
+      .json(req)
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn request_email_change(
+    &self,
+    token: &str,
+    req: &ChangeEmailRequest,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<ChangeEmailResponse>)> {
+    let resp = HTTP
+      .post(format!("{}/api/v1/user/email", self.addr))
+      .json(req)
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn confirm_email_change(
+    &self,
+    token: &str,
+    req: &ConfirmEmailRequest,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<MessageResponse>)> {
+    let resp = HTTP
+      .put(format!("{}/api/v1/user/email", self.addr))
+      .json(req)
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn create_oauth_client(
+    &self,
+    token: &str,
+    req: &CreateOauthClientRequest,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<CreateOauthClientResponse>)> {
+    let resp = HTTP
+      .post(format!("{}/api/v1/admin/clients", self.addr))
+      .json(req)
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn list_oauth_clients(
+    &self,
+    token: &str,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<OauthClientListResponse>)> {
+    let resp = HTTP
+      .get(format!("{}/api/v1/admin/clients", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn delete_oauth_client(
+    &self,
+    token: &str,
+    id: &Uuid,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<MessageResponse>)> {
+    let resp = HTTP
+      .delete(format!("{}/api/v1/admin/clients/{id}", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn openid_configuration(
+    &self,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<OpenIdConfigurationResponse>)> {
+    let resp = HTTP
+      .get(format!("{}/.well-known/openid-configuration", self.addr))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn oauth_authorize(
+    &self,
+    params: &AuthorizeParams,
+  ) -> anyhow::Result<reqwest::Response> {
+    let resp = NO_REDIRECT_HTTP
+      .get(format!("{}/oauth2/authorize", self.addr))
+      .query(params)
+      .send()
+      .await?;
+    Ok(resp)
+  }
+
+  #[logfn(Info)]
+  pub async fn oauth_consent(
+    &self,
+    req: &AuthorizeConsentRequest,
+  ) -> anyhow::Result<reqwest::Response> {
+    let resp = NO_REDIRECT_HTTP
+      .post(format!("{}/oauth2/authorize", self.addr))
+      .form(req)
+      .send()
+      .await?;
+    Ok(resp)
+  }
+
+  #[logfn(Info)]
+  pub async fn oauth_token(
+    &self,
+    req: &OauthTokenRequest,
+    basic: Option<(&str, &str)>,
+  ) -> anyhow::Result<(StatusCode, serde_json::Value)> {
+    let mut builder = HTTP.post(format!("{}/oauth2/token", self.addr)).form(req);
+    if let Some((client_id, secret)) = basic {
+      builder = builder.basic_auth(client_id, Some(secret));
+    }
+    let resp = builder.send().await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn oauth_device_authorization(
+    &self,
+    req: &DeviceAuthorizationRequest,
+  ) -> anyhow::Result<(StatusCode, serde_json::Value)> {
+    let resp = HTTP
+      .post(format!("{}/oauth2/device_authorization", self.addr))
+      .form(req)
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn oauth_device_page(&self, user_code: &str) -> anyhow::Result<(StatusCode, String)> {
+    let resp = HTTP
+      .get(format!("{}/oauth2/device", self.addr))
+      .query(&[("user_code", user_code)])
+      .send()
+      .await?;
+    Ok((resp.status(), resp.text().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn oauth_device_verify(
+    &self,
+    req: &DeviceVerificationRequest,
+  ) -> anyhow::Result<(StatusCode, String)> {
+    let resp = HTTP
+      .post(format!("{}/oauth2/device", self.addr))
+      .form(req)
+      .send()
+      .await?;
+    Ok((resp.status(), resp.text().await?))
+  }
+
+  #[logfn(Info)]
+  pub async fn oauth_userinfo(
+    &self,
+    token: &str,
+  ) -> anyhow::Result<(StatusCode, AppResponseResult<UserInfoResponse>)> {
+    let resp = HTTP
+      .get(format!("{}/oauth2/userinfo", self.addr))
+      .header(reqwest::header::AUTHORIZATION, format!("Bearer {token}"))
+      .send()
+      .await?;
+    Ok((resp.status(), resp.json().await?))
+  }
 }
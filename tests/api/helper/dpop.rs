@@ -0,0 +1,43 @@
+use chrono::Utc;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use rustfulapi::constant::DPOP_PROOF_TYPE;
+use rustfulapi::util::dpop::{DpopClaims, access_token_hash};
+use rustfulapi::util::jwk::KeyRing;
+use rustfulapi::util::key::PairKey;
+
+/// An ES256 key of a client, it signs a DPoP proof for each request.
+pub struct DpopKey {
+  ring: KeyRing,
+  encoding_key: EncodingKey,
+}
+
+impl DpopKey {
+  pub fn generate() -> Self {
+    let pair = PairKey::ec().unwrap();
+    let private_key = String::from_utf8(pair.private_key).unwrap();
+    let public_key = String::from_utf8(pair.public_key).unwrap();
+    Self {
+      ring: KeyRing::new(&Header::new(Algorithm::ES256), &private_key, &[public_key]).unwrap(),
+      encoding_key: EncodingKey::from_ec_pem(private_key.as_bytes()).unwrap(),
+    }
+  }
+
+  /// The kid of a key ring is the thumbprint of its key.
+  pub fn thumbprint(&self) -> &str {
+    self.ring.kid().unwrap()
+  }
+
+  pub fn proof(&self, method: &str, url: &str, access_token: Option<&str>) -> String {
+    let mut header = Header::new(Algorithm::ES256);
+    header.typ = Some(DPOP_PROOF_TYPE.to_string());
+    header.jwk = Some(self.ring.jwks().keys[0].clone());
+    let claims = DpopClaims {
+      jti: uuid::Uuid::new_v4().to_string(),
+      htm: method.to_string(),
+      htu: url.to_string(),
+      iat: Utc::now().timestamp(),
+      ath: access_token.map(access_token_hash),
+    };
+    jsonwebtoken::encode(&header, &claims, &self.encoding_key).unwrap()
+  }
+}
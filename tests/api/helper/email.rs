@@ -93,13 +93,21 @@ impl MailHogClient {
 
   pub async fn get_code_and_id_from_email(&self, email: &str) -> anyhow::Result<(String, Uuid)> {
     let resp = self.search(QueryKindSearch::To, email).await?;
-    let body = resp
+    let content = &resp
       .items
       .first()
       .ok_or_else(|| anyhow!("Item not found"))?
-      .content
-      .body
-      .clone();
+      .content;
+    let body = if content
+      .headers
+      .content_transfer_encoding
+      .iter()
+      .any(|e| e.eq_ignore_ascii_case("quoted-printable"))
+    {
+      decode_quoted_printable(&content.body)
+    } else {
+      content.body.clone()
+    };
     let html = Html::parse_document(&body);
     let selector =
       Selector::parse(r#"strong"#).map_err(|e| anyhow!("parse strong tag failed {:?}", e))?;
@@ -122,6 +130,28 @@ impl MailHogClient {
   }
 }
 
+// Long lines, such as links, are wrapped with soft line breaks.
+fn decode_quoted_printable(body: &str) -> String {
+  let body = body.replace("=\r\n", "").replace("=\n", "");
+  let bytes = body.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len() {
+    let hex = bytes
+      .get(i + 1..i + 3)
+      .and_then(|hex| std::str::from_utf8(hex).ok())
+      .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+    if let (b'=', Some(byte)) = (bytes[i], hex) {
+      decoded.push(byte);
+      i += 3;
+      continue;
+    }
+    decoded.push(bytes[i]);
+    i += 1;
+  }
+  String::from_utf8_lossy(&decoded).into_owned()
+}
+
 #[derive(Default, Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Response {
@@ -5,9 +5,12 @@ use tracing_subscriber::{EnvFilter, Registry, layer::SubscriberExt};
 
 pub mod api;
 pub mod assert;
+pub mod dpop;
 pub mod email;
 pub mod http;
+pub mod passkey;
 pub mod result;
+pub mod tls;
 pub mod user;
 
 pub(crate) static INIT_SUBSCRIBER: LazyLock<()> = LazyLock::new(|| {
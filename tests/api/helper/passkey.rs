@@ -0,0 +1,141 @@
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use ciborium::Value;
+use openssl::bn::{BigNum, BigNumContext};
+use openssl::ec::{EcGroup, EcKey};
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use openssl::sign::Signer;
+use rustfulapi::dto::*;
+use sha2::{Digest, Sha256};
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+
+/// An ES256 authenticator in software, it answers the ceremonies the way a
+/// browser hands them to the server.
+pub struct SoftAuthenticator {
+  key: PKey<Private>,
+  credential_id: Vec<u8>,
+  user_handle: Option<String>,
+  sign_count: u32,
+  origin: String,
+}
+
+impl SoftAuthenticator {
+  pub fn new(origin: &str) -> Self {
+    let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+    Self {
+      key: PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap(),
+      credential_id: rand::random::<[u8; 16]>().to_vec(),
+      user_handle: None,
+      sign_count: 0,
+      origin: origin.to_string(),
+    }
+  }
+
+  pub fn credential_id(&self) -> String {
+    URL_SAFE_NO_PAD.encode(&self.credential_id)
+  }
+
+  pub fn register(
+    &mut self,
+    options: &PasskeyCreationOptionsResponse,
+  ) -> PasskeyRegistrationCredential {
+    self.user_handle = Some(options.user.id.clone());
+    let mut auth_data = self.auth_data(
+      &options.rp.id,
+      FLAG_USER_PRESENT | FLAG_USER_VERIFIED | FLAG_ATTESTED_CREDENTIAL,
+    );
+    auth_data.extend_from_slice(&[0; 16]);
+    auth_data.extend_from_slice(&(self.credential_id.len() as u16).to_be_bytes());
+    auth_data.extend_from_slice(&self.credential_id);
+    ciborium::into_writer(&self.cose_key(), &mut auth_data).unwrap();
+    let attestation = Value::Map(vec![
+      ("fmt".into(), "none".into()),
+      ("attStmt".into(), Value::Map(vec![])),
+      ("authData".into(), Value::Bytes(auth_data)),
+    ]);
+    let mut attestation_object = vec![];
+    ciborium::into_writer(&attestation, &mut attestation_object).unwrap();
+    PasskeyRegistrationCredential {
+      id: self.credential_id(),
+      response: PasskeyAttestationResponse {
+        client_data_json: self.client_data("webauthn.create", &options.challenge),
+        attestation_object: URL_SAFE_NO_PAD.encode(attestation_object),
+      },
+    }
+  }
+
+  pub fn assert(
+    &mut self,
+    options: &PasskeyRequestOptionsResponse,
+    user_verified: bool,
+  ) -> PasskeyAssertionCredential {
+    self.sign_count += 1;
+    let flags = if user_verified {
+      FLAG_USER_PRESENT | FLAG_USER_VERIFIED
+    } else {
+      FLAG_USER_PRESENT
+    };
+    let auth_data = self.auth_data(&options.rp_id, flags);
+    let client_data_json = self.client_data("webauthn.get", &options.challenge);
+    let mut message = auth_data.clone();
+    message.extend_from_slice(&Sha256::digest(
+      URL_SAFE_NO_PAD.decode(&client_data_json).unwrap(),
+    ));
+    let signature = Signer::new(MessageDigest::sha256(), &self.key)
+      .unwrap()
+      .sign_oneshot_to_vec(&message)
+      .unwrap();
+    PasskeyAssertionCredential {
+      id: self.credential_id(),
+      response: PasskeyAssertionResponse {
+        client_data_json,
+        authenticator_data: URL_SAFE_NO_PAD.encode(auth_data),
+        signature: URL_SAFE_NO_PAD.encode(signature),
+        user_handle: self.user_handle.clone(),
+      },
+    }
+  }
+
+  fn auth_data(&self, rp_id: &str, flags: u8) -> Vec<u8> {
+    let mut auth_data = Sha256::digest(rp_id.as_bytes()).to_vec();
+    auth_data.push(flags);
+    auth_data.extend_from_slice(&self.sign_count.to_be_bytes());
+    auth_data
+  }
+
+  fn client_data(&self, kind: &str, challenge: &str) -> String {
+    let json = serde_json::json!({
+      "type": kind,
+      "challenge": challenge,
+      "origin": self.origin,
+      "crossOrigin": false,
+    });
+    URL_SAFE_NO_PAD.encode(json.to_string())
+  }
+
+  fn cose_key(&self) -> Value {
+    let key = self.key.ec_key().unwrap();
+    let mut x = BigNum::new().unwrap();
+    let mut y = BigNum::new().unwrap();
+    key
+      .public_key()
+      .affine_coordinates(
+        key.group(),
+        &mut x,
+        &mut y,
+        &mut BigNumContext::new().unwrap(),
+      )
+      .unwrap();
+    Value::Map(vec![
+      (1.into(), 2.into()),
+      (3.into(), (-7).into()),
+      ((-1).into(), 1.into()),
+      ((-2).into(), Value::Bytes(x.to_vec_padded(32).unwrap())),
+      ((-3).into(), Value::Bytes(y.to_vec_padded(32).unwrap())),
+    ])
+  }
+}
@@ -0,0 +1,182 @@
+use openssl::asn1::{Asn1Integer, Asn1Time};
+use openssl::bn::BigNum;
+use openssl::ec::{EcGroup, EcKey};
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::{PKey, Private};
+use openssl::x509::extension::{BasicConstraints, SubjectAlternativeName};
+use openssl::x509::{X509, X509NameBuilder};
+use rustfulapi::configure::AppConfig;
+use rustfulapi::configure::server::TlsConfig;
+use rustfulapi::error::AppResult;
+use rustfulapi::util::certificate::ClientCertificate;
+use tokio::task::JoinHandle;
+
+use crate::context::app::spawn_server;
+
+/// A certificate authority signing the server and client certificates of a test.
+pub struct TestPki {
+  key: PKey<Private>,
+  certificate: X509,
+}
+
+/// A certificate with its PKCS#8 private key.
+pub struct TestCertificate {
+  pub certificate: X509,
+  pub private_key: PKey<Private>,
+}
+
+impl TestPki {
+  pub fn generate() -> Self {
+    let key = generate_key();
+    let certificate = sign(&key, "Test CA", &key, None, |builder| {
+      builder.append_extension(BasicConstraints::new().critical().ca().build()?)
+    });
+    Self { key, certificate }
+  }
+
+  pub fn ca_pem(&self) -> String {
+    String::from_utf8(self.certificate.to_pem().unwrap()).unwrap()
+  }
+
+  pub fn server(&self) -> TestCertificate {
+    self.issue("localhost", &["localhost"], &["127.0.0.1"], &[])
+  }
+
+  pub fn client(&self, common_name: &str) -> TestCertificate {
+    self.issue(common_name, &[], &[], &[])
+  }
+
+  pub fn client_with_email(&self, common_name: &str, email: &str) -> TestCertificate {
+    self.issue(common_name, &[], &[], &[email])
+  }
+
+  fn issue(
+    &self,
+    common_name: &str,
+    dns_names: &[&str],
+    ips: &[&str],
+    emails: &[&str],
+  ) -> TestCertificate {
+    let private_key = generate_key();
+    let certificate = sign(
+      &private_key,
+      common_name,
+      &self.key,
+      Some(&self.certificate),
+      |builder| {
+        if dns_names.is_empty() && ips.is_empty() && emails.is_empty() {
+          return Ok(());
+        }
+        let mut san = SubjectAlternativeName::new();
+        dns_names.iter().for_each(|name| {
+          san.dns(name);
+        });
+        ips.iter().for_each(|ip| {
+          san.ip(ip);
+        });
+        emails.iter().for_each(|email| {
+          san.email(email);
+        });
+        let san = san.build(&builder.x509v3_context(Some(&self.certificate), None))?;
+        builder.append_extension(san)
+      },
+    );
+    TestCertificate {
+      certificate,
+      private_key,
+    }
+  }
+}
+
+impl TestCertificate {
+  pub fn pem(&self) -> String {
+    String::from_utf8(self.certificate.to_pem().unwrap()).unwrap()
+  }
+
+  pub fn private_key_pem(&self) -> String {
+    String::from_utf8(self.private_key.private_key_to_pem_pkcs8().unwrap()).unwrap()
+  }
+
+  pub fn thumbprint(&self) -> String {
+    ClientCertificate::from_x509(&self.certificate)
+      .unwrap()
+      .thumbprint
+  }
+
+  pub fn identity(&self) -> reqwest::Identity {
+    reqwest::Identity::from_pkcs8_pem(self.pem().as_bytes(), self.private_key_pem().as_bytes())
+      .unwrap()
+  }
+}
+
+/// Serves the app of `config` over TLS, on its database and redis.
+pub async fn serve(
+  mut config: AppConfig,
+  pki: &TestPki,
+  certificate_bound_tokens: bool,
+) -> (String, JoinHandle<AppResult>) {
+  let server = pki.server();
+  config.server.tls = TlsConfig {
+    enabled: true,
+    certificate: Some(server.pem().into()),
+    private_key: Some(server.private_key_pem().into()),
+    client_ca: Some(pki.ca_pem().into()),
+    certificate_bound_tokens,
+  };
+  let (state, task) = spawn_server(config).await;
+  let addr = format!("https://localhost:{}", state.config.server.port);
+  (addr, task)
+}
+
+/// A client trusting the test CA, which presents `certificate` when given.
+pub fn client(pki: &TestPki, certificate: Option<&TestCertificate>) -> reqwest::Client {
+  let ca = reqwest::Certificate::from_pem(pki.ca_pem().as_bytes()).unwrap();
+  let mut builder = reqwest::Client::builder()
+    .use_native_tls()
+    .tls_built_in_root_certs(false)
+    .add_root_certificate(ca);
+  if let Some(certificate) = certificate {
+    builder = builder.identity(certificate.identity());
+  }
+  builder.build().unwrap()
+}
+
+fn generate_key() -> PKey<Private> {
+  let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+  PKey::from_ec_key(EcKey::generate(&group).unwrap()).unwrap()
+}
+
+fn sign(
+  key: &PKey<Private>,
+  common_name: &str,
+  issuer_key: &PKey<Private>,
+  issuer: Option<&X509>,
+  extend: impl FnOnce(&mut openssl::x509::X509Builder) -> Result<(), openssl::error::ErrorStack>,
+) -> X509 {
+  let mut name = X509NameBuilder::new().unwrap();
+  name
+    .append_entry_by_nid(Nid::COMMONNAME, common_name)
+    .unwrap();
+  let name = name.build();
+  let mut builder = X509::builder().unwrap();
+  builder.set_version(2).unwrap();
+  let serial = BigNum::from_u32(rand::random::<u32>()).unwrap();
+  builder
+    .set_serial_number(&Asn1Integer::from_bn(&serial).unwrap())
+    .unwrap();
+  builder.set_subject_name(&name).unwrap();
+  builder
+    .set_issuer_name(issuer.map_or(&*name, |issuer| issuer.subject_name()))
+    .unwrap();
+  builder.set_pubkey(key).unwrap();
+  builder
+    .set_not_before(&Asn1Time::days_from_now(0).unwrap())
+    .unwrap();
+  builder
+    .set_not_after(&Asn1Time::days_from_now(1).unwrap())
+    .unwrap();
+  extend(&mut builder).unwrap();
+  builder.sign(issuer_key, MessageDigest::sha256()).unwrap();
+  builder.build()
+}
@@ -4,7 +4,10 @@ use chrono::Utc;
 use fake::faker::internet::en::FreeEmail;
 use fake::{Fake, Faker};
 use rustfulapi::{entity, util};
-use rustfulapi::{entity::role::RoleUser, error::AppResult};
+use rustfulapi::{
+  entity::{role::RoleUser, two_factor::TwoFactorMethod},
+  error::AppResult,
+};
 use sea_orm::{ActiveModelTrait, DatabaseConnection, Iterable, Set};
 use uuid::Uuid;
 
@@ -28,6 +31,9 @@ impl TestUser {
         role: Set(role),
         is_active: Set(true),
         is_2fa: Set(false),
+        is_approved: Set(true),
+        two_factor_method: Set(TwoFactorMethod::Email),
+        totp_secret: Set(None),
         create_at: Set(Utc::now()),
         update_at: Set(Utc::now()),
       };
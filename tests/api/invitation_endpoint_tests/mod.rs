@@ -0,0 +1,2 @@
+pub mod test_invitation;
+pub mod test_registration_mode;
@@ -0,0 +1,128 @@
+use crate::context::seeder::SeedDbTestContext;
+use crate::{assert_err, assert_ok, unwrap};
+use fake::faker::internet::en::SafeEmail;
+use fake::{Fake, Faker};
+use rustfulapi::dto::*;
+use rustfulapi::entity::role::RoleUser;
+use rustfulapi::error::AppResponseError;
+use rustfulapi::repo;
+use test_context::test_context;
+
+async fn get_access_token(ctx: &SeedDbTestContext, role: RoleUser) -> String {
+  let user = ctx.users.get(&role).unwrap();
+  let req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  ctx.app.api.get_token(&req).await.unwrap().access_token
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_register_with_emailed_invitation(ctx: &mut SeedDbTestContext) {
+  let token = get_access_token(ctx, RoleUser::Admin).await;
+  let email: String = SafeEmail().fake();
+  let req = CreateInvitationRequest {
+    email: Some(email.clone()),
+    role: Some(RoleUser::Admin),
+    max_uses: None,
+    expire_secs: None,
+  };
+  let (status, resp) = ctx.app.api.create_invitation(&token, &req).await.unwrap();
+  let resp = unwrap!(resp);
+  assert!(status.is_success(), "status: {status}");
+  assert_eq!(resp.invitation.role, RoleUser::Admin);
+  let (code, _) = ctx
+    .app
+    .mail
+    .get_code_and_id_from_email(&email)
+    .await
+    .unwrap();
+  assert_eq!(code, resp.code);
+  let mut other: RegisterRequest = Faker.fake();
+  other.invitation_code = Some(code.clone());
+  let (status, resp) = ctx.app.api.register(&other).await.unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind == "INVALID_INPUT_ERROR");
+  assert!(status.is_client_error(), "status: {status}");
+  let mut register: RegisterRequest = Faker.fake();
+  register.email = email.clone();
+  register.invitation_code = Some(code.clone());
+  let (status, resp) = ctx.app.api.register(&register).await.unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let user = repo::user::find_by_email(&*ctx.app.state.db, &email)
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(user.role, RoleUser::Admin);
+  let (status, resp) = ctx.app.api.list_invitations(&token).await.unwrap();
+  let resp = unwrap!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let invitation = resp.list.iter().find(|i| i.email.as_ref() == Some(&email));
+  assert_eq!(invitation.unwrap().use_count, 1);
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_invitation_max_uses_and_revoke(ctx: &mut SeedDbTestContext) {
+  let token = get_access_token(ctx, RoleUser::Admin).await;
+  let req = CreateInvitationRequest {
+    email: None,
+    role: None,
+    max_uses: Some(1),
+    expire_secs: Some(3600),
+  };
+  let (status, resp) = ctx.app.api.create_invitation(&token, &req).await.unwrap();
+  let resp = unwrap!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let mut register: RegisterRequest = Faker.fake();
+  register.invitation_code = Some(resp.code.clone());
+  let (status, resp_register) = ctx.app.api.register(&register).await.unwrap();
+  assert_ok!(resp_register);
+  assert!(status.is_success(), "status: {status}");
+  let mut register: RegisterRequest = Faker.fake();
+  register.invitation_code = Some(resp.code.clone());
+  let (status, resp_register) = ctx.app.api.register(&register).await.unwrap();
+  assert_err!(resp_register, |e: &AppResponseError| e.kind
+    == "INVALID_INPUT_ERROR");
+  assert!(status.is_client_error(), "status: {status}");
+  let id = resp.invitation.id;
+  let (status, resp) = ctx.app.api.revoke_invitation(&token, &id).await.unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let (status, resp) = ctx.app.api.revoke_invitation(&token, &id).await.unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind
+    == "INVITATION_NOT_FOUND_ERROR");
+  assert_eq!(status, reqwest::StatusCode::NOT_FOUND);
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_user_can_not_create_invitation_by_default(ctx: &mut SeedDbTestContext) {
+  let token = get_access_token(ctx, RoleUser::User).await;
+  let req = CreateInvitationRequest {
+    email: None,
+    role: None,
+    max_uses: None,
+    expire_secs: None,
+  };
+  let (status, resp) = ctx.app.api.create_invitation(&token, &req).await.unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind
+    == "PERMISSION_DENIED_ERROR");
+  assert_eq!(status, reqwest::StatusCode::FORBIDDEN);
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_invitation_expire_secs_out_of_range(ctx: &mut SeedDbTestContext) {
+  let token = get_access_token(ctx, RoleUser::Admin).await;
+  let req = CreateInvitationRequest {
+    email: None,
+    role: None,
+    max_uses: None,
+    expire_secs: Some(u64::MAX),
+  };
+  let (status, resp) = ctx.app.api.create_invitation(&token, &req).await.unwrap();
+  assert_eq!(status, reqwest::StatusCode::BAD_REQUEST);
+  assert_err!(resp, |e: &AppResponseError| e.kind == "INVALID_INPUT_ERROR");
+}
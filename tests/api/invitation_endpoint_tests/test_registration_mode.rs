@@ -0,0 +1,79 @@
+use std::sync::Arc;
+
+use crate::context::seeder::SeedDbTestContext;
+use fake::{Fake, Faker};
+use rustfulapi::configure::registration::RegistrationMode;
+use rustfulapi::dto::*;
+use rustfulapi::entity::role::RoleUser;
+use rustfulapi::error::AppError;
+use rustfulapi::server::state::AppState;
+use rustfulapi::service;
+use rustfulapi::util::claim::UserClaims;
+use test_context::test_context;
+
+fn registration_state(
+  state: &AppState,
+  mode: RegistrationMode,
+  allow_user_invitations: bool,
+) -> AppState {
+  let mut config = (*state.config).clone();
+  config.registration.mode = mode;
+  config.registration.allow_user_invitations = allow_user_invitations;
+  AppState {
+    config: Arc::new(config),
+    ..state.clone()
+  }
+}
+
+fn claims(ctx: &SeedDbTestContext, role: RoleUser) -> UserClaims {
+  let user = ctx.users.get(&role).unwrap();
+  UserClaims::new(
+    std::time::Duration::from_secs(60),
+    user.id,
+    Faker.fake(),
+    role,
+  )
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_register_when_registration_is_closed(ctx: &mut SeedDbTestContext) {
+  let state = registration_state(&ctx.app.state, RegistrationMode::Closed, false);
+  let req: RegisterRequest = Faker.fake();
+  let err = service::user::register(state, req).await.unwrap_err();
+  assert!(matches!(err, AppError::PermissionDeniedError(_)));
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_register_when_registration_is_invite_only(ctx: &mut SeedDbTestContext) {
+  let state = registration_state(&ctx.app.state, RegistrationMode::InviteOnly, true);
+  let req: RegisterRequest = Faker.fake();
+  let err = service::user::register(state.clone(), req)
+    .await
+    .unwrap_err();
+  assert!(matches!(err, AppError::InvalidInputError(_)));
+  let user = claims(ctx, RoleUser::User);
+  let req = CreateInvitationRequest {
+    email: None,
+    role: Some(RoleUser::Admin),
+    max_uses: None,
+    expire_secs: None,
+  };
+  let err = service::invitation::create(&state, &user, req)
+    .await
+    .unwrap_err();
+  assert!(matches!(err, AppError::InvalidInputError(_)));
+  let req = CreateInvitationRequest {
+    email: None,
+    role: None,
+    max_uses: None,
+    expire_secs: None,
+  };
+  let invitation = service::invitation::create(&state, &user, req)
+    .await
+    .unwrap();
+  let mut req: RegisterRequest = Faker.fake();
+  req.invitation_code = Some(invitation.code);
+  service::user::register(state, req).await.unwrap();
+}
@@ -1,6 +1,8 @@
 mod admin_endpoint_tests;
 mod context;
 mod helper;
+mod invitation_endpoint_tests;
+mod oauth_endpoint_tests;
 mod server_endpoint_tests;
 mod test_invalid_request;
 mod token_endpoint_tests;
@@ -0,0 +1,42 @@
+use rustfulapi::dto::*;
+use rustfulapi::entity::role::RoleUser;
+use rustfulapi::entity::scope::Scope;
+
+use crate::context::seeder::SeedDbTestContext;
+use crate::unwrap;
+
+mod test_oauth_authorize;
+mod test_oauth_client;
+mod test_oauth_device;
+
+pub const REDIRECT_URI: &str = "http://localhost/callback";
+
+/// Registers a client through the admin endpoint.
+pub async fn create_client(
+  ctx: &SeedDbTestContext,
+  confidential: bool,
+) -> CreateOauthClientResponse {
+  let admin = ctx.users.get(&RoleUser::Admin).unwrap();
+  let token = ctx
+    .app
+    .api
+    .get_token(&LoginRequest {
+      email: admin.email.clone(),
+      password: admin.password.clone(),
+    })
+    .await
+    .unwrap();
+  let req = CreateOauthClientRequest {
+    name: "Dashboard".to_string(),
+    redirect_uris: vec![REDIRECT_URI.to_string()],
+    scope: Some(vec![Scope::UserRead, Scope::AdminRead]),
+    confidential,
+  };
+  let (_, resp) = ctx
+    .app
+    .api
+    .create_oauth_client(&token.access_token, &req)
+    .await
+    .unwrap();
+  unwrap!(resp)
+}
@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+
+use crate::assert_ok;
+use crate::context::seeder::SeedDbTestContext;
+use crate::helper::user::TestUser;
+use crate::oauth_endpoint_tests::{REDIRECT_URI, create_client};
+use fake::{Fake, Faker};
+use reqwest::StatusCode;
+use rustfulapi::constant::ACCESS_TOKEN_KEY_RING;
+use rustfulapi::dto::*;
+use rustfulapi::entity::role::RoleUser;
+use rustfulapi::entity::scope::Scope;
+use rustfulapi::util::claim::{IdTokenClaims, UserClaims};
+use rustfulapi::{service, util};
+use test_context::test_context;
+
+fn authorize_params(client_id: &str, code_verifier: Option<&str>) -> AuthorizeParams {
+  AuthorizeParams {
+    response_type: "code".to_string(),
+    client_id: client_id.to_string(),
+    redirect_uri: REDIRECT_URI.to_string(),
+    scope: Some("openid email profile user:read admin:read".to_string()),
+    state: Some(Faker.fake()),
+    nonce: Some(Faker.fake()),
+    code_challenge: code_verifier.map(service::oidc::code_challenge),
+    code_challenge_method: code_verifier.map(|_| "S256".to_string()),
+  }
+}
+
+fn consent_request(
+  params: AuthorizeParams,
+  user: &TestUser,
+  decision: AuthorizeDecision,
+) -> AuthorizeConsentRequest {
+  AuthorizeConsentRequest {
+    params,
+    email: user.email.clone(),
+    password: user.password.clone(),
+    code: None,
+    decision,
+  }
+}
+
+/// The query of the redirect back to the client.
+fn redirect_query(resp: &reqwest::Response) -> HashMap<String, String> {
+  assert_eq!(resp.status(), StatusCode::SEE_OTHER);
+  let location = resp.headers()[reqwest::header::LOCATION].to_str().unwrap();
+  assert!(location.starts_with(REDIRECT_URI), "location: {location}");
+  url::Url::parse(location)
+    .unwrap()
+    .query_pairs()
+    .into_owned()
+    .collect()
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_authorization_code_flow_with_pkce(ctx: &mut SeedDbTestContext) {
+  let client = create_client(ctx, false).await;
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let verifier = util::random::generate_random_string(64);
+  let params = authorize_params(&client.client.client_id, Some(&verifier));
+
+  let resp = ctx.app.api.oauth_authorize(&params).await.unwrap();
+  assert_eq!(resp.status(), StatusCode::OK);
+  let page = resp.text().await.unwrap();
+  assert!(page.contains("Dashboard"));
+  assert!(page.contains(&client.client.client_id));
+
+  let resp = ctx
+    .app
+    .api
+    .oauth_consent(&consent_request(
+      params.clone(),
+      user,
+      AuthorizeDecision::Allow,
+    ))
+    .await
+    .unwrap();
+  let query = redirect_query(&resp);
+  assert_eq!(query.get("state"), params.state.as_ref());
+  let mut req = OauthTokenRequest {
+    grant_type: "authorization_code".to_string(),
+    code: query.get("code").cloned(),
+    redirect_uri: Some(REDIRECT_URI.to_string()),
+    code_verifier: Some(verifier),
+    client_id: Some(client.client.client_id.clone()),
+    ..Default::default()
+  };
+  let (status, body) = ctx.app.api.oauth_token(&req, None).await.unwrap();
+  assert_eq!(status, StatusCode::OK, "body: {body}");
+  let tokens: OauthTokenResponse = serde_json::from_value(body).unwrap();
+  // admin:read is dropped, the user does not have it
+  assert_eq!(
+    tokens.scope.as_deref(),
+    Some("openid email profile user:read")
+  );
+  let id_token = IdTokenClaims::decode(
+    tokens.id_token.as_deref().unwrap(),
+    &ACCESS_TOKEN_KEY_RING,
+    &client.client.client_id,
+  )
+  .unwrap()
+  .claims;
+  assert_eq!(id_token.sub, user.id.to_string());
+  assert_eq!(id_token.nonce, params.nonce);
+  assert_eq!(id_token.email.as_deref(), Some(user.email.as_str()));
+  assert_eq!(
+    id_token.preferred_username.as_deref(),
+    Some(user.username.as_str())
+  );
+  let claims = UserClaims::decode(&tokens.access_token, &ACCESS_TOKEN_KEY_RING)
+    .unwrap()
+    .claims;
+  assert_eq!(claims.scope, vec![Scope::UserRead]);
+
+  let (status, resp) = ctx
+    .app
+    .api
+    .oauth_userinfo(&tokens.access_token)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  assert_ok!(resp, |r: &UserInfoResponse| r.sub == user.id.to_string()
+    && r.email == user.email);
+
+  let (status, body) = ctx.app.api.oauth_token(&req, None).await.unwrap();
+  assert_eq!(status, StatusCode::BAD_REQUEST);
+  assert_eq!(body["error"], "invalid_grant");
+
+  req.grant_type = "refresh_token".to_string();
+  req.refresh_token = Some(tokens.refresh_token);
+  let (status, body) = ctx.app.api.oauth_token(&req, None).await.unwrap();
+  assert_eq!(status, StatusCode::OK, "body: {body}");
+  let tokens: OauthTokenResponse = serde_json::from_value(body).unwrap();
+  let claims = UserClaims::decode(&tokens.access_token, &ACCESS_TOKEN_KEY_RING)
+    .unwrap()
+    .claims;
+  assert_eq!(claims.scope, vec![Scope::UserRead]);
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_refresh_token_of_another_client(ctx: &mut SeedDbTestContext) {
+  let client = create_client(ctx, false).await;
+  let other = create_client(ctx, false).await;
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let verifier = util::random::generate_random_string(64);
+  let params = authorize_params(&client.client.client_id, Some(&verifier));
+  let resp = ctx
+    .app
+    .api
+    .oauth_consent(&consent_request(params, user, AuthorizeDecision::Allow))
+    .await
+    .unwrap();
+  let query = redirect_query(&resp);
+  let req = OauthTokenRequest {
+    grant_type: "authorization_code".to_string(),
+    code: query.get("code").cloned(),
+    redirect_uri: Some(REDIRECT_URI.to_string()),
+    code_verifier: Some(verifier),
+    client_id: Some(client.client.client_id.clone()),
+    ..Default::default()
+  };
+  let (status, body) = ctx.app.api.oauth_token(&req, None).await.unwrap();
+  assert_eq!(status, StatusCode::OK, "body: {body}");
+  let tokens: OauthTokenResponse = serde_json::from_value(body).unwrap();
+  let mut req = OauthTokenRequest {
+    grant_type: "refresh_token".to_string(),
+    refresh_token: Some(tokens.refresh_token),
+    client_id: Some(other.client.client_id.clone()),
+    ..Default::default()
+  };
+  let (status, body) = ctx.app.api.oauth_token(&req, None).await.unwrap();
+  assert_eq!(status, StatusCode::BAD_REQUEST);
+  assert_eq!(body["error"], "invalid_grant");
+  // the session is left to the client it was issued to
+  req.client_id = Some(client.client.client_id.clone());
+  let (status, body) = ctx.app.api.oauth_token(&req, None).await.unwrap();
+  assert_eq!(status, StatusCode::OK, "body: {body}");
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_authorization_code_with_wrong_verifier(ctx: &mut SeedDbTestContext) {
+  let client = create_client(ctx, false).await;
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let verifier = "a".repeat(43);
+  let params = authorize_params(&client.client.client_id, Some(&verifier));
+  let resp = ctx
+    .app
+    .api
+    .oauth_consent(&consent_request(params, user, AuthorizeDecision::Allow))
+    .await
+    .unwrap();
+  let query = redirect_query(&resp);
+  let req = OauthTokenRequest {
+    grant_type: "authorization_code".to_string(),
+    code: query.get("code").cloned(),
+    redirect_uri: Some(REDIRECT_URI.to_string()),
+    code_verifier: Some("b".repeat(43)),
+    client_id: Some(client.client.client_id.clone()),
+    ..Default::default()
+  };
+  let (status, body) = ctx.app.api.oauth_token(&req, None).await.unwrap();
+  assert_eq!(status, StatusCode::BAD_REQUEST);
+  assert_eq!(body["error"], "invalid_grant");
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_confidential_client_authentication(ctx: &mut SeedDbTestContext) {
+  let client = create_client(ctx, true).await;
+  let secret = client.client_secret.clone().unwrap();
+  let client_id = client.client.client_id.as_str();
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let params = authorize_params(client_id, None);
+  let resp = ctx
+    .app
+    .api
+    .oauth_consent(&consent_request(params, user, AuthorizeDecision::Allow))
+    .await
+    .unwrap();
+  let query = redirect_query(&resp);
+  let req = OauthTokenRequest {
+    grant_type: "authorization_code".to_string(),
+    code: query.get("code").cloned(),
+    redirect_uri: Some(REDIRECT_URI.to_string()),
+    ..Default::default()
+  };
+  let (status, body) = ctx
+    .app
+    .api
+    .oauth_token(&req, Some((client_id, "wrong")))
+    .await
+    .unwrap();
+  assert_eq!(status, StatusCode::UNAUTHORIZED);
+  assert_eq!(body["error"], "invalid_client");
+  let (status, body) = ctx
+    .app
+    .api
+    .oauth_token(&req, Some((client_id, &secret)))
+    .await
+    .unwrap();
+  assert_eq!(status, StatusCode::OK, "body: {body}");
+  assert!(body["id_token"].is_string());
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_authorize_with_invalid_request(ctx: &mut SeedDbTestContext) {
+  let client = create_client(ctx, false).await;
+  let client_id = client.client.client_id.as_str();
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+
+  let mut params = authorize_params(client_id, Some(&"a".repeat(43)));
+  params.redirect_uri = "http://localhost/other".to_string();
+  let resp = ctx.app.api.oauth_authorize(&params).await.unwrap();
+  assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+  let params = authorize_params(client_id, None);
+  let resp = ctx.app.api.oauth_authorize(&params).await.unwrap();
+  assert_eq!(redirect_query(&resp)["error"], "invalid_request");
+
+  let mut params = authorize_params(client_id, Some(&"a".repeat(43)));
+  params.scope = Some("openid token:info".to_string());
+  let resp = ctx.app.api.oauth_authorize(&params).await.unwrap();
+  assert_eq!(redirect_query(&resp)["error"], "invalid_scope");
+
+  let params = authorize_params(client_id, Some(&"a".repeat(43)));
+  let resp = ctx
+    .app
+    .api
+    .oauth_consent(&consent_request(
+      params.clone(),
+      user,
+      AuthorizeDecision::Deny,
+    ))
+    .await
+    .unwrap();
+  let query = redirect_query(&resp);
+  assert_eq!(query["error"], "access_denied");
+  assert_eq!(query.get("state"), params.state.as_ref());
+
+  let mut req = consent_request(params, user, AuthorizeDecision::Allow);
+  req.password = "wrong password".to_string();
+  let resp = ctx.app.api.oauth_consent(&req).await.unwrap();
+  assert_eq!(resp.status(), StatusCode::OK);
+  assert!(resp.text().await.unwrap().contains("id=\"error\""));
+}
@@ -0,0 +1,138 @@
+use crate::context::seeder::SeedDbTestContext;
+use crate::{assert_err, assert_ok, unwrap};
+use rustfulapi::dto::*;
+use rustfulapi::entity::role::RoleUser;
+use rustfulapi::entity::scope::Scope;
+use rustfulapi::error::AppResponseError;
+use test_context::test_context;
+
+fn create_request(confidential: bool) -> CreateOauthClientRequest {
+  CreateOauthClientRequest {
+    name: "Dashboard".to_string(),
+    redirect_uris: vec!["http://localhost/callback".to_string()],
+    scope: Some(vec![Scope::UserRead]),
+    confidential,
+  }
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_admin_create_list_and_delete_client(ctx: &mut SeedDbTestContext) {
+  let admin = ctx.users.get(&RoleUser::Admin).unwrap();
+  let token = ctx
+    .app
+    .api
+    .get_token(&LoginRequest {
+      email: admin.email.clone(),
+      password: admin.password.clone(),
+    })
+    .await
+    .unwrap();
+  let (status, resp) = ctx
+    .app
+    .api
+    .create_oauth_client(&token.access_token, &create_request(true))
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let resp = unwrap!(resp);
+  assert!(resp.client_secret.is_some());
+  assert!(resp.client.confidential);
+  assert_eq!(resp.client.scope, vec![Scope::UserRead]);
+
+  let (status, list) = ctx
+    .app
+    .api
+    .list_oauth_clients(&token.access_token)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  assert_ok!(list, |r: &OauthClientListResponse| r
+    .list
+    .iter()
+    .any(|c| c.client_id == resp.client.client_id));
+
+  let (status, _) = ctx
+    .app
+    .api
+    .delete_oauth_client(&token.access_token, &resp.client.id)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let (status, resp) = ctx
+    .app
+    .api
+    .delete_oauth_client(&token.access_token, &resp.client.id)
+    .await
+    .unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind
+    == "OAUTH_CLIENT_NOT_FOUND_ERROR");
+  assert_eq!(status, reqwest::StatusCode::NOT_FOUND);
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_create_client_with_invalid_redirect_uri(ctx: &mut SeedDbTestContext) {
+  let admin = ctx.users.get(&RoleUser::Admin).unwrap();
+  let token = ctx
+    .app
+    .api
+    .get_token(&LoginRequest {
+      email: admin.email.clone(),
+      password: admin.password.clone(),
+    })
+    .await
+    .unwrap();
+  let mut req = create_request(false);
+  req.redirect_uris = vec!["http://localhost/callback#fragment".to_string()];
+  let (status, resp) = ctx
+    .app
+    .api
+    .create_oauth_client(&token.access_token, &req)
+    .await
+    .unwrap();
+  assert_err!(resp);
+  assert_eq!(status, reqwest::StatusCode::BAD_REQUEST);
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_user_create_client(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let token = ctx
+    .app
+    .api
+    .get_token(&LoginRequest {
+      email: user.email.clone(),
+      password: user.password.clone(),
+    })
+    .await
+    .unwrap();
+  let (status, resp) = ctx
+    .app
+    .api
+    .create_oauth_client(&token.access_token, &create_request(false))
+    .await
+    .unwrap();
+  assert_err!(resp);
+  assert_eq!(status, reqwest::StatusCode::FORBIDDEN);
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_openid_configuration(ctx: &mut SeedDbTestContext) {
+  let (status, resp) = ctx.app.api.openid_configuration().await.unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let resp = unwrap!(resp);
+  assert_eq!(resp.issuer, ctx.app.state.config.secret.issuer);
+  assert!(resp.token_endpoint.ends_with("/oauth2/token"));
+  assert!(resp.jwks_uri.ends_with("/.well-known/jwks.json"));
+  assert_eq!(resp.code_challenge_methods_supported, vec!["S256"]);
+  assert!(
+    resp
+      .dpop_signing_alg_values_supported
+      .contains(&"ES256".to_string())
+  );
+  assert!(!resp.tls_client_certificate_bound_access_tokens);
+  assert!(resp.scopes_supported.contains(&"openid".to_string()));
+}
@@ -0,0 +1,125 @@
+use crate::context::seeder::SeedDbTestContext;
+use crate::helper::user::TestUser;
+use crate::oauth_endpoint_tests::create_client;
+use reqwest::StatusCode;
+use rustfulapi::constant::{ACCESS_TOKEN_KEY_RING, DEVICE_CODE_GRANT_TYPE};
+use rustfulapi::dto::*;
+use rustfulapi::entity::role::RoleUser;
+use rustfulapi::entity::scope::Scope;
+use rustfulapi::util::claim::UserClaims;
+use test_context::test_context;
+
+async fn start(ctx: &SeedDbTestContext, client_id: &str) -> DeviceAuthorizationResponse {
+  let req = DeviceAuthorizationRequest {
+    client_id: Some(client_id.to_string()),
+    scope: Some("user:read admin:read".to_string()),
+    ..Default::default()
+  };
+  let (status, body) = ctx.app.api.oauth_device_authorization(&req).await.unwrap();
+  assert_eq!(status, StatusCode::OK, "body: {body}");
+  serde_json::from_value(body).unwrap()
+}
+
+fn poll_request(client_id: &str, device_code: &str) -> OauthTokenRequest {
+  OauthTokenRequest {
+    grant_type: DEVICE_CODE_GRANT_TYPE.to_string(),
+    device_code: Some(device_code.to_string()),
+    client_id: Some(client_id.to_string()),
+    ..Default::default()
+  }
+}
+
+fn verify_request(
+  user_code: &str,
+  user: &TestUser,
+  decision: AuthorizeDecision,
+) -> DeviceVerificationRequest {
+  DeviceVerificationRequest {
+    user_code: user_code.to_string(),
+    email: user.email.clone(),
+    password: user.password.clone(),
+    code: None,
+    decision,
+  }
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_device_authorization_flow(ctx: &mut SeedDbTestContext) {
+  let client = create_client(ctx, false).await;
+  let client_id = client.client.client_id.as_str();
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let device = start(ctx, client_id).await;
+  assert!(device.verification_uri_complete.contains(&device.user_code));
+  let poll = poll_request(client_id, &device.device_code);
+
+  let (status, body) = ctx.app.api.oauth_token(&poll, None).await.unwrap();
+  assert_eq!(status, StatusCode::BAD_REQUEST);
+  assert_eq!(body["error"], "authorization_pending");
+  let (_, body) = ctx.app.api.oauth_token(&poll, None).await.unwrap();
+  assert_eq!(body["error"], "slow_down");
+
+  // the code is accepted as typed, in lower case and without the separator
+  let typed = device.user_code.replace('-', "").to_lowercase();
+  let (status, page) = ctx.app.api.oauth_device_page(&typed).await.unwrap();
+  assert_eq!(status, StatusCode::OK);
+  assert!(page.contains("Dashboard"));
+
+  let mut req = verify_request(&typed, user, AuthorizeDecision::Allow);
+  req.password = "wrong password".to_string();
+  let (_, page) = ctx.app.api.oauth_device_verify(&req).await.unwrap();
+  assert!(page.contains("id=\"error\""));
+  let req = verify_request(&typed, user, AuthorizeDecision::Allow);
+  let (_, page) = ctx.app.api.oauth_device_verify(&req).await.unwrap();
+  assert!(page.contains("id=\"message\""));
+  let (_, page) = ctx.app.api.oauth_device_verify(&req).await.unwrap();
+  assert!(page.contains("id=\"error\""));
+
+  let (status, body) = ctx.app.api.oauth_token(&poll, None).await.unwrap();
+  assert_eq!(status, StatusCode::OK, "body: {body}");
+  let tokens: OauthTokenResponse = serde_json::from_value(body).unwrap();
+  let claims = UserClaims::decode(&tokens.access_token, &ACCESS_TOKEN_KEY_RING)
+    .unwrap()
+    .claims;
+  assert_eq!(claims.uid, user.id);
+  assert_eq!(claims.scope, vec![Scope::UserRead]);
+
+  let (status, body) = ctx.app.api.oauth_token(&poll, None).await.unwrap();
+  assert_eq!(status, StatusCode::BAD_REQUEST);
+  assert_eq!(body["error"], "invalid_grant");
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_device_authorization_denied(ctx: &mut SeedDbTestContext) {
+  let client = create_client(ctx, false).await;
+  let client_id = client.client.client_id.as_str();
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let device = start(ctx, client_id).await;
+
+  let req = verify_request("BCDF-GHJK", user, AuthorizeDecision::Allow);
+  let (_, page) = ctx.app.api.oauth_device_verify(&req).await.unwrap();
+  assert!(page.contains("id=\"error\""));
+
+  // a denial without signing in leaves the request pending
+  let mut req = verify_request(&device.user_code, user, AuthorizeDecision::Deny);
+  req.password = "wrong-password".to_string();
+  let (_, page) = ctx.app.api.oauth_device_verify(&req).await.unwrap();
+  assert!(page.contains("id=\"error\""));
+  let poll = poll_request(client_id, &device.device_code);
+  let (_, body) = ctx.app.api.oauth_token(&poll, None).await.unwrap();
+  assert_eq!(body["error"], "authorization_pending");
+
+  let req = verify_request(&device.user_code, user, AuthorizeDecision::Deny);
+  let (_, page) = ctx.app.api.oauth_device_verify(&req).await.unwrap();
+  assert!(page.contains("id=\"message\""));
+  let (status, body) = ctx.app.api.oauth_token(&poll, None).await.unwrap();
+  assert_eq!(status, StatusCode::BAD_REQUEST);
+  assert_eq!(body["error"], "access_denied");
+
+  let other = create_client(ctx, false).await;
+  let device = start(ctx, client_id).await;
+  let poll = poll_request(&other.client.client_id, &device.device_code);
+  let (_, body) = ctx.app.api.oauth_token(&poll, None).await.unwrap();
+  assert_eq!(body["error"], "invalid_grant");
+}
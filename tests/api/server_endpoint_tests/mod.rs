@@ -1,2 +1,3 @@
 pub mod test_server_health_check;
+pub mod test_server_jwks;
 pub mod test_server_state;
@@ -0,0 +1,29 @@
+use jsonwebtoken::{DecodingKey, Validation};
+use rustfulapi::dto::LoginRequest;
+use rustfulapi::entity::role::RoleUser;
+use rustfulapi::util::claim::{DECODE_HEADER, UserClaims};
+use test_context::test_context;
+
+use crate::{context::seeder::SeedDbTestContext, unwrap};
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_server_jwks_verify_access_token(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let token = ctx.app.api.get_token(&req).await.unwrap();
+  let (status, resp) = ctx.app.api.jwks().await.unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let jwks = unwrap!(resp);
+  let header = jsonwebtoken::decode_header(&token.access_token).unwrap();
+  let jwk = jwks.find(&header.kid.unwrap()).unwrap();
+  let key = DecodingKey::from_jwk(jwk).unwrap();
+  let validation: &Validation = &DECODE_HEADER;
+  let claims = jsonwebtoken::decode::<UserClaims>(&token.access_token, &key, validation)
+    .unwrap()
+    .claims;
+  assert_eq!(claims.uid, user.id);
+}
@@ -1,2 +1,4 @@
 mod test_refresh_token;
+mod test_token_cookie;
+mod test_token_dpop;
 mod test_token_info;
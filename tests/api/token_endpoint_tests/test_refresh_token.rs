@@ -49,3 +49,50 @@ pub async fn test_refresh_token(ctx: &mut SeedDbTestContext) {
     }
   }
 }
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_refresh_token_reuse_revokes_family(ctx: &mut SeedDbTestContext) {
+  let user = ctx
+    .users
+    .get(&rustfulapi::entity::role::RoleUser::User)
+    .unwrap();
+  let req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let first = ctx.app.api.get_token(&req).await.unwrap();
+  let first_req = RefreshTokenRequest {
+    token: first.refresh_token,
+  };
+  let (status, resp) = ctx.app.api.refresh_token(&first_req).await.unwrap();
+  assert!(status.is_success(), "status: {status:?}");
+  let second = unwrap!(resp);
+  let second_req = RefreshTokenRequest {
+    token: second.refresh_token,
+  };
+  let (status, resp) = ctx.app.api.refresh_token(&second_req).await.unwrap();
+  assert!(status.is_success(), "status: {status:?}");
+  let third = unwrap!(resp);
+  let (status, _) = ctx.app.api.get_profile(&third.access_token).await.unwrap();
+  assert!(status.is_success(), "status: {status:?}");
+  let (status, resp) = ctx.app.api.refresh_token(&first_req).await.unwrap();
+  assert!(!status.is_success(), "status: {status:?}");
+  assert_err!(resp, |e: &AppResponseError| e.kind
+    == "INVALID_SESSION_ERROR");
+  let (status, _) = ctx.app.api.get_profile(&third.access_token).await.unwrap();
+  assert!(!status.is_success(), "status: {status:?}");
+  let third_req = RefreshTokenRequest {
+    token: third.refresh_token,
+  };
+  let (status, _) = ctx.app.api.refresh_token(&third_req).await.unwrap();
+  assert!(!status.is_success(), "status: {status:?}");
+  let events = rustfulapi::repo::security_event::find_by_user(&*ctx.app.state.db, user.id)
+    .await
+    .unwrap();
+  assert_eq!(events.len(), 1);
+  assert_eq!(
+    events[0].kind,
+    rustfulapi::entity::security_event::SecurityEventKind::RefreshTokenReuse
+  );
+}
@@ -0,0 +1,220 @@
+use crate::context::app::spawn_server;
+use crate::context::seeder::SeedDbTestContext;
+use crate::helper::result::AppResponseResult;
+use crate::{assert_err, unwrap};
+use reqwest::StatusCode;
+use reqwest::header::{COOKIE, SET_COOKIE};
+use rustfulapi::constant::{
+  ACCESS_TOKEN_COOKIE, CSRF_COOKIE, CSRF_HEADER, HTTP, REFRESH_TOKEN_COOKIE,
+};
+use rustfulapi::dto::*;
+use rustfulapi::entity::role::RoleUser;
+use rustfulapi::error::AppResponseError;
+use test_context::test_context;
+
+/// The `Set-Cookie` headers of a response, by cookie name.
+struct SetCookies(Vec<String>);
+
+impl SetCookies {
+  fn of(resp: &reqwest::Response) -> Self {
+    Self(
+      resp
+        .headers()
+        .get_all(SET_COOKIE)
+        .iter()
+        .map(|value| value.to_str().unwrap().to_string())
+        .collect(),
+    )
+  }
+
+  fn header(&self, name: &str) -> Option<&str> {
+    self
+      .0
+      .iter()
+      .map(String::as_str)
+      .find(|header| header.starts_with(&format!("{name}=")))
+  }
+
+  fn value(&self, name: &str) -> Option<String> {
+    let header = self.header(name)?;
+    let value = header[name.len() + 1..].split(';').next()?;
+    Some(value.to_string()).filter(|value| !value.is_empty())
+  }
+}
+
+async fn serve(ctx: &mut SeedDbTestContext, access_token: bool) -> String {
+  let mut config = (*ctx.app.state.config).clone();
+  config.session_cookie.enable = true;
+  config.session_cookie.access_token = access_token;
+  let (state, task) = spawn_server(config).await;
+  // aborted on teardown with the server of the test
+  ctx.app.tasks.push(task);
+  state.config.server.get_http_addr()
+}
+
+async fn login(ctx: &SeedDbTestContext, addr: &str) -> (TokenResponse, SetCookies) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let resp = HTTP
+    .post(format!("{addr}/api/v1/user/login"))
+    .json(&LoginRequest {
+      email: user.email.clone(),
+      password: user.password.clone(),
+    })
+    .send()
+    .await
+    .unwrap();
+  assert!(resp.status().is_success(), "status: {}", resp.status());
+  let cookies = SetCookies::of(&resp);
+  match unwrap!(
+    resp
+      .json::<AppResponseResult<LoginResponse>>()
+      .await
+      .unwrap()
+  ) {
+    LoginResponse::Token(token) => (token, cookies),
+    LoginResponse::Code { .. } => panic!("login with session cookies failed."),
+  }
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_success_cookie_session(ctx: &mut SeedDbTestContext) {
+  let addr = serve(ctx, true).await;
+  let (token, cookies) = login(ctx, &addr).await;
+  assert!(token.access_token.is_empty());
+  assert!(token.refresh_token.is_empty());
+  let access = cookies.header(ACCESS_TOKEN_COOKIE).unwrap();
+  assert!(access.contains("HttpOnly"), "{access}");
+  assert!(access.contains("SameSite=Strict"), "{access}");
+  assert!(access.contains("Path=/api"), "{access}");
+  let refresh = cookies.header(REFRESH_TOKEN_COOKIE).unwrap();
+  assert!(refresh.contains("HttpOnly"), "{refresh}");
+  assert!(refresh.contains("Path=/api/v1/token/refresh"), "{refresh}");
+  assert!(!cookies.header(CSRF_COOKIE).unwrap().contains("HttpOnly"));
+  let access = cookies.value(ACCESS_TOKEN_COOKIE).unwrap();
+  let csrf = cookies.value(CSRF_COOKIE).unwrap();
+  let session = format!("{ACCESS_TOKEN_COOKIE}={access}; {CSRF_COOKIE}={csrf}");
+
+  let resp = HTTP
+    .get(format!("{addr}/api/v1/user/profile"))
+    .header(COOKIE, &session)
+    .send()
+    .await
+    .unwrap();
+  assert!(resp.status().is_success(), "status: {}", resp.status());
+
+  let update = |csrf_header: Option<&str>| {
+    let mut req = HTTP
+      .put(format!("{addr}/api/v1/user/profile"))
+      .header(COOKIE, &session)
+      .json(&UpdateProfileRequest {
+        is_private: Some(false),
+        ..Default::default()
+      });
+    if let Some(value) = csrf_header {
+      req = req.header(CSRF_HEADER, value);
+    }
+    req.send()
+  };
+  let resp = update(None).await.unwrap();
+  assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+  let resp: AppResponseResult<MessageResponse> = resp.json().await.unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind
+    == "PERMISSION_DENIED_ERROR");
+  let resp = update(Some("forged")).await.unwrap();
+  assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+  let resp = update(Some(&csrf)).await.unwrap();
+  assert!(resp.status().is_success(), "status: {}", resp.status());
+
+  let refresh_token = cookies.value(REFRESH_TOKEN_COOKIE).unwrap();
+  let refresh = |csrf_header: Option<&str>| {
+    let mut req = HTTP.post(format!("{addr}/api/v1/token/refresh")).header(
+      COOKIE,
+      format!("{REFRESH_TOKEN_COOKIE}={refresh_token}; {CSRF_COOKIE}={csrf}"),
+    );
+    if let Some(value) = csrf_header {
+      req = req.header(CSRF_HEADER, value);
+    }
+    req.send()
+  };
+  let resp = refresh(None).await.unwrap();
+  assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+  let resp = refresh(Some(&csrf)).await.unwrap();
+  assert!(resp.status().is_success(), "status: {}", resp.status());
+  let cookies = SetCookies::of(&resp);
+  let token: TokenResponse = unwrap!(resp.json::<AppResponseResult<_>>().await.unwrap());
+  assert!(token.refresh_token.is_empty());
+  assert_ne!(cookies.value(REFRESH_TOKEN_COOKIE).unwrap(), refresh_token);
+  let access = cookies.value(ACCESS_TOKEN_COOKIE).unwrap();
+  let csrf = cookies.value(CSRF_COOKIE).unwrap();
+  let session = format!("{ACCESS_TOKEN_COOKIE}={access}; {CSRF_COOKIE}={csrf}");
+
+  // logging out is a GET request, it still needs the CSRF token
+  let logout = |csrf_header: Option<&str>| {
+    let mut req = HTTP
+      .get(format!("{addr}/api/v1/user/logout"))
+      .header(COOKIE, &session);
+    if let Some(value) = csrf_header {
+      req = req.header(CSRF_HEADER, value);
+    }
+    req.send()
+  };
+  let resp = logout(None).await.unwrap();
+  assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+  let resp = logout(Some(&csrf)).await.unwrap();
+  assert!(resp.status().is_success(), "status: {}", resp.status());
+  let cookies = SetCookies::of(&resp);
+  for name in [ACCESS_TOKEN_COOKIE, REFRESH_TOKEN_COOKIE, CSRF_COOKIE] {
+    let header = cookies.header(name).unwrap();
+    assert!(header.contains("Max-Age=0"), "{header}");
+  }
+  let resp = HTTP
+    .get(format!("{addr}/api/v1/user/profile"))
+    .header(COOKIE, &session)
+    .send()
+    .await
+    .unwrap();
+  assert!(!resp.status().is_success(), "status: {}", resp.status());
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_cookie_session_keeps_access_token_in_body(ctx: &mut SeedDbTestContext) {
+  let addr = serve(ctx, false).await;
+  let (token, cookies) = login(ctx, &addr).await;
+  assert!(!token.access_token.is_empty());
+  assert!(token.refresh_token.is_empty());
+  assert!(cookies.header(ACCESS_TOKEN_COOKIE).is_none());
+  let refresh_token = cookies.value(REFRESH_TOKEN_COOKIE).unwrap();
+
+  // a request without the session cookies needs no CSRF token
+  let resp = HTTP
+    .put(format!("{addr}/api/v1/user/profile"))
+    .bearer_auth(&token.access_token)
+    .json(&UpdateProfileRequest {
+      is_private: Some(false),
+      ..Default::default()
+    })
+    .send()
+    .await
+    .unwrap();
+  assert!(resp.status().is_success(), "status: {}", resp.status());
+
+  let resp = HTTP
+    .post(format!("{addr}/api/v1/token/refresh"))
+    .send()
+    .await
+    .unwrap();
+  assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+  let resp = HTTP
+    .post(format!("{addr}/api/v1/token/refresh"))
+    .json(&RefreshTokenRequest {
+      token: refresh_token,
+    })
+    .send()
+    .await
+    .unwrap();
+  assert!(resp.status().is_success(), "status: {}", resp.status());
+  let token: TokenResponse = unwrap!(resp.json::<AppResponseResult<_>>().await.unwrap());
+  assert!(!token.access_token.is_empty());
+}
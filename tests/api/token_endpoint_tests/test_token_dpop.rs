@@ -0,0 +1,141 @@
+use crate::context::seeder::SeedDbTestContext;
+use crate::helper::dpop::DpopKey;
+use crate::{assert_err, unwrap};
+use reqwest::StatusCode;
+use rustfulapi::constant::{ACCESS_TOKEN_KEY_RING, DPOP_SCHEME};
+use rustfulapi::dto::*;
+use rustfulapi::entity::role::RoleUser;
+use rustfulapi::error::AppResponseError;
+use rustfulapi::util::claim::UserClaims;
+use test_context::test_context;
+
+const LOGIN_PATH: &str = "/api/v1/user/login";
+const PROFILE_PATH: &str = "/api/v1/user/profile";
+const REFRESH_PATH: &str = "/api/v1/token/refresh";
+
+fn login_request(ctx: &SeedDbTestContext) -> LoginRequest {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  }
+}
+
+async fn login(ctx: &SeedDbTestContext, key: &DpopKey) -> TokenResponse {
+  let proof = key.proof("POST", &ctx.app.api.url(LOGIN_PATH), None);
+  let (status, resp) = ctx
+    .app
+    .api
+    .login_with_dpop(&login_request(ctx), &proof)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  match unwrap!(resp) {
+    LoginResponse::Token(token) => token,
+    LoginResponse::Code { .. } => panic!("login with dpop failed."),
+  }
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_success_dpop_bound_tokens(ctx: &mut SeedDbTestContext) {
+  let key = DpopKey::generate();
+  let token = login(ctx, &key).await;
+  assert_eq!(token.token_type, DPOP_SCHEME);
+  let claims = UserClaims::decode(&token.access_token, &ACCESS_TOKEN_KEY_RING)
+    .unwrap()
+    .claims;
+  assert_eq!(claims.cnf.unwrap().jkt.as_deref(), Some(key.thumbprint()));
+
+  let profile_url = ctx.app.api.url(PROFILE_PATH);
+  let (status, _) = ctx.app.api.get_profile(&token.access_token).await.unwrap();
+  assert_eq!(status, StatusCode::UNAUTHORIZED);
+  let proof = key.proof("GET", &profile_url, Some(&token.access_token));
+  let (status, _) = ctx
+    .app
+    .api
+    .get_profile_with_dpop(&token.access_token, &proof)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  // a proof is accepted once
+  let (status, resp) = ctx
+    .app
+    .api
+    .get_profile_with_dpop(&token.access_token, &proof)
+    .await
+    .unwrap();
+  assert_eq!(status, StatusCode::UNAUTHORIZED);
+  assert_err!(resp, |e: &AppResponseError| e.kind == "UNAUTHORIZED_ERROR");
+  let other = DpopKey::generate().proof("GET", &profile_url, Some(&token.access_token));
+  let (status, _) = ctx
+    .app
+    .api
+    .get_profile_with_dpop(&token.access_token, &other)
+    .await
+    .unwrap();
+  assert_eq!(status, StatusCode::UNAUTHORIZED);
+  let wrong_method = key.proof("POST", &profile_url, Some(&token.access_token));
+  let (status, _) = ctx
+    .app
+    .api
+    .get_profile_with_dpop(&token.access_token, &wrong_method)
+    .await
+    .unwrap();
+  assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+  // the refresh token needs the key too, without it the session is left alone
+  let req = RefreshTokenRequest {
+    token: token.refresh_token,
+  };
+  let (status, _) = ctx.app.api.refresh_token(&req).await.unwrap();
+  assert_eq!(status, StatusCode::UNAUTHORIZED);
+  let proof = key.proof("POST", &ctx.app.api.url(REFRESH_PATH), None);
+  let (status, resp) = ctx
+    .app
+    .api
+    .refresh_token_with_dpop(&req, &proof)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let token = unwrap!(resp);
+  assert_eq!(token.token_type, DPOP_SCHEME);
+  let proof = key.proof("GET", &profile_url, Some(&token.access_token));
+  let (status, _) = ctx
+    .app
+    .api
+    .get_profile_with_dpop(&token.access_token, &proof)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_dpop_scheme_with_unbound_token(ctx: &mut SeedDbTestContext) {
+  let token = ctx.app.api.get_token(&login_request(ctx)).await.unwrap();
+  assert_eq!(token.token_type, "Bearer");
+  let key = DpopKey::generate();
+  let proof = key.proof(
+    "GET",
+    &ctx.app.api.url(PROFILE_PATH),
+    Some(&token.access_token),
+  );
+  let (status, _) = ctx
+    .app
+    .api
+    .get_profile_with_dpop(&token.access_token, &proof)
+    .await
+    .unwrap();
+  assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+  let proof = key.proof("POST", "http://localhost/other", None);
+  let (status, resp) = ctx
+    .app
+    .api
+    .login_with_dpop(&login_request(ctx), &proof)
+    .await
+    .unwrap();
+  assert_eq!(status, StatusCode::UNAUTHORIZED);
+  assert_err!(resp, |e: &AppResponseError| e.kind == "UNAUTHORIZED_ERROR");
+}
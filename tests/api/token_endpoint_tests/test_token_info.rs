@@ -1,10 +1,12 @@
 use rustfulapi::{
+  constant::CONFIG,
   dto::{LoginRequest, TokenInfoRequest},
-  entity::role::RoleUser,
+  entity::{role::RoleUser, scope::Scope},
+  error::AppResponseError,
 };
 use test_context::test_context;
 
-use crate::{assert_ok, context::seeder::SeedDbTestContext};
+use crate::{assert_err, assert_ok, context::seeder::SeedDbTestContext, unwrap};
 
 #[test_context(SeedDbTestContext)]
 #[tokio::test]
@@ -54,3 +56,59 @@ pub async fn test_get_token_with_invalid_access(ctx: &mut SeedDbTestContext) {
     .unwrap();
   assert_eq!(status, reqwest::StatusCode::FORBIDDEN);
 }
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_token_info_contains_issuer_audience_and_scope(ctx: &mut SeedDbTestContext) {
+  let system = ctx.users.get(&RoleUser::System).unwrap();
+  let req = LoginRequest {
+    email: system.email.clone(),
+    password: system.password.clone(),
+  };
+  let system_token = ctx.app.api.get_token(&req).await.unwrap();
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let user_token = ctx.app.api.get_token(&req).await.unwrap();
+  let req = TokenInfoRequest {
+    token: user_token.access_token.clone(),
+  };
+  let (status, resp) = ctx
+    .app
+    .api
+    .token_info(&system_token.access_token, &req)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let claims = unwrap!(resp);
+  assert_eq!(claims.iss, CONFIG.secret.issuer);
+  assert_eq!(claims.aud, CONFIG.secret.audience);
+  assert!(claims.nbf <= claims.exp);
+  assert_eq!(claims.scope, vec![Scope::UserRead, Scope::UserWrite]);
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_token_info_missing_scope(ctx: &mut SeedDbTestContext) {
+  let admin = ctx.users.get(&RoleUser::Admin).unwrap();
+  let req = LoginRequest {
+    email: admin.email.clone(),
+    password: admin.password.clone(),
+  };
+  let admin_token = ctx.app.api.get_token(&req).await.unwrap();
+  let req = TokenInfoRequest {
+    token: admin_token.access_token.clone(),
+  };
+  let (status, resp) = ctx
+    .app
+    .api
+    .token_info(&admin_token.access_token, &req)
+    .await
+    .unwrap();
+  assert_eq!(status, reqwest::StatusCode::FORBIDDEN);
+  assert_err!(resp, |e: &AppResponseError| e.kind
+    == "PERMISSION_DENIED_ERROR"
+    && e.error_message.contains("token:info"));
+}
@@ -1,7 +1,18 @@
 pub mod test_user_active;
+pub mod test_user_api_keys;
+pub mod test_user_certificate;
+pub mod test_user_change_email;
 pub mod test_user_forgot_password;
 pub mod test_user_login;
 pub mod test_user_logout;
+pub mod test_user_magic_link;
+pub mod test_user_oidc;
+pub mod test_user_passkey;
+pub mod test_user_privacy;
 pub mod test_user_profile;
+pub mod test_user_rate_limit;
+pub mod test_user_recovery_code;
 pub mod test_user_register;
 pub mod test_user_reset_password;
+pub mod test_user_sessions;
+pub mod test_user_totp;
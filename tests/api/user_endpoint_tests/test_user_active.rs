@@ -1,8 +1,12 @@
+use chrono::{Duration, Utc};
 use fake::{Fake, Faker};
 use rustfulapi::dto::{LoginResponse, request::*};
+use rustfulapi::entity;
+use rustfulapi::error::AppResponseError;
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, sea_query::Expr};
 use test_context::test_context;
 
-use crate::{assert_ok, context::app::AppTestContext};
+use crate::{assert_err, assert_ok, context::app::AppTestContext};
 
 #[test_context(AppTestContext)]
 #[tokio::test]
@@ -34,3 +38,82 @@ pub async fn test_active_user(ctx: &mut AppTestContext) {
   assert_ok!(resp, |d| matches!(d, &LoginResponse::Token(_)));
   assert!(status.is_success(), "status: {status}");
 }
+
+#[test_context(AppTestContext)]
+#[tokio::test]
+pub async fn test_resend_active_code(ctx: &mut AppTestContext) {
+  let req: RegisterRequest = Faker.fake();
+  let (status, resp) = ctx.api.register(&req).await.unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let (old_code, user_id) = ctx
+    .mail
+    .get_code_and_id_from_email(&req.email)
+    .await
+    .unwrap();
+  let resend_req = ResendActiveCodeRequest {
+    email: req.email.clone(),
+  };
+  let (status, resp) = ctx.api.resend_active_code(&resend_req).await.unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let (status, resp) = ctx.api.resend_active_code(&resend_req).await.unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind
+    == "TOO_MANY_REQUESTS_ERROR");
+  assert_eq!(status, reqwest::StatusCode::TOO_MANY_REQUESTS);
+  let (code, _) = ctx
+    .mail
+    .get_code_and_id_from_email(&req.email)
+    .await
+    .unwrap();
+  let (status, resp) = ctx
+    .api
+    .active(&ActiveRequest {
+      user_id,
+      code: old_code,
+    })
+    .await
+    .unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind == "INVALID_INPUT_ERROR");
+  assert!(status.is_client_error(), "status: {status}");
+  let (status, resp) = ctx
+    .api
+    .active(&ActiveRequest { user_id, code })
+    .await
+    .unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let (status, resp) = ctx.api.resend_active_code(&resend_req).await.unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind == "BAD_REQUEST_ERROR");
+  assert!(status.is_client_error(), "status: {status}");
+}
+
+#[test_context(AppTestContext)]
+#[tokio::test]
+pub async fn test_active_user_with_expired_code(ctx: &mut AppTestContext) {
+  let req: RegisterRequest = Faker.fake();
+  let (status, resp) = ctx.api.register(&req).await.unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let (code, user_id) = ctx
+    .mail
+    .get_code_and_id_from_email(&req.email)
+    .await
+    .unwrap();
+  entity::message::Entity::update_many()
+    .col_expr(
+      entity::message::Column::CreateAt,
+      Expr::value(Utc::now() - Duration::days(2)),
+    )
+    .filter(entity::message::Column::UserId.eq(user_id))
+    .exec(&*ctx.state.db)
+    .await
+    .unwrap();
+  let (status, resp) = ctx
+    .api
+    .active(&ActiveRequest { user_id, code })
+    .await
+    .unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind == "INVALID_INPUT_ERROR");
+  assert!(status.is_client_error(), "status: {status}");
+}
@@ -0,0 +1,171 @@
+use crate::assert_err;
+use crate::assert_ok;
+use crate::context::seeder::SeedDbTestContext;
+use crate::unwrap;
+use rustfulapi::dto::*;
+use rustfulapi::entity::role::RoleUser;
+use rustfulapi::entity::scope::Scope;
+use rustfulapi::error::AppResponseError;
+use rustfulapi::util::claim::UserClaims;
+use test_context::test_context;
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_success_system_api_key_token_info(ctx: &mut SeedDbTestContext) {
+  let system = ctx.users.get(&RoleUser::System).unwrap();
+  let req = LoginRequest {
+    email: system.email.clone(),
+    password: system.password.clone(),
+  };
+  let system_token = ctx.app.api.get_token(&req).await.unwrap();
+  let req = CreateApiKeyRequest {
+    name: "ci".to_string(),
+    scope: Some(vec![Scope::TokenInfo]),
+    expire_secs: Some(3600),
+  };
+  let (status, resp) = ctx
+    .app
+    .api
+    .create_api_key(&system_token.access_token, &req)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let created = unwrap!(resp);
+  assert!(created.key.starts_with("rfa_"));
+  assert_eq!(created.api_key.scope, vec![Scope::TokenInfo]);
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let user_token = ctx.app.api.get_token(&req).await.unwrap();
+  let req = TokenInfoRequest {
+    token: user_token.access_token.clone(),
+  };
+  let (status, resp) = ctx
+    .app
+    .api
+    .token_info_with_api_key(&created.key, &req)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  assert_ok!(resp, |c: &UserClaims| c.uid == user.id);
+  let (status, resp) = ctx
+    .app
+    .api
+    .get_profile_with_api_key(&created.key)
+    .await
+    .unwrap();
+  assert_eq!(status, reqwest::StatusCode::FORBIDDEN);
+  assert_err!(resp, |e: &AppResponseError| e.kind
+    == "PERMISSION_DENIED_ERROR");
+  let (_, resp) = ctx
+    .app
+    .api
+    .list_api_keys(&system_token.access_token)
+    .await
+    .unwrap();
+  let list = unwrap!(resp);
+  let listed = list
+    .list
+    .iter()
+    .find(|k| k.id == created.api_key.id)
+    .unwrap();
+  assert!(listed.last_used_at.is_some());
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_revoked_api_key_is_rejected(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let token = ctx.app.api.get_token(&req).await.unwrap();
+  let req = CreateApiKeyRequest {
+    name: "script".to_string(),
+    scope: None,
+    expire_secs: None,
+  };
+  let (status, resp) = ctx
+    .app
+    .api
+    .create_api_key(&token.access_token, &req)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let created = unwrap!(resp);
+  let (status, resp) = ctx
+    .app
+    .api
+    .get_profile_with_api_key(&created.key)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  assert_ok!(resp, |p: &ProfileResponse| p.email == user.email);
+  let (status, resp) = ctx
+    .app
+    .api
+    .revoke_api_key(&token.access_token, &created.api_key.id)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  assert_ok!(resp);
+  let (status, resp) = ctx
+    .app
+    .api
+    .get_profile_with_api_key(&created.key)
+    .await
+    .unwrap();
+  assert_eq!(status, reqwest::StatusCode::UNAUTHORIZED);
+  assert_err!(resp);
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_api_key_scope_not_granted(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let token = ctx.app.api.get_token(&req).await.unwrap();
+  let req = CreateApiKeyRequest {
+    name: "escalate".to_string(),
+    scope: Some(vec![Scope::TokenInfo]),
+    expire_secs: None,
+  };
+  let (status, resp) = ctx
+    .app
+    .api
+    .create_api_key(&token.access_token, &req)
+    .await
+    .unwrap();
+  assert_eq!(status, reqwest::StatusCode::BAD_REQUEST);
+  assert_err!(resp, |e: &AppResponseError| e.kind == "INVALID_INPUT_ERROR");
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_api_key_expire_secs_out_of_range(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let token = ctx.app.api.get_token(&req).await.unwrap();
+  let req = CreateApiKeyRequest {
+    name: "forever".to_string(),
+    scope: None,
+    expire_secs: Some(u64::MAX),
+  };
+  let (status, resp) = ctx
+    .app
+    .api
+    .create_api_key(&token.access_token, &req)
+    .await
+    .unwrap();
+  assert_eq!(status, reqwest::StatusCode::BAD_REQUEST);
+  assert_err!(resp, |e: &AppResponseError| e.kind == "INVALID_INPUT_ERROR");
+}
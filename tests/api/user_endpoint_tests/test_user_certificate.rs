@@ -0,0 +1,144 @@
+use crate::context::seeder::SeedDbTestContext;
+use crate::helper::result::AppResponseResult;
+use crate::helper::tls::{self, TestPki};
+use crate::{assert_err, unwrap};
+use reqwest::StatusCode;
+use rustfulapi::constant::{ACCESS_TOKEN_KEY_RING, BEARER};
+use rustfulapi::dto::*;
+use rustfulapi::entity::role::RoleUser;
+use rustfulapi::error::AppResponseError;
+use rustfulapi::util::claim::UserClaims;
+use test_context::test_context;
+
+async fn get_profile(
+  client: &reqwest::Client,
+  addr: &str,
+  token: Option<&str>,
+) -> (StatusCode, AppResponseResult<ProfileResponse>) {
+  let mut req = client.get(format!("{addr}/api/v1/user/profile"));
+  if let Some(token) = token {
+    req = req.bearer_auth(token);
+  }
+  let resp = req.send().await.unwrap();
+  (resp.status(), resp.json().await.unwrap())
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_success_service_account_with_client_certificate(ctx: &mut SeedDbTestContext) {
+  let pki = TestPki::generate();
+  let (addr, task) = tls::serve((*ctx.app.state.config).clone(), &pki, false).await;
+  let system = ctx.users.get(&RoleUser::System).unwrap();
+  let certificate = pki.client(&system.username);
+  let client = tls::client(&pki, Some(&certificate));
+  let (status, resp) = get_profile(&client, &addr, None).await;
+  assert!(status.is_success(), "status: {status}");
+  assert_eq!(unwrap!(resp).email, system.email);
+
+  // without a certificate a token is still needed
+  let (status, resp) = get_profile(&tls::client(&pki, None), &addr, None).await;
+  assert!(!status.is_success(), "status: {status}");
+  assert_err!(resp, |e: &AppResponseError| e.kind == "TYPE_HEADER_ERROR");
+
+  // only a service account signs in with its certificate
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let client = tls::client(&pki, Some(&pki.client(&user.username)));
+  let (status, resp) = get_profile(&client, &addr, None).await;
+  assert_eq!(status, StatusCode::UNAUTHORIZED);
+  assert_err!(resp, |e: &AppResponseError| e.kind == "UNAUTHORIZED_ERROR");
+
+  // the email of a user is skipped for the common name of the service account
+  let client = tls::client(
+    &pki,
+    Some(&pki.client_with_email(&system.username, &user.email)),
+  );
+  let (status, resp) = get_profile(&client, &addr, None).await;
+  assert!(status.is_success(), "status: {status}");
+  assert_eq!(unwrap!(resp).email, system.email);
+
+  // no session backs a certificate, there is none to end
+  let resp = client
+    .get(format!("{addr}/api/v1/user/logout"))
+    .send()
+    .await
+    .unwrap();
+  assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+  let resp = client
+    .delete(format!("{addr}/api/v1/user/sessions"))
+    .send()
+    .await
+    .unwrap();
+  assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+  // a certificate of another authority fails the handshake
+  let other = TestPki::generate();
+  let client = tls::client(&pki, Some(&other.client(&system.username)));
+  let result = client
+    .get(format!("{addr}/api/v1/user/profile"))
+    .send()
+    .await;
+  assert!(result.is_err());
+  task.abort();
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_certificate_bound_tokens(ctx: &mut SeedDbTestContext) {
+  let pki = TestPki::generate();
+  let (addr, task) = tls::serve((*ctx.app.state.config).clone(), &pki, true).await;
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let certificate = pki.client("mobile-app");
+  let client = tls::client(&pki, Some(&certificate));
+  let resp = client
+    .post(format!("{addr}/api/v1/user/login"))
+    .json(&LoginRequest {
+      email: user.email.clone(),
+      password: user.password.clone(),
+    })
+    .send()
+    .await
+    .unwrap();
+  assert!(resp.status().is_success(), "status: {}", resp.status());
+  let token = match unwrap!(
+    resp
+      .json::<AppResponseResult<LoginResponse>>()
+      .await
+      .unwrap()
+  ) {
+    LoginResponse::Token(token) => token,
+    LoginResponse::Code { .. } => panic!("login with client certificate failed."),
+  };
+  assert_eq!(token.token_type, BEARER);
+  let claims = UserClaims::decode(&token.access_token, &ACCESS_TOKEN_KEY_RING)
+    .unwrap()
+    .claims;
+  assert_eq!(
+    claims.cnf.and_then(|cnf| cnf.x5t_s256),
+    Some(certificate.thumbprint())
+  );
+
+  let (status, resp) = get_profile(&client, &addr, Some(&token.access_token)).await;
+  assert!(status.is_success(), "status: {status}");
+  assert_eq!(unwrap!(resp).email, user.email);
+  let other = tls::client(&pki, Some(&pki.client("mobile-app")));
+  let (status, resp) = get_profile(&other, &addr, Some(&token.access_token)).await;
+  assert_eq!(status, StatusCode::UNAUTHORIZED);
+  assert_err!(resp, |e: &AppResponseError| e.kind == "UNAUTHORIZED_ERROR");
+  let (status, _) = get_profile(&tls::client(&pki, None), &addr, Some(&token.access_token)).await;
+  assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+  let refresh = |client: &reqwest::Client| {
+    client
+      .post(format!("{addr}/api/v1/token/refresh"))
+      .json(&RefreshTokenRequest {
+        token: token.refresh_token.clone(),
+      })
+      .send()
+  };
+  let resp = refresh(&other).await.unwrap();
+  assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+  // the refused attempt did not rotate the refresh token
+  let resp = refresh(&client).await.unwrap();
+  assert!(resp.status().is_success(), "status: {}", resp.status());
+  task.abort();
+}
@@ -0,0 +1,129 @@
+use crate::context::seeder::SeedDbTestContext;
+use crate::helper::email::QueryKindSearch;
+use crate::{assert_err, assert_ok, unwrap};
+use fake::Fake;
+use fake::faker::internet::en::SafeEmail;
+use rustfulapi::dto::*;
+use rustfulapi::entity::role::RoleUser;
+use rustfulapi::error::AppResponseError;
+use test_context::test_context;
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_success_change_email(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let token = ctx.app.api.get_token(&req).await.unwrap();
+  let new_email: String = SafeEmail().fake();
+  let (status, resp) = ctx
+    .app
+    .api
+    .request_email_change(
+      &token.access_token,
+      &ChangeEmailRequest {
+        email: new_email.clone(),
+      },
+    )
+    .await
+    .unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let (code, user_id) = ctx
+    .app
+    .mail
+    .get_code_and_id_from_email(&new_email)
+    .await
+    .unwrap();
+  assert_eq!(user_id, user.id);
+  let notice = ctx
+    .app
+    .mail
+    .search(QueryKindSearch::To, &user.email)
+    .await
+    .unwrap();
+  assert!(notice.total > 0);
+  let (status, resp) = ctx
+    .app
+    .api
+    .confirm_email_change(&token.access_token, &ConfirmEmailRequest { code })
+    .await
+    .unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let (_, resp) = ctx.app.api.get_profile(&token.access_token).await.unwrap();
+  let resp = unwrap!(resp);
+  assert_eq!(resp.email, new_email);
+  let req = LoginRequest {
+    email: new_email,
+    password: user.password.clone(),
+  };
+  let (status, resp) = ctx.app.api.login(&req).await.unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_fail_change_email_to_existing_email(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let admin = ctx.users.get(&RoleUser::Admin).unwrap();
+  let req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let token = ctx.app.api.get_token(&req).await.unwrap();
+  let (status, resp) = ctx
+    .app
+    .api
+    .request_email_change(
+      &token.access_token,
+      &ChangeEmailRequest {
+        email: admin.email.clone(),
+      },
+    )
+    .await
+    .unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind
+    == "USER_ALREADY_EXISTS_ERROR");
+  assert!(!status.is_success(), "status: {status}");
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_fail_confirm_email_change_with_wrong_code(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let token = ctx.app.api.get_token(&req).await.unwrap();
+  let new_email: String = SafeEmail().fake();
+  let (_, resp) = ctx
+    .app
+    .api
+    .request_email_change(
+      &token.access_token,
+      &ChangeEmailRequest { email: new_email },
+    )
+    .await
+    .unwrap();
+  assert_ok!(resp);
+  let (status, resp) = ctx
+    .app
+    .api
+    .confirm_email_change(
+      &token.access_token,
+      &ConfirmEmailRequest {
+        code: "wrong".to_string(),
+      },
+    )
+    .await
+    .unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind == "INVALID_INPUT_ERROR");
+  assert!(status.is_client_error(), "status: {status}");
+  let (_, resp) = ctx.app.api.get_profile(&token.access_token).await.unwrap();
+  assert_eq!(unwrap!(resp).email, user.email);
+}
@@ -1,4 +1,5 @@
-use rustfulapi::entity::role::RoleUser;
+use rustfulapi::entity::{message::MessageKind, role::RoleUser};
+use rustfulapi::repo;
 use test_context::test_context;
 
 use crate::context::seeder::SeedDbTestContext;
@@ -9,11 +10,18 @@ pub async fn test_forget_password(ctx: &mut SeedDbTestContext) {
   let user = ctx.users.get(&RoleUser::User).unwrap();
   let (status, _body) = ctx.app.api.forget_password(&user.email).await.unwrap();
   assert!(status.is_success(), "status: {status}");
-  let (_, user_id) = ctx
+  let (code, user_id) = ctx
     .app
     .mail
     .get_code_and_id_from_email(&user.email)
     .await
     .unwrap();
   assert_eq!(user.id, user_id);
+  let state = &ctx.app.state;
+  let message =
+    repo::message::find_by_user_and_kind(&*state.db, user.id, MessageKind::ForgetPasswordCode)
+      .await
+      .unwrap()
+      .unwrap();
+  assert!(!message.content.contains(&code));
 }
@@ -79,7 +79,11 @@ pub async fn test_success_2fa_login(ctx: &mut AppTestContext) {
             .get_code_and_id_from_email(&req.email)
             .await
             .unwrap();
-          let login_req = Login2faRequest { user_id, code };
+          let login_req = Login2faRequest {
+            user_id,
+            code: Some(code),
+            passkey: None,
+          };
           let (status, resp) = ctx.api.login2fa(&login_req).await.unwrap();
           let resp = unwrap!(resp);
           assert!(status.is_success(), "status: {status}");
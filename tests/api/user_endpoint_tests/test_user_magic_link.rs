@@ -0,0 +1,137 @@
+use crate::context::seeder::SeedDbTestContext;
+use crate::{assert_err, assert_ok, unwrap};
+use rustfulapi::dto::*;
+use rustfulapi::entity::role::RoleUser;
+use rustfulapi::error::AppResponseError;
+use test_context::test_context;
+
+async fn get_magic_link_token(ctx: &SeedDbTestContext, email: &str) -> String {
+  let req = MagicLinkRequest {
+    email: email.to_string(),
+  };
+  let (status, resp) = ctx.app.api.request_magic_link(&req).await.unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let (link, _) = ctx
+    .app
+    .mail
+    .get_code_and_id_from_email(email)
+    .await
+    .unwrap();
+  link.split("token=").nth(1).unwrap().to_string()
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_login_with_magic_link(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let token = get_magic_link_token(ctx, &user.email).await;
+  let (status, resp) = ctx.app.api.login_magic_link(&token).await.unwrap();
+  let resp = unwrap!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let LoginResponse::Token(token_resp) = resp else {
+    panic!("Login with magic link failed.");
+  };
+  let (status, resp) = ctx
+    .app
+    .api
+    .get_profile(&token_resp.access_token)
+    .await
+    .unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let (status, resp) = ctx.app.api.login_magic_link(&token).await.unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind == "UNAUTHORIZED_ERROR");
+  assert_eq!(status, reqwest::StatusCode::UNAUTHORIZED);
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_magic_link_page_keeps_the_token(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let token = get_magic_link_token(ctx, &user.email).await;
+  let (status, page) = ctx.app.api.magic_link_page(&token).await.unwrap();
+  assert!(status.is_success(), "status: {status}");
+  assert!(page.contains(&token));
+  let (status, resp) = ctx.app.api.login_magic_link(&token).await.unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_magic_link_is_replaced_by_a_new_one(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let old_token = get_magic_link_token(ctx, &user.email).await;
+  let new_token = get_magic_link_token(ctx, &user.email).await;
+  let (status, resp) = ctx.app.api.login_magic_link(&old_token).await.unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind == "UNAUTHORIZED_ERROR");
+  assert_eq!(status, reqwest::StatusCode::UNAUTHORIZED);
+  let (status, resp) = ctx.app.api.login_magic_link(&new_token).await.unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_magic_link_requires_second_factor(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let token = ctx.app.api.get_token(&req).await.unwrap();
+  let update_req = UpdateProfileRequest {
+    is_2fa: Some(true),
+    ..Default::default()
+  };
+  let (status, _) = ctx
+    .app
+    .api
+    .update_profile(&token.access_token, &update_req)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let link_token = get_magic_link_token(ctx, &user.email).await;
+  let (status, resp) = ctx.app.api.login_magic_link(&link_token).await.unwrap();
+  let resp = unwrap!(resp);
+  assert!(status.is_success(), "status: {status}");
+  assert!(matches!(resp, LoginResponse::Code { .. }));
+  let (code, user_id) = ctx
+    .app
+    .mail
+    .get_code_and_id_from_email(&user.email)
+    .await
+    .unwrap();
+  let (status, resp) = ctx
+    .app
+    .api
+    .login2fa(&Login2faRequest {
+      user_id,
+      code: Some(code),
+      passkey: None,
+    })
+    .await
+    .unwrap();
+  assert_ok!(resp, |d| matches!(d, &LoginResponse::Token(_)));
+  assert!(status.is_success(), "status: {status}");
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_access_token_is_not_a_magic_link(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let token = ctx.app.api.get_token(&req).await.unwrap();
+  let (status, resp) = ctx
+    .app
+    .api
+    .login_magic_link(&token.access_token)
+    .await
+    .unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind == "UNAUTHORIZED_ERROR");
+  assert_eq!(status, reqwest::StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::context::seeder::SeedDbTestContext;
+use crate::{assert_err, assert_ok};
+use fake::{Fake, Faker};
+use jsonwebtoken::{Algorithm, Header};
+use rustfulapi::configure::oidc::OidcProviderConfig;
+use rustfulapi::dto::*;
+use rustfulapi::entity::role::RoleUser;
+use rustfulapi::error::{AppError, AppResponseError};
+use rustfulapi::server::state::AppState;
+use rustfulapi::util::client_info::ClientInfo;
+use rustfulapi::util::jwk::KeyRing;
+use rustfulapi::util::key::PairKey;
+use rustfulapi::{repo, service};
+use serde_json::json;
+use test_context::test_context;
+use uuid::Uuid;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+const PROVIDER: &str = "mock";
+const CLIENT_ID: &str = "rustfulapi";
+
+struct MockIssuer {
+  state: AppState,
+  key_ring: KeyRing,
+}
+
+impl MockIssuer {
+  fn new(app_state: &AppState, server: &MockServer) -> Self {
+    let pair = PairKey::rsa(2048).unwrap();
+    let key_ring = KeyRing::new(
+      &Header::new(Algorithm::RS256),
+      &String::from_utf8(pair.private_key).unwrap(),
+      &[String::from_utf8(pair.public_key).unwrap()],
+    )
+    .unwrap();
+    let issuer = server.uri();
+    let mut config = (*app_state.config).clone();
+    config.oidc.providers = vec![OidcProviderConfig {
+      name: PROVIDER.to_string(),
+      issuer,
+      client_id: CLIENT_ID.to_string(),
+      client_secret: "secret".to_string(),
+      redirect_uri: "http://localhost/callback".to_string(),
+      scope: vec!["openid".to_string(), "email".to_string()],
+    }];
+    let state = AppState {
+      config: Arc::new(config),
+      ..app_state.clone()
+    };
+    Self { state, key_ring }
+  }
+
+  async fn mount_discovery(&self, server: &MockServer) {
+    let issuer = server.uri();
+    Mock::given(method("GET"))
+      .and(path("/.well-known/openid-configuration"))
+      .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+        "issuer": issuer,
+        "authorization_endpoint": format!("{issuer}/authorize"),
+        "token_endpoint": format!("{issuer}/token"),
+        "jwks_uri": format!("{issuer}/jwks"),
+      })))
+      .mount(server)
+      .await;
+    Mock::given(method("GET"))
+      .and(path("/jwks"))
+      .respond_with(ResponseTemplate::new(200).set_body_json(self.key_ring.jwks()))
+      .mount(server)
+      .await;
+  }
+
+  /// Starts an authorization and answers its code with an id token for the subject.
+  async fn authorize(
+    &self,
+    server: &MockServer,
+    user_id: Option<Uuid>,
+    subject: &str,
+    email: &str,
+  ) -> OidcCallbackRequest {
+    server.reset().await;
+    self.mount_discovery(server).await;
+    let resp = service::oidc::authorize(&self.state, PROVIDER, user_id)
+      .await
+      .unwrap();
+    let query: HashMap<String, String> = url::Url::parse(&resp.authorization_url)
+      .unwrap()
+      .query_pairs()
+      .into_owned()
+      .collect();
+    assert_eq!(query["state"], resp.state);
+    assert_eq!(query["code_challenge_method"], "S256");
+    let code: String = Faker.fake();
+    let id_token = self
+      .key_ring
+      .encode(&json!({
+        "iss": server.uri(),
+        "aud": CLIENT_ID,
+        "sub": subject,
+        "exp": chrono::Utc::now().timestamp() + 60,
+        "nonce": query["nonce"],
+        "email": email,
+        "email_verified": true,
+      }))
+      .unwrap();
+    let challenge = query["code_challenge"].clone();
+    let expected_code = code.clone();
+    Mock::given(method("POST"))
+      .and(path("/token"))
+      .respond_with(move |req: &wiremock::Request| {
+        let form: HashMap<String, String> = url::form_urlencoded::parse(&req.body)
+          .into_owned()
+          .collect();
+        let verifier = form.get("code_verifier").cloned().unwrap_or_default();
+        if form.get("code") != Some(&expected_code)
+          || service::oidc::code_challenge(&verifier) != challenge
+        {
+          return ResponseTemplate::new(400).set_body_json(json!({"error": "invalid_grant"}));
+        }
+        ResponseTemplate::new(200).set_body_json(json!({
+          "access_token": "access_token",
+          "token_type": "Bearer",
+          "id_token": id_token,
+        }))
+      })
+      .mount(server)
+      .await;
+    OidcCallbackRequest {
+      code,
+      state: resp.state,
+    }
+  }
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_oidc_login_registers_new_user(ctx: &mut SeedDbTestContext) {
+  let issuer = MockIssuer::new(&ctx.app.state, &ctx.app.mock_server);
+  let subject = Uuid::new_v4().to_string();
+  let email = format!("{}@example.com", Uuid::new_v4().simple());
+  let req = issuer
+    .authorize(&ctx.app.mock_server, None, &subject, &email)
+    .await;
+  let resp = service::oidc::login(&issuer.state, ClientInfo::default(), PROVIDER, req)
+    .await
+    .unwrap();
+  assert!(matches!(resp, LoginResponse::Token(_)));
+  let user = repo::user::find_by_email(&*issuer.state.db, &email)
+    .await
+    .unwrap()
+    .unwrap();
+  assert!(user.is_active);
+  let req = issuer
+    .authorize(&ctx.app.mock_server, None, &subject, &email)
+    .await;
+  let resp = service::oidc::login(&issuer.state, ClientInfo::default(), PROVIDER, req)
+    .await
+    .unwrap();
+  assert!(matches!(resp, LoginResponse::Token(_)));
+  let identities = service::oidc::list(&issuer.state, user.id).await.unwrap();
+  assert_eq!(identities.list.len(), 1);
+  assert_eq!(identities.list[0].provider, PROVIDER);
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_oidc_login_with_invalid_state(ctx: &mut SeedDbTestContext) {
+  let issuer = MockIssuer::new(&ctx.app.state, &ctx.app.mock_server);
+  let email = format!("{}@example.com", Uuid::new_v4().simple());
+  let mut req = issuer
+    .authorize(&ctx.app.mock_server, None, "subject", &email)
+    .await;
+  let state = req.state.clone();
+  req.state = Faker.fake();
+  let err = service::oidc::login(&issuer.state, ClientInfo::default(), PROVIDER, req)
+    .await
+    .unwrap_err();
+  assert!(matches!(err, AppError::UnauthorizedError(_)));
+  let req = OidcCallbackRequest {
+    code: Faker.fake(),
+    state,
+  };
+  let err = service::oidc::login(&issuer.state, ClientInfo::default(), PROVIDER, req)
+    .await
+    .unwrap_err();
+  assert!(matches!(err, AppError::UnauthorizedError(_)));
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_oidc_login_with_email_of_existing_user(ctx: &mut SeedDbTestContext) {
+  let issuer = MockIssuer::new(&ctx.app.state, &ctx.app.mock_server);
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let req = issuer
+    .authorize(&ctx.app.mock_server, None, "subject", &user.email)
+    .await;
+  let err = service::oidc::login(&issuer.state, ClientInfo::default(), PROVIDER, req)
+    .await
+    .unwrap_err();
+  assert!(matches!(err, AppError::ResourceExistsError(_)));
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_link_and_unlink_identity(ctx: &mut SeedDbTestContext) {
+  let issuer = MockIssuer::new(&ctx.app.state, &ctx.app.mock_server);
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let subject = Uuid::new_v4().to_string();
+  let req = issuer
+    .authorize(&ctx.app.mock_server, Some(user.id), &subject, &user.email)
+    .await;
+  let state = req.state.clone();
+  let err = service::oidc::login(
+    &issuer.state,
+    ClientInfo::default(),
+    PROVIDER,
+    OidcCallbackRequest {
+      code: req.code.clone(),
+      state,
+    },
+  )
+  .await
+  .unwrap_err();
+  assert!(matches!(err, AppError::UnauthorizedError(_)));
+
+  let req = issuer
+    .authorize(&ctx.app.mock_server, Some(user.id), &subject, &user.email)
+    .await;
+  let identity = service::oidc::link(&issuer.state, user.id, PROVIDER, req)
+    .await
+    .unwrap();
+  assert_eq!(identity.provider, PROVIDER);
+  let req = issuer
+    .authorize(&ctx.app.mock_server, Some(user.id), &subject, &user.email)
+    .await;
+  let err = service::oidc::link(&issuer.state, user.id, PROVIDER, req)
+    .await
+    .unwrap_err();
+  assert!(matches!(err, AppError::ResourceExistsError(_)));
+
+  let req = issuer
+    .authorize(&ctx.app.mock_server, None, &subject, &user.email)
+    .await;
+  let resp = service::oidc::login(&issuer.state, ClientInfo::default(), PROVIDER, req)
+    .await
+    .unwrap();
+  let LoginResponse::Token(token) = resp else {
+    panic!("Login with identity provider failed.");
+  };
+  let (status, resp) = ctx
+    .app
+    .api
+    .list_identities(&token.access_token)
+    .await
+    .unwrap();
+  assert_ok!(resp, |r: &IdentityListResponse| r.list.len() == 1);
+  assert!(status.is_success(), "status: {status}");
+
+  service::oidc::unlink(&issuer.state, user.id, PROVIDER)
+    .await
+    .unwrap();
+  let err = service::oidc::unlink(&issuer.state, user.id, PROVIDER)
+    .await
+    .unwrap_err();
+  assert!(matches!(err, AppError::NotFoundError(_)));
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_oidc_authorize_with_unknown_provider(ctx: &mut SeedDbTestContext) {
+  let (status, resp) = ctx.app.api.oidc_authorize("unknown").await.unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind
+    == "OIDC_PROVIDER_NOT_FOUND_ERROR");
+  assert_eq!(status, reqwest::StatusCode::NOT_FOUND);
+}
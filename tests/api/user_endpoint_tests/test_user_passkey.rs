@@ -0,0 +1,220 @@
+use crate::context::seeder::SeedDbTestContext;
+use crate::helper::passkey::SoftAuthenticator;
+use crate::helper::user::TestUser;
+use crate::{assert_err, assert_ok, unwrap};
+use rustfulapi::dto::*;
+use rustfulapi::entity::role::RoleUser;
+use rustfulapi::error::AppResponseError;
+use test_context::test_context;
+
+async fn register(
+  ctx: &SeedDbTestContext,
+  user: &TestUser,
+) -> (TokenResponse, SoftAuthenticator, PasskeyResponse) {
+  let token = ctx
+    .app
+    .api
+    .get_token(&LoginRequest {
+      email: user.email.clone(),
+      password: user.password.clone(),
+    })
+    .await
+    .unwrap();
+  let mut authenticator = SoftAuthenticator::new(&ctx.app.state.config.webauthn.origins[0]);
+  let (status, resp) = ctx
+    .app
+    .api
+    .start_passkey_registration(&token.access_token)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let options = unwrap!(resp);
+  let req = RegisterPasskeyRequest {
+    name: "laptop".to_string(),
+    credential: authenticator.register(&options),
+  };
+  let (status, resp) = ctx
+    .app
+    .api
+    .register_passkey(&token.access_token, &req)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let passkey = unwrap!(resp);
+  (token, authenticator, passkey)
+}
+
+async fn login_options(
+  ctx: &SeedDbTestContext,
+  user_id: Option<uuid::Uuid>,
+) -> PasskeyRequestOptionsResponse {
+  let (status, resp) = ctx
+    .app
+    .api
+    .passkey_login_options(&PasskeyLoginOptionsRequest { user_id })
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  unwrap!(resp)
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_success_register_and_login_with_passkey(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let (token, mut authenticator, passkey) = register(ctx, user).await;
+  let (_, resp) = ctx
+    .app
+    .api
+    .list_passkeys(&token.access_token)
+    .await
+    .unwrap();
+  assert_ok!(resp, |r: &PasskeyListResponse| r.list.len() == 1
+    && r.list[0].id == passkey.id);
+
+  let options = login_options(ctx, None).await;
+  assert!(options.allow_credentials.is_empty());
+  let req = PasskeyLoginRequest {
+    credential: authenticator.assert(&options, true),
+  };
+  let (status, resp) = ctx.app.api.login_passkey(&req).await.unwrap();
+  assert!(status.is_success(), "status: {status}");
+  assert!(matches!(unwrap!(resp), LoginResponse::Token(_)));
+  // the challenge is accepted once
+  let (status, _) = ctx.app.api.login_passkey(&req).await.unwrap();
+  assert_eq!(status, reqwest::StatusCode::BAD_REQUEST);
+
+  // a passwordless login needs the user verified by the authenticator
+  let options = login_options(ctx, None).await;
+  let req = PasskeyLoginRequest {
+    credential: authenticator.assert(&options, false),
+  };
+  let (status, resp) = ctx.app.api.login_passkey(&req).await.unwrap();
+  assert_eq!(status, reqwest::StatusCode::UNAUTHORIZED);
+  assert_err!(resp, |e: &AppResponseError| e.kind == "UNAUTHORIZED_ERROR");
+
+  let (status, _) = ctx
+    .app
+    .api
+    .delete_passkey(&token.access_token, &passkey.id)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let options = login_options(ctx, None).await;
+  let req = PasskeyLoginRequest {
+    credential: authenticator.assert(&options, true),
+  };
+  let (status, _) = ctx.app.api.login_passkey(&req).await.unwrap();
+  assert_eq!(status, reqwest::StatusCode::UNAUTHORIZED);
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_register_passkey_with_invalid_credential(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let (token, mut authenticator, _) = register(ctx, user).await;
+  let (_, resp) = ctx
+    .app
+    .api
+    .start_passkey_registration(&token.access_token)
+    .await
+    .unwrap();
+  let options = unwrap!(resp);
+  assert_eq!(options.exclude_credentials.len(), 1);
+  assert_eq!(
+    options.exclude_credentials[0].id,
+    authenticator.credential_id()
+  );
+  let req = RegisterPasskeyRequest {
+    name: "laptop".to_string(),
+    credential: authenticator.register(&options),
+  };
+  let (status, resp) = ctx
+    .app
+    .api
+    .register_passkey(&token.access_token, &req)
+    .await
+    .unwrap();
+  assert_eq!(status, reqwest::StatusCode::CONFLICT);
+  assert_err!(resp, |e: &AppResponseError| e.kind
+    == "PASSKEY_ALREADY_EXISTS_ERROR");
+
+  let mut phishing = SoftAuthenticator::new("https://rustfulapi.example.net");
+  let (_, resp) = ctx
+    .app
+    .api
+    .start_passkey_registration(&token.access_token)
+    .await
+    .unwrap();
+  let req = RegisterPasskeyRequest {
+    name: "phone".to_string(),
+    credential: phishing.register(&unwrap!(resp)),
+  };
+  let (status, resp) = ctx
+    .app
+    .api
+    .register_passkey(&token.access_token, &req)
+    .await
+    .unwrap();
+  assert_eq!(status, reqwest::StatusCode::BAD_REQUEST);
+  assert_err!(resp, |e: &AppResponseError| e.kind == "INVALID_INPUT_ERROR");
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_success_login2fa_with_passkey(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let (token, mut authenticator, _) = register(ctx, user).await;
+  let (status, resp) = ctx
+    .app
+    .api
+    .passkey_login_options(&PasskeyLoginOptionsRequest {
+      user_id: Some(user.id),
+    })
+    .await
+    .unwrap();
+  assert_eq!(status, reqwest::StatusCode::BAD_REQUEST);
+  assert_err!(resp);
+
+  let update_req = UpdateProfileRequest {
+    is_2fa: Some(true),
+    ..Default::default()
+  };
+  let (status, _) = ctx
+    .app
+    .api
+    .update_profile(&token.access_token, &update_req)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let login_req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let (_, resp) = ctx.app.api.login(&login_req).await.unwrap();
+  assert!(matches!(unwrap!(resp), LoginResponse::Code { .. }));
+
+  let options = login_options(ctx, Some(user.id)).await;
+  assert_eq!(options.allow_credentials.len(), 1);
+  assert_eq!(options.user_verification, "discouraged");
+  // a challenge for a passwordless login is not a second factor
+  let passwordless = login_options(ctx, None).await;
+  let req = Login2faRequest {
+    user_id: user.id,
+    code: None,
+    passkey: Some(authenticator.assert(&passwordless, false)),
+  };
+  let (status, _) = ctx.app.api.login2fa(&req).await.unwrap();
+  assert_eq!(status, reqwest::StatusCode::UNAUTHORIZED);
+
+  let req = Login2faRequest {
+    user_id: user.id,
+    code: None,
+    passkey: Some(authenticator.assert(&options, false)),
+  };
+  let (status, resp) = ctx.app.api.login2fa(&req).await.unwrap();
+  assert!(status.is_success(), "status: {status}");
+  assert!(matches!(unwrap!(resp), LoginResponse::Token(_)));
+  let (status, _) = ctx.app.api.login2fa(&req).await.unwrap();
+  assert!(!status.is_success(), "status: {status}");
+}
@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use crate::context::seeder::SeedDbTestContext;
+use fake::{Fake, Faker};
+use rustfulapi::constant::{EXPIRE_FORGET_PASS_CODE_SECS, MAX_LOGIN_ATTEMPTS};
+use rustfulapi::dto::*;
+use rustfulapi::entity::{message::MessageKind, role::RoleUser};
+use rustfulapi::error::AppError;
+use rustfulapi::server::state::AppState;
+use rustfulapi::util::client_info::ClientInfo;
+use rustfulapi::{repo, service};
+use test_context::test_context;
+
+fn privacy_state(state: &AppState) -> AppState {
+  let mut config = (*state.config).clone();
+  config.privacy.enable = true;
+  AppState {
+    config: Arc::new(config),
+    ..state.clone()
+  }
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_privacy_login_returns_generic_error(ctx: &mut SeedDbTestContext) {
+  let state = privacy_state(&ctx.app.state);
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let unknown = LoginRequest {
+    email: "unknown@email.com".to_string(),
+    password: user.password.clone(),
+  };
+  let wrong = LoginRequest {
+    email: user.email.clone(),
+    password: "wrong_password".to_string(),
+  };
+  let unknown_err = service::user::login(&state, ClientInfo::default(), unknown)
+    .await
+    .unwrap_err();
+  let wrong_err = service::user::login(&state, ClientInfo::default(), wrong)
+    .await
+    .unwrap_err();
+  assert!(matches!(unknown_err, AppError::UnauthorizedError(_)));
+  assert_eq!(unknown_err.to_string(), wrong_err.to_string());
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_privacy_login_hides_lockout(ctx: &mut SeedDbTestContext) {
+  let state = privacy_state(&ctx.app.state);
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let login = |password: &str| {
+    let req = LoginRequest {
+      email: user.email.clone(),
+      password: password.to_string(),
+    };
+    service::user::login(&state, ClientInfo::default(), req)
+  };
+  for _ in 0..MAX_LOGIN_ATTEMPTS + 1 {
+    let err = login("wrong_password").await.unwrap_err();
+    assert!(matches!(err, AppError::UnauthorizedError(_)), "{err:?}");
+  }
+  let err = login(&user.password).await.unwrap_err();
+  assert!(matches!(err, AppError::UnauthorizedError(_)), "{err:?}");
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_privacy_forget_password_existing_email(ctx: &mut SeedDbTestContext) {
+  let state = privacy_state(&ctx.app.state);
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  for _ in 0..2 {
+    let req = ForgetPasswordQueryParam {
+      email: user.email.clone(),
+    };
+    let resp = service::user::forget_password(&state, req).await.unwrap();
+    assert_eq!(resp.expire_in, EXPIRE_FORGET_PASS_CODE_SECS.as_secs());
+  }
+  let (_, user_id) = ctx
+    .app
+    .mail
+    .get_code_and_id_from_email(&user.email)
+    .await
+    .unwrap();
+  assert_eq!(user_id, user.id);
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_privacy_forget_password_unknown_email(ctx: &mut SeedDbTestContext) {
+  let state = privacy_state(&ctx.app.state);
+  let req = ForgetPasswordQueryParam {
+    email: "unknown@email.com".to_string(),
+  };
+  let resp = service::user::forget_password(&state, req).await.unwrap();
+  assert_eq!(resp.expire_in, EXPIRE_FORGET_PASS_CODE_SECS.as_secs());
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_privacy_register_existing_email(ctx: &mut SeedDbTestContext) {
+  let state = privacy_state(&ctx.app.state);
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let username: String = Faker.fake();
+  let req = RegisterRequest::new(&username, &user.email, "password1234");
+  let id = service::user::register(state.clone(), req).await.unwrap();
+  assert_ne!(id, user.id);
+  let message =
+    repo::message::find_by_user_and_kind(&*state.db, user.id, MessageKind::AccountExists)
+      .await
+      .unwrap();
+  assert!(message.is_some());
+}
@@ -1,10 +1,13 @@
-use crate::assert_ok;
 use crate::context::seeder::SeedDbTestContext;
 use crate::unwrap;
+use crate::{assert_err, assert_ok};
 use fake::{Fake, Faker};
+use rustfulapi::constant::MAX_PASSWORD_CHECK_ATTEMPTS;
 use rustfulapi::{
-  dto::{LoginRequest, UpdateProfileRequest},
+  dto::{ChangePasswordRequest, LoginRequest, UpdateProfileRequest},
   entity::role::RoleUser,
+  error::AppResponseError,
+  util,
 };
 use test_context::test_context;
 
@@ -54,3 +57,83 @@ pub async fn test_update_profile_user(ctx: &mut SeedDbTestContext) {
   assert!(status.is_success(), "status: {status}");
   assert_eq!(resp.username, req.username.unwrap());
 }
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_change_password_user(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let token = ctx.app.api.get_token(&req).await.unwrap();
+  let other_token = ctx.app.api.get_token(&req).await.unwrap();
+  let change_req = ChangePasswordRequest {
+    current_password: user.password.clone(),
+    new_password: util::random::generate_random_string(12),
+  };
+  let (status, resp) = ctx
+    .app
+    .api
+    .change_password(&token.access_token, &change_req)
+    .await
+    .unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let (status, _) = ctx.app.api.get_profile(&token.access_token).await.unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let (status, _) = ctx
+    .app
+    .api
+    .get_profile(&other_token.access_token)
+    .await
+    .unwrap();
+  assert!(!status.is_success(), "status: {status}");
+  let (_, resp) = ctx.app.api.login(&req).await.unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind == "INVALID_INPUT_ERROR");
+  let req = LoginRequest {
+    email: user.email.clone(),
+    password: change_req.new_password,
+  };
+  let (status, resp) = ctx.app.api.login(&req).await.unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_change_password_with_wrong_current_password(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let token = ctx.app.api.get_token(&req).await.unwrap();
+  let change_req = ChangePasswordRequest {
+    current_password: "wrong_password".to_string(),
+    new_password: util::random::generate_random_string(12),
+  };
+  for _ in 0..MAX_PASSWORD_CHECK_ATTEMPTS {
+    let (status, resp) = ctx
+      .app
+      .api
+      .change_password(&token.access_token, &change_req)
+      .await
+      .unwrap();
+    assert_err!(resp, |e: &AppResponseError| e.kind == "INVALID_INPUT_ERROR");
+    assert!(status.is_client_error(), "status: {status}");
+  }
+  let (status, resp) = ctx
+    .app
+    .api
+    .change_password(&token.access_token, &change_req)
+    .await
+    .unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind
+    == "TOO_MANY_REQUESTS_ERROR");
+  assert_eq!(status, reqwest::StatusCode::TOO_MANY_REQUESTS);
+  // the checks are throttled, the account is not locked
+  let (status, resp) = ctx.app.api.login(&req).await.unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+}
@@ -0,0 +1,39 @@
+use crate::assert_err;
+use crate::context::app::AppTestContext;
+use rustfulapi::dto::Login2faRequest;
+use rustfulapi::error::AppResponseError;
+use test_context::test_context;
+use uuid::Uuid;
+
+#[test_context(AppTestContext)]
+#[tokio::test]
+pub async fn test_login2fa_rate_limit_by_account(ctx: &mut AppTestContext) {
+  let req = Login2faRequest {
+    user_id: Uuid::new_v4(),
+    code: Some("00000".to_string()),
+    passkey: None,
+  };
+  for _ in 0..5 {
+    let (status, _, _) = ctx.api.login2fa_with_headers(&req).await.unwrap();
+    assert_ne!(status, reqwest::StatusCode::TOO_MANY_REQUESTS);
+  }
+  let (status, headers, resp) = ctx.api.login2fa_with_headers(&req).await.unwrap();
+  assert_eq!(status, reqwest::StatusCode::TOO_MANY_REQUESTS);
+  let retry_after: u64 = headers
+    .get(reqwest::header::RETRY_AFTER)
+    .unwrap()
+    .to_str()
+    .unwrap()
+    .parse()
+    .unwrap();
+  assert!(retry_after > 0 && retry_after <= 60);
+  assert_err!(resp, |e: &AppResponseError| e.kind
+    == "TOO_MANY_REQUESTS_ERROR");
+  let other = Login2faRequest {
+    user_id: Uuid::new_v4(),
+    code: Some("00000".to_string()),
+    passkey: None,
+  };
+  let (status, _, _) = ctx.api.login2fa_with_headers(&other).await.unwrap();
+  assert_ne!(status, reqwest::StatusCode::TOO_MANY_REQUESTS);
+}
@@ -0,0 +1,104 @@
+use crate::assert_err;
+use crate::assert_ok;
+use crate::context::seeder::SeedDbTestContext;
+use crate::unwrap;
+use rustfulapi::dto::*;
+use rustfulapi::entity::role::RoleUser;
+use test_context::test_context;
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_success_login2fa_with_recovery_code(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let login_req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let token = ctx.app.api.get_token(&login_req).await.unwrap();
+  let req = UpdateProfileRequest {
+    is_2fa: Some(true),
+    ..Default::default()
+  };
+  let (status, resp) = ctx
+    .app
+    .api
+    .update_profile(&token.access_token, &req)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let recovery_codes = unwrap!(resp).recovery_codes.unwrap();
+  assert_eq!(recovery_codes.len(), 10);
+  let (status, resp) = ctx.app.api.login(&login_req).await.unwrap();
+  assert!(status.is_success(), "status: {status}");
+  assert_ok!(resp, |r: &LoginResponse| matches!(
+    r,
+    LoginResponse::Code { .. }
+  ));
+  ctx
+    .app
+    .mail
+    .get_code_and_id_from_email(&user.email)
+    .await
+    .unwrap();
+  let req = Login2faRequest {
+    user_id: user.id,
+    code: Some(recovery_codes[0].clone()),
+    passkey: None,
+  };
+  let (status, resp) = ctx.app.api.login2fa(&req).await.unwrap();
+  assert!(status.is_success(), "status: {status}");
+  assert_ok!(resp);
+  let (remaining, user_id) = ctx
+    .app
+    .mail
+    .get_code_and_id_from_email(&user.email)
+    .await
+    .unwrap();
+  assert_eq!(user_id, user.id);
+  assert_eq!(remaining, "9");
+  let (status, _) = ctx.app.api.login(&login_req).await.unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let (status, resp) = ctx.app.api.login2fa(&req).await.unwrap();
+  assert!(!status.is_success(), "status: {status}");
+  assert_err!(resp);
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_regenerate_recovery_codes(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let login_req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let token = ctx.app.api.get_token(&login_req).await.unwrap();
+  let (status, resp) = ctx
+    .app
+    .api
+    .regenerate_recovery_codes(&token.access_token)
+    .await
+    .unwrap();
+  assert!(!status.is_success(), "status: {status}");
+  assert_err!(resp);
+  let req = UpdateProfileRequest {
+    is_2fa: Some(true),
+    ..Default::default()
+  };
+  let (_, resp) = ctx
+    .app
+    .api
+    .update_profile(&token.access_token, &req)
+    .await
+    .unwrap();
+  let old_codes = unwrap!(resp).recovery_codes.unwrap();
+  let (status, resp) = ctx
+    .app
+    .api
+    .regenerate_recovery_codes(&token.access_token)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let new_codes = unwrap!(resp).recovery_codes;
+  assert_eq!(new_codes.len(), 10);
+  assert!(new_codes.iter().all(|c| !old_codes.contains(c)));
+}
@@ -1,7 +1,10 @@
-use crate::{assert_ok, context::seeder::SeedDbTestContext, unwrap};
+use crate::{assert_err, assert_ok, context::seeder::SeedDbTestContext, unwrap};
 use rustfulapi::{
+  constant::MAX_CODE_ATTEMPTS,
   dto::{LoginRequest, LoginResponse, SetPasswordRequest},
   entity::role::RoleUser,
+  error::AppResponseError,
+  service::{self, redis::ForgetPasswordKey},
   util,
 };
 use test_context::test_context;
@@ -44,3 +47,76 @@ pub async fn test_reset_password(ctx: &mut SeedDbTestContext) {
     }
   }
 }
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_reset_password_code_invalidated_after_failed_attempts(
+  ctx: &mut SeedDbTestContext,
+) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let (status, resp) = ctx.app.api.forget_password(&user.email).await.unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let req = SetPasswordRequest {
+    user_id: user.id,
+    code: "wrong".to_string(),
+    new_password: util::random::generate_random_string(10),
+  };
+  for _ in 1..MAX_CODE_ATTEMPTS {
+    let (_, resp) = ctx.app.api.reset_password(&req).await.unwrap();
+    assert_err!(resp, |e: &AppResponseError| e.kind == "INVALID_INPUT_ERROR");
+  }
+  let key = ForgetPasswordKey { user_id: user.id };
+  assert!(
+    service::redis::check_exist_key(&ctx.app.state.redis, &key)
+      .await
+      .unwrap()
+  );
+  let (status, resp) = ctx.app.api.reset_password(&req).await.unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind == "INVALID_INPUT_ERROR");
+  assert!(status.is_client_error(), "status: {status}");
+  assert!(
+    !service::redis::check_exist_key(&ctx.app.state.redis, &key)
+      .await
+      .unwrap()
+  );
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_reset_password_code_is_single_use_and_ends_sessions(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let login_req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let token = ctx.app.api.get_token(&login_req).await.unwrap();
+  let (status, resp) = ctx.app.api.forget_password(&user.email).await.unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let (code, user_id) = ctx
+    .app
+    .mail
+    .get_code_and_id_from_email(&user.email)
+    .await
+    .unwrap();
+  let key = ForgetPasswordKey { user_id };
+  let stored = service::redis::get(&ctx.app.state.redis, &key)
+    .await
+    .unwrap()
+    .unwrap();
+  assert_ne!(stored, code);
+  let req = SetPasswordRequest {
+    user_id,
+    code,
+    new_password: util::random::generate_random_string(10),
+  };
+  let (status, resp) = ctx.app.api.reset_password(&req).await.unwrap();
+  assert_ok!(resp);
+  assert!(status.is_success(), "status: {status}");
+  let (status, _) = ctx.app.api.get_profile(&token.access_token).await.unwrap();
+  assert!(!status.is_success(), "status: {status}");
+  let (status, resp) = ctx.app.api.reset_password(&req).await.unwrap();
+  assert_err!(resp, |e: &AppResponseError| e.kind == "INVALID_INPUT_ERROR");
+  assert!(status.is_client_error(), "status: {status}");
+}
@@ -0,0 +1,91 @@
+use crate::assert_err;
+use crate::assert_ok;
+use crate::context::seeder::SeedDbTestContext;
+use crate::unwrap;
+use rustfulapi::dto::LoginRequest;
+use rustfulapi::entity::role::RoleUser;
+use rustfulapi::error::AppResponseError;
+use test_context::test_context;
+use uuid::Uuid;
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_multiple_sessions_list_and_revoke(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let laptop = ctx.app.api.get_token(&req).await.unwrap();
+  let phone = ctx.app.api.get_token(&req).await.unwrap();
+  let (status, _) = ctx.app.api.get_profile(&laptop.access_token).await.unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let (status, resp) = ctx
+    .app
+    .api
+    .list_sessions(&phone.access_token)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let sessions = unwrap!(resp).list;
+  assert_eq!(sessions.len(), 2);
+  assert_eq!(sessions.iter().filter(|s| s.current).count(), 1);
+  let laptop_session = sessions.iter().find(|s| !s.current).unwrap();
+  assert!(laptop_session.ip.is_some());
+  let (status, resp) = ctx
+    .app
+    .api
+    .revoke_session(&phone.access_token, &laptop_session.id)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  assert_ok!(resp);
+  let (status, _) = ctx.app.api.get_profile(&laptop.access_token).await.unwrap();
+  assert!(!status.is_success(), "status: {status}");
+  let (status, _) = ctx.app.api.get_profile(&phone.access_token).await.unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let (status, resp) = ctx
+    .app
+    .api
+    .revoke_session(&phone.access_token, &Uuid::new_v4())
+    .await
+    .unwrap();
+  assert_eq!(status, reqwest::StatusCode::NOT_FOUND);
+  assert_err!(resp, |e: &AppResponseError| e.kind
+    == "SESSION_NOT_FOUND_ERROR");
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_revoke_other_sessions(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let first = ctx.app.api.get_token(&req).await.unwrap();
+  let second = ctx.app.api.get_token(&req).await.unwrap();
+  let current = ctx.app.api.get_token(&req).await.unwrap();
+  let (status, resp) = ctx
+    .app
+    .api
+    .revoke_other_sessions(&current.access_token)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  assert_ok!(resp);
+  for token in [&first, &second] {
+    let (status, _) = ctx.app.api.get_profile(&token.access_token).await.unwrap();
+    assert!(!status.is_success(), "status: {status}");
+  }
+  let (status, resp) = ctx
+    .app
+    .api
+    .list_sessions(&current.access_token)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let sessions = unwrap!(resp).list;
+  assert_eq!(sessions.len(), 1);
+  assert!(sessions[0].current);
+}
@@ -0,0 +1,177 @@
+use crate::assert_err;
+use crate::assert_ok;
+use crate::context::seeder::SeedDbTestContext;
+use crate::unwrap;
+use rustfulapi::constant::TOTP_STEP_SECS;
+use rustfulapi::dto::*;
+use rustfulapi::entity::role::RoleUser;
+use rustfulapi::entity::two_factor::TwoFactorMethod;
+use rustfulapi::error::AppResponseError;
+use rustfulapi::util;
+use test_context::test_context;
+
+fn current_totp_code(secret: &str) -> String {
+  let now = chrono::Utc::now().timestamp() as u64;
+  util::totp::generate_code(secret, now / TOTP_STEP_SECS).unwrap()
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_success_totp_enroll_and_login(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let login_req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let token = ctx.app.api.get_token(&login_req).await.unwrap();
+  let (status, resp) = ctx.app.api.enroll_totp(&token.access_token).await.unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let enroll = unwrap!(resp);
+  assert!(enroll.uri.starts_with("otpauth://totp/"));
+  let req = ConfirmTotpRequest {
+    code: "000000".to_string(),
+  };
+  let (status, resp) = ctx
+    .app
+    .api
+    .confirm_totp(&token.access_token, &req)
+    .await
+    .unwrap();
+  assert!(!status.is_success(), "status: {status}");
+  assert_err!(resp, |e: &AppResponseError| e.kind == "INVALID_INPUT_ERROR");
+  let req = ConfirmTotpRequest {
+    code: current_totp_code(&enroll.secret),
+  };
+  let (status, resp) = ctx
+    .app
+    .api
+    .confirm_totp(&token.access_token, &req)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  assert_ok!(resp);
+  let (_, resp) = ctx.app.api.get_profile(&token.access_token).await.unwrap();
+  let profile = unwrap!(resp);
+  assert!(profile.is_2fa);
+  assert_eq!(profile.two_factor_method, TwoFactorMethod::Totp);
+  let (status, resp) = ctx.app.api.login(&login_req).await.unwrap();
+  assert!(status.is_success(), "status: {status}");
+  match unwrap!(resp) {
+    LoginResponse::Code { message, .. } => {
+      assert_eq!(
+        message,
+        "Please enter the code from your authenticator app."
+      )
+    }
+    LoginResponse::Token(_) => panic!("It was not expected to receive token."),
+  }
+  let req = Login2faRequest {
+    user_id: user.id,
+    code: Some(current_totp_code(&enroll.secret)),
+    passkey: None,
+  };
+  let (status, resp) = ctx.app.api.login2fa(&req).await.unwrap();
+  assert!(status.is_success(), "status: {status}");
+  assert_ok!(resp);
+  let (status, resp) = ctx.app.api.login2fa(&req).await.unwrap();
+  assert!(!status.is_success(), "status: {status}");
+  assert_err!(resp);
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_success_totp_login_with_email_fallback(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let login_req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let token = ctx.app.api.get_token(&login_req).await.unwrap();
+  let (_, resp) = ctx.app.api.enroll_totp(&token.access_token).await.unwrap();
+  let enroll = unwrap!(resp);
+  let req = ConfirmTotpRequest {
+    code: current_totp_code(&enroll.secret),
+  };
+  let (status, _) = ctx
+    .app
+    .api
+    .confirm_totp(&token.access_token, &req)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let req = Login2faEmailRequest { user_id: user.id };
+  let (status, resp) = ctx.app.api.login2fa_email(&req).await.unwrap();
+  assert!(!status.is_success(), "status: {status}");
+  assert_err!(resp, |e: &AppResponseError| e.kind
+    == "INVALID_SESSION_ERROR");
+  let (status, _) = ctx.app.api.login(&login_req).await.unwrap();
+  assert!(status.is_success(), "status: {status}");
+  let (status, resp) = ctx.app.api.login2fa_email(&req).await.unwrap();
+  assert!(status.is_success(), "status: {status}");
+  assert_ok!(resp);
+  let (code, user_id) = ctx
+    .app
+    .mail
+    .get_code_and_id_from_email(&user.email)
+    .await
+    .unwrap();
+  assert_eq!(user_id, user.id);
+  let req = Login2faRequest {
+    user_id,
+    code: Some(code),
+    passkey: None,
+  };
+  let (status, resp) = ctx.app.api.login2fa(&req).await.unwrap();
+  assert!(status.is_success(), "status: {status}");
+  assert_ok!(resp);
+}
+
+#[test_context(SeedDbTestContext)]
+#[tokio::test]
+pub async fn test_disable_totp_requires_a_code(ctx: &mut SeedDbTestContext) {
+  let user = ctx.users.get(&RoleUser::User).unwrap();
+  let login_req = LoginRequest {
+    email: user.email.clone(),
+    password: user.password.clone(),
+  };
+  let token = ctx.app.api.get_token(&login_req).await.unwrap();
+  let (_, resp) = ctx.app.api.enroll_totp(&token.access_token).await.unwrap();
+  let enroll = unwrap!(resp);
+  let req = ConfirmTotpRequest {
+    code: current_totp_code(&enroll.secret),
+  };
+  let (status, _) = ctx
+    .app
+    .api
+    .confirm_totp(&token.access_token, &req)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  // the access token alone does not remove the factor
+  let req = DisableTotpRequest {
+    code: "000000".to_string(),
+  };
+  let (status, resp) = ctx
+    .app
+    .api
+    .disable_totp(&token.access_token, &req)
+    .await
+    .unwrap();
+  assert!(status.is_client_error(), "status: {status}");
+  assert_err!(resp, |e: &AppResponseError| e.kind == "INVALID_INPUT_ERROR");
+  let (_, resp) = ctx.app.api.get_profile(&token.access_token).await.unwrap();
+  assert_eq!(unwrap!(resp).two_factor_method, TwoFactorMethod::Totp);
+  let req = DisableTotpRequest {
+    code: current_totp_code(&enroll.secret),
+  };
+  let (status, resp) = ctx
+    .app
+    .api
+    .disable_totp(&token.access_token, &req)
+    .await
+    .unwrap();
+  assert!(status.is_success(), "status: {status}");
+  assert_ok!(resp);
+  let (_, resp) = ctx.app.api.get_profile(&token.access_token).await.unwrap();
+  assert_eq!(unwrap!(resp).two_factor_method, TwoFactorMethod::Email);
+}